@@ -317,6 +317,7 @@ impl From<ShardLayoutError> for Error {
     fn from(error: ShardLayoutError) -> Self {
         match error {
             ShardLayoutError::InvalidShardIdError { shard_id } => Error::InvalidShardId(shard_id),
+            err => Error::Other(format!("{:?}", err)),
         }
     }
 }
@@ -52,7 +52,7 @@ use near_primitives::views::{
 };
 #[cfg(feature = "protocol_feature_flat_state")]
 use near_store::flat_state;
-use near_store::{DBCol, ShardTries, StorageError, StoreUpdate, WrappedTrieChanges};
+use near_store::{DBCol, ShardTries, StorageError, Store, StoreUpdate, WrappedTrieChanges};
 
 use crate::block_processing_utils::{
     BlockPreprocessInfo, BlockProcessingArtifact, BlocksInProcessing, DoneApplyChunkCallback,
@@ -516,6 +516,25 @@ impl Chain {
         save_trie_changes: bool,
     ) -> Result<Chain, Error> {
         let (store, _) = runtime_adapter.genesis_state();
+        Self::new_for_view_client_with_store(
+            store,
+            runtime_adapter,
+            chain_genesis,
+            doomslug_threshold_mode,
+            save_trie_changes,
+        )
+    }
+
+    /// Like [`Self::new_for_view_client`] but reads chain data from given
+    /// store rather than the store of the runtime adapter, e.g. from a store
+    /// which falls back to cold storage.
+    pub fn new_for_view_client_with_store(
+        store: Store,
+        runtime_adapter: Arc<dyn RuntimeAdapter>,
+        chain_genesis: &ChainGenesis,
+        doomslug_threshold_mode: DoomslugThresholdMode,
+        save_trie_changes: bool,
+    ) -> Result<Chain, Error> {
         let store = ChainStore::new(store, chain_genesis.height, save_trie_changes);
         let genesis = Self::make_genesis_block(&*runtime_adapter, chain_genesis)?;
         let (sc, rc) = unbounded();
@@ -913,6 +932,27 @@ impl Chain {
         }
         let mut gc_blocks_remaining = gc_config.gc_blocks_limit;
 
+        // Delayed Clearing
+        // Columns kept for more epochs than other data are garbage collected
+        // once the head moves forward by the additional epochs.
+        let gc_delayed_columns = gc_config
+            .gc_num_epochs_to_keep_per_column
+            .keys()
+            .filter(|col_name| {
+                near_chain_configs::GC_DELAYABLE_COLUMNS.contains(&col_name.as_str())
+            })
+            .filter_map(|col_name| {
+                let col = col_name.parse::<DBCol>().ok()?;
+                let num_epochs = gc_config.gc_num_epochs_to_keep_for_column(col_name)
+                    - gc_config.gc_num_epochs_to_keep();
+                (num_epochs > 0).then(|| (col, head.height + num_epochs * self.epoch_length))
+            })
+            .collect();
+        self.store.set_gc_delayed_columns(gc_delayed_columns);
+        let mut chain_store_update = self.store.store_update();
+        chain_store_update.clear_gc_delayed_data(head.height)?;
+        chain_store_update.commit()?;
+
         // Forks Cleaning
         let gc_fork_clean_step = gc_config.gc_fork_clean_step;
         let stop_height = tail.max(fork_tail.saturating_sub(gc_fork_clean_step));
@@ -3481,14 +3521,15 @@ impl Chain {
         Ok(results)
     }
 
-    pub fn get_final_transaction_result(
-        &self,
+    /// Returns the status of the transaction given the outcomes of the
+    /// transaction and of its receipts, starting with the transaction.
+    pub fn get_final_transaction_status(
         transaction_hash: &CryptoHash,
-    ) -> Result<FinalExecutionOutcomeView, Error> {
-        let mut outcomes = self.get_recursive_transaction_results(transaction_hash)?;
+        outcomes: &[ExecutionOutcomeWithIdView],
+    ) -> FinalExecutionStatus {
         let mut looking_for_id = *transaction_hash;
         let num_outcomes = outcomes.len();
-        let status = outcomes
+        outcomes
             .iter()
             .find_map(|outcome_with_id| {
                 if outcome_with_id.id == looking_for_id {
@@ -3512,7 +3553,15 @@ impl Chain {
                     None
                 }
             })
-            .expect("results should resolve to a final outcome");
+            .expect("results should resolve to a final outcome")
+    }
+
+    pub fn get_final_transaction_result(
+        &self,
+        transaction_hash: &CryptoHash,
+    ) -> Result<FinalExecutionOutcomeView, Error> {
+        let mut outcomes = self.get_recursive_transaction_results(transaction_hash)?;
+        let status = Self::get_final_transaction_status(transaction_hash, &outcomes);
         let receipts_outcome = outcomes.split_off(1);
         let transaction = self.store.get_transaction(transaction_hash)?.ok_or_else(|| {
             Error::DBNotFoundErr(format!("Transaction {} is not found", transaction_hash))
@@ -365,6 +365,9 @@ pub struct ChainStore {
     /// - archive is false - non archival nodes need trie changes for garbage collection
     /// - the node will be migrated to split storage in the near future - split storage nodes need trie changes for hot storage garbage collection
     save_trie_changes: bool,
+    /// Columns whose garbage collection is delayed, mapped to the head height
+    /// at which their rows are garbage collected.
+    gc_delayed_columns: HashMap<DBCol, BlockHeight>,
 }
 
 fn option_to_not_found<T, F>(res: io::Result<Option<T>>, field_name: F) -> Result<T, Error>
@@ -407,6 +410,7 @@ impl ChainStore {
             block_ordinal_to_hash: CellLruCache::new(CACHE_SIZE),
             processed_block_heights: CellLruCache::new(CACHE_SIZE),
             save_trie_changes,
+            gc_delayed_columns: HashMap::new(),
         }
     }
 
@@ -418,6 +422,13 @@ impl ChainStore {
         ChainStoreUpdate::new(self)
     }
 
+    /// Sets columns whose garbage collection is delayed until the head
+    /// reaches the given height.  Instead of being deleted, keys of their rows
+    /// are saved in DBCol::GCDelayedKeys.
+    pub fn set_gc_delayed_columns(&mut self, gc_delayed_columns: HashMap<DBCol, BlockHeight>) {
+        self.gc_delayed_columns = gc_delayed_columns;
+    }
+
     pub fn iterate_state_sync_infos(&self) -> Result<Vec<(CryptoHash, StateSyncInfo)>, Error> {
         self.store
             .iter(DBCol::StateDlInfos)
@@ -1199,6 +1210,9 @@ pub struct ChainStoreUpdate<'a> {
     add_state_dl_infos: Vec<StateSyncInfo>,
     remove_state_dl_infos: Vec<CryptoHash>,
     challenged_blocks: HashSet<CryptoHash>,
+    // Keys whose garbage collection is delayed, by head height at which they
+    // are garbage collected and column
+    gc_delayed_keys: HashMap<(BlockHeight, DBCol), Vec<Vec<u8>>>,
 }
 
 impl<'a> ChainStoreUpdate<'a> {
@@ -1223,6 +1237,7 @@ impl<'a> ChainStoreUpdate<'a> {
             add_state_dl_infos: vec![],
             remove_state_dl_infos: vec![],
             challenged_blocks: HashSet::default(),
+            gc_delayed_keys: HashMap::new(),
         }
     }
 }
@@ -2283,7 +2298,44 @@ impl<'a> ChainStoreUpdate<'a> {
         Ok(())
     }
 
+    /// Garbage collects rows whose garbage collection was delayed until head
+    /// height `height` or earlier.
+    pub fn clear_gc_delayed_data(&mut self, height: BlockHeight) -> Result<(), Error> {
+        let mut rows = vec![];
+        for item in self.store().iter(DBCol::GCDelayedKeys) {
+            let (key, value) = item?;
+            let gc_height = BlockHeight::from_be_bytes(key[..8].try_into().unwrap());
+            if gc_height > height {
+                break;
+            }
+            rows.push((key, Vec::<Vec<u8>>::try_from_slice(&value)?));
+        }
+
+        let mut store_update = self.store().store_update();
+        for (key, col_keys) in rows {
+            let col_name = std::str::from_utf8(&key[8..])
+                .map_err(|err| Error::GCError(format!("invalid delayed column name: {}", err)))?;
+            let col = col_name
+                .parse::<DBCol>()
+                .map_err(|_| Error::GCError(format!("unknown delayed column {}", col_name)))?;
+            for col_key in col_keys {
+                self.gc_col_now(col, &col_key);
+            }
+            store_update.delete(DBCol::GCDelayedKeys, &key);
+        }
+        self.merge(store_update);
+        Ok(())
+    }
+
     fn gc_col(&mut self, col: DBCol, key: &[u8]) {
+        if let Some(&gc_height) = self.chain_store.gc_delayed_columns.get(&col) {
+            self.gc_delayed_keys.entry((gc_height, col)).or_default().push(key.to_vec());
+        } else {
+            self.gc_col_now(col, key);
+        }
+    }
+
+    fn gc_col_now(&mut self, col: DBCol, key: &[u8]) {
         let mut store_update = self.store().store_update();
         match col {
             DBCol::OutgoingReceipts => {
@@ -2411,7 +2463,9 @@ impl<'a> ChainStoreUpdate<'a> {
             | DBCol::_TransactionRefCount
             | DBCol::_TransactionResult
             | DBCol::StateChangesForSplitStates
-            | DBCol::CachedContractCode => {
+            | DBCol::CachedContractCode
+            | DBCol::TransactionPool
+            | DBCol::GCDelayedKeys => {
                 unreachable!();
             }
             #[cfg(feature = "protocol_feature_flat_state")]
@@ -2566,6 +2620,14 @@ impl<'a> ChainStoreUpdate<'a> {
             LARGEST_TARGET_HEIGHT_KEY,
             &mut self.largest_target_height,
         )?;
+        for ((gc_height, col), mut keys) in self.gc_delayed_keys.drain() {
+            let col_name: &str = col.into();
+            let key = [&gc_height.to_be_bytes()[..], col_name.as_bytes()].concat();
+            let mut delayed_keys: Vec<Vec<u8>> =
+                self.chain_store.store.get_ser(DBCol::GCDelayedKeys, &key)?.unwrap_or_default();
+            delayed_keys.append(&mut keys);
+            store_update.set_ser(DBCol::GCDelayedKeys, &key, &delayed_keys)?;
+        }
         debug_assert!(self.chain_store_cache_update.blocks.len() <= 1);
         for (hash, block) in self.chain_store_cache_update.blocks.iter() {
             let mut map =
@@ -2979,9 +3041,10 @@ mod tests {
     use near_primitives::block::{Block, Tip};
     use near_primitives::epoch_manager::block_info::BlockInfo;
     use near_primitives::errors::InvalidTxError;
-    use near_primitives::hash::hash;
+    use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::test_utils::create_test_signer;
     use near_primitives::test_utils::TestBlockBuilder;
+    use near_primitives::transaction::ExecutionOutcomeWithId;
     use near_primitives::types::{BlockHeight, EpochId, NumBlocks};
     use near_primitives::utils::index_to_bytes;
     use near_primitives::validator_signer::InMemoryValidatorSigner;
@@ -3242,6 +3305,85 @@ mod tests {
         }
     }
 
+    /// Test that garbage collection of a column kept for more epochs than other
+    /// data is delayed by the additional epochs.
+    #[test]
+    fn test_clear_old_data_delayed_column() {
+        let mut chain = get_chain_with_epoch_length(1);
+        let runtime_adapter = chain.runtime_adapter.clone();
+        let genesis = chain.get_block_by_height(0).unwrap();
+        let signer = Arc::new(create_test_signer("test1"));
+        let mut prev_block = genesis;
+        let mut blocks = vec![prev_block.clone()];
+        let outcome_id = |height: usize| hash(&height.to_le_bytes());
+        for i in 1..15 {
+            add_block_with_outcome(
+                &mut chain,
+                runtime_adapter.clone(),
+                &mut prev_block,
+                &mut blocks,
+                signer.clone(),
+                i,
+                outcome_id(i as usize),
+            );
+        }
+
+        let trie = chain.runtime_adapter.get_tries();
+        let gc_config = GCConfig {
+            gc_blocks_limit: 100,
+            gc_num_epochs_to_keep_per_column: [
+                ("TransactionResultForBlock".to_string(), 6),
+                ("OutcomeIds".to_string(), 6),
+            ]
+            .into_iter()
+            .collect(),
+            ..GCConfig::default()
+        };
+        chain.clear_data(trie.clone(), &gc_config).unwrap();
+
+        // Outcomes are kept for one more epoch, other data is garbage collected.
+        for i in 1..15 {
+            let block_hash = blocks[i].hash();
+            assert_eq!(chain.get_block(block_hash).is_ok(), i >= 8);
+            assert_eq!(chain.mut_store().get_outcomes_by_id(&outcome_id(i)).unwrap().len(), 1);
+            assert_eq!(
+                chain.mut_store().get_outcomes_by_block_hash_and_shard_id(block_hash, 0).unwrap(),
+                vec![outcome_id(i)]
+            );
+        }
+
+        let delay = chain.epoch_length as usize;
+        for i in 15..15 + delay {
+            add_block_with_outcome(
+                &mut chain,
+                runtime_adapter.clone(),
+                &mut prev_block,
+                &mut blocks,
+                signer.clone(),
+                i as BlockHeight,
+                outcome_id(i),
+            );
+        }
+        chain.clear_data(trie, &gc_config).unwrap();
+
+        for i in 1..15 + delay {
+            let block_hash = blocks[i].hash();
+            assert_eq!(chain.get_block(block_hash).is_ok(), i >= 8 + delay);
+            assert_eq!(
+                chain.mut_store().get_outcomes_by_id(&outcome_id(i)).unwrap().is_empty(),
+                i < 8
+            );
+            assert_eq!(
+                chain
+                    .mut_store()
+                    .get_outcomes_by_block_hash_and_shard_id(block_hash, 0)
+                    .unwrap()
+                    .is_empty(),
+                i < 8
+            );
+        }
+    }
+
     // Adds block to the chain at given height after prev_block.
     fn add_block(
         chain: &mut Chain,
@@ -3250,13 +3392,39 @@ mod tests {
         blocks: &mut Vec<Block>,
         signer: Arc<InMemoryValidatorSigner>,
         height: u64,
+    ) {
+        add_block_impl(chain, runtime_adapter, prev_block, blocks, signer, height, None)
+    }
+
+    // Adds block to the chain at given height after prev_block, with the
+    // chunk of shard 0 included in it and producing an outcome with given id.
+    fn add_block_with_outcome(
+        chain: &mut Chain,
+        runtime_adapter: Arc<dyn RuntimeAdapter>,
+        prev_block: &mut Block,
+        blocks: &mut Vec<Block>,
+        signer: Arc<InMemoryValidatorSigner>,
+        height: u64,
+        outcome_id: CryptoHash,
+    ) {
+        add_block_impl(chain, runtime_adapter, prev_block, blocks, signer, height, Some(outcome_id))
+    }
+
+    fn add_block_impl(
+        chain: &mut Chain,
+        runtime_adapter: Arc<dyn RuntimeAdapter>,
+        prev_block: &mut Block,
+        blocks: &mut Vec<Block>,
+        signer: Arc<InMemoryValidatorSigner>,
+        height: u64,
+        outcome_id: Option<CryptoHash>,
     ) {
         let next_epoch_id = runtime_adapter
             .get_next_epoch_id_from_prev_block(prev_block.hash())
             .expect("block must exist");
         let mut store_update = chain.mut_store().store_update();
 
-        let block = if next_epoch_id == *prev_block.header().next_epoch_id() {
+        let mut block = if next_epoch_id == *prev_block.header().next_epoch_id() {
             TestBlockBuilder::new(&prev_block, signer.clone()).height(height).build()
         } else {
             let prev_hash = prev_block.hash();
@@ -3275,6 +3443,20 @@ mod tests {
                 .next_bp_hash(next_bp_hash)
                 .build()
         };
+        if let Some(outcome_id) = outcome_id {
+            let chunks = block
+                .chunks()
+                .iter()
+                .cloned()
+                .map(|mut chunk| {
+                    *chunk.height_included_mut() = height;
+                    chunk
+                })
+                .collect();
+            block.set_chunks(chunks);
+            let outcome = ExecutionOutcomeWithId { id: outcome_id, outcome: Default::default() };
+            store_update.save_outcomes_with_proofs(block.hash(), 0, vec![outcome], vec![vec![]]);
+        }
         blocks.push(block.clone());
         store_update.save_block(block.clone());
         store_update.inc_block_refcount(block.header().prev_hash()).unwrap();
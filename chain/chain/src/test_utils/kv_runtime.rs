@@ -38,14 +38,16 @@ use near_primitives::types::{
 };
 use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use near_primitives::views::{
-    AccessKeyInfoView, AccessKeyList, CallResult, ContractCodeView, EpochValidatorInfo,
-    QueryRequest, QueryResponse, QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, AccessKeyList, AccountsWithPrefixView, CallResult, ContractCodeView,
+    EpochValidatorInfo, QueryRequest, QueryResponse, QueryResponseKind, ViewStateResult,
 };
 use near_store::{
     DBCol, PartialStorage, ShardTries, Store, StoreUpdate, Trie, TrieChanges, WrappedTrieChanges,
 };
 
-use crate::types::{ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo};
+use crate::types::{
+    ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, SimulateTransactionResult,
+};
 use crate::{BlockHeader, RuntimeAdapter};
 
 use near_primitives::epoch_manager::ShardConfig;
@@ -1196,6 +1198,15 @@ impl RuntimeAdapter for KeyValueRuntime {
                 kind: QueryResponseKind::ViewState(ViewStateResult {
                     values: Default::default(),
                     proof: vec![],
+                    next_start_after: None,
+                }),
+                block_height,
+                block_hash: *block_hash,
+            }),
+            QueryRequest::ViewAccountsWithPrefix { .. } => Ok(QueryResponse {
+                kind: QueryResponseKind::ViewAccountsWithPrefix(AccountsWithPrefixView {
+                    accounts: vec![],
+                    next_start_after: None,
                 }),
                 block_height,
                 block_hash: *block_hash,
@@ -1204,6 +1215,7 @@ impl RuntimeAdapter for KeyValueRuntime {
                 kind: QueryResponseKind::CallResult(CallResult {
                     result: Default::default(),
                     logs: Default::default(),
+                    trace: None,
                 }),
                 block_height,
                 block_hash: *block_hash,
@@ -1211,6 +1223,20 @@ impl RuntimeAdapter for KeyValueRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        _block_height: BlockHeight,
+        _block_timestamp: u64,
+        _block_hash: &CryptoHash,
+        _gas_price: Balance,
+        _random_seed: CryptoHash,
+        _state_roots: &HashMap<ShardId, StateRoot>,
+        _transaction: &SignedTransaction,
+        _verify_signature: bool,
+    ) -> Result<SimulateTransactionResult, Error> {
+        unimplemented!();
+    }
+
     fn obtain_state_part(
         &self,
         _shard_id: ShardId,
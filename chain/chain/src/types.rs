@@ -24,7 +24,7 @@ use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
 use near_primitives::types::validator_stake::{ValidatorStake, ValidatorStakeIter};
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, BlockHeightDelta, EpochId, Gas, MerkleHash, NumBlocks,
-    ShardId, StateChangesForSplitStates, StateRoot, StateRootNode,
+    RawStateChangesWithTrieKey, ShardId, StateChangesForSplitStates, StateRoot, StateRootNode,
 };
 use near_primitives::version::{
     ProtocolVersion, MIN_GAS_PRICE_NEP_92, MIN_GAS_PRICE_NEP_92_FIX, MIN_PROTOCOL_VERSION_NEP_92,
@@ -106,6 +106,16 @@ pub struct ApplyTransactionResult {
     pub processed_delayed_receipts: Vec<Receipt>,
 }
 
+/// Result of simulating a transaction with all the receipts it produced.
+pub struct SimulateTransactionResult {
+    /// Outcomes of the transaction and of its receipts in the order of execution.
+    pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// Action receipts produced by the transaction and its receipts, including refunds.
+    pub receipts: Vec<Receipt>,
+    /// State changes caused by the transaction and its receipts.
+    pub state_changes: Vec<RawStateChangesWithTrieKey>,
+}
+
 impl ApplyTransactionResult {
     /// Returns root and paths for all the outcomes in the result.
     pub fn compute_outcomes_proof(
@@ -508,6 +518,27 @@ pub trait RuntimeAdapter: EpochManagerAdapter + Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Applies the transaction and all the receipts it produces on top of the
+    /// state after the given block, in as many consecutive chunks as needed,
+    /// without writing anything to the store.  `state_roots` contains the
+    /// state roots of the shards which can be used, applying a receipt to any
+    /// other shard fails with `Error::InvalidShardId`.  The signature of the
+    /// transaction is only checked if `verify_signature` is set.  Receipts
+    /// which are already delayed aren't executed, and the simulation fails if
+    /// the receipts of the transaction are still executed after a fixed number
+    /// of blocks.
+    fn simulate_transaction(
+        &self,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        block_hash: &CryptoHash,
+        gas_price: Balance,
+        random_seed: CryptoHash,
+        state_roots: &HashMap<ShardId, StateRoot>,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<SimulateTransactionResult, Error>;
+
     /// Get the part of the state from given state root.
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
     fn obtain_state_part(
@@ -8,19 +8,22 @@ use chrono::DateTime;
 use near_primitives::time::Utc;
 
 use near_chain_configs::ProtocolConfigView;
+use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::network::PeerId;
 use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
-    AccountId, BlockHeight, BlockReference, EpochId, EpochReference, MaybeBlockId, ShardId,
-    TransactionOrReceiptId,
+    AccountId, BlockHeight, BlockHeightDelta, BlockReference, EpochId, EpochReference,
+    MaybeBlockId, ShardId, StoreKey, TransactionOrReceiptId,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, DownloadStatusView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, ShardSyncDownloadView,
+    AccountHistoryEntryView, BlockView, ChunkView, DownloadStatusView, EpochValidatorInfo,
+    ExecutionOutcomeWithIdView, FinalExecutionOutcomeViewEnum, GasPriceView,
+    LightClientBlockLiteView, LightClientBlockView, MaintenanceWindowsView, QueryRequest,
+    QueryResponse, ReceiptView, ShardSyncDownloadView, SimulatedTransactionView,
     StateChangesKindsView, StateChangesRequestView, StateChangesView, SyncStatusView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
@@ -738,6 +741,117 @@ impl Message for GetStateChangesWithCauseInBlockForTrackedShards {
     type Result = Result<HashMap<ShardId, StateChangesView>, GetStateChangesError>;
 }
 
+/// Returns the account at every block of the height range in which the
+/// account, or its contract state under `state_prefix` if given, has changed.
+pub struct GetAccountHistory {
+    pub account_id: AccountId,
+    pub from_block_height: BlockHeight,
+    pub to_block_height: BlockHeight,
+    pub state_prefix: Option<StoreKey>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetAccountHistoryError {
+    #[error("IO Error: {error_message}")]
+    IOError { error_message: String },
+    #[error("Height range from {from_block_height} to {to_block_height} is empty or longer than {max_blocks} blocks")]
+    InvalidRange {
+        from_block_height: BlockHeight,
+        to_block_height: BlockHeight,
+        max_blocks: BlockHeightDelta,
+    },
+    #[error("There are no fully synchronized blocks yet")]
+    NotSyncedYet,
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: ShardId },
+    #[error("The data for block #{block_height} is garbage collected on this node, use an archival node to fetch historical data")]
+    GarbageCollectedBlock { block_height: BlockHeight },
+    #[error("State of contract {contract_account_id} is too large to be viewed")]
+    TooLargeContractState { contract_account_id: AccountId },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetAccountHistoryError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => {
+                Self::IOError { error_message: error.to_string() }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+impl From<QueryError> for GetAccountHistoryError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::NoSyncedBlocks => Self::NotSyncedYet,
+            QueryError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            QueryError::GarbageCollectedBlock { block_height, .. } => {
+                Self::GarbageCollectedBlock { block_height }
+            }
+            QueryError::TooLargeContractState { contract_account_id, .. } => {
+                Self::TooLargeContractState { contract_account_id }
+            }
+            QueryError::InternalError { error_message } => Self::IOError { error_message },
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+impl Message for GetAccountHistory {
+    type Result = Result<Vec<AccountHistoryEntryView>, GetAccountHistoryError>;
+}
+
+/// Applies a transaction and all the receipts it produces on top of the latest
+/// state without persisting anything.
+pub struct SimulateTransaction {
+    pub transaction: SignedTransaction,
+    /// Unset for unsigned transactions, whose signature is left empty.
+    pub verify_signature: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulateTransactionError {
+    #[error("IO Error: {error_message}")]
+    IOError { error_message: String },
+    #[error("Transaction is invalid: {context:?}")]
+    InvalidTransaction { context: InvalidTxError },
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: ShardId },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for SimulateTransactionError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => {
+                Self::IOError { error_message: error.to_string() }
+            }
+            near_chain_primitives::Error::InvalidShardId(requested_shard_id) => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+impl Message for SimulateTransaction {
+    type Result = Result<SimulatedTransactionView, SimulateTransactionError>;
+}
+
 pub struct GetExecutionOutcome {
     pub id: TransactionOrReceiptId,
 }
@@ -134,6 +134,9 @@ pub enum ProcessTxResponse {
     /// The node being queried does not track the shard needed and therefore cannot provide userful
     /// response.
     DoesNotTrackShard,
+    /// The transaction pool of the shard is full and the transaction isn't
+    /// allowed to evict any other transaction.
+    TransactionPoolFull,
 }
 
 pub struct Adapter {
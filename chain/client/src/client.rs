@@ -6,6 +6,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use borsh::BorshDeserialize;
 use lru::LruCache;
 use near_chunks::client::{ClientAdapterForShardsManager, ShardedTransactionPool};
 use near_chunks::logic::{
@@ -30,6 +31,7 @@ use near_chunks::ShardsManager;
 use near_network::types::{
     HighestHeightPeerInfo, NetworkRequests, PeerManagerAdapter, ReasonForBan,
 };
+use near_pool::InsertTransactionResult;
 use near_primitives::block::{Approval, ApprovalInner, ApprovalMessage, Block, BlockHeader, Tip};
 use near_primitives::challenge::{Challenge, ChallengeBody};
 use near_primitives::hash::CryptoHash;
@@ -39,12 +41,13 @@ use near_primitives::sharding::{
     ChunkHash, EncodedShardChunk, PartialEncodedChunk, ReedSolomonWrapper, ShardChunk,
     ShardChunkHeader, ShardInfo,
 };
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{verify_transaction_signatures, SignedTransaction};
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{AccountId, ApprovalStake, BlockHeight, EpochId, NumBlocks, ShardId};
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::MaybeValidated;
 use near_primitives::validator_signer::ValidatorSigner;
+use near_store::DBCol;
 
 use crate::adapter::ProcessTxResponse;
 use crate::debug::BlockProductionTracker;
@@ -58,6 +61,7 @@ use near_client_primitives::types::{Error, ShardSyncDownload, ShardSyncStatus};
 use near_network::types::{AccountKeys, ChainInfo, PeerManagerMessageRequest, SetChainInfo};
 use near_o11y::{log_assert, WithSpanContextExt};
 use near_primitives::block_header::ApprovalType;
+use near_primitives::checked_feature;
 use near_primitives::epoch_manager::RngSeed;
 use near_primitives::network::PeerId;
 use near_primitives::version::PROTOCOL_VERSION;
@@ -205,7 +209,8 @@ impl Client {
             chain.store().new_read_only_chunks_store(),
             chain.head().ok(),
         );
-        let sharded_tx_pool = ShardedTransactionPool::new(rng_seed);
+        let sharded_tx_pool =
+            ShardedTransactionPool::new(rng_seed, config.transaction_pool.clone());
         let sync_status = SyncStatus::AwaitingPeers;
         let genesis_block = chain.genesis_block();
         let epoch_sync = EpochSync::new(
@@ -232,7 +237,12 @@ impl Client {
         );
         let block_sync =
             BlockSync::new(network_adapter.clone(), config.block_fetch_horizon, config.archive);
-        let state_sync = StateSync::new(network_adapter.clone(), config.state_sync_timeout);
+        let state_sync = StateSync::new(
+            network_adapter.clone(),
+            config.state_sync_timeout,
+            &config.chain_id,
+            &config.state_sync,
+        );
         let num_block_producer_seats = config.num_block_producer_seats as usize;
         let data_parts = runtime_adapter.num_data_parts();
         let parity_parts = runtime_adapter.num_total_parts() - data_parts;
@@ -1895,11 +1905,92 @@ impl Client {
         is_forwarded: bool,
         check_only: bool,
     ) -> ProcessTxResponse {
-        unwrap_or_return!(self.process_tx_internal(&tx, is_forwarded, check_only), {
-            let me = self.validator_signer.as_ref().map(|vs| vs.validator_id());
-            warn!(target: "client", "I'm: {:?} Dropping tx: {:?}", me, tx);
-            ProcessTxResponse::NoResponse
-        })
+        self.process_tx_with_signature(tx, is_forwarded, check_only, false)
+    }
+
+    /// Processes transactions like `process_tx`, but verifies their signatures as a batch (see
+    /// `Signature::verify_batch`) once batch verification is enabled by the protocol, so that
+    /// the pool admits exactly the transactions which pass verification when the chunk is
+    /// applied.  Transactions failing the batch are verified again one by one.
+    pub fn process_txs(
+        &mut self,
+        txs: Vec<SignedTransaction>,
+        is_forwarded: bool,
+        check_only: bool,
+    ) -> Vec<ProcessTxResponse> {
+        // Same protocol version as the one transactions are validated with.
+        let protocol_version = (|| {
+            let head = self.chain.head()?;
+            let epoch_id =
+                self.runtime_adapter.get_epoch_id_from_prev_block(&head.last_block_hash)?;
+            self.runtime_adapter.get_epoch_protocol_version(&epoch_id)
+        })();
+        let batch_enabled = protocol_version.map_or(false, |protocol_version| {
+            checked_feature!(
+                "protocol_feature_batch_signature_verification",
+                BatchSignatureVerification,
+                protocol_version
+            )
+        });
+        let signatures_verified = if batch_enabled {
+            verify_transaction_signatures(&txs)
+        } else {
+            vec![false; txs.len()]
+        };
+        txs.into_iter()
+            .zip(signatures_verified)
+            .map(|(tx, signature_verified)| {
+                self.process_tx_with_signature(tx, is_forwarded, check_only, signature_verified)
+            })
+            .collect()
+    }
+
+    /// Processes a transaction, skipping signature verification if `signature_verified` is set.
+    fn process_tx_with_signature(
+        &mut self,
+        tx: SignedTransaction,
+        is_forwarded: bool,
+        check_only: bool,
+        signature_verified: bool,
+    ) -> ProcessTxResponse {
+        unwrap_or_return!(
+            self.process_tx_internal(&tx, is_forwarded, check_only, signature_verified),
+            {
+                let me = self.validator_signer.as_ref().map(|vs| vs.validator_id());
+                warn!(target: "client", "I'm: {:?} Dropping tx: {:?}", me, tx);
+                ProcessTxResponse::NoResponse
+            }
+        )
+    }
+
+    /// Saves the transactions in the pool to the database, replacing the
+    /// previously saved ones, so that they can be restored after a restart.
+    pub fn save_transaction_pool(&self) -> std::io::Result<()> {
+        let mut store_update = self.chain.store().store().store_update();
+        store_update.delete_all(DBCol::TransactionPool);
+        for (shard_id, transactions) in self.sharded_tx_pool.transactions_by_shard() {
+            store_update.set_ser(DBCol::TransactionPool, &shard_id.to_le_bytes(), &transactions)?;
+        }
+        store_update.commit()
+    }
+
+    /// Re-adds the transactions saved by `save_transaction_pool` to the pool.
+    /// They are processed like forwarded transactions, i.e. validated against
+    /// the current head, so expired and no longer valid ones are dropped.
+    pub fn restore_transaction_pool(&mut self) -> std::io::Result<()> {
+        let mut transactions = vec![];
+        for item in self.chain.store().store().iter(DBCol::TransactionPool) {
+            let (_, value) = item?;
+            transactions.extend(Vec::<SignedTransaction>::try_from_slice(&value)?);
+        }
+        let total = transactions.len();
+        let restored = self
+            .process_txs(transactions, true, false)
+            .into_iter()
+            .filter(|response| response == &ProcessTxResponse::ValidTx)
+            .count();
+        info!(target: "client", restored, dropped = total - restored, "Restored transaction pool");
+        Ok(())
     }
 
     /// If we are close to epoch boundary, return next epoch id, otherwise return None.
@@ -1935,11 +2026,13 @@ impl Client {
     }
 
     /// Process transaction and either add it to the mempool or return to redirect to another validator.
+    /// The signature isn't checked if `signature_verified` is set.
     fn process_tx_internal(
         &mut self,
         tx: &SignedTransaction,
         is_forwarded: bool,
         check_only: bool,
+        signature_verified: bool,
     ) -> Result<ProcessTxResponse, Error> {
         let head = self.chain.head()?;
         let me = self.validator_signer.as_ref().map(|vs| vs.validator_id());
@@ -1963,7 +2056,7 @@ impl Client {
 
         if let Some(err) = self
             .runtime_adapter
-            .validate_tx(gas_price, None, tx, true, &epoch_id, protocol_version)
+            .validate_tx(gas_price, None, tx, !signature_verified, &epoch_id, protocol_version)
             .expect("no storage errors")
         {
             debug!(target: "client", "Invalid tx during basic validation: {:?}", err);
@@ -2004,8 +2097,8 @@ impl Client {
                 // TODO #6713: Transactions don't need to be recorded if the node is not a validator
                 // for the shard.
                 // If I'm not an active validator I should forward tx to next validators.
-                self.sharded_tx_pool.insert_transaction(shard_id, tx.clone());
-                trace!(target: "client", shard_id, "Recorded a transaction.");
+                let insert_result = self.sharded_tx_pool.insert_transaction(shard_id, tx.clone());
+                trace!(target: "client", shard_id, ?insert_result, "Recorded a transaction.");
 
                 // Active validator:
                 //   possibly forward to next epoch validators
@@ -2016,6 +2109,9 @@ impl Client {
                     trace!(target: "client", account = ?me, shard_id, is_forwarded, "Recording a transaction.");
                     metrics::TRANSACTION_RECEIVED_VALIDATOR.inc();
 
+                    if insert_result == InsertTransactionResult::NoSpaceLeft {
+                        return Ok(ProcessTxResponse::TransactionPoolFull);
+                    }
                     if !is_forwarded {
                         self.possibly_forward_tx_to_next_epoch(tx)?;
                     }
@@ -2118,11 +2214,18 @@ impl Client {
                 }
             };
             let state_sync_timeout = self.config.state_sync_timeout;
+            let chain_id = &self.config.chain_id;
+            let state_sync_config = &self.config.state_sync;
             let epoch_id = self.chain.get_block(&sync_hash)?.header().epoch_id().clone();
             let (state_sync, new_shard_sync, blocks_catch_up_state) =
                 self.catchup_state_syncs.entry(sync_hash).or_insert_with(|| {
                     (
-                        StateSync::new(network_adapter1, state_sync_timeout),
+                        StateSync::new(
+                            network_adapter1,
+                            state_sync_timeout,
+                            chain_id,
+                            state_sync_config,
+                        ),
                         new_shard_sync,
                         BlocksCatchUpState::new(sync_hash, epoch_id),
                     )
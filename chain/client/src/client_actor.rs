@@ -105,6 +105,7 @@ pub struct ClientActor {
     doomslug_timer_next_attempt: DateTime<Utc>,
     sync_timer_next_attempt: DateTime<Utc>,
     chunk_request_retry_next_attempt: DateTime<Utc>,
+    transaction_pool_snapshot_next_attempt: DateTime<Utc>,
     sync_started: bool,
     state_parts_task_scheduler: Box<dyn Fn(ApplyStatePartsRequest)>,
     block_catch_up_scheduler: Box<dyn Fn(BlockCatchUpRequest)>,
@@ -208,6 +209,7 @@ impl ClientActor {
             doomslug_timer_next_attempt: now,
             sync_timer_next_attempt: now,
             chunk_request_retry_next_attempt: now,
+            transaction_pool_snapshot_next_attempt: now,
             sync_started: false,
             state_parts_task_scheduler: create_sync_job_scheduler::<ApplyStatePartsRequest>(
                 sync_jobs_actor_addr.clone(),
@@ -251,6 +253,11 @@ impl Actor for ClientActor {
     type Context = Context<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
+        // Re-add transactions which were in the pool before the restart.
+        if let Err(err) = self.client.restore_transaction_pool() {
+            error!(target: "client", ?err, "Failed to restore transaction pool");
+        }
+
         // Start syncing job.
         self.start_sync(ctx);
 
@@ -1198,6 +1205,21 @@ impl ClientActor {
                 .unwrap_or(delay),
         );
 
+        self.transaction_pool_snapshot_next_attempt = self.run_timer(
+            self.client.config.transaction_pool.snapshot_period,
+            self.transaction_pool_snapshot_next_attempt,
+            ctx,
+            |act, _ctx| act.save_transaction_pool(),
+            "save_transaction_pool",
+        );
+        delay = core::cmp::min(
+            delay,
+            self.transaction_pool_snapshot_next_attempt
+                .signed_duration_since(now)
+                .to_std()
+                .unwrap_or(delay),
+        );
+
         self.chunk_request_retry_next_attempt = self.run_timer(
             self.client.config.chunk_request_retry_period,
             self.chunk_request_retry_next_attempt,
@@ -1712,6 +1734,12 @@ impl ClientActor {
         }
     }
 
+    fn save_transaction_pool(&self) {
+        if let Err(err) = self.client.save_transaction_pool() {
+            error!(target: "client", ?err, "Failed to save transaction pool");
+        }
+    }
+
     /// Print current summary.
     fn log_summary(&mut self) {
         let _span = tracing::debug_span!(target: "client", "log_summary").entered();
@@ -1724,6 +1752,7 @@ impl Drop for ClientActor {
     fn drop(&mut self) {
         let _span = tracing::debug_span!(target: "client", "drop").entered();
         self.state_parts_client_arbiter.stop();
+        self.save_transaction_pool();
     }
 }
 
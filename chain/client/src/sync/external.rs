@@ -0,0 +1,228 @@
+//! Access to state parts dumped to an external storage.
+//!
+//! Nodes configured with `state_sync.dump` write parts of the state of each
+//! shard they track at the beginning of every epoch to the external storage
+//! (see `nearcore::state_sync`).  Nodes configured with `state_sync.sync_from`
+//! download the parts from there during state sync instead of requesting them
+//! from peers.  Parts are validated against the state root from the state
+//! header just like parts received from peers.
+//!
+//! The parts are stored under the following keys:
+//!
+//! ```text
+//! chain_id={chain_id}/epoch_id={epoch_id}/shard_id={shard_id}/state_part_{part_id:06}_of_{num_parts:06}
+//! ```
+//!
+//! where `epoch_id` is the id of the epoch whose first block is the sync hash.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hyper::body::HttpBody;
+use hyper::client::HttpConnector;
+use hyper_tls::HttpsConnector;
+
+use near_chain_configs::ExternalStorageLocation;
+use near_primitives::types::{EpochId, ShardId};
+
+/// Time limit for a single request to S3, including reading the response.
+const S3_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Largest state part accepted from an external storage.  Parts are split by
+/// the memory usage of their trie nodes (see `get_num_state_parts`) so they are
+/// usually around 1 MiB, but a part may contain several large values.
+pub const MAX_EXTERNAL_PART_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExternalStorageError {
+    #[error("{0}")]
+    IO(#[from] std::io::Error),
+    #[error("{0}")]
+    Http(#[from] hyper::Error),
+    #[error("{0}")]
+    HttpRequest(#[from] hyper::http::Error),
+    #[error("unexpected HTTP status {0} for {1}")]
+    HttpStatus(hyper::StatusCode, String),
+    #[error("request for {0} timed out")]
+    Timeout(String),
+    #[error("object {0} is larger than {MAX_EXTERNAL_PART_SIZE} bytes")]
+    TooLarge(String),
+}
+
+/// Returns key under which given state part is stored in external storage.
+pub fn external_storage_location(
+    chain_id: &str,
+    epoch_id: &EpochId,
+    shard_id: ShardId,
+    part_id: u64,
+    num_parts: u64,
+) -> String {
+    format!(
+        "chain_id={}/epoch_id={}/shard_id={}/state_part_{:06}_of_{:06}",
+        chain_id, epoch_id.0, shard_id, part_id, num_parts
+    )
+}
+
+/// Connection to an external storage holding state parts.
+pub enum ExternalConnection {
+    Filesystem { root_dir: PathBuf },
+    S3 { client: hyper::Client<HttpsConnector<HttpConnector>>, endpoint: String, bucket: String },
+}
+
+impl ExternalConnection {
+    pub fn new(location: &ExternalStorageLocation) -> Self {
+        match location {
+            ExternalStorageLocation::Filesystem { root_dir } => {
+                Self::Filesystem { root_dir: root_dir.clone() }
+            }
+            ExternalStorageLocation::S3 { endpoint, bucket } => Self::S3 {
+                client: hyper::Client::builder().build(HttpsConnector::new()),
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                bucket: bucket.clone(),
+            },
+        }
+    }
+
+    /// Uses path-style URLs which are supported by all S3-compatible stores.
+    fn s3_url(endpoint: &str, bucket: &str, key: &str) -> String {
+        format!("{}/{}/{}", endpoint, bucket, key)
+    }
+
+    /// Runs given S3 request failing if it doesn't complete within
+    /// `S3_REQUEST_TIMEOUT`.
+    async fn with_timeout<T>(
+        url: &str,
+        request: impl std::future::Future<Output = Result<T, ExternalStorageError>>,
+    ) -> Result<T, ExternalStorageError> {
+        tokio::time::timeout(S3_REQUEST_TIMEOUT, request)
+            .await
+            .map_err(|_| ExternalStorageError::Timeout(url.to_string()))?
+    }
+
+    /// Reads the body of the response failing if it is larger than
+    /// `MAX_EXTERNAL_PART_SIZE`.
+    async fn read_body(url: &str, mut body: hyper::Body) -> Result<Vec<u8>, ExternalStorageError> {
+        if body.size_hint().lower() > MAX_EXTERNAL_PART_SIZE as u64 {
+            return Err(ExternalStorageError::TooLarge(url.to_string()));
+        }
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk?;
+            if data.len() + chunk.len() > MAX_EXTERNAL_PART_SIZE {
+                return Err(ExternalStorageError::TooLarge(url.to_string()));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+
+    /// Reads object stored under given key.
+    pub async fn get(&self, key: &str) -> Result<Vec<u8>, ExternalStorageError> {
+        match self {
+            Self::Filesystem { root_dir } => {
+                let path = root_dir.join(key);
+                if tokio::fs::metadata(&path).await?.len() > MAX_EXTERNAL_PART_SIZE as u64 {
+                    return Err(ExternalStorageError::TooLarge(path.display().to_string()));
+                }
+                Ok(tokio::fs::read(path).await?)
+            }
+            Self::S3 { client, endpoint, bucket } => {
+                let url = Self::s3_url(endpoint, bucket, key);
+                Self::with_timeout(&url, async {
+                    let request = hyper::Request::get(&url).body(hyper::Body::empty())?;
+                    let response = client.request(request).await?;
+                    if !response.status().is_success() {
+                        return Err(ExternalStorageError::HttpStatus(
+                            response.status(),
+                            url.clone(),
+                        ));
+                    }
+                    Self::read_body(&url, response.into_body()).await
+                })
+                .await
+            }
+        }
+    }
+
+    /// Stores object under given key overwriting existing one if any.
+    pub async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), ExternalStorageError> {
+        match self {
+            Self::Filesystem { root_dir } => {
+                let path = root_dir.join(key);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                // Write to a temporary file first so that readers never see
+                // partially written parts.
+                let tmp_path = path.with_extension("tmp");
+                tokio::fs::write(&tmp_path, data).await?;
+                Ok(tokio::fs::rename(&tmp_path, &path).await?)
+            }
+            Self::S3 { client, endpoint, bucket } => {
+                let url = Self::s3_url(endpoint, bucket, key);
+                Self::with_timeout(&url, async {
+                    let request = hyper::Request::put(&url).body(hyper::Body::from(data))?;
+                    let response = client.request(request).await?;
+                    if !response.status().is_success() {
+                        return Err(ExternalStorageError::HttpStatus(
+                            response.status(),
+                            url.clone(),
+                        ));
+                    }
+                    Ok(())
+                })
+                .await
+            }
+        }
+    }
+
+    /// Checks whether an object is stored under given key.
+    pub async fn exists(&self, key: &str) -> Result<bool, ExternalStorageError> {
+        match self {
+            Self::Filesystem { root_dir } => match tokio::fs::metadata(root_dir.join(key)).await {
+                Ok(_) => Ok(true),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+                Err(err) => Err(err.into()),
+            },
+            Self::S3 { client, endpoint, bucket } => {
+                let url = Self::s3_url(endpoint, bucket, key);
+                Self::with_timeout(&url, async {
+                    let request = hyper::Request::head(&url).body(hyper::Body::empty())?;
+                    let response = client.request(request).await?;
+                    match response.status() {
+                        status if status.is_success() => Ok(true),
+                        hyper::StatusCode::NOT_FOUND => Ok(false),
+                        status => Err(ExternalStorageError::HttpStatus(status, url.clone())),
+                    }
+                })
+                .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filesystem_connection() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let location = ExternalStorageLocation::Filesystem { root_dir: tmp_dir.path().into() };
+        let conn = ExternalConnection::new(&location);
+        let key = external_storage_location("unittest", &EpochId::default(), 1, 2, 10);
+        assert_eq!(
+            key,
+            "chain_id=unittest/epoch_id=11111111111111111111111111111111/shard_id=1/state_part_000002_of_000010"
+        );
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            assert!(!conn.exists(&key).await.unwrap());
+            assert!(conn.get(&key).await.is_err());
+            conn.put(&key, b"part".to_vec()).await.unwrap();
+            assert!(conn.exists(&key).await.unwrap());
+            assert_eq!(b"part".to_vec(), conn.get(&key).await.unwrap());
+        });
+    }
+}
@@ -19,13 +19,16 @@
 //!         Currently validators defend against it, by having a rate limiters - but we should improve the algorithm
 //!         here to depend more on local peers instead.
 //!
+//! Alternatively, if `state_sync.sync_from` is configured, the parts are downloaded from an external storage where
+//! other nodes dumped them (see the `external` module).  The header is still requested from the peers.
+//!
 
 use near_chain::{near_chain_primitives, Error};
 use near_primitives::state_part::PartId;
 use std::collections::HashMap;
 use std::ops::Add;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration as TimeDuration;
 
 use ansi_term::Color::{Purple, Yellow};
@@ -42,9 +45,10 @@ use near_network::types::{
 use near_primitives::hash::CryptoHash;
 use near_primitives::syncing::{get_num_state_parts, ShardStateSyncResponse};
 use near_primitives::time::{Clock, Utc};
-use near_primitives::types::{AccountId, ShardId, StateRoot};
+use near_primitives::types::{AccountId, EpochId, ShardId, StateRoot};
 
 use near_chain::chain::{ApplyStatePartsRequest, StateSplitRequest};
+use near_chain_configs::StateSyncConfig;
 use near_client_primitives::types::{
     DownloadStatus, ShardSyncDownload, ShardSyncStatus, StateSplitApplyingStatus,
 };
@@ -53,11 +57,15 @@ use near_network::types::PeerManagerMessageRequest;
 use near_o11y::WithSpanContextExt;
 use near_primitives::shard_layout::ShardUId;
 
+use crate::sync::external::{external_storage_location, ExternalConnection};
+
 /// Maximum number of state parts to request per peer on each round when node is trying to download the state.
 pub const MAX_STATE_PART_REQUEST: u64 = 16;
 /// Number of state parts already requested stored as pending.
 /// This number should not exceed MAX_STATE_PART_REQUEST times (number of peers in the network).
 pub const MAX_PENDING_PART: u64 = MAX_STATE_PART_REQUEST * 10000;
+/// Maximum number of state parts being downloaded from an external storage at the same time.
+pub const MAX_EXTERNAL_PART_REQUESTS: usize = 64;
 
 pub enum StateSyncResult {
     /// No shard has changed its status
@@ -97,6 +105,118 @@ fn make_account_or_peer_id_or_hash(
     }
 }
 
+/// Counts a download from an external storage as in flight until dropped, so
+/// that the counter is decremented however the download ends.
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl InFlightGuard {
+    fn new(in_flight: Arc<AtomicUsize>) -> Self {
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Self(in_flight)
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Downloads state parts from an external storage.
+struct ExternalStateSync {
+    conn: Arc<ExternalConnection>,
+    chain_id: String,
+    /// Whether every other request for a part should go to peers.
+    fallback_to_peers: bool,
+    /// Number of parts being downloaded at the moment.
+    in_flight: Arc<AtomicUsize>,
+    /// Parts which have been downloaded but haven't been validated and saved yet.
+    downloaded_parts: Arc<Mutex<HashMap<(CryptoHash, ShardId, u64), Vec<u8>>>>,
+}
+
+impl ExternalStateSync {
+    /// Starts downloading parts which need to be requested (see `fallback_to_peers`).
+    fn request_parts(
+        &self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        epoch_id: &EpochId,
+        shard_sync_download: &mut ShardSyncDownload,
+    ) {
+        let num_parts = shard_sync_download.downloads.len() as u64;
+        let available =
+            MAX_EXTERNAL_PART_REQUESTS.saturating_sub(self.in_flight.load(Ordering::SeqCst));
+        for (part_id, download) in shard_sync_download
+            .downloads
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, download)| download.run_me.load(Ordering::SeqCst))
+            .filter(|(_, download)| {
+                !self.fallback_to_peers || download.state_requests_count % 2 == 0
+            })
+            .take(available)
+        {
+            download.run_me.store(false, Ordering::SeqCst);
+            download.state_requests_count += 1;
+            download.last_target = None;
+
+            let part_id = part_id as u64;
+            let key =
+                external_storage_location(&self.chain_id, epoch_id, shard_id, part_id, num_parts);
+            let conn = self.conn.clone();
+            let in_flight = InFlightGuard::new(self.in_flight.clone());
+            let downloaded_parts = self.downloaded_parts.clone();
+            near_performance_metrics::actix::spawn(std::any::type_name::<Self>(), async move {
+                let _in_flight = in_flight;
+                match conn.get(&key).await {
+                    Ok(data) => {
+                        downloaded_parts
+                            .lock()
+                            .unwrap()
+                            .insert((sync_hash, shard_id, part_id), data);
+                    }
+                    // The part will be requested again once the request times out.
+                    Err(err) => {
+                        warn!(target: "sync", %key, %err, "Failed to download state part from external storage")
+                    }
+                }
+            });
+        }
+    }
+
+    /// Validates and saves parts which have been downloaded.
+    fn save_downloaded_parts(
+        &self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        chain: &mut Chain,
+        shard_sync_download: &mut ShardSyncDownload,
+    ) {
+        let num_parts = shard_sync_download.downloads.len() as u64;
+        let mut downloaded_parts = self.downloaded_parts.lock().unwrap();
+        for (part_id, download) in shard_sync_download.downloads.iter_mut().enumerate() {
+            let part_id = part_id as u64;
+            let data = match downloaded_parts.remove(&(sync_hash, shard_id, part_id)) {
+                Some(data) => data,
+                None => continue,
+            };
+            if download.done {
+                continue;
+            }
+            match chain.set_state_part(shard_id, sync_hash, PartId::new(part_id, num_parts), &data)
+            {
+                Ok(()) => {
+                    download.done = true;
+                }
+                Err(err) => {
+                    error!(target: "sync", "State sync set_state_part error for part downloaded from external storage, shard = {}, part = {}, hash = {}: {:?}", shard_id, part_id, sync_hash, err);
+                    download.error = true;
+                }
+            }
+        }
+    }
+}
+
 /// Helper to track state sync.
 pub struct StateSync {
     network_adapter: Arc<dyn PeerManagerAdapter>,
@@ -116,10 +236,25 @@ pub struct StateSync {
 
     /// Maps shard_id to result of splitting state for resharding
     split_state_roots: HashMap<ShardId, Result<HashMap<ShardUId, StateRoot>, Error>>,
+
+    /// Set if state parts are downloaded from an external storage.
+    external: Option<ExternalStateSync>,
 }
 
 impl StateSync {
-    pub fn new(network_adapter: Arc<dyn PeerManagerAdapter>, timeout: TimeDuration) -> Self {
+    pub fn new(
+        network_adapter: Arc<dyn PeerManagerAdapter>,
+        timeout: TimeDuration,
+        chain_id: &str,
+        state_sync_config: &StateSyncConfig,
+    ) -> Self {
+        let external = state_sync_config.sync_from.as_ref().map(|location| ExternalStateSync {
+            conn: Arc::new(ExternalConnection::new(location)),
+            chain_id: chain_id.to_string(),
+            fallback_to_peers: state_sync_config.fallback_to_peers,
+            in_flight: Default::default(),
+            downloaded_parts: Default::default(),
+        });
         StateSync {
             network_adapter,
             last_time_block_requested: None,
@@ -128,6 +263,7 @@ impl StateSync {
             timeout: Duration::from_std(timeout).unwrap(),
             state_parts_apply_results: HashMap::new(),
             split_state_roots: HashMap::new(),
+            external,
         }
     }
 
@@ -244,6 +380,14 @@ impl StateSync {
                 }
                 ShardSyncStatus::StateDownloadParts => {
                     // Step 2 - download all the parts (each part is usually around 1MB).
+                    if let Some(external) = &self.external {
+                        external.save_downloaded_parts(
+                            shard_id,
+                            sync_hash,
+                            chain,
+                            shard_sync_download,
+                        );
+                    }
                     let mut parts_done = true;
                     for part_download in shard_sync_download.downloads.iter_mut() {
                         if !part_download.done {
@@ -577,6 +721,15 @@ impl StateSync {
         shard_sync_download: ShardSyncDownload,
         highest_height_peers: &[HighestHeightPeerInfo],
     ) -> Result<ShardSyncDownload, near_chain::Error> {
+        let mut new_shard_sync_download = shard_sync_download.clone();
+
+        if let (ShardSyncStatus::StateDownloadParts, Some(external)) =
+            (&shard_sync_download.status, &self.external)
+        {
+            let epoch_id = chain.get_block_header(&sync_hash)?.epoch_id().clone();
+            external.request_parts(shard_id, sync_hash, &epoch_id, &mut new_shard_sync_download);
+        }
+
         let possible_targets = self.possible_targets(
             me,
             shard_id,
@@ -588,11 +741,10 @@ impl StateSync {
 
         if possible_targets.is_empty() {
             // In most cases it means that all the targets are currently busy (that we have a pending request with them).
-            return Ok(shard_sync_download);
+            return Ok(new_shard_sync_download);
         }
 
         // Downloading strategy starts here
-        let mut new_shard_sync_download = shard_sync_download.clone();
 
         match shard_sync_download.status {
             ShardSyncStatus::StateDownloadHeader => {
@@ -635,11 +787,21 @@ impl StateSync {
                 // to request the part from.
                 // IMPORTANT: here we use 'zip' with possible_target_sampler - which is limited. So at any moment we'll not request more than
                 // possible_targets.len() * MAX_STATE_PART_REQUEST parts.
+                // When syncing from an external storage, only the requests `ExternalStateSync::request_parts` leaves to peers are
+                // sent; parts it didn't get to because of its limit wait for its next call.
+                let fallback_to_peers =
+                    self.external.as_ref().map(|external| external.fallback_to_peers);
                 for ((part_id, download), target) in new_shard_sync_download
                     .downloads
                     .iter_mut()
                     .enumerate()
                     .filter(|(_, download)| download.run_me.load(Ordering::SeqCst))
+                    .filter(|(_, download)| match fallback_to_peers {
+                        None => true,
+                        Some(fallback_to_peers) => {
+                            fallback_to_peers && download.state_requests_count % 2 == 1
+                        }
+                    })
                     .zip(possible_targets_sampler)
                 {
                     self.sent_request_part(target.clone(), part_id as u64, shard_id, sync_hash);
@@ -879,6 +1041,7 @@ mod test {
     use actix::System;
     use near_actix_test_utils::run_actix;
     use near_chain::{test_utils::process_block_sync, BlockProcessingArtifact, Provenance};
+    use near_chain_configs::ExternalStorageLocation;
 
     use near_epoch_manager::EpochManagerAdapter;
     use near_network::test_utils::MockPeerManagerAdapter;
@@ -896,7 +1059,12 @@ mod test {
     // Start a new state sync - and check that it asks for a header.
     fn test_ask_for_header() {
         let mock_peer_manager = Arc::new(MockPeerManagerAdapter::default());
-        let mut state_sync = StateSync::new(mock_peer_manager.clone(), TimeDuration::from_secs(1));
+        let mut state_sync = StateSync::new(
+            mock_peer_manager.clone(),
+            TimeDuration::from_secs(1),
+            "chain_id",
+            &StateSyncConfig::default(),
+        );
         let mut new_shard_sync = HashMap::new();
 
         let (mut chain, kv, signer) = test_utils::setup();
@@ -1005,4 +1173,85 @@ mod test {
             System::current().stop()
         });
     }
+
+    #[test]
+    // Parts which aren't downloaded from the external storage because of the limit on concurrent
+    // downloads are left for later rather than requested from peers.
+    fn test_external_storage_without_fallback_to_peers() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let mock_peer_manager = Arc::new(MockPeerManagerAdapter::default());
+        let mut state_sync = StateSync::new(
+            mock_peer_manager.clone(),
+            TimeDuration::from_secs(1),
+            "chain_id",
+            &StateSyncConfig {
+                sync_from: Some(ExternalStorageLocation::Filesystem {
+                    root_dir: tmp_dir.path().into(),
+                }),
+                fallback_to_peers: false,
+                ..StateSyncConfig::default()
+            },
+        );
+
+        let (mut chain, kv, signer) = test_utils::setup();
+        let genesis = chain.get_block(&chain.head().unwrap().last_block_hash).unwrap();
+        let block = TestBlockBuilder::new(&genesis, signer).build();
+        let sync_hash = *block.hash();
+        process_block_sync(
+            &mut chain,
+            &None,
+            block.into(),
+            Provenance::PRODUCED,
+            &mut BlockProcessingArtifact::default(),
+        )
+        .unwrap();
+        let runtime_adapter = kv as Arc<dyn RuntimeAdapter>;
+
+        let now = Clock::utc();
+        let shard_sync_download = ShardSyncDownload {
+            downloads: vec![
+                DownloadStatus {
+                    start_time: now,
+                    prev_update_time: now,
+                    run_me: Arc::new(AtomicBool::new(true)),
+                    error: false,
+                    done: false,
+                    state_requests_count: 0,
+                    last_target: None,
+                };
+                MAX_EXTERNAL_PART_REQUESTS + 10
+            ],
+            status: ShardSyncStatus::StateDownloadParts,
+        };
+
+        run_actix(async {
+            // There are peers to request the parts from.
+            assert!(!state_sync
+                .possible_targets(&None, 0, &chain, &runtime_adapter, sync_hash, &[])
+                .unwrap()
+                .is_empty());
+
+            let download = state_sync
+                .request_shard(
+                    &None,
+                    0,
+                    &chain,
+                    &runtime_adapter,
+                    sync_hash,
+                    shard_sync_download,
+                    &[],
+                )
+                .unwrap();
+
+            let requested = download.downloads.iter().filter(|d| d.state_requests_count > 0);
+            assert_eq!(requested.count(), MAX_EXTERNAL_PART_REQUESTS);
+            assert_eq!(
+                download.downloads.iter().filter(|d| d.run_me.load(Ordering::SeqCst)).count(),
+                10
+            );
+            assert!(mock_peer_manager.pop().is_none());
+
+            System::current().stop()
+        });
+    }
 }
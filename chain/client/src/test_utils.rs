@@ -246,6 +246,7 @@ pub fn setup(
         Some(signer.validator_id().clone()),
         chain_genesis.clone(),
         runtime.clone(),
+        None,
         network_adapter.clone(),
         config.clone(),
         adv.clone(),
@@ -332,6 +333,7 @@ pub fn setup_only_view(
         Some(signer.validator_id().clone()),
         chain_genesis,
         runtime,
+        None,
         network_adapter.clone(),
         config,
         adv,
@@ -1622,6 +1624,8 @@ impl TestEnv {
                     account_id,
                     prefix: vec![].into(),
                     include_proof: false,
+                    start_after: None,
+                    limit: None,
                 },
             )
             .unwrap();
@@ -1,4 +1,5 @@
 use crate::test_utils::TestEnv;
+use crate::ProcessTxResponse;
 use near_chain::{test_utils, ChainGenesis, Provenance};
 use near_crypto::{KeyType, PublicKey};
 use near_primitives::network::PeerId;
@@ -37,3 +38,31 @@ fn test_not_process_height_twice() {
     // check that we didn't rebroadcast the second block
     assert!(env.network_adapters[0].pop().is_none());
 }
+
+/// Transactions in the pool are saved to the database and re-added to the
+/// pool after a restart unless they have expired in the meantime.
+#[test]
+fn test_restore_transaction_pool() {
+    let mut chain_genesis = ChainGenesis::test();
+    chain_genesis.transaction_validity_period = 5;
+    let mut env = TestEnv::builder(chain_genesis).build();
+    let pool_len = |env: &TestEnv| -> usize {
+        env.clients[0].sharded_tx_pool.transactions_by_shard().values().map(Vec::len).sum()
+    };
+
+    assert_eq!(env.send_money(0), ProcessTxResponse::ValidTx);
+    assert_eq!(pool_len(&env), 1);
+    env.clients[0].save_transaction_pool().unwrap();
+
+    env.restart(0);
+    assert_eq!(pool_len(&env), 0);
+    env.clients[0].restore_transaction_pool().unwrap();
+    assert_eq!(pool_len(&env), 1);
+
+    env.restart(0);
+    for height in 1..=6 {
+        env.produce_block(0, height);
+    }
+    env.clients[0].restore_transaction_pool().unwrap();
+    assert_eq!(pool_len(&env), 0);
+}
@@ -5,7 +5,7 @@ use actix::{Actor, Addr, Handler, SyncArbiter, SyncContext};
 use near_primitives::receipt::Receipt;
 use near_primitives::time::Clock;
 use std::cmp::Ordering;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
@@ -18,13 +18,14 @@ use near_chain::{
 };
 use near_chain_configs::{ClientConfig, ProtocolConfigView};
 use near_client_primitives::types::{
-    Error, GetBlock, GetBlockError, GetBlockProof, GetBlockProofError, GetBlockProofResponse,
-    GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome, GetExecutionOutcomeError,
-    GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetMaintenanceWindows,
-    GetMaintenanceWindowsError, GetNextLightClientBlockError, GetProtocolConfig,
-    GetProtocolConfigError, GetReceipt, GetReceiptError, GetStateChangesError,
-    GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
-    GetValidatorInfoError, Query, QueryError, TxStatus, TxStatusError,
+    Error, GetAccountHistory, GetAccountHistoryError, GetBlock, GetBlockError, GetBlockProof,
+    GetBlockProofError, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunkError,
+    GetExecutionOutcome, GetExecutionOutcomeError, GetExecutionOutcomesForBlock, GetGasPrice,
+    GetGasPriceError, GetMaintenanceWindows, GetMaintenanceWindowsError,
+    GetNextLightClientBlockError, GetProtocolConfig, GetProtocolConfigError, GetReceipt,
+    GetReceiptError, GetStateChangesError, GetStateChangesWithCauseInBlock,
+    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfoError, Query, QueryError,
+    SimulateTransaction, SimulateTransactionError, TxStatus, TxStatusError,
 };
 #[cfg(feature = "test_features")]
 use near_network::types::NetworkAdversarialMessage;
@@ -45,16 +46,19 @@ use near_primitives::syncing::{
     ShardStateSyncResponseV2,
 };
 use near_primitives::types::{
-    AccountId, BlockHeight, BlockId, BlockReference, EpochReference, Finality, MaybeBlockId,
-    ShardId, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
+    AccountId, BlockHeight, BlockHeightDelta, BlockId, BlockReference, EpochReference, Finality,
+    MaybeBlockId, ShardId, StateChanges, StateChangesExt, StateChangesRequest, SyncCheckpoint,
+    TransactionOrReceiptId, ValidatorInfoIdentifier,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView,
-    StateChangesView,
+    AccountHistoryEntryView, AccountsWithPrefixView, BlockView, ChunkView, EpochValidatorInfo,
+    ExecutionOutcomeWithIdView, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
+    FinalExecutionOutcomeWithReceiptView, GasPriceView, LightClientBlockView,
+    MaintenanceWindowsView, QueryRequest, QueryResponse, QueryResponseKind, ReceiptView,
+    SimulatedTransactionView, StateChangesKindsView, StateChangesView,
 };
+use near_store::Store;
 
 use crate::adapter::{
     AnnounceAccountRequest, BlockHeadersRequest, BlockRequest, StateRequestHeader,
@@ -72,6 +76,9 @@ const REQUEST_WAIT_TIME: u64 = 1000;
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 
+/// Max number of blocks which can be scanned by a single account history request.
+const ACCOUNT_HISTORY_MAX_BLOCKS: BlockHeightDelta = 100;
+
 /// Request and response manager across all instances of ViewClientActor.
 pub struct ViewClientRequestManager {
     /// Transaction query that needs to be forwarded to other shards
@@ -116,22 +123,34 @@ impl ViewClientActor {
     /// Maximum number of state requests allowed per `view_client_throttle_period`.
     const MAX_NUM_STATE_REQUESTS: usize = 30;
 
+    /// Creates the actor.  If `chain_store` is given, chain data is read
+    /// from it rather than from the store of the runtime adapter.
     pub fn new(
         validator_account_id: Option<AccountId>,
         chain_genesis: &ChainGenesis,
         runtime_adapter: Arc<dyn RuntimeAdapter>,
+        chain_store: Option<Store>,
         network_adapter: Arc<dyn PeerManagerAdapter>,
         config: ClientConfig,
         request_manager: Arc<RwLock<ViewClientRequestManager>>,
         adv: crate::adversarial::Controls,
     ) -> Result<Self, Error> {
         // TODO: should we create shared ChainStore that is passed to both Client and ViewClient?
-        let chain = Chain::new_for_view_client(
-            runtime_adapter.clone(),
-            chain_genesis,
-            DoomslugThresholdMode::TwoThirds,
-            config.save_trie_changes,
-        )?;
+        let chain = match chain_store {
+            Some(store) => Chain::new_for_view_client_with_store(
+                store,
+                runtime_adapter.clone(),
+                chain_genesis,
+                DoomslugThresholdMode::TwoThirds,
+                config.save_trie_changes,
+            )?,
+            None => Chain::new_for_view_client(
+                runtime_adapter.clone(),
+                chain_genesis,
+                DoomslugThresholdMode::TwoThirds,
+                config.save_trie_changes,
+            )?,
+        };
         Ok(ViewClientActor {
             adv,
             validator_account_id,
@@ -313,6 +332,9 @@ impl ViewClientActor {
         }?;
 
         let account_id = match &msg.request {
+            QueryRequest::ViewAccountsWithPrefix { .. } => {
+                return self.query_all_shards(&header, &msg.request);
+            }
             QueryRequest::ViewAccount { account_id, .. } => account_id,
             QueryRequest::ViewState { account_id, .. } => account_id,
             QueryRequest::ViewAccessKey { account_id, .. } => account_id,
@@ -324,6 +346,16 @@ impl ViewClientActor {
             self.runtime_adapter
                 .account_id_to_shard_id(account_id, header.epoch_id())
                 .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
+        self.query_shard(&header, shard_id, &msg.request)
+    }
+
+    /// Runs a query against a single shard using the state as of the given block.
+    fn query_shard(
+        &self,
+        header: &BlockHeader,
+        shard_id: ShardId,
+        request: &QueryRequest,
+    ) -> Result<QueryResponse, QueryError> {
         let shard_uid = self
             .runtime_adapter
             .shard_id_to_uid(shard_id, header.epoch_id())
@@ -362,7 +394,7 @@ impl ViewClientActor {
             header.prev_hash(),
             header.hash(),
             header.epoch_id(),
-            &msg.request,
+            request,
         ) {
             Ok(query_response) => Ok(query_response),
             Err(query_error) => Err(match query_error {
@@ -412,6 +444,270 @@ impl ViewClientActor {
         }
     }
 
+    /// Runs a query which spans all shards and merges the per-shard responses.
+    ///
+    /// Currently only used for listing accounts by prefix.  Every shard
+    /// returns its accounts in order along with a continuation cursor if the
+    /// listing was cut short, so the merged page ends at the smallest cursor.
+    fn query_all_shards(
+        &self,
+        header: &BlockHeader,
+        request: &QueryRequest,
+    ) -> Result<QueryResponse, QueryError> {
+        let num_shards = self
+            .runtime_adapter
+            .num_shards(header.epoch_id())
+            .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?;
+        let mut accounts = vec![];
+        let mut next_start_after: Option<AccountId> = None;
+        for shard_id in 0..num_shards {
+            let response = self.query_shard(header, shard_id, request)?;
+            let page = match response.kind {
+                QueryResponseKind::ViewAccountsWithPrefix(page) => page,
+                kind => {
+                    return Err(QueryError::Unreachable {
+                        error_message: format!("unexpected query response: {:?}", kind),
+                    })
+                }
+            };
+            accounts.extend(page.accounts);
+            next_start_after = match (next_start_after, page.next_start_after) {
+                (Some(current), Some(cursor)) => Some(std::cmp::min(current, cursor)),
+                (current, cursor) => current.or(cursor),
+            };
+        }
+        if let Some(cursor) = &next_start_after {
+            accounts.retain(|account| &account.account_id <= cursor);
+        }
+        accounts.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        Ok(QueryResponse {
+            kind: QueryResponseKind::ViewAccountsWithPrefix(AccountsWithPrefixView {
+                accounts,
+                next_start_after,
+            }),
+            block_height: header.height(),
+            block_hash: *header.hash(),
+        })
+    }
+
+    /// Returns the account (and optionally its contract state under a prefix)
+    /// at every block of the height range which changed it.  Blocks are found
+    /// using the recorded state changes, so the state is only read at blocks
+    /// where it is known to differ from the previous one.
+    fn get_account_history(
+        &mut self,
+        msg: GetAccountHistory,
+    ) -> Result<Vec<AccountHistoryEntryView>, GetAccountHistoryError> {
+        let GetAccountHistory { account_id, from_block_height, to_block_height, state_prefix } =
+            msg;
+        if from_block_height > to_block_height
+            || to_block_height - from_block_height >= ACCOUNT_HISTORY_MAX_BLOCKS
+        {
+            return Err(GetAccountHistoryError::InvalidRange {
+                from_block_height,
+                to_block_height,
+                max_blocks: ACCOUNT_HISTORY_MAX_BLOCKS,
+            });
+        }
+
+        let head = self.chain.head()?;
+        if !self.config.archive
+            && from_block_height < self.runtime_adapter.get_gc_stop_height(&head.last_block_hash)
+        {
+            return Err(GetAccountHistoryError::GarbageCollectedBlock {
+                block_height: from_block_height,
+            });
+        }
+        let mut state_changes_requests =
+            vec![StateChangesRequest::AccountChanges { account_ids: vec![account_id.clone()] }];
+        if let Some(key_prefix) = &state_prefix {
+            state_changes_requests.push(StateChangesRequest::DataChanges {
+                account_ids: vec![account_id.clone()],
+                key_prefix: key_prefix.clone(),
+            });
+        }
+
+        // The range may span several epochs with different shard layouts and
+        // tracked shards, so the shard is resolved separately for every epoch.
+        let mut checked_epochs = HashSet::new();
+        let mut history = Vec::new();
+        for block_height in from_block_height..=to_block_height.min(head.height) {
+            let block_hash = match self.chain.get_block_hash_by_height(block_height) {
+                Ok(block_hash) => block_hash,
+                // The height has been skipped.
+                Err(near_chain::Error::DBNotFoundErr(_)) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let header = self.chain.get_block_header(&block_hash)?;
+            if !checked_epochs.contains(header.epoch_id()) {
+                self.check_account_history_shard(&account_id, &header)?;
+                checked_epochs.insert(header.epoch_id().clone());
+            }
+            let mut changed = false;
+            for request in &state_changes_requests {
+                if !self.chain.store().get_state_changes(&block_hash, request)?.is_empty() {
+                    changed = true;
+                    break;
+                }
+            }
+            if !changed {
+                continue;
+            }
+
+            let block_reference = BlockReference::BlockId(BlockId::Hash(block_hash));
+            let request = QueryRequest::ViewAccount { account_id: account_id.clone() };
+            let account = match self.handle_query(Query::new(block_reference.clone(), request)) {
+                Ok(QueryResponse { kind: QueryResponseKind::ViewAccount(account), .. }) => {
+                    Some(account)
+                }
+                Ok(response) => {
+                    return Err(GetAccountHistoryError::Unreachable {
+                        error_message: format!("Unexpected query response: {:?}", response.kind),
+                    })
+                }
+                Err(QueryError::UnknownAccount { .. }) => None,
+                Err(err) => return Err(err.into()),
+            };
+            let state = match (&state_prefix, &account) {
+                (Some(prefix), Some(_)) => {
+                    let request = QueryRequest::ViewState {
+                        account_id: account_id.clone(),
+                        prefix: prefix.clone(),
+                        include_proof: false,
+                        start_after: None,
+                        limit: None,
+                    };
+                    match self.handle_query(Query::new(block_reference, request))?.kind {
+                        QueryResponseKind::ViewState(state) => Some(state),
+                        kind => {
+                            return Err(GetAccountHistoryError::Unreachable {
+                                error_message: format!("Unexpected query response: {:?}", kind),
+                            })
+                        }
+                    }
+                }
+                _ => None,
+            };
+            history.push(AccountHistoryEntryView { block_height, block_hash, account, state });
+        }
+        Ok(history)
+    }
+
+    /// Checks that the shard of the account in the epoch of given block is
+    /// tracked.  State changes are only recorded for tracked shards, so
+    /// without this check we would silently return an empty history.
+    fn check_account_history_shard(
+        &self,
+        account_id: &AccountId,
+        header: &BlockHeader,
+    ) -> Result<(), GetAccountHistoryError> {
+        let shard_id =
+            self.runtime_adapter.account_id_to_shard_id(account_id, header.epoch_id()).map_err(
+                |err| GetAccountHistoryError::Unreachable { error_message: err.to_string() },
+            )?;
+        // Genesis block has no parent but belongs to the same epoch as its
+        // children.
+        let parent_hash = if header.prev_hash() == &CryptoHash::default() {
+            header.hash()
+        } else {
+            header.prev_hash()
+        };
+        if !self.runtime_adapter.cares_about_shard(
+            self.validator_account_id.as_ref(),
+            parent_hash,
+            shard_id,
+            true,
+        ) {
+            return Err(GetAccountHistoryError::UnavailableShard { requested_shard_id: shard_id });
+        }
+        Ok(())
+    }
+
+    /// Simulates the transaction on top of the state of the head block.  The
+    /// transaction is validated first so that an invalid one is reported with
+    /// the reason, like `check_tx` does.
+    fn simulate_transaction(
+        &mut self,
+        msg: SimulateTransaction,
+    ) -> Result<SimulatedTransactionView, SimulateTransactionError> {
+        let SimulateTransaction { transaction, verify_signature } = msg;
+        let header = self.maybe_block_id_to_block_header(None)?;
+        let epoch_id = header.epoch_id();
+
+        // Only the shards tracked by this node have their state available.
+        let mut state_roots = HashMap::new();
+        for shard_id in 0..self.runtime_adapter.num_shards(epoch_id)? {
+            let shard_uid = self.runtime_adapter.shard_id_to_uid(shard_id, epoch_id)?;
+            match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => {
+                    state_roots.insert(shard_id, *chunk_extra.state_root());
+                }
+                Err(near_chain::Error::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let signer_shard_id = self
+            .runtime_adapter
+            .account_id_to_shard_id(&transaction.transaction.signer_id, epoch_id)?;
+        let state_root = *state_roots.get(&signer_shard_id).ok_or(
+            SimulateTransactionError::UnavailableShard { requested_shard_id: signer_shard_id },
+        )?;
+        let protocol_version = self.runtime_adapter.get_epoch_protocol_version(epoch_id)?;
+        if let Some(context) = self.runtime_adapter.validate_tx(
+            header.gas_price(),
+            Some(state_root),
+            &transaction,
+            verify_signature,
+            epoch_id,
+            protocol_version,
+        )? {
+            return Err(SimulateTransactionError::InvalidTransaction { context });
+        }
+
+        let result = self.runtime_adapter.simulate_transaction(
+            header.height(),
+            header.raw_timestamp(),
+            header.hash(),
+            header.gas_price(),
+            *header.random_value(),
+            &state_roots,
+            &transaction,
+            verify_signature,
+        )?;
+
+        // Simulated outcomes aren't included in any block.
+        let mut outcomes: Vec<ExecutionOutcomeWithIdView> = result
+            .outcomes
+            .into_iter()
+            .map(|outcome_with_id| ExecutionOutcomeWithIdView {
+                proof: vec![],
+                block_hash: CryptoHash::default(),
+                id: outcome_with_id.id,
+                outcome: outcome_with_id.outcome.into(),
+            })
+            .collect();
+        let status = Chain::get_final_transaction_status(&transaction.get_hash(), &outcomes);
+        let receipts_outcome = outcomes.split_off(1);
+        let transaction_outcome =
+            outcomes.pop().ok_or_else(|| SimulateTransactionError::Unreachable {
+                error_message: "transaction has no outcome".to_string(),
+            })?;
+        let state_changes = StateChanges::from_changes(result.state_changes.into_iter().map(Ok))
+            .map_err(|err| SimulateTransactionError::IOError { error_message: err.to_string() })?;
+        Ok(SimulatedTransactionView {
+            final_outcome: FinalExecutionOutcomeWithReceiptView {
+                final_outcome: FinalExecutionOutcomeView {
+                    status,
+                    transaction: transaction.into(),
+                    transaction_outcome,
+                    receipts_outcome,
+                },
+                receipts: result.receipts.into_iter().map(Into::into).collect(),
+            },
+            state_changes: state_changes.into_iter().map(Into::into).collect(),
+        })
+    }
+
     fn get_tx_status(
         &mut self,
         tx_hash: CryptoHash,
@@ -773,6 +1069,42 @@ impl Handler<WithSpanContext<GetStateChanges>> for ViewClientActor {
     }
 }
 
+/// Returns states of an account at blocks in which it was changed.
+impl Handler<WithSpanContext<GetAccountHistory>> for ViewClientActor {
+    type Result = Result<Vec<AccountHistoryEntryView>, GetAccountHistoryError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<GetAccountHistory>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["GetAccountHistory"])
+            .start_timer();
+        self.get_account_history(msg)
+    }
+}
+
+/// Applies a transaction and its receipts on top of the latest state without persisting them.
+impl Handler<WithSpanContext<SimulateTransaction>> for ViewClientActor {
+    type Result = Result<SimulatedTransactionView, SimulateTransactionError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<SimulateTransaction>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["SimulateTransaction"])
+            .start_timer();
+        self.simulate_transaction(msg)
+    }
+}
+
 /// Returns a list of changes in a store with causes for a given block.
 impl Handler<WithSpanContext<GetStateChangesWithCauseInBlock>> for ViewClientActor {
     type Result = Result<StateChangesView, GetStateChangesError>;
@@ -1427,6 +1759,7 @@ pub fn start_view_client(
     validator_account_id: Option<AccountId>,
     chain_genesis: ChainGenesis,
     runtime_adapter: Arc<dyn RuntimeAdapter>,
+    chain_store: Option<Store>,
     network_adapter: Arc<dyn PeerManagerAdapter>,
     config: ClientConfig,
     adv: crate::adversarial::Controls,
@@ -1436,6 +1769,7 @@ pub fn start_view_client(
         // ViewClientActor::start_in_arbiter(&Arbiter::current(), move |_ctx| {
         let validator_account_id1 = validator_account_id.clone();
         let runtime_adapter1 = runtime_adapter.clone();
+        let chain_store1 = chain_store.clone();
         let network_adapter1 = network_adapter.clone();
         let config1 = config.clone();
         let request_manager1 = request_manager.clone();
@@ -1443,6 +1777,7 @@ pub fn start_view_client(
             validator_account_id1,
             &chain_genesis,
             runtime_adapter1,
+            chain_store1,
             network_adapter1,
             config1,
             request_manager1,
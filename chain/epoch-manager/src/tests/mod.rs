@@ -10,7 +10,7 @@ use crate::test_utils::{
     DEFAULT_TOTAL_SUPPLY,
 };
 use near_primitives::challenge::SlashedValidator;
-use near_primitives::epoch_manager::EpochConfig;
+use near_primitives::epoch_manager::{EpochConfig, ShardLayoutUpgrade};
 use near_primitives::hash::hash;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::ValidatorKickoutReason::{NotEnoughBlocks, NotEnoughChunks};
@@ -2187,6 +2187,60 @@ fn test_protocol_version_switch_with_shard_layout_change() {
     assert_eq!(epoch_manager.will_shard_layout_change(&h[6]).unwrap(), false);
 }
 
+#[test]
+fn test_protocol_version_switch_with_shard_layout_upgrade() {
+    let store = create_test_store();
+    let new_shard_layout =
+        ShardLayout::v1(vec![], vec!["test2".parse().unwrap()], Some(vec![vec![0, 1]]), 1);
+    let config =
+        epoch_config(2, 1, 2, 0, 90, 60, 0).with_shard_layout_upgrades(vec![ShardLayoutUpgrade {
+            protocol_version: PROTOCOL_VERSION,
+            shard_layout: new_shard_layout.clone(),
+        }]);
+    let amount_staked = 1_000_000;
+    let validators = vec![
+        stake("test1".parse().unwrap(), amount_staked),
+        stake("test2".parse().unwrap(), amount_staked),
+    ];
+    let mut epoch_manager = EpochManager::new(
+        store,
+        config,
+        PROTOCOL_VERSION - 1,
+        default_reward_calculator(),
+        validators,
+    )
+    .unwrap();
+    let h = hash_range(8);
+    record_block(&mut epoch_manager, CryptoHash::default(), h[0], 0, vec![]);
+    for i in 1..8 {
+        let mut block_info = block_info(
+            h[i],
+            i as u64,
+            i as u64 - 1,
+            h[i - 1],
+            h[i - 1],
+            h[0],
+            vec![],
+            DEFAULT_TOTAL_SUPPLY,
+        );
+        if i == 1 {
+            set_block_info_protocol_version(&mut block_info, PROTOCOL_VERSION - 1);
+        } else {
+            set_block_info_protocol_version(&mut block_info, PROTOCOL_VERSION);
+        }
+        epoch_manager.record_block_info(block_info, [0; 32]).unwrap();
+    }
+    assert_eq!(epoch_manager.get_shard_layout(&EpochId(h[2])).unwrap(), ShardLayout::v0(1, 0));
+    assert_eq!(epoch_manager.get_shard_layout(&EpochId(h[4])).unwrap(), new_shard_layout);
+    let epoch_config = epoch_manager.get_epoch_config(&EpochId(h[4])).unwrap();
+    assert_eq!(epoch_config.num_block_producer_seats_per_shard, vec![2, 2]);
+    assert_eq!(epoch_manager.will_shard_layout_change(&h[3]).unwrap(), false);
+    for i in 4..=5 {
+        assert_eq!(epoch_manager.will_shard_layout_change(&h[i]).unwrap(), true);
+    }
+    assert_eq!(epoch_manager.will_shard_layout_change(&h[6]).unwrap(), false);
+}
+
 #[test]
 fn test_protocol_version_switch_with_many_seats() {
     let store = create_test_store();
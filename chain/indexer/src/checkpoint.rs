@@ -0,0 +1,114 @@
+//! Persistence of the indexer progress.
+//!
+//! The indexer keeps a small RocksDB database next to the node’s database.
+//! Besides the height of the last block sent by the streamer (used by
+//! [`crate::SyncModeEnum::FromInterruption`]) it stores checkpoints
+//! acknowledged by consumers (used by [`crate::SyncModeEnum::FromCheckpoint`]).
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context;
+use rocksdb::DB;
+
+use near_primitives::types::BlockHeight;
+
+/// Key under which the streamer stores height of the last block it has sent.
+pub(crate) const LAST_SYNCED_BLOCK_HEIGHT_KEY: &[u8] = b"last_synced_block_height";
+
+/// Opens the indexer database located inside of the node’s database directory.
+pub(crate) fn open_db(
+    home_dir: &Path,
+    store_config: &near_store::StoreConfig,
+) -> anyhow::Result<Arc<DB>> {
+    let path = near_store::NodeStorage::opener(home_dir, store_config, None).path().join("indexer");
+    let db = DB::open_default(&path)
+        .with_context(|| format!("failed to open indexer database at {}", path.display()))?;
+    Ok(Arc::new(db))
+}
+
+/// Reads block height stored under given key.
+pub(crate) fn read_height(db: &DB, key: &[u8]) -> anyhow::Result<Option<BlockHeight>> {
+    let value = match db.get(key)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let height = String::from_utf8(value)?.parse()?;
+    Ok(Some(height))
+}
+
+/// Writes block height under given key.
+pub(crate) fn write_height(db: &DB, key: &[u8], height: BlockHeight) -> anyhow::Result<()> {
+    Ok(db.put(key, height.to_string())?)
+}
+
+fn checkpoint_key(consumer: &str) -> Vec<u8> {
+    format!("checkpoint:{}", consumer).into_bytes()
+}
+
+/// Reads the checkpoint acknowledged by given consumer.
+pub(crate) fn read_checkpoint(db: &DB, consumer: &str) -> anyhow::Result<Option<BlockHeight>> {
+    read_height(db, &checkpoint_key(consumer))
+}
+
+/// Checkpoint of a single consumer of the indexer stream.
+///
+/// Streamer messages are delivered through a channel so the streamer has no
+/// way of knowing whether the consumer has finished processing them.  To
+/// resume after a restart without losing or reprocessing blocks, the consumer
+/// acknowledges each block once it’s done with it and starts the streamer with
+/// [`crate::SyncModeEnum::FromCheckpoint`] and its name.
+///
+/// Checkpoints of different consumers are independent from each other so
+/// multiple consumers (e.g. workers backfilling different block ranges) can
+/// share the same indexer database.
+#[derive(Clone)]
+pub struct IndexerCheckpoint {
+    db: Arc<DB>,
+    consumer: String,
+}
+
+impl IndexerCheckpoint {
+    pub(crate) fn new(db: Arc<DB>, consumer: String) -> Self {
+        Self { db, consumer }
+    }
+
+    /// Returns name of the consumer this checkpoint belongs to.
+    pub fn consumer(&self) -> &str {
+        &self.consumer
+    }
+
+    /// Returns height of the last block acknowledged by the consumer or `None`
+    /// if it hasn’t acknowledged any block yet.
+    pub fn get(&self) -> anyhow::Result<Option<BlockHeight>> {
+        read_checkpoint(&self.db, &self.consumer)
+    }
+
+    /// Acknowledges that the consumer has processed the block at given height
+    /// (and all the blocks streamed before it).
+    pub fn ack(&self, block_height: BlockHeight) -> anyhow::Result<()> {
+        write_height(&self.db, &checkpoint_key(&self.consumer), block_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_read_and_ack() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(DB::open_default(dir.path()).unwrap());
+        let first = IndexerCheckpoint::new(db.clone(), "first".to_string());
+        let second = IndexerCheckpoint::new(db.clone(), "second".to_string());
+        assert_eq!(first.get().unwrap(), None);
+
+        first.ack(10).unwrap();
+        first.ack(11).unwrap();
+        second.ack(5).unwrap();
+        assert_eq!(first.get().unwrap(), Some(11));
+        assert_eq!(second.get().unwrap(), Some(5));
+        // Checkpoints don’t interfere with the streamer’s own progress.
+        assert_eq!(read_height(&db, LAST_SYNCED_BLOCK_HEIGHT_KEY).unwrap(), None);
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+
+use near_primitives::types::{AccountId, ShardId};
+use near_primitives::views;
+
+/// Kind of an action in a transaction or receipt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionKind {
+    CreateAccount,
+    DeployContract,
+    FunctionCall,
+    Transfer,
+    Stake,
+    AddKey,
+    DeleteKey,
+    DeleteAccount,
+    Delegate,
+}
+
+impl From<&views::ActionView> for ActionKind {
+    fn from(action: &views::ActionView) -> Self {
+        match action {
+            views::ActionView::CreateAccount => Self::CreateAccount,
+            views::ActionView::DeployContract { .. } => Self::DeployContract,
+            views::ActionView::FunctionCall { .. } => Self::FunctionCall,
+            views::ActionView::Transfer { .. } => Self::Transfer,
+            views::ActionView::Stake { .. } => Self::Stake,
+            views::ActionView::AddKey { .. } => Self::AddKey,
+            views::ActionView::DeleteKey { .. } => Self::DeleteKey,
+            views::ActionView::DeleteAccount { .. } => Self::DeleteAccount,
+            views::ActionView::Delegate { .. } => Self::Delegate,
+        }
+    }
+}
+
+/// Filter limiting data included in `StreamerMessage`s.
+///
+/// The filter is applied by the streamer while it fetches data for a block so
+/// the data which doesn’t pass it is dropped as early as possible.  Every
+/// criterion left empty doesn’t restrict anything, thus the default filter
+/// passes everything.  Blocks themselves are always streamed, even if none of
+/// their data passes the filter.
+#[derive(Debug, Clone, Default)]
+pub struct StreamerFilter {
+    /// Accounts to stream data of.
+    ///
+    /// Transactions are matched by signer or receiver, receipts (and their
+    /// execution outcomes) by predecessor or receiver and state changes by
+    /// the account they change.
+    pub account_ids: HashSet<AccountId>,
+    /// Accounts to stream data of, together with all their sub-accounts, e.g.
+    /// `aurora` matches `aurora` and `relay.aurora` but not `notaurora`.
+    ///
+    /// Matched the same way as [`Self::account_ids`], i.e. an account passes
+    /// the filter if it matches either of them.
+    pub account_suffixes: Vec<String>,
+    /// Kinds of actions to stream transactions and receipts with.  A
+    /// transaction or an action receipt passes the filter if it includes at
+    /// least one action of listed kinds; data receipts never do.
+    pub action_kinds: HashSet<ActionKind>,
+    /// Shards to stream data of.  Data of other shards isn’t fetched at all
+    /// and the shards are omitted from `StreamerMessage::shards`.
+    pub shards: HashSet<ShardId>,
+}
+
+impl StreamerFilter {
+    /// Whether the filter passes everything.
+    pub fn is_empty(&self) -> bool {
+        self.account_ids.is_empty()
+            && self.account_suffixes.is_empty()
+            && self.action_kinds.is_empty()
+            && self.shards.is_empty()
+    }
+
+    pub fn matches_shard(&self, shard_id: ShardId) -> bool {
+        self.shards.is_empty() || self.shards.contains(&shard_id)
+    }
+
+    pub fn matches_account(&self, account_id: &AccountId) -> bool {
+        if self.account_ids.is_empty() && self.account_suffixes.is_empty() {
+            return true;
+        }
+        if self.account_ids.contains(account_id) {
+            return true;
+        }
+        let account_id = account_id.as_str();
+        self.account_suffixes.iter().any(|suffix| {
+            account_id
+                .strip_suffix(suffix.as_str())
+                .map_or(false, |prefix| prefix.is_empty() || prefix.ends_with('.'))
+        })
+    }
+
+    fn matches_actions(&self, actions: &[views::ActionView]) -> bool {
+        self.action_kinds.is_empty()
+            || actions.iter().any(|action| self.action_kinds.contains(&ActionKind::from(action)))
+    }
+
+    pub fn matches_transaction(&self, transaction: &views::SignedTransactionView) -> bool {
+        (self.matches_account(&transaction.signer_id)
+            || self.matches_account(&transaction.receiver_id))
+            && self.matches_actions(&transaction.actions)
+    }
+
+    pub fn matches_receipt(&self, receipt: &views::ReceiptView) -> bool {
+        if !self.matches_account(&receipt.predecessor_id)
+            && !self.matches_account(&receipt.receiver_id)
+        {
+            return false;
+        }
+        match &receipt.receipt {
+            views::ReceiptEnumView::Action { actions, .. } => self.matches_actions(actions),
+            views::ReceiptEnumView::Data { .. } => self.action_kinds.is_empty(),
+        }
+    }
+
+    pub fn matches_state_change(&self, state_change: &views::StateChangeWithCauseView) -> bool {
+        use views::StateChangeValueView::*;
+        let account_id = match &state_change.value {
+            AccountUpdate { account_id, .. }
+            | AccountDeletion { account_id }
+            | AccessKeyUpdate { account_id, .. }
+            | AccessKeyDeletion { account_id, .. }
+            | DataUpdate { account_id, .. }
+            | DataDeletion { account_id, .. }
+            | ContractCodeUpdate { account_id, .. }
+            | ContractCodeDeletion { account_id } => account_id,
+        };
+        self.matches_account(account_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_matches_account() {
+        assert!(StreamerFilter::default().matches_account(&account("alice.near")));
+
+        let filter = StreamerFilter {
+            account_ids: [account("alice.near")].into_iter().collect(),
+            account_suffixes: vec!["aurora".to_string()],
+            ..StreamerFilter::default()
+        };
+        assert!(filter.matches_account(&account("alice.near")));
+        assert!(!filter.matches_account(&account("bob.near")));
+        assert!(filter.matches_account(&account("aurora")));
+        assert!(filter.matches_account(&account("relay.aurora")));
+        assert!(!filter.matches_account(&account("notaurora")));
+    }
+
+    #[test]
+    fn test_matches_transaction() {
+        let transaction =
+            |signer_id: &str, receiver_id: &str, actions| views::SignedTransactionView {
+                signer_id: account(signer_id),
+                public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+                nonce: 0,
+                receiver_id: account(receiver_id),
+                actions,
+                signature: Default::default(),
+                hash: Default::default(),
+                priority_fee: None,
+            };
+        let filter = StreamerFilter {
+            account_ids: [account("contract.near")].into_iter().collect(),
+            action_kinds: [ActionKind::FunctionCall].into_iter().collect(),
+            ..StreamerFilter::default()
+        };
+        let call = views::ActionView::FunctionCall {
+            method_name: "method".to_string(),
+            args: vec![],
+            gas: 0,
+            deposit: 0,
+        };
+        let transfer = views::ActionView::Transfer { deposit: 1 };
+        assert!(filter.matches_transaction(&transaction(
+            "alice.near",
+            "contract.near",
+            vec![transfer.clone(), call]
+        )));
+        assert!(!filter.matches_transaction(&transaction(
+            "alice.near",
+            "contract.near",
+            vec![transfer.clone()]
+        )));
+        assert!(!filter.matches_transaction(&transaction(
+            "alice.near",
+            "bob.near",
+            vec![transfer]
+        )));
+    }
+}
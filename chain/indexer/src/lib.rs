@@ -1,11 +1,15 @@
 #![doc = include_str!("../README.md")]
 
+use std::path::Path;
+use std::sync::Arc;
+
 use anyhow::Context;
+use rocksdb::DB;
 use tokio::sync::mpsc;
 
 use near_chain_configs::GenesisValidationMode;
 pub use near_primitives;
-use near_primitives::types::Gas;
+use near_primitives::types::{BlockHeight, Gas};
 pub use nearcore::{get_default_home, init_configs, NearConfig};
 
 pub use near_indexer_primitives::{
@@ -14,6 +18,11 @@ pub use near_indexer_primitives::{
     StreamerMessage,
 };
 
+pub use checkpoint::IndexerCheckpoint;
+pub use filter::{ActionKind, StreamerFilter};
+
+mod checkpoint;
+mod filter;
 mod streamer;
 
 pub const INDEXER: &str = "indexer";
@@ -59,6 +68,13 @@ pub enum SyncModeEnum {
     FromInterruption,
     /// Specific block height to start syncing from
     BlockHeight(u64),
+    /// Starts syncing from the block following the last one acknowledged by
+    /// the consumer with given name (see [`IndexerCheckpoint`]) or from the
+    /// latest block if the consumer hasn’t acknowledged any block yet
+    FromCheckpoint(String),
+    /// Streams blocks in given inclusive range of heights and terminates
+    /// once the last of them has been streamed
+    BlockRange(u64, u64),
 }
 
 /// Enum to define whether await for node to be fully synced or stream while syncing (useful for indexing from genesis)
@@ -81,6 +97,8 @@ pub struct IndexerConfig {
     pub await_for_node_synced: AwaitForNodeSyncedEnum,
     /// Tells whether to validate the genesis file before starting
     pub validate_genesis: bool,
+    /// Limits the data streamed in `StreamerMessage`s, the default filter passes everything
+    pub filter: StreamerFilter,
 }
 
 /// This is the core component, which handles `nearcore` and internal `streamer`.
@@ -89,6 +107,7 @@ pub struct Indexer {
     near_config: nearcore::NearConfig,
     view_client: actix::Addr<near_client::ViewClientActor>,
     client: actix::Addr<near_client::ClientActor>,
+    db: Arc<DB>,
 }
 
 impl Indexer {
@@ -100,26 +119,13 @@ impl Indexer {
             indexer_config.home_dir.display()
         );
 
-        let genesis_validation_mode = if indexer_config.validate_genesis {
-            GenesisValidationMode::Full
-        } else {
-            GenesisValidationMode::UnsafeFast
-        };
         let near_config =
-            nearcore::config::load_config(&indexer_config.home_dir, genesis_validation_mode)
-                .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
-
-        assert!(
-            !&near_config.client_config.tracked_shards.is_empty(),
-            "Indexer should track at least one shard. \n\
-            Tip: You may want to update {} with `\"tracked_shards\": [0]`
-            ",
-            indexer_config.home_dir.join("config.json").display()
-        );
+            load_near_config(&indexer_config.home_dir, indexer_config.validate_genesis);
+        let db = checkpoint::open_db(&indexer_config.home_dir, &near_config.config.store)?;
         let nearcore::NearNode { client, view_client, .. } =
             nearcore::start_with_config(&indexer_config.home_dir, near_config.clone())
                 .with_context(|| "start_with_config")?;
-        Ok(Self { view_client, client, near_config, indexer_config })
+        Ok(Self { view_client, client, near_config, indexer_config, db })
     }
 
     /// Boots up `near_indexer::streamer`, so it monitors the new blocks with chunks, transactions, receipts, and execution outcomes inside. The returned stream handler should be drained and handled on the user side.
@@ -127,14 +133,21 @@ impl Indexer {
         let (sender, receiver) = mpsc::channel(100);
         actix::spawn(streamer::start(
             self.view_client.clone(),
-            self.client.clone(),
+            Some(self.client.clone()),
             self.indexer_config.clone(),
-            self.near_config.config.store.clone(),
+            self.db.clone(),
             sender,
         ));
         receiver
     }
 
+    /// Returns checkpoint of the consumer with given name.  Consumers started
+    /// with [`SyncModeEnum::FromCheckpoint`] should acknowledge streamed blocks
+    /// once they are done processing them.
+    pub fn checkpoint(&self, consumer: impl Into<String>) -> IndexerCheckpoint {
+        IndexerCheckpoint::new(self.db.clone(), consumer.into())
+    }
+
     /// Expose neard config
     pub fn near_config(&self) -> &nearcore::NearConfig {
         &self.near_config
@@ -148,6 +161,112 @@ impl Indexer {
     }
 }
 
+/// Indexer which streams blocks already present in node’s database.
+///
+/// Unlike [`Indexer`] it doesn’t start the node.  Instead it opens the database
+/// of a (typically archival) node in read-only mode and streams requested
+/// ranges of blocks from it.  This is meant for backfilling historical data;
+/// multiple ranges can be streamed in parallel by calling
+/// [`BackfillIndexer::streamer`] several times and each worker can track its
+/// progress with its own [`IndexerCheckpoint`].
+///
+/// Must be created inside of a running actix system.
+pub struct BackfillIndexer {
+    home_dir: std::path::PathBuf,
+    near_config: nearcore::NearConfig,
+    view_client: actix::Addr<near_client::ViewClientActor>,
+    db: Arc<DB>,
+}
+
+impl BackfillIndexer {
+    /// Opens node’s database and starts view client over it
+    pub fn new(home_dir: &Path, validate_genesis: bool) -> Result<Self, anyhow::Error> {
+        tracing::info!(target: INDEXER, "Load config from {}...", home_dir.display());
+        let near_config = load_near_config(home_dir, validate_genesis);
+        let db = checkpoint::open_db(home_dir, &near_config.config.store)?;
+
+        let storage = near_store::NodeStorage::opener(home_dir, &near_config.config.store, None)
+            .open_in_mode(near_store::Mode::ReadOnly)
+            .with_context(|| "failed to open node’s database")?;
+        let runtime = Arc::new(nearcore::NightshadeRuntime::from_config(
+            home_dir,
+            storage.get_store(near_store::Temperature::Hot),
+            &near_config,
+        ));
+        // The node isn’t connected to the network so nothing is ever sent to
+        // the peer manager.
+        let network_adapter = Arc::new(near_network::types::NetworkRecipient::<
+            actix::Addr<near_network::PeerManagerActor>,
+        >::default());
+        let view_client = near_client::start_view_client(
+            None,
+            near_chain::ChainGenesis::new(&near_config.genesis),
+            runtime,
+            None,
+            network_adapter,
+            near_config.client_config.clone(),
+            near_client::adversarial::Controls::new(near_config.client_config.archive),
+        );
+        Ok(Self { home_dir: home_dir.to_path_buf(), near_config, view_client, db })
+    }
+
+    /// Streams blocks in the inclusive range `from..=to` (or up to the latest
+    /// block in the database if `to` is greater than it) with the data passing
+    /// given filter.  The stream ends once all the blocks have been sent.
+    pub fn streamer(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+        filter: StreamerFilter,
+    ) -> mpsc::Receiver<StreamerMessage> {
+        let (sender, receiver) = mpsc::channel(100);
+        let indexer_config = IndexerConfig {
+            home_dir: self.home_dir.clone(),
+            sync_mode: SyncModeEnum::BlockRange(from, to),
+            await_for_node_synced: AwaitForNodeSyncedEnum::StreamWhileSyncing,
+            validate_genesis: false,
+            filter,
+        };
+        actix::spawn(streamer::start(
+            self.view_client.clone(),
+            None,
+            indexer_config,
+            self.db.clone(),
+            sender,
+        ));
+        receiver
+    }
+
+    /// Returns checkpoint of the consumer with given name
+    pub fn checkpoint(&self, consumer: impl Into<String>) -> IndexerCheckpoint {
+        IndexerCheckpoint::new(self.db.clone(), consumer.into())
+    }
+
+    /// Expose neard config
+    pub fn near_config(&self) -> &nearcore::NearConfig {
+        &self.near_config
+    }
+}
+
+fn load_near_config(home_dir: &Path, validate_genesis: bool) -> nearcore::NearConfig {
+    let genesis_validation_mode = if validate_genesis {
+        GenesisValidationMode::Full
+    } else {
+        GenesisValidationMode::UnsafeFast
+    };
+    let near_config = nearcore::config::load_config(home_dir, genesis_validation_mode)
+        .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
+
+    assert!(
+        !&near_config.client_config.tracked_shards.is_empty(),
+        "Indexer should track at least one shard. \n\
+        Tip: You may want to update {} with `\"tracked_shards\": [0]`
+        ",
+        home_dir.join("config.json").display()
+    );
+    near_config
+}
+
 /// Function that initializes configs for the node which
 /// accepts `InitConfigWrapper` and calls original `init_configs` from `neard`
 pub fn indexer_init_configs(
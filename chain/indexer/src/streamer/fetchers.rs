@@ -13,6 +13,7 @@ use near_primitives::{types, views};
 
 use super::errors::FailedToFetchData;
 use super::INDEXER;
+use crate::StreamerFilter;
 
 pub(crate) async fn fetch_status(
     client: &Addr<near_client::ClientActor>,
@@ -81,11 +82,12 @@ pub(crate) async fn fetch_state_changes(
         .map_err(|err| FailedToFetchData::String(err.to_string()))
 }
 
-/// Fetch all ExecutionOutcomeWithId for current block
+/// Fetch all ExecutionOutcomeWithId for current block in shards passing the filter
 /// Returns a HashMap where the key is shard id IndexerExecutionOutcomeWithOptionalReceipt
 pub(crate) async fn fetch_outcomes(
     client: &Addr<near_client::ViewClientActor>,
     block_hash: CryptoHash,
+    filter: &StreamerFilter,
 ) -> Result<
     HashMap<near_primitives::types::ShardId, Vec<IndexerExecutionOutcomeWithOptionalReceipt>>,
     FailedToFetchData,
@@ -100,6 +102,9 @@ pub(crate) async fn fetch_outcomes(
         Vec<IndexerExecutionOutcomeWithOptionalReceipt>,
     > = HashMap::new();
     for (shard_id, shard_outcomes) in outcomes {
+        if !filter.matches_shard(shard_id) {
+            continue;
+        }
         let mut outcomes_with_receipts: Vec<IndexerExecutionOutcomeWithOptionalReceipt> = vec![];
         for outcome in shard_outcomes {
             let receipt = match fetch_receipt_by_id(&client, outcome.id).await {
@@ -147,16 +152,18 @@ async fn fetch_single_chunk(
         .map_err(|err| FailedToFetchData::String(err.to_string()))
 }
 
-/// Fetches all chunks belonging to given block.
+/// Fetches all chunks belonging to given block in shards passing the filter.
 /// Includes transactions and receipts in custom struct (to provide more info).
 pub(crate) async fn fetch_block_chunks(
     client: &Addr<near_client::ViewClientActor>,
     block: &views::BlockView,
+    filter: &StreamerFilter,
 ) -> Result<Vec<views::ChunkView>, FailedToFetchData> {
     let mut futures: futures::stream::FuturesUnordered<_> = block
         .chunks
         .iter()
         .filter(|chunk| chunk.height_included == block.header.height)
+        .filter(|chunk| filter.matches_shard(chunk.shard_id))
         .map(|chunk| fetch_single_chunk(&client, chunk.chunk_hash))
         .collect();
     let mut chunks = Vec::<views::ChunkView>::with_capacity(futures.len());
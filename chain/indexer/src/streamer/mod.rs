@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix::Addr;
@@ -5,7 +6,7 @@ use async_recursion::async_recursion;
 use rocksdb::DB;
 use tokio::sync::mpsc;
 use tokio::time;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 use near_indexer_primitives::{
     IndexerChunkView, IndexerExecutionOutcomeWithOptionalReceipt,
@@ -13,9 +14,11 @@ use near_indexer_primitives::{
     StreamerMessage,
 };
 use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockHeight;
 use near_primitives::views;
 
-use crate::{AwaitForNodeSyncedEnum, IndexerConfig};
+use crate::checkpoint::{read_checkpoint, read_height, write_height, LAST_SYNCED_BLOCK_HEIGHT_KEY};
+use crate::{AwaitForNodeSyncedEnum, IndexerConfig, StreamerFilter, SyncModeEnum};
 
 use self::errors::FailedToFetchData;
 use self::fetchers::{
@@ -63,22 +66,72 @@ fn test_problematic_blocks_hash() {
     );
 }
 
+/// Transactions and receipt execution outcomes of a single chunk.
+struct ChunkOutcomes {
+    /// Transactions passing the filter.
+    transactions: Vec<IndexerTransactionWithOutcome>,
+    /// All transactions of the chunk converted into local receipts (i.e. the
+    /// ones whose signer is also the receiver).  These are not filtered since
+    /// the execution outcome of a local receipt may pass the filter even if
+    /// the transaction doesn’t (e.g. because of `action_kinds`) and the
+    /// receipt has to be found for it.
+    local_transactions: Vec<IndexerTransactionWithOutcome>,
+    /// Execution outcomes of receipts which may pass the filter.  Receipts
+    /// are missing for local receipts and are matched once they are found.
+    receipt_outcomes: Vec<IndexerExecutionOutcomeWithOptionalReceipt>,
+}
+
+fn split_chunk_outcomes(
+    transactions: Vec<views::SignedTransactionView>,
+    mut outcomes: Vec<IndexerExecutionOutcomeWithOptionalReceipt>,
+    filter: &StreamerFilter,
+) -> ChunkOutcomes {
+    // Take execution outcomes for receipts from the vec and keep only the ones for transactions
+    let mut receipt_outcomes = outcomes.split_off(transactions.len());
+    // Receipts are missing only for local receipts whose predecessor is
+    // the same account as the receiver (i.e. the executor) so outcomes
+    // not passing the filter can be dropped before looking for them.
+    receipt_outcomes.retain(|outcome| match &outcome.receipt {
+        Some(receipt) => filter.matches_receipt(receipt),
+        None => filter.matches_account(&outcome.execution_outcome.outcome.executor_id),
+    });
+
+    let mut local_transactions = vec![];
+    let mut matching_transactions = vec![];
+    for (transaction, outcome) in transactions.into_iter().zip(outcomes.into_iter()) {
+        assert_eq!(
+            outcome.execution_outcome.id, transaction.hash,
+            "This ExecutionOutcome must have the same id as Transaction hash"
+        );
+        let transaction = IndexerTransactionWithOutcome { outcome, transaction };
+        if transaction.transaction.signer_id == transaction.transaction.receiver_id {
+            local_transactions.push(transaction.clone());
+        }
+        if filter.matches_transaction(&transaction.transaction) {
+            matching_transactions.push(transaction);
+        }
+    }
+    ChunkOutcomes { transactions: matching_transactions, local_transactions, receipt_outcomes }
+}
+
 /// This function supposed to return the entire `StreamerMessage`.
 /// It fetches the block and all related parts (chunks, outcomes, state changes etc.)
-/// and returns everything together in one struct
+/// and returns everything together in one struct.  Only the data passing the
+/// filter is fetched and included.
 #[async_recursion]
 async fn build_streamer_message(
     client: &Addr<near_client::ViewClientActor>,
     block: views::BlockView,
+    filter: &StreamerFilter,
 ) -> Result<StreamerMessage, FailedToFetchData> {
     let _timer = metrics::BUILD_STREAMER_MESSAGE_TIME.start_timer();
-    let chunks = fetch_block_chunks(&client, &block).await?;
+    let chunks = fetch_block_chunks(&client, &block, filter).await?;
 
     let protocol_config_view = fetch_protocol_config(&client, block.header.hash).await?;
     let num_shards = protocol_config_view.num_block_producer_seats_per_shard.len()
         as near_primitives::types::NumShards;
 
-    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash).await?;
+    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash, filter).await?;
     let mut state_changes = fetch_state_changes(
         &client,
         block.header.hash,
@@ -86,11 +139,16 @@ async fn build_streamer_message(
     )
     .await?;
     let mut indexer_shards = (0..num_shards)
-        .map(|shard_id| IndexerShard {
-            shard_id,
-            chunk: None,
-            receipt_execution_outcomes: vec![],
-            state_changes: state_changes.remove(&shard_id).unwrap_or_default(),
+        .filter(|shard_id| filter.matches_shard(*shard_id))
+        .map(|shard_id| {
+            let mut shard_state_changes = state_changes.remove(&shard_id).unwrap_or_default();
+            shard_state_changes.retain(|state_change| filter.matches_state_change(state_change));
+            IndexerShard {
+                shard_id,
+                chunk: None,
+                receipt_execution_outcomes: vec![],
+                state_changes: shard_state_changes,
+            }
         })
         .collect::<Vec<_>>();
 
@@ -98,34 +156,19 @@ async fn build_streamer_message(
         let views::ChunkView { transactions, author, header, receipts: chunk_non_local_receipts } =
             chunk;
 
-        let shard_id = header.shard_id.clone() as usize;
-
-        let mut outcomes = shards_outcomes
+        let outcomes = shards_outcomes
             .remove(&header.shard_id)
             .expect("Execution outcomes for given shard should be present");
-
-        // Take execution outcomes for receipts from the vec and keep only the ones for transactions
-        let mut receipt_outcomes = outcomes.split_off(transactions.len());
-
-        let indexer_transactions = transactions
-            .into_iter()
-            .zip(outcomes.into_iter())
-            .map(|(transaction, outcome)| {
-                assert_eq!(
-                    outcome.execution_outcome.id, transaction.hash,
-                    "This ExecutionOutcome must have the same id as Transaction hash"
-                );
-                IndexerTransactionWithOutcome { outcome, transaction }
-            })
-            .collect::<Vec<IndexerTransactionWithOutcome>>();
+        let ChunkOutcomes {
+            transactions: indexer_transactions,
+            local_transactions,
+            mut receipt_outcomes,
+        } = split_chunk_outcomes(transactions, outcomes, filter);
 
         let chunk_local_receipts = convert_transactions_sir_into_local_receipts(
             &client,
             &protocol_config_view,
-            indexer_transactions
-                .iter()
-                .filter(|tx| tx.transaction.signer_id == tx.transaction.receiver_id)
-                .collect::<Vec<&IndexerTransactionWithOutcome>>(),
+            local_transactions.iter().collect::<Vec<&IndexerTransactionWithOutcome>>(),
             &block,
         )
         .await?;
@@ -141,7 +184,10 @@ async fn build_streamer_message(
             }
         }
 
-        let mut chunk_receipts = chunk_local_receipts;
+        let mut chunk_receipts: Vec<views::ReceiptView> = chunk_local_receipts
+            .into_iter()
+            .filter(|receipt| filter.matches_receipt(receipt))
+            .collect();
 
         let mut receipt_execution_outcomes: Vec<IndexerExecutionOutcomeWithReceipt> = vec![];
         for outcome in receipt_outcomes {
@@ -170,6 +216,7 @@ async fn build_streamer_message(
                         &protocol_config_view,
                         prev_block,
                         execution_outcome.id,
+                        filter,
                     )
                     .await?
                     {
@@ -179,8 +226,10 @@ async fn build_streamer_message(
                     prev_block_tried += 1;
                 }
             };
-            receipt_execution_outcomes
-                .push(IndexerExecutionOutcomeWithReceipt { execution_outcome, receipt: receipt });
+            if filter.matches_receipt(&receipt) {
+                receipt_execution_outcomes
+                    .push(IndexerExecutionOutcomeWithReceipt { execution_outcome, receipt });
+            }
         }
 
         // Blocks #47317863 and #47317864
@@ -206,11 +255,17 @@ async fn build_streamer_message(
             chunk_receipts.extend(restored_receipts);
         }
 
-        chunk_receipts.extend(chunk_non_local_receipts);
+        chunk_receipts.extend(
+            chunk_non_local_receipts.into_iter().filter(|receipt| filter.matches_receipt(receipt)),
+        );
 
-        indexer_shards[shard_id].receipt_execution_outcomes = receipt_execution_outcomes;
+        let indexer_shard = indexer_shards
+            .iter_mut()
+            .find(|indexer_shard| indexer_shard.shard_id == header.shard_id)
+            .expect("Chunks are fetched only for shards passing the filter");
+        indexer_shard.receipt_execution_outcomes = receipt_execution_outcomes;
         // Put the chunk into corresponding indexer shard
-        indexer_shards[shard_id].chunk = Some(IndexerChunkView {
+        indexer_shard.chunk = Some(IndexerChunkView {
             author,
             header,
             transactions: indexer_transactions,
@@ -222,11 +277,18 @@ async fn build_streamer_message(
     // chunks and we end up with non-empty `shards_outcomes` we want to be sure we put them into IndexerShard
     // That might happen before the fix https://github.com/near/nearcore/pull/4228
     for (shard_id, outcomes) in shards_outcomes {
-        indexer_shards[shard_id as usize].receipt_execution_outcomes.extend(
-            outcomes.into_iter().map(|outcome| IndexerExecutionOutcomeWithReceipt {
-                execution_outcome: outcome.execution_outcome,
-                receipt: outcome.receipt.expect("`receipt` must be present at this moment"),
-            }),
+        let indexer_shard = indexer_shards
+            .iter_mut()
+            .find(|indexer_shard| indexer_shard.shard_id == shard_id)
+            .expect("Outcomes are fetched only for shards passing the filter");
+        indexer_shard.receipt_execution_outcomes.extend(
+            outcomes
+                .into_iter()
+                .map(|outcome| IndexerExecutionOutcomeWithReceipt {
+                    execution_outcome: outcome.execution_outcome,
+                    receipt: outcome.receipt.expect("`receipt` must be present at this moment"),
+                })
+                .filter(|outcome| filter.matches_receipt(&outcome.receipt)),
         )
     }
 
@@ -240,10 +302,11 @@ async fn find_local_receipt_by_id_in_block(
     protocol_config_view: &near_chain_configs::ProtocolConfigView,
     block: views::BlockView,
     receipt_id: near_primitives::hash::CryptoHash,
+    filter: &StreamerFilter,
 ) -> Result<Option<views::ReceiptView>, FailedToFetchData> {
-    let chunks = fetch_block_chunks(&client, &block).await?;
+    let chunks = fetch_block_chunks(&client, &block, filter).await?;
 
-    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash).await?;
+    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash, filter).await?;
 
     for chunk in chunks {
         let views::ChunkView { header, transactions, .. } = chunk;
@@ -278,39 +341,77 @@ async fn find_local_receipt_by_id_in_block(
     Ok(None)
 }
 
+/// Returns whether all blocks of the [`SyncModeEnum::BlockRange`] have been
+/// streamed.  Always `false` for other sync modes.
+fn is_block_range_streamed(
+    sync_mode: &SyncModeEnum,
+    last_synced_block_height: Option<BlockHeight>,
+) -> bool {
+    match *sync_mode {
+        SyncModeEnum::BlockRange(from, to) => {
+            last_synced_block_height.map_or(from > to, |height| height >= to)
+        }
+        _ => false,
+    }
+}
+
+/// Returns height of the first block to stream in the current round.
+fn start_syncing_block_height(
+    db: &DB,
+    sync_mode: &SyncModeEnum,
+    last_synced_block_height: Option<BlockHeight>,
+    latest_block_height: BlockHeight,
+) -> anyhow::Result<BlockHeight> {
+    if let Some(last_synced_block_height) = last_synced_block_height {
+        return Ok(last_synced_block_height + 1);
+    }
+    Ok(match sync_mode {
+        SyncModeEnum::FromInterruption => {
+            read_height(db, LAST_SYNCED_BLOCK_HEIGHT_KEY)?.unwrap_or(latest_block_height)
+        }
+        SyncModeEnum::FromCheckpoint(consumer) => match read_checkpoint(db, consumer)? {
+            Some(height) => height + 1,
+            None => latest_block_height,
+        },
+        SyncModeEnum::LatestSynced => latest_block_height,
+        SyncModeEnum::BlockHeight(height) => *height,
+        SyncModeEnum::BlockRange(from, _) => *from,
+    })
+}
+
 /// Function that starts Streamer's busy loop. Every half a seconds it fetches the status
 /// compares to already fetched block height and in case it differs fetches new block of given height.
 ///
 /// We have to pass `client: Addr<near_client::ClientActor>` and `view_client: Addr<near_client::ViewClientActor>`.
+/// When `client` is `None` the streamer works over a database which isn’t being
+/// updated (see [`crate::BackfillIndexer`]): it doesn’t check node’s sync status
+/// and terminates once it reaches the latest block.
 pub(crate) async fn start(
     view_client: Addr<near_client::ViewClientActor>,
-    client: Addr<near_client::ClientActor>,
+    client: Option<Addr<near_client::ClientActor>>,
     indexer_config: IndexerConfig,
-    store_config: near_store::StoreConfig,
+    db: Arc<DB>,
     blocks_sink: mpsc::Sender<StreamerMessage>,
 ) {
     info!(target: INDEXER, "Starting Streamer...");
-    let indexer_db_path =
-        near_store::NodeStorage::opener(&indexer_config.home_dir, &store_config, None)
-            .path()
-            .join("indexer");
-
-    // TODO: implement proper error handling
-    let db = DB::open_default(indexer_db_path).unwrap();
-    let mut last_synced_block_height: Option<near_primitives::types::BlockHeight> = None;
+    let mut last_synced_block_height: Option<BlockHeight> = None;
 
     'main: loop {
+        if is_block_range_streamed(&indexer_config.sync_mode, last_synced_block_height) {
+            info!(target: INDEXER, "All blocks of the range have been streamed. terminating...");
+            break 'main;
+        }
         time::sleep(INTERVAL).await;
-        match indexer_config.await_for_node_synced {
-            AwaitForNodeSyncedEnum::WaitForFullSync => {
-                let status = fetch_status(&client).await;
+        match (&client, &indexer_config.await_for_node_synced) {
+            (Some(client), AwaitForNodeSyncedEnum::WaitForFullSync) => {
+                let status = fetch_status(client).await;
                 if let Ok(status) = status {
                     if status.sync_info.syncing {
                         continue;
                     }
                 }
             }
-            AwaitForNodeSyncedEnum::StreamWhileSyncing => {}
+            (None, _) | (_, AwaitForNodeSyncedEnum::StreamWhileSyncing) => {}
         };
 
         let block = if let Ok(block) = fetch_latest_block(&view_client).await {
@@ -320,22 +421,22 @@ pub(crate) async fn start(
         };
 
         let latest_block_height = block.header.height;
-        let start_syncing_block_height = if let Some(last_synced_block_height) =
-            last_synced_block_height
-        {
-            last_synced_block_height + 1
-        } else {
-            match indexer_config.sync_mode {
-                crate::SyncModeEnum::FromInterruption => {
-                    match db.get(b"last_synced_block_height").unwrap() {
-                        Some(value) => String::from_utf8(value).unwrap().parse::<u64>().unwrap(),
-                        None => latest_block_height,
-                    }
-                }
-                crate::SyncModeEnum::LatestSynced => latest_block_height,
-                crate::SyncModeEnum::BlockHeight(height) => height,
+        let start_syncing_block_height = match start_syncing_block_height(
+            &db,
+            &indexer_config.sync_mode,
+            last_synced_block_height,
+            latest_block_height,
+        ) {
+            Ok(height) => height,
+            Err(err) => {
+                error!(target: INDEXER, ?err, "Failed to read the indexer progress, retrying...");
+                continue;
             }
         };
+        let end_syncing_block_height = match indexer_config.sync_mode {
+            SyncModeEnum::BlockRange(_, to) => std::cmp::min(latest_block_height, to),
+            _ => latest_block_height,
+        };
 
         debug!(
             target: INDEXER,
@@ -345,10 +446,11 @@ pub(crate) async fn start(
         );
         metrics::START_BLOCK_HEIGHT.set(start_syncing_block_height as i64);
         metrics::LATEST_BLOCK_HEIGHT.set(latest_block_height as i64);
-        for block_height in start_syncing_block_height..=latest_block_height {
+        for block_height in start_syncing_block_height..=end_syncing_block_height {
             metrics::CURRENT_BLOCK_HEIGHT.set(block_height as i64);
             if let Ok(block) = fetch_block_by_height(&view_client, block_height).await {
-                let response = build_streamer_message(&view_client, block).await;
+                let response =
+                    build_streamer_message(&view_client, block, &indexer_config.filter).await;
 
                 match response {
                     Ok(streamer_message) => {
@@ -372,8 +474,123 @@ pub(crate) async fn start(
                     }
                 }
             }
-            db.put(b"last_synced_block_height", &block_height.to_string()).unwrap();
+            // Block ranges may be streamed in parallel so they don’t track
+            // their progress in the shared key.
+            if !matches!(indexer_config.sync_mode, SyncModeEnum::BlockRange(..)) {
+                if let Err(err) = write_height(&db, LAST_SYNCED_BLOCK_HEIGHT_KEY, block_height) {
+                    error!(
+                        target: INDEXER,
+                        ?err,
+                        "Failed to store the height of the last synced block #{}",
+                        block_height
+                    );
+                }
+            }
             last_synced_block_height = Some(block_height);
         }
+
+        if client.is_none() && end_syncing_block_height >= latest_block_height {
+            info!(
+                target: INDEXER,
+                "Reached the latest block #{}. terminating...", latest_block_height
+            );
+            break 'main;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::IndexerCheckpoint;
+
+    fn open_test_db() -> (tempfile::TempDir, Arc<DB>) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(DB::open_default(dir.path()).unwrap());
+        (dir, db)
+    }
+
+    #[test]
+    fn test_start_from_checkpoint() {
+        let (_dir, db) = open_test_db();
+        let sync_mode = SyncModeEnum::FromCheckpoint("worker".to_string());
+        assert_eq!(start_syncing_block_height(&db, &sync_mode, None, 100).unwrap(), 100);
+
+        IndexerCheckpoint::new(db.clone(), "worker".to_string()).ack(42).unwrap();
+        assert_eq!(start_syncing_block_height(&db, &sync_mode, None, 100).unwrap(), 43);
+        // Once streaming has started, the checkpoint is no longer consulted.
+        assert_eq!(start_syncing_block_height(&db, &sync_mode, Some(50), 100).unwrap(), 51);
+        // Checkpoints of other consumers don’t matter.
+        let other = SyncModeEnum::FromCheckpoint("other".to_string());
+        assert_eq!(start_syncing_block_height(&db, &other, None, 100).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_local_receipt_of_filtered_out_transaction() {
+        use crate::ActionKind;
+
+        let alice: near_primitives::types::AccountId = "alice.near".parse().unwrap();
+        let outcome = |id: CryptoHash, receipt_id: Option<CryptoHash>| {
+            IndexerExecutionOutcomeWithOptionalReceipt {
+                execution_outcome: views::ExecutionOutcomeWithIdView {
+                    proof: vec![],
+                    block_hash: CryptoHash::default(),
+                    id,
+                    outcome: views::ExecutionOutcomeView {
+                        logs: vec![],
+                        receipt_ids: receipt_id.into_iter().collect(),
+                        gas_burnt: 0,
+                        tokens_burnt: 0,
+                        executor_id: alice.clone(),
+                        status: views::ExecutionStatusView::Unknown,
+                        metadata: Default::default(),
+                    },
+                },
+                receipt: None,
+            }
+        };
+        let tx_hash = CryptoHash::hash_bytes(b"transaction");
+        let receipt_id = CryptoHash::hash_bytes(b"receipt");
+        let transaction = views::SignedTransactionView {
+            signer_id: alice.clone(),
+            public_key: near_crypto::PublicKey::empty(near_crypto::KeyType::ED25519),
+            nonce: 0,
+            receiver_id: alice.clone(),
+            actions: vec![views::ActionView::Transfer { deposit: 1 }],
+            signature: Default::default(),
+            hash: tx_hash,
+            priority_fee: None,
+        };
+        let filter = StreamerFilter {
+            account_ids: [alice.clone()].into_iter().collect(),
+            action_kinds: [ActionKind::FunctionCall].into_iter().collect(),
+            ..StreamerFilter::default()
+        };
+
+        let chunk = split_chunk_outcomes(
+            vec![transaction],
+            vec![outcome(tx_hash, Some(receipt_id)), outcome(receipt_id, None)],
+            &filter,
+        );
+        assert!(chunk.transactions.is_empty());
+        // The outcome of the local receipt passes the filter by its executor
+        // so the transaction has to be converted to find the receipt.
+        assert_eq!(chunk.receipt_outcomes.len(), 1);
+        assert_eq!(chunk.local_transactions.len(), 1);
+        assert_eq!(
+            chunk.local_transactions[0].outcome.execution_outcome.outcome.receipt_ids,
+            vec![chunk.receipt_outcomes[0].execution_outcome.id]
+        );
+    }
+
+    #[test]
+    fn test_block_range_termination() {
+        let sync_mode = SyncModeEnum::BlockRange(10, 20);
+        assert!(!is_block_range_streamed(&sync_mode, None));
+        assert!(!is_block_range_streamed(&sync_mode, Some(19)));
+        assert!(is_block_range_streamed(&sync_mode, Some(20)));
+        // An empty range has nothing to stream.
+        assert!(is_block_range_streamed(&SyncModeEnum::BlockRange(20, 10), None));
+        assert!(!is_block_range_streamed(&SyncModeEnum::LatestSynced, Some(20)));
     }
 }
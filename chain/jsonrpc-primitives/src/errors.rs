@@ -143,6 +143,22 @@ impl RpcError {
             )),
         }
     }
+
+    /// Create an error for a request rejected because the client exceeded
+    /// the rate limit configured for the method.
+    pub fn too_many_requests(method: String) -> Self {
+        let error_data = Some(Value::String(format!(
+            "Too many requests to the {} method, please retry later",
+            method
+        )));
+        Self::new_handler_error(
+            error_data,
+            serde_json::json!({
+                "name": "TOO_MANY_REQUESTS",
+                "info": serde_json::json!({"method_name": method})
+            }),
+        )
+    }
 }
 
 impl fmt::Display for RpcError {
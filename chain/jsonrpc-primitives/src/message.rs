@@ -73,6 +73,18 @@ impl Request {
     }
 }
 
+/// Notifications are processed like requests whose responses are dropped.
+impl From<Notification> for Request {
+    fn from(notification: Notification) -> Self {
+        Request {
+            jsonrpc: Version,
+            method: notification.method,
+            params: notification.params,
+            id: Value::Null,
+        }
+    }
+}
+
 /// A response to an RPC.
 ///
 /// It is created by the methods on [Request](struct.Request.html).
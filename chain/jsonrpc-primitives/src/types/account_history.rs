@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcViewAccountHistoryRequest {
+    pub account_id: near_primitives::types::AccountId,
+    pub from_block_height: near_primitives::types::BlockHeight,
+    pub to_block_height: near_primitives::types::BlockHeight,
+    /// If set, the contract state under this key prefix is returned as well
+    /// and changes of this state are included in the history.
+    #[serde(
+        default,
+        rename = "prefix_base64",
+        with = "near_primitives::serialize::option_base64_format",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub prefix: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcViewAccountHistoryResponse {
+    pub account_id: near_primitives::types::AccountId,
+    pub history: Vec<near_primitives::views::AccountHistoryEntryView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcViewAccountHistoryError {
+    #[error("Height range from {from_block_height} to {to_block_height} is empty or longer than {max_blocks} blocks")]
+    InvalidRange {
+        from_block_height: near_primitives::types::BlockHeight,
+        to_block_height: near_primitives::types::BlockHeight,
+        max_blocks: near_primitives::types::BlockHeightDelta,
+    },
+    #[error("There are no fully synchronized blocks yet")]
+    NotSyncedYet,
+    #[error("The node does not track the shard ID {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error("The data for block #{block_height} is garbage collected on this node, use an archival node to fetch historical data")]
+    GarbageCollectedBlock { block_height: near_primitives::types::BlockHeight },
+    #[error("State of contract {contract_account_id} is too large to be viewed")]
+    TooLargeContractState { contract_account_id: near_primitives::types::AccountId },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcViewAccountHistoryError> for crate::errors::RpcError {
+    fn from(error: RpcViewAccountHistoryError) -> Self {
+        let error_data = Some(serde_json::Value::String(error.to_string()));
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcViewAccountHistoryError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod account_history;
 pub mod blocks;
 pub mod changes;
 pub mod chunks;
@@ -10,5 +11,6 @@ pub mod query;
 pub mod receipts;
 pub mod sandbox;
 pub mod status;
+pub mod subscriptions;
 pub mod transactions;
 pub mod validator;
@@ -83,6 +83,7 @@ pub enum QueryResponseKind {
     CallResult(near_primitives::views::CallResult),
     AccessKey(near_primitives::views::AccessKeyView),
     AccessKeyList(near_primitives::views::AccessKeyList),
+    ViewAccountsWithPrefix(near_primitives::views::AccountsWithPrefixView),
 }
 
 impl From<RpcQueryError> for crate::errors::RpcError {
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Identifier of a subscription, unique within a single WebSocket connection.
+pub type SubscriptionId = u64;
+
+/// Parameters of the `subscribe` method of the WebSocket endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "subscription_type", rename_all = "snake_case")]
+pub enum RpcSubscribeRequest {
+    /// Notifies about every new final block.
+    FinalBlocks,
+    /// Notifies about state changes matching the request in every new final
+    /// block which contains at least one such change.
+    StateChanges {
+        #[serde(flatten)]
+        state_changes_request: near_primitives::views::StateChangesRequestView,
+    },
+    /// Notifies once the given transaction has been fully executed.  The
+    /// subscription is removed after the notification has been sent.
+    TransactionOutcome {
+        tx_hash: near_primitives::hash::CryptoHash,
+        sender_account_id: near_primitives::types::AccountId,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcSubscribeResponse {
+    pub subscription_id: SubscriptionId,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcUnsubscribeRequest {
+    pub subscription_id: SubscriptionId,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcUnsubscribeResponse {
+    pub subscription_id: SubscriptionId,
+}
+
+/// Parameters of the `subscription` notification sent by the node to the
+/// WebSocket client.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RpcSubscriptionNotification {
+    pub subscription_id: SubscriptionId,
+    #[serde(flatten)]
+    pub event: RpcSubscriptionEvent,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub enum RpcSubscriptionEvent {
+    FinalBlock {
+        block: near_primitives::views::BlockView,
+    },
+    StateChanges {
+        block_hash: near_primitives::hash::CryptoHash,
+        changes: near_primitives::views::StateChangesView,
+    },
+    TransactionOutcome {
+        final_execution_outcome: near_primitives::views::FinalExecutionOutcomeViewEnum,
+    },
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSubscriptionError {
+    #[error("Subscription {subscription_id} doesn't exist")]
+    UnknownSubscription { subscription_id: SubscriptionId },
+    #[error("Too many subscriptions on this connection, the limit is {limit}")]
+    TooManySubscriptions { limit: usize },
+}
+
+impl From<RpcSubscriptionError> for crate::errors::RpcError {
+    fn from(error: RpcSubscriptionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSubscriptionError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
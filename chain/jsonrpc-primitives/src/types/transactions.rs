@@ -6,6 +6,13 @@ pub struct RpcBroadcastTransactionRequest {
     pub signed_transaction: near_primitives::transaction::SignedTransaction,
 }
 
+#[derive(Debug, Clone)]
+pub struct RpcSimulateTransactionRequest {
+    pub signed_transaction: near_primitives::transaction::SignedTransaction,
+    /// Unset if the transaction was sent without a signature.
+    pub verify_signature: bool,
+}
+
 #[derive(Debug)]
 pub struct RpcTransactionStatusCommonRequest {
     pub transaction_info: TransactionInfo,
@@ -38,6 +45,8 @@ pub enum RpcTransactionError {
     InternalError { debug_info: String },
     #[error("Timeout")]
     TimeoutError,
+    #[error("Transaction pool is full. Try again later or with a higher priority fee")]
+    TransactionPoolFull,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -46,6 +55,12 @@ pub struct RpcTransactionResponse {
     pub final_execution_outcome: near_primitives::views::FinalExecutionOutcomeViewEnum,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcSimulateTransactionResponse {
+    #[serde(flatten)]
+    pub simulated_transaction: near_primitives::views::SimulatedTransactionView,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RpcBroadcastTxSyncResponse {
     pub transaction_hash: near_primitives::hash::CryptoHash,
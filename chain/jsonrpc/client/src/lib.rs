@@ -194,6 +194,8 @@ jsonrpc_client!(pub struct JsonRpcClient {
     pub fn EXPERIMENTAL_broadcast_tx_sync(&self, tx: String) -> RpcRequest<serde_json::Value>;
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_tx_status(&self, tx: String) -> RpcRequest<serde_json::Value>;
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_simulate_tx(&self, tx: String) -> RpcRequest<serde_json::Value>;
     pub fn health(&self) -> RpcRequest<()>;
     pub fn tx(&self, hash: String, account_id: AccountId) -> RpcRequest<FinalExecutionOutcomeView>;
     pub fn chunk(&self, id: ChunkId) -> RpcRequest<ChunkView>;
@@ -258,6 +260,15 @@ impl JsonRpcClient {
     ) -> RpcRequest<near_jsonrpc_primitives::types::config::RpcProtocolConfigResponse> {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_protocol_config", request)
     }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_view_account_history(
+        &self,
+        request: near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_view_account_history", request)
+    }
 }
 
 fn create_client() -> Client {
@@ -26,6 +26,17 @@ pub fn start_all_with_validity_period_and_no_epoch_sync(
     node_type: NodeType,
     transaction_validity_period: NumBlocks,
     enable_doomslug: bool,
+) -> (Addr<ViewClientActor>, String) {
+    start_all_with_rpc_config(node_type, transaction_validity_period, enable_doomslug, |_| {})
+}
+
+/// Like [`start_all_with_validity_period_and_no_epoch_sync`] but lets the
+/// caller adjust the configuration of the JSON RPC server.
+pub fn start_all_with_rpc_config(
+    node_type: NodeType,
+    transaction_validity_period: NumBlocks,
+    enable_doomslug: bool,
+    configure: impl FnOnce(&mut RpcConfig),
 ) -> (Addr<ViewClientActor>, String) {
     let (client_addr, view_client_addr) = setup_no_network_with_validity_period_and_no_epoch_sync(
         vec!["test1".parse().unwrap(), "test2".parse().unwrap()],
@@ -40,8 +51,10 @@ pub fn start_all_with_validity_period_and_no_epoch_sync(
     );
 
     let addr = format!("127.0.0.1:{}", open_port());
+    let mut rpc_config = RpcConfig::new(&addr);
+    configure(&mut rpc_config);
     start_http(
-        RpcConfig::new(&addr),
+        rpc_config,
         TEST_GENESIS_CONFIG.clone(),
         client_addr,
         view_client_addr.clone(),
@@ -0,0 +1,132 @@
+use actix::System;
+use serde_json::json;
+
+use near_actix_test_utils::run_actix;
+use near_jsonrpc::RpcRateLimit;
+use near_jsonrpc_primitives::errors::RpcError;
+use near_jsonrpc_primitives::message::{from_slice, Message};
+use near_o11y::testonly::init_test_logger;
+
+use near_jsonrpc_tests as test_utils;
+
+async fn post(addr: &str, body: serde_json::Value) -> Option<Message> {
+    let mut response = awc::Client::new()
+        .post(format!("http://{}", addr))
+        .insert_header(("Content-Type", "application/json"))
+        .send_json(&body)
+        .await
+        .unwrap();
+    let bytes = response.body().await.unwrap();
+    if bytes.is_empty() {
+        None
+    } else {
+        Some(from_slice(&bytes).unwrap())
+    }
+}
+
+fn block_request(id: u64) -> serde_json::Value {
+    json!({"jsonrpc": "2.0", "method": "block", "id": id, "params": {"block_id": 0}})
+}
+
+/// Batch requests are answered with a batch of responses matched by ids.
+#[test]
+fn test_batch_request() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let batch = json!([
+                block_request(1),
+                {"jsonrpc": "2.0", "method": "no_such_method", "id": 2},
+                {"jsonrpc": "2.0", "method": "block", "params": {"block_id": 0}},
+                true,
+            ]);
+            let responses = match post(&addr, batch).await {
+                Some(Message::Batch(responses)) => responses,
+                message => panic!("expected a batch, got {:?}", message),
+            };
+            // The notification doesn't produce a response.
+            assert_eq!(responses.len(), 3);
+            for response in responses {
+                match response {
+                    Message::Response(response) if response.id == json!(1) => {
+                        assert_eq!(response.result.unwrap()["header"]["height"], 0);
+                    }
+                    Message::Response(response) if response.id == json!(2) => {
+                        assert_eq!(response.result.unwrap_err().code, -32_601);
+                    }
+                    Message::Response(response) => {
+                        assert!(response.id.is_null());
+                        assert!(response.result.is_err());
+                    }
+                    message => panic!("expected a response, got {:?}", message),
+                }
+            }
+
+            match post(&addr, json!([])).await {
+                Some(Message::Response(response)) => assert!(response.result.is_err()),
+                message => panic!("expected an error, got {:?}", message),
+            }
+
+            let notifications = json!([
+                {"jsonrpc": "2.0", "method": "block", "params": {"block_id": 0}},
+            ]);
+            assert_eq!(post(&addr, notifications).await, None);
+            System::current().stop();
+        });
+    });
+}
+
+/// Requests exceeding the configured rate limit of a method are rejected
+/// while other methods are unaffected.
+#[test]
+fn test_rate_limit() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all_with_rpc_config(
+            test_utils::NodeType::NonValidator,
+            100,
+            false,
+            |config| {
+                config.rate_limits.insert(
+                    "block".to_string(),
+                    RpcRateLimit { requests_per_second: 0.001, burst: 2 },
+                );
+            },
+        );
+
+        actix::spawn(async move {
+            let batch = json!([block_request(1), block_request(2), block_request(3)]);
+            let responses = match post(&addr, batch).await {
+                Some(Message::Batch(responses)) => responses,
+                message => panic!("expected a batch, got {:?}", message),
+            };
+            let results: Vec<Result<serde_json::Value, RpcError>> = responses
+                .into_iter()
+                .map(|response| match response {
+                    Message::Response(response) => response.result,
+                    message => panic!("expected a response, got {:?}", message),
+                })
+                .collect();
+            assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+            let error = results.into_iter().find_map(Result::err).unwrap();
+            assert_eq!(
+                error.error_struct.unwrap(),
+                near_jsonrpc_primitives::errors::RpcErrorKind::HandlerError(json!({
+                    "name": "TOO_MANY_REQUESTS",
+                    "info": {"method_name": "block"},
+                }))
+            );
+
+            let status = json!({"jsonrpc": "2.0", "method": "status", "id": 4, "params": []});
+            match post(&addr, status).await {
+                Some(Message::Response(response)) => assert!(response.result.is_ok()),
+                message => panic!("expected a response, got {:?}", message),
+            }
+            System::current().stop();
+        });
+    });
+}
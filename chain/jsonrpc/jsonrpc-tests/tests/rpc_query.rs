@@ -8,6 +8,7 @@ use serde_json::json;
 use near_actix_test_utils::run_actix;
 use near_crypto::{KeyType, PublicKey, Signature};
 use near_jsonrpc::client::{new_client, ChunkId};
+use near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryRequest;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 use near_jsonrpc_primitives::types::validator::RpcValidatorsOrderedRequest;
 use near_network::test_utils::wait_or_timeout;
@@ -299,6 +300,8 @@ fn test_query_state() {
                     account_id: "test".parse().unwrap(),
                     prefix: vec![].into(),
                     include_proof: false,
+                    start_after: None,
+                    limit: None,
                 },
             })
             .await
@@ -324,6 +327,7 @@ fn test_query_call_function() {
                     account_id: "test".parse().unwrap(),
                     method_name: "method".to_string(),
                     args: vec![].into(),
+                    trace: false,
                 },
             })
             .await
@@ -459,6 +463,24 @@ fn test_validators_ordered() {
     });
 }
 
+/// Account history can only be requested for bounded height ranges.
+#[test]
+fn test_view_account_history_invalid_range() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let error = client
+            .EXPERIMENTAL_view_account_history(RpcViewAccountHistoryRequest {
+                account_id: "test1".parse().unwrap(),
+                from_block_height: 10,
+                to_block_height: 5,
+                prefix: None,
+            })
+            .await
+            .unwrap_err();
+        let error = serde_json::to_value(error).unwrap();
+        assert_eq!(error["cause"]["name"], json!("INVALID_RANGE"));
+    });
+}
+
 /// Retrieve genesis config via JSON RPC.
 /// WARNING: Be mindful about changing genesis structure as it is part of the public protocol!
 #[test]
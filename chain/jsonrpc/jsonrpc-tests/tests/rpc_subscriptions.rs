@@ -0,0 +1,130 @@
+use actix::System;
+use awc::ws;
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+
+use near_actix_test_utils::run_actix;
+use near_jsonrpc_primitives::message::{from_slice, Message};
+use near_jsonrpc_primitives::types::subscriptions::{
+    RpcSubscribeResponse, RpcSubscriptionEvent, RpcSubscriptionNotification,
+};
+use near_o11y::testonly::init_test_logger;
+
+use near_jsonrpc_tests as test_utils;
+
+async fn send_request<S>(connection: &mut S, method: &str, params: serde_json::Value)
+where
+    S: futures::Sink<ws::Message> + Unpin,
+    S::Error: std::fmt::Debug,
+{
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "id": "dontcare",
+        "params": params,
+    });
+    connection.send(ws::Message::Text(request.to_string().into())).await.unwrap();
+}
+
+async fn next_message<S>(connection: &mut S) -> Message
+where
+    S: futures::Stream<Item = Result<ws::Frame, ws::ProtocolError>> + Unpin,
+{
+    loop {
+        match connection.next().await.unwrap().unwrap() {
+            ws::Frame::Text(bytes) => return from_slice(&bytes).unwrap(),
+            ws::Frame::Ping(_) | ws::Frame::Pong(_) => continue,
+            frame => panic!("unexpected frame: {:?}", frame),
+        }
+    }
+}
+
+/// Subscribe to final blocks over WebSocket and wait for the first notification.
+#[test]
+fn test_subscribe_final_blocks() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::Validator);
+
+        actix::spawn(async move {
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+
+            send_request(
+                &mut connection,
+                "subscribe",
+                json!({"subscription_type": "final_blocks"}),
+            )
+            .await;
+            let subscription_id = match next_message(&mut connection).await {
+                Message::Response(response) => {
+                    let response: RpcSubscribeResponse =
+                        serde_json::from_value(response.result.unwrap()).unwrap();
+                    response.subscription_id
+                }
+                message => panic!("expected a response, got {:?}", message),
+            };
+
+            match next_message(&mut connection).await {
+                Message::Notification(notification) => {
+                    assert_eq!(notification.method, "subscription");
+                    let notification: RpcSubscriptionNotification =
+                        serde_json::from_value(notification.params.unwrap()).unwrap();
+                    assert_eq!(notification.subscription_id, subscription_id);
+                    assert!(matches!(notification.event, RpcSubscriptionEvent::FinalBlock { .. }));
+                }
+                message => panic!("expected a notification, got {:?}", message),
+            }
+
+            send_request(
+                &mut connection,
+                "unsubscribe",
+                json!({ "subscription_id": subscription_id }),
+            )
+            .await;
+            loop {
+                match next_message(&mut connection).await {
+                    Message::Response(response) => {
+                        assert!(response.result.is_ok());
+                        break;
+                    }
+                    Message::Notification(_) => continue,
+                    message => panic!("expected a response, got {:?}", message),
+                }
+            }
+            System::current().stop();
+        });
+    });
+}
+
+/// Regular JSON RPC methods are available over WebSocket and unknown
+/// subscriptions are reported as errors.
+#[test]
+fn test_websocket_requests() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+
+            send_request(&mut connection, "block", json!({"block_id": 0})).await;
+            match next_message(&mut connection).await {
+                Message::Response(response) => {
+                    assert_eq!(response.result.unwrap()["header"]["height"], 0);
+                }
+                message => panic!("expected a response, got {:?}", message),
+            }
+
+            send_request(&mut connection, "unsubscribe", json!({"subscription_id": 42})).await;
+            match next_message(&mut connection).await {
+                Message::Response(response) => assert!(response.result.is_err()),
+                message => panic!("expected a response, got {:?}", message),
+            }
+            System::current().stop();
+        });
+    });
+}
@@ -9,13 +9,15 @@ use serde_json::json;
 use near_actix_test_utils::run_actix;
 use near_crypto::{InMemorySigner, KeyType};
 use near_jsonrpc::client::new_client;
+use near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryRequest;
+use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryRequest};
 use near_network::test_utils::WaitOrTimeoutActor;
 use near_o11y::testonly::{init_integration_logger, init_test_logger};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::serialize::to_base64;
 use near_primitives::transaction::SignedTransaction;
-use near_primitives::types::BlockReference;
-use near_primitives::views::FinalExecutionStatus;
+use near_primitives::types::{BlockId, BlockReference};
+use near_primitives::views::{FinalExecutionStatus, QueryRequest};
 
 use near_jsonrpc_tests::{self as test_utils, test_with_client};
 
@@ -99,6 +101,51 @@ fn test_send_tx_commit() {
     });
 }
 
+/// Test that a transfer shows up in the history of the receiver account.
+#[test]
+fn test_view_account_history() {
+    test_with_client!(test_utils::NodeType::Validator, client, async move {
+        let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+        let signer = InMemorySigner::from_seed("test1".parse().unwrap(), KeyType::ED25519, "test1");
+        let tx = SignedTransaction::send_money(
+            1,
+            "test1".parse().unwrap(),
+            "test2".parse().unwrap(),
+            &signer,
+            100,
+            block_hash,
+        );
+        let bytes = tx.try_to_vec().unwrap();
+        client.broadcast_tx_commit(to_base64(&bytes)).await.unwrap();
+
+        let head = client.block(BlockReference::latest()).await.unwrap().header;
+        let response = client
+            .EXPERIMENTAL_view_account_history(RpcViewAccountHistoryRequest {
+                account_id: "test2".parse().unwrap(),
+                from_block_height: 0,
+                to_block_height: head.height,
+                prefix: None,
+            })
+            .await
+            .unwrap();
+        let last_entry = response.history.last().unwrap();
+        let account = match client
+            .query(RpcQueryRequest {
+                block_reference: BlockReference::BlockId(BlockId::Hash(head.hash)),
+                request: QueryRequest::ViewAccount { account_id: "test2".parse().unwrap() },
+            })
+            .await
+            .unwrap()
+            .kind
+        {
+            QueryResponseKind::ViewAccount(account) => account,
+            kind => panic!("queried account, but received something else: {:?}", kind),
+        };
+        assert_eq!(last_entry.account.as_ref(), Some(&account));
+        assert!(response.history.windows(2).all(|w| w[0].block_height < w[1].block_height));
+    });
+}
+
 /// Test get_recursive_transaction_results (called by get_final_transaction_result)
 /// only returns non-refund receipts
 #[test]
@@ -0,0 +1,53 @@
+use serde_json::Value;
+
+use near_client_primitives::types::GetAccountHistoryError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::account_history::{
+    RpcViewAccountHistoryError, RpcViewAccountHistoryRequest,
+};
+
+use super::{parse_params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcViewAccountHistoryRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        parse_params::<Self>(value)
+    }
+}
+
+impl RpcFrom<actix::MailboxError> for RpcViewAccountHistoryError {
+    fn rpc_from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<GetAccountHistoryError> for RpcViewAccountHistoryError {
+    fn rpc_from(error: GetAccountHistoryError) -> Self {
+        match error {
+            GetAccountHistoryError::IOError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetAccountHistoryError::InvalidRange {
+                from_block_height,
+                to_block_height,
+                max_blocks,
+            } => Self::InvalidRange { from_block_height, to_block_height, max_blocks },
+            GetAccountHistoryError::NotSyncedYet => Self::NotSyncedYet,
+            GetAccountHistoryError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            GetAccountHistoryError::GarbageCollectedBlock { block_height } => {
+                Self::GarbageCollectedBlock { block_height }
+            }
+            GetAccountHistoryError::TooLargeContractState { contract_account_id } => {
+                Self::TooLargeContractState { contract_account_id }
+            }
+            GetAccountHistoryError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcViewAccountHistoryError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
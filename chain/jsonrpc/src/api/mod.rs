@@ -5,6 +5,7 @@ use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::errors::{RpcError, ServerError};
 use near_primitives::borsh::BorshDeserialize;
 
+mod account_history;
 mod blocks;
 mod changes;
 mod chunks;
@@ -17,6 +18,7 @@ mod query;
 mod receipts;
 mod sandbox;
 mod status;
+mod subscriptions;
 mod transactions;
 mod validator;
 
@@ -87,12 +89,15 @@ pub(crate) fn parse_params<T: DeserializeOwned>(value: Option<Value>) -> Result<
     }
 }
 
+fn parse_base64_param(value: Option<Value>) -> Result<Vec<u8>, RpcParseError> {
+    let (encoded,) = parse_params::<(String,)>(value)?;
+    near_primitives::serialize::from_base64(&encoded).map_err(|err| RpcParseError(err.to_string()))
+}
+
 fn parse_signed_transaction(
     value: Option<Value>,
 ) -> Result<near_primitives::transaction::SignedTransaction, RpcParseError> {
-    let (encoded,) = parse_params::<(String,)>(value)?;
-    let bytes = near_primitives::serialize::from_base64(&encoded)
-        .map_err(|err| RpcParseError(err.to_string()))?;
+    let bytes = parse_base64_param(value)?;
     Ok(near_primitives::transaction::SignedTransaction::try_from_slice(&bytes)
         .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))?)
 }
@@ -68,12 +68,15 @@ impl RpcRequest for RpcQueryRequest {
                     account_id,
                     prefix: parse_data()?.into(),
                     include_proof: false,
+                    start_after: None,
+                    limit: None,
                 },
                 "call" => match maybe_extra_arg {
                     Some(method_name) => QueryRequest::CallFunction {
                         account_id,
                         method_name: method_name.to_string(),
                         args: parse_data()?.into(),
+                        trace: false,
                     },
                     None => return Err(RpcParseError("Method name is missing".to_string())),
                 },
@@ -168,6 +171,9 @@ impl RpcFrom<near_primitives::views::QueryResponseKind>
             near_primitives::views::QueryResponseKind::AccessKeyList(access_key_list) => {
                 Self::AccessKeyList(access_key_list)
             }
+            near_primitives::views::QueryResponseKind::ViewAccountsWithPrefix(accounts) => {
+                Self::ViewAccountsWithPrefix(accounts)
+            }
         }
     }
 }
@@ -1,16 +1,20 @@
 use serde_json::Value;
 
-use near_client_primitives::types::TxStatusError;
+use near_client_primitives::types::{SimulateTransactionError, TxStatusError};
+use near_crypto::Signature;
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::types::transactions::{
-    RpcBroadcastTransactionRequest, RpcTransactionError, RpcTransactionResponse,
-    RpcTransactionStatusCommonRequest, TransactionInfo,
+    RpcBroadcastTransactionRequest, RpcSimulateTransactionRequest, RpcSimulateTransactionResponse,
+    RpcTransactionError, RpcTransactionResponse, RpcTransactionStatusCommonRequest,
+    TransactionInfo,
 };
+use near_primitives::borsh::BorshDeserialize;
 use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{SignedTransaction, Transaction};
 use near_primitives::types::AccountId;
-use near_primitives::views::FinalExecutionOutcomeViewEnum;
+use near_primitives::views::{FinalExecutionOutcomeViewEnum, SimulatedTransactionView};
 
-use super::{parse_params, parse_signed_transaction, RpcFrom, RpcRequest};
+use super::{parse_base64_param, parse_params, parse_signed_transaction, RpcFrom, RpcRequest};
 
 impl RpcRequest for RpcBroadcastTransactionRequest {
     fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
@@ -19,6 +23,23 @@ impl RpcRequest for RpcBroadcastTransactionRequest {
     }
 }
 
+impl RpcRequest for RpcSimulateTransactionRequest {
+    fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
+        let bytes = parse_base64_param(value)?;
+        if let Ok(signed_transaction) = SignedTransaction::try_from_slice(&bytes) {
+            return Ok(Self { signed_transaction, verify_signature: true });
+        }
+        // Wallets estimating gas before the user signs send the bare transaction.
+        let transaction = Transaction::try_from_slice(&bytes)
+            .map_err(|err| RpcParseError(format!("Failed to decode transaction: {}", err)))?;
+        let signature = Signature::empty(transaction.public_key.key_type());
+        Ok(Self {
+            signed_transaction: SignedTransaction::new(signature, transaction),
+            verify_signature: false,
+        })
+    }
+}
+
 impl RpcRequest for RpcTransactionStatusCommonRequest {
     fn parse(value: Option<Value>) -> Result<Self, RpcParseError> {
         if let Ok((hash, account_id)) = parse_params::<(CryptoHash, AccountId)>(value.clone()) {
@@ -53,6 +74,33 @@ impl RpcFrom<TxStatusError> for RpcTransactionError {
     }
 }
 
+impl RpcFrom<SimulateTransactionError> for RpcTransactionError {
+    fn rpc_from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::IOError { error_message } => {
+                Self::InternalError { debug_info: error_message }
+            }
+            SimulateTransactionError::InvalidTransaction { context } => {
+                Self::InvalidTransaction { context }
+            }
+            SimulateTransactionError::UnavailableShard { .. } => Self::DoesNotTrackShard,
+            SimulateTransactionError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcTransactionError"])
+                    .inc();
+                Self::InternalError { debug_info: error.to_string() }
+            }
+        }
+    }
+}
+
+impl RpcFrom<SimulatedTransactionView> for RpcSimulateTransactionResponse {
+    fn rpc_from(simulated_transaction: SimulatedTransactionView) -> Self {
+        Self { simulated_transaction }
+    }
+}
+
 impl RpcFrom<FinalExecutionOutcomeViewEnum> for RpcTransactionResponse {
     fn rpc_from(final_execution_outcome: FinalExecutionOutcomeViewEnum) -> Self {
         Self { final_execution_outcome }
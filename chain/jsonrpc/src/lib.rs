@@ -1,6 +1,9 @@
 #![doc = include_str!("../README.md")]
 
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use actix::{Addr, MailboxError};
@@ -18,10 +21,11 @@ use tracing::info;
 
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    ClientActor, DebugStatus, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome, GetGasPrice,
-    GetMaintenanceWindows, GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt,
-    GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
-    ProcessTxRequest, ProcessTxResponse, Query, Status, TxStatus, ViewClientActor,
+    ClientActor, DebugStatus, GetAccountHistory, GetBlock, GetBlockProof, GetChunk,
+    GetExecutionOutcome, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
+    GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
+    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, ProcessTxRequest,
+    ProcessTxResponse, Query, SimulateTransaction, Status, TxStatus, ViewClientActor,
 };
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_primitives::errors::RpcError;
@@ -35,10 +39,14 @@ use near_primitives::views::FinalExecutionOutcomeViewEnum;
 
 mod api;
 mod metrics;
+mod rate_limiter;
+mod ws;
 
 use api::RpcRequest;
 pub use api::{RpcFrom, RpcInto};
 use near_o11y::{WithSpanContext, WithSpanContextExt};
+use rate_limiter::RateLimiter;
+pub use rate_limiter::RpcRateLimit;
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct RpcPollingConfig {
@@ -55,15 +63,33 @@ impl Default for RpcPollingConfig {
     }
 }
 
+fn default_max_subscriptions_per_connection() -> usize {
+    100
+}
+
+fn default_max_batch_size() -> usize {
+    100
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RpcLimitsConfig {
     /// Maximum byte size of the json payload.
     pub json_payload_max_size: usize,
+    /// Maximum number of active subscriptions on a single WebSocket connection.
+    #[serde(default = "default_max_subscriptions_per_connection")]
+    pub max_subscriptions_per_connection: usize,
+    /// Maximum number of requests in a single JSON RPC batch.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
 }
 
 impl Default for RpcLimitsConfig {
     fn default() -> Self {
-        Self { json_payload_max_size: 10 * 1024 * 1024 }
+        Self {
+            json_payload_max_size: 10 * 1024 * 1024,
+            max_subscriptions_per_connection: default_max_subscriptions_per_connection(),
+            max_batch_size: default_max_batch_size(),
+        }
     }
 }
 
@@ -80,6 +106,10 @@ pub struct RpcConfig {
     pub polling_config: RpcPollingConfig,
     #[serde(default)]
     pub limits_config: RpcLimitsConfig,
+    // Rate limits of JSON RPC methods keyed by method name, applied to each
+    // client IP address separately.  Methods not listed here are not limited.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rate_limits: HashMap<String, RpcRateLimit>,
     // If true, enable some debug RPC endpoints (like one to get the latest block).
     // We disable it by default, as some of those endpoints might be quite CPU heavy.
     #[serde(default = "default_enable_debug_rpc")]
@@ -98,6 +128,7 @@ impl Default for RpcConfig {
             cors_allowed_origins: vec!["*".to_owned()],
             polling_config: Default::default(),
             limits_config: Default::default(),
+            rate_limits: HashMap::new(),
             enable_debug_rpc: false,
             experimental_debug_pages_src_path: None,
         }
@@ -142,6 +173,7 @@ impl near_jsonrpc_primitives::types::transactions::RpcTransactionError {
         match resp {
             ProcessTxResponse::InvalidTx(context) => Self::InvalidTransaction { context },
             ProcessTxResponse::NoResponse => Self::TimeoutError,
+            ProcessTxResponse::TransactionPoolFull => Self::TransactionPoolFull,
             ProcessTxResponse::DoesNotTrackShard | ProcessTxResponse::RequestRouted => {
                 Self::DoesNotTrackShard
             }
@@ -225,24 +257,103 @@ struct JsonRpcHandler {
     genesis_config: GenesisConfig,
     enable_debug_rpc: bool,
     debug_pages_src_path: Option<PathBuf>,
+    max_subscriptions_per_connection: usize,
+    max_batch_size: usize,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl JsonRpcHandler {
-    pub async fn process(&self, message: Message) -> Result<Message, HttpError> {
+    /// Processes a single request or a batch of requests sent by the client
+    /// with the given address.
+    ///
+    /// Returns `None` if there is nothing to respond with, i.e. when the
+    /// message is a batch consisting solely of notifications.
+    pub async fn process(
+        &self,
+        message: Message,
+        client_addr: Option<IpAddr>,
+    ) -> Result<Option<Message>, HttpError> {
         let id = message.id();
         match message {
             Message::Request(request) => {
-                Ok(Message::response(id, self.process_request(request).await))
+                Ok(Some(Message::response(id, self.process_request(request, client_addr).await)))
             }
-            _ => Ok(Message::error(RpcError::parse_error(
+            Message::Batch(messages) => Ok(self.process_batch(messages, client_addr).await),
+            _ => Ok(Some(Message::error(RpcError::parse_error(
                 "JSON RPC Request format was expected".to_owned(),
-            ))),
+            )))),
+        }
+    }
+
+    /// Processes entries of a batch concurrently as described by the JSON RPC
+    /// 2.0 specification.  Notifications are processed but their results
+    /// aren't included in the response.
+    async fn process_batch(
+        &self,
+        messages: Vec<Message>,
+        client_addr: Option<IpAddr>,
+    ) -> Option<Message> {
+        if messages.is_empty() {
+            return Some(Message::error(RpcError::parse_error(
+                "JSON RPC batch must not be empty".to_owned(),
+            )));
+        }
+        if messages.len() > self.max_batch_size {
+            return Some(Message::error(RpcError::parse_error(format!(
+                "JSON RPC batch of {} requests exceeds the limit of {} requests",
+                messages.len(),
+                self.max_batch_size
+            ))));
+        }
+        metrics::RPC_BATCH_SIZE.observe(messages.len() as f64);
+        let responses = futures::future::join_all(messages.into_iter().map(|message| async move {
+            match message {
+                Message::Request(request) => {
+                    let id = request.id.clone();
+                    Some(Message::response(id, self.process_request(request, client_addr).await))
+                }
+                Message::Notification(notification) => {
+                    let _ = self.process_request(notification.into(), client_addr).await;
+                    None
+                }
+                _ => Some(Message::error(RpcError::parse_error(
+                    "JSON RPC Request format was expected".to_owned(),
+                ))),
+            }
+        }))
+        .await;
+        let responses: Vec<Message> = responses.into_iter().flatten().collect();
+        if responses.is_empty() {
+            None
+        } else {
+            Some(Message::Batch(responses))
         }
     }
 
+    /// Consumes a request of given method from the rate limit of the client.
+    pub(crate) fn check_rate_limit(
+        &self,
+        method: &str,
+        client_addr: Option<IpAddr>,
+    ) -> Result<(), RpcError> {
+        if let Some(client_addr) = client_addr {
+            if !self.rate_limiter.try_acquire(method, client_addr) {
+                metrics::RPC_RATE_LIMITED_COUNT.with_label_values(&[method]).inc();
+                return Err(RpcError::too_many_requests(method.to_string()));
+            }
+        }
+        Ok(())
+    }
+
     // `process_request` increments affected metrics but the request processing is done by
     // `process_request_internal`.
-    async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
+    async fn process_request(
+        &self,
+        request: Request,
+        client_addr: Option<IpAddr>,
+    ) -> Result<Value, RpcError> {
+        self.check_rate_limit(&request.method, client_addr)?;
+
         let timer = Instant::now();
 
         let request_method = request.method.clone();
@@ -343,12 +454,18 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_receipt" => {
                 process_method_call(request, |params| self.receipt(params)).await
             }
+            "EXPERIMENTAL_simulate_tx" => {
+                process_method_call(request, |params| self.simulate_tx(params)).await
+            }
             "EXPERIMENTAL_tx_status" => {
                 process_method_call(request, |params| self.tx_status_common(params, true)).await
             }
             "EXPERIMENTAL_validators_ordered" => {
                 process_method_call(request, |params| self.validators_ordered(params)).await
             }
+            "EXPERIMENTAL_view_account_history" => {
+                process_method_call(request, |params| self.view_account_history(params)).await
+            }
             "EXPERIMENTAL_maintenance_windows" => {
                 process_method_call(request, |params| self.maintenance_windows(params)).await
             }
@@ -685,6 +802,22 @@ impl JsonRpcHandler {
         }
     }
 
+    async fn simulate_tx(
+        &self,
+        request_data: near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::transactions::RpcSimulateTransactionResponse,
+        near_jsonrpc_primitives::types::transactions::RpcTransactionError,
+    > {
+        let simulated_transaction = self
+            .view_client_send(SimulateTransaction {
+                transaction: request_data.signed_transaction,
+                verify_signature: request_data.verify_signature,
+            })
+            .await?;
+        Ok(simulated_transaction.rpc_into())
+    }
+
     async fn send_tx_commit(
         &self,
         request_data: near_jsonrpc_primitives::types::transactions::RpcBroadcastTransactionRequest,
@@ -958,6 +1091,28 @@ impl JsonRpcHandler {
         })
     }
 
+    async fn view_account_history(
+        &self,
+        request: near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryResponse,
+        near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryError,
+    > {
+        let account_id = request.account_id;
+        let history = self
+            .view_client_send(GetAccountHistory {
+                account_id: account_id.clone(),
+                from_block_height: request.from_block_height,
+                to_block_height: request.to_block_height,
+                state_prefix: request.prefix.map(Into::into),
+            })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::account_history::RpcViewAccountHistoryResponse {
+            account_id,
+            history,
+        })
+    }
+
     async fn next_light_client_block(
         &self,
         request: near_jsonrpc_primitives::types::light_client::RpcLightClientNextBlockRequest,
@@ -1306,12 +1461,16 @@ impl JsonRpcHandler {
 }
 
 fn rpc_handler(
+    req: HttpRequest,
     message: web::Json<Message>,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let client_addr = req.peer_addr().map(|addr| addr.ip());
     let response = async move {
-        let message = handler.process(message.0).await?;
-        Ok(HttpResponse::Ok().json(&message))
+        match handler.process(message.0, client_addr).await? {
+            Some(message) => Ok(HttpResponse::Ok().json(&message)),
+            None => Ok(HttpResponse::NoContent().finish()),
+        }
     };
     response.boxed()
 }
@@ -1468,8 +1627,9 @@ async fn display_debug_html(
 
 /// Starts HTTP server(s) listening for RPC requests.
 ///
-/// Starts an HTTP server which handles JSON RPC calls as well as states
-/// endpoints such as `/status`, `/health`, `/metrics` etc.  Depending on
+/// Starts an HTTP server which handles JSON RPC calls (including the
+/// WebSocket endpoint at `/ws`) as well as states endpoints such as
+/// `/status`, `/health`, `/metrics` etc.  Depending on
 /// configuration may also start another HTTP server just for providing
 /// Prometheus metrics (i.e. covering the `/metrics` path).
 ///
@@ -1490,9 +1650,11 @@ pub fn start_http(
         cors_allowed_origins,
         polling_config,
         limits_config,
+        rate_limits,
         enable_debug_rpc,
         experimental_debug_pages_src_path: debug_pages_src_path,
     } = config;
+    let rate_limiter = Arc::new(RateLimiter::new(rate_limits));
     let prometheus_addr = prometheus_addr.filter(|it| it != &addr);
     let cors_allowed_origins_clone = cors_allowed_origins.clone();
     info!(target:"network", "Starting http server at {}", addr);
@@ -1508,10 +1670,14 @@ pub fn start_http(
                 genesis_config: genesis_config.clone(),
                 enable_debug_rpc,
                 debug_pages_src_path: debug_pages_src_path.clone().map(Into::into),
+                max_subscriptions_per_connection: limits_config.max_subscriptions_per_connection,
+                max_batch_size: limits_config.max_batch_size,
+                rate_limiter: rate_limiter.clone(),
             }))
             .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
+            .service(web::resource("/ws").route(web::get().to(ws::ws_handler)))
             .service(
                 web::resource("/status")
                     .route(web::get().to(status_handler))
@@ -0,0 +1,157 @@
+//! Per-method, per-client rate limiting of JSON RPC requests.
+//!
+//! Every configured method has a token bucket for each client IP address.
+//! Buckets start full with `burst` tokens and are refilled at
+//! `requests_per_second`.  Each request consumes a single token and is
+//! rejected if the bucket is empty.  Methods without a configured limit are
+//! never throttled.
+//!
+//! IPv6 clients are identified by their /64 prefix since a single host
+//! usually has a whole /64 network at its disposal.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of tracked buckets.  When exceeded, the least recently
+/// used bucket is forgotten.  Such a bucket has most likely been refilled
+/// completely and a full bucket behaves the same as a missing one.
+const MAX_TRACKED_BUCKETS: usize = 100_000;
+
+/// Rate limit of a single JSON RPC method applied to each client separately.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RpcRateLimit {
+    /// Sustained number of requests per second a client is allowed to make.
+    pub requests_per_second: f64,
+    /// Number of requests a client may make in a burst after a period of
+    /// inactivity.
+    pub burst: u32,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, limit: &RpcRateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.requests_per_second).min(limit.burst as f64);
+        self.last_refill = now;
+    }
+}
+
+/// Returns the address under which the client is rate limited: IPv4 address
+/// as is and /64 prefix of an IPv6 address.
+fn client_key(client: IpAddr) -> IpAddr {
+    match client {
+        IpAddr::V4(_) => client,
+        IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+            Some(addr) => IpAddr::V4(addr),
+            None => {
+                let prefix = u128::from(addr) & !((1u128 << 64) - 1);
+                IpAddr::V6(Ipv6Addr::from(prefix))
+            }
+        },
+    }
+}
+
+pub(crate) struct RateLimiter {
+    limits: HashMap<String, RpcRateLimit>,
+    buckets: Mutex<LruCache<(String, IpAddr), Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: HashMap<String, RpcRateLimit>) -> Self {
+        Self { limits, buckets: Mutex::new(LruCache::new(MAX_TRACKED_BUCKETS)) }
+    }
+
+    /// Consumes a token from the bucket of the given method and client.
+    ///
+    /// Returns `false` if the request should be rejected.
+    pub(crate) fn try_acquire(&self, method: &str, client: IpAddr) -> bool {
+        self.try_acquire_at(method, client, Instant::now())
+    }
+
+    fn try_acquire_at(&self, method: &str, client: IpAddr, now: Instant) -> bool {
+        let limit = match self.limits.get(method) {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let key = (method.to_owned(), client_key(client));
+        let mut buckets = self.buckets.lock().unwrap();
+        if !buckets.contains(&key) {
+            buckets.put(key.clone(), Bucket { tokens: limit.burst as f64, last_refill: now });
+        }
+        let bucket = buckets.get_mut(&key).unwrap();
+        bucket.refill(limit, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(HashMap::from([(
+            "query".to_owned(),
+            RpcRateLimit { requests_per_second: 2.0, burst: 3 },
+        )]))
+    }
+
+    #[test]
+    fn test_burst_and_refill() {
+        let limiter = limiter();
+        let client: IpAddr = "127.0.0.1".parse().unwrap();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at("query", client, start));
+        }
+        assert!(!limiter.try_acquire_at("query", client, start));
+        // Two requests per second means a new token every 500ms.
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire_at("query", client, later));
+        assert!(!limiter.try_acquire_at("query", client, later));
+    }
+
+    #[test]
+    fn test_limits_are_per_client_and_method() {
+        let limiter = limiter();
+        let first: IpAddr = "127.0.0.1".parse().unwrap();
+        let second: IpAddr = "::1".parse().unwrap();
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at("query", first, now));
+        }
+        assert!(!limiter.try_acquire_at("query", first, now));
+        assert!(limiter.try_acquire_at("query", second, now));
+        for _ in 0..10 {
+            assert!(limiter.try_acquire_at("broadcast_tx_commit", first, now));
+        }
+    }
+
+    #[test]
+    fn test_ipv6_clients_share_prefix() {
+        let limiter = limiter();
+        let now = Instant::now();
+        for i in 0..3 {
+            let client: IpAddr = format!("2001:db8::{}", i + 1).parse().unwrap();
+            assert!(limiter.try_acquire_at("query", client, now));
+        }
+        let same_prefix: IpAddr = "2001:db8::ffff:1".parse().unwrap();
+        assert!(!limiter.try_acquire_at("query", same_prefix, now));
+        let other_prefix: IpAddr = "2001:db8:0:1::1".parse().unwrap();
+        assert!(limiter.try_acquire_at("query", other_prefix, now));
+    }
+}
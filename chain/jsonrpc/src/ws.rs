@@ -0,0 +1,408 @@
+//! WebSocket endpoint of the JSON RPC server.
+//!
+//! Every regular JSON RPC method can be called over the WebSocket connection.
+//! On top of that, the connection supports `subscribe` and `unsubscribe`
+//! methods which let the client receive `subscription` notifications about
+//! new final blocks, state changes and transaction outcomes instead of polling
+//! the node for them.
+//!
+//! Subscriptions are served by polling the view client once per
+//! `polling_interval` on behalf of all subscriptions of the connection.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use actix::{Actor, ActorContext, ActorFutureExt, AsyncContext, StreamHandler, WrapFuture};
+use actix_web::{web, Error as HttpError, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde_json::Value;
+
+use near_client::{GetBlock, GetStateChanges, TxStatus};
+use near_jsonrpc_primitives::errors::RpcError;
+use near_jsonrpc_primitives::message::{Message, Request};
+use near_jsonrpc_primitives::types::blocks::RpcBlockError;
+use near_jsonrpc_primitives::types::changes::RpcStateChangesError;
+use near_jsonrpc_primitives::types::subscriptions::{
+    RpcSubscribeRequest, RpcSubscribeResponse, RpcSubscriptionError, RpcSubscriptionEvent,
+    RpcSubscriptionNotification, RpcUnsubscribeRequest, RpcUnsubscribeResponse, SubscriptionId,
+};
+use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
+use near_primitives::types::{BlockHeight, BlockId, BlockReference, Finality};
+use near_primitives::views::{BlockView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus};
+
+use crate::api::RpcRequest;
+use crate::{metrics, serialize_response, JsonRpcHandler};
+
+/// Maximum number of final blocks reported in a single polling round.  If the
+/// connection falls further behind, the older blocks are skipped.
+const MAX_BLOCKS_PER_POLL: usize = 32;
+
+/// Name of the notification method used to deliver subscription events.
+const SUBSCRIPTION_NOTIFICATION_METHOD: &str = "subscription";
+
+pub(crate) async fn ws_handler(
+    req: HttpRequest,
+    stream: web::Payload,
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    let client_addr = req.peer_addr().map(|addr| addr.ip());
+    ws::start(WsSession::new(handler.into_inner(), client_addr), &req, stream)
+}
+
+fn subscription_type(request: &RpcSubscribeRequest) -> &'static str {
+    match request {
+        RpcSubscribeRequest::FinalBlocks => "final_blocks",
+        RpcSubscribeRequest::StateChanges { .. } => "state_changes",
+        RpcSubscribeRequest::TransactionOutcome { .. } => "transaction_outcome",
+    }
+}
+
+/// Result of a single polling round.
+#[derive(Default)]
+struct PollResult {
+    /// Height of the latest final block processed during the round.
+    final_block_height: Option<BlockHeight>,
+    notifications: Vec<RpcSubscriptionNotification>,
+    /// Subscriptions which won't produce any more notifications.
+    finished: Vec<SubscriptionId>,
+}
+
+struct WsSession {
+    handler: Arc<JsonRpcHandler>,
+    /// Address of the client, used for rate limiting of regular requests.
+    client_addr: Option<IpAddr>,
+    subscriptions: BTreeMap<SubscriptionId, RpcSubscribeRequest>,
+    next_subscription_id: SubscriptionId,
+    /// Height of the last final block the block based subscriptions have been
+    /// notified about.
+    last_final_block_height: Option<BlockHeight>,
+    /// Whether a polling round is currently in progress.
+    polling: bool,
+}
+
+impl WsSession {
+    fn new(handler: Arc<JsonRpcHandler>, client_addr: Option<IpAddr>) -> Self {
+        Self {
+            handler,
+            client_addr,
+            subscriptions: BTreeMap::new(),
+            next_subscription_id: 0,
+            last_final_block_height: None,
+            polling: false,
+        }
+    }
+
+    fn send(ctx: &mut ws::WebsocketContext<Self>, message: &Message) {
+        match serde_json::to_string(message) {
+            Ok(text) => ctx.text(text),
+            Err(err) => {
+                tracing::warn!(target: "jsonrpc", ?err, "Failed to serialize WebSocket message")
+            }
+        }
+    }
+
+    fn handle_text(&mut self, text: &str, ctx: &mut ws::WebsocketContext<Self>) {
+        let message = match serde_json::from_str::<Message>(text) {
+            Ok(message) => message,
+            Err(err) => {
+                Self::send(ctx, &Message::error(RpcError::parse_error(err.to_string())));
+                return;
+            }
+        };
+        match message {
+            Message::Request(request) if request.method == "subscribe" => {
+                let id = request.id.clone();
+                let response = self.subscribe(request);
+                Self::send(ctx, &Message::response(id, response));
+            }
+            Message::Request(request) if request.method == "unsubscribe" => {
+                let id = request.id.clone();
+                let response = self.unsubscribe(request);
+                Self::send(ctx, &Message::response(id, response));
+            }
+            message => {
+                let handler = self.handler.clone();
+                let client_addr = self.client_addr;
+                let response = async move { handler.process(message, client_addr).await };
+                ctx.spawn(response.into_actor(self).map(|response, _act, ctx| match response {
+                    Ok(Some(message)) => Self::send(ctx, &message),
+                    Ok(None) => {}
+                    Err(err) => tracing::warn!(
+                        target: "jsonrpc", ?err, "Failed to process WebSocket request"
+                    ),
+                }));
+            }
+        }
+    }
+
+    fn subscribe(&mut self, request: Request) -> Result<Value, RpcError> {
+        self.handler.check_rate_limit(&request.method, self.client_addr)?;
+        let subscription = RpcSubscribeRequest::parse(request.params)?;
+        let limit = self.handler.max_subscriptions_per_connection;
+        if self.subscriptions.len() >= limit {
+            return Err(RpcSubscriptionError::TooManySubscriptions { limit }.into());
+        }
+        let subscription_id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        metrics::WEBSOCKET_SUBSCRIPTIONS
+            .with_label_values(&[subscription_type(&subscription)])
+            .inc();
+        self.subscriptions.insert(subscription_id, subscription);
+        serialize_response(RpcSubscribeResponse { subscription_id })
+    }
+
+    fn unsubscribe(&mut self, request: Request) -> Result<Value, RpcError> {
+        self.handler.check_rate_limit(&request.method, self.client_addr)?;
+        let RpcUnsubscribeRequest { subscription_id } =
+            RpcUnsubscribeRequest::parse(request.params)?;
+        if !self.remove_subscription(subscription_id) {
+            return Err(RpcSubscriptionError::UnknownSubscription { subscription_id }.into());
+        }
+        serialize_response(RpcUnsubscribeResponse { subscription_id })
+    }
+
+    fn remove_subscription(&mut self, subscription_id: SubscriptionId) -> bool {
+        match self.subscriptions.remove(&subscription_id) {
+            Some(subscription) => {
+                metrics::WEBSOCKET_SUBSCRIPTIONS
+                    .with_label_values(&[subscription_type(&subscription)])
+                    .dec();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts a polling round unless one is already running.
+    fn poll_subscriptions(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        if self.polling || self.subscriptions.is_empty() {
+            return;
+        }
+        self.polling = true;
+        let handler = self.handler.clone();
+        let subscriptions = self.subscriptions.clone();
+        let last_final_block_height = self.last_final_block_height;
+        ctx.spawn(
+            async move { poll(&handler, subscriptions, last_final_block_height).await }
+                .into_actor(self)
+                .map(|result, act, ctx| {
+                    act.polling = false;
+                    act.last_final_block_height = result.final_block_height;
+                    for notification in result.notifications {
+                        // The client might have unsubscribed while the round
+                        // was in progress.
+                        if !act.subscriptions.contains_key(&notification.subscription_id) {
+                            continue;
+                        }
+                        match serde_json::to_value(notification) {
+                            Ok(params) => Self::send(
+                                ctx,
+                                &Message::notification(
+                                    SUBSCRIPTION_NOTIFICATION_METHOD.to_string(),
+                                    Some(params),
+                                ),
+                            ),
+                            Err(err) => tracing::warn!(
+                                target: "jsonrpc", ?err, "Failed to serialize subscription notification"
+                            ),
+                        }
+                    }
+                    for subscription_id in result.finished {
+                        act.remove_subscription(subscription_id);
+                    }
+                }),
+        );
+    }
+}
+
+impl Actor for WsSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        metrics::WEBSOCKET_CONNECTIONS.inc();
+        ctx.run_interval(self.handler.polling_config.polling_interval, |act, ctx| {
+            act.poll_subscriptions(ctx)
+        });
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        metrics::WEBSOCKET_CONNECTIONS.dec();
+        let subscription_ids: Vec<_> = self.subscriptions.keys().copied().collect();
+        for subscription_id in subscription_ids {
+            self.remove_subscription(subscription_id);
+        }
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Text(text)) => self.handle_text(&text, ctx),
+            Ok(ws::Message::Binary(bytes)) => match std::str::from_utf8(&bytes) {
+                Ok(text) => self.handle_text(text, ctx),
+                Err(err) => {
+                    Self::send(ctx, &Message::error(RpcError::parse_error(err.to_string())))
+                }
+            },
+            Ok(ws::Message::Ping(bytes)) => ctx.pong(&bytes),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(ws::Message::Pong(_) | ws::Message::Continuation(_) | ws::Message::Nop) => {}
+            Err(err) => {
+                tracing::debug!(target: "jsonrpc", ?err, "WebSocket protocol error");
+                ctx.stop();
+            }
+        }
+    }
+}
+
+/// Runs a single polling round for given subscriptions.
+///
+/// Errors are logged and the affected subscriptions are retried in the next
+/// round.
+async fn poll(
+    handler: &JsonRpcHandler,
+    subscriptions: BTreeMap<SubscriptionId, RpcSubscribeRequest>,
+    last_final_block_height: Option<BlockHeight>,
+) -> PollResult {
+    let mut result = PollResult::default();
+    let needs_blocks = subscriptions.values().any(|subscription| {
+        !matches!(subscription, RpcSubscribeRequest::TransactionOutcome { .. })
+    });
+    if needs_blocks {
+        result.final_block_height = last_final_block_height;
+        match new_final_blocks(handler, last_final_block_height).await {
+            Ok(blocks) => {
+                for block in blocks {
+                    let height = block.header.height;
+                    match notify_about_block(handler, &subscriptions, block).await {
+                        Ok(notifications) => {
+                            result.notifications.extend(notifications);
+                            result.final_block_height = Some(height);
+                        }
+                        Err(err) => {
+                            tracing::warn!(target: "jsonrpc", ?err, "Failed to fetch state changes");
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(err) => tracing::warn!(target: "jsonrpc", ?err, "Failed to fetch final blocks"),
+        }
+    }
+
+    for (subscription_id, subscription) in &subscriptions {
+        if let RpcSubscribeRequest::TransactionOutcome { tx_hash, sender_account_id } = subscription
+        {
+            let outcome = handler
+                .view_client_send::<_, _, RpcTransactionError, _>(TxStatus {
+                    tx_hash: *tx_hash,
+                    signer_account_id: sender_account_id.clone(),
+                    fetch_receipt: false,
+                })
+                .await;
+            match outcome {
+                Ok(Some(final_execution_outcome)) => {
+                    let status = match &final_execution_outcome {
+                        FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome) => {
+                            &outcome.status
+                        }
+                        FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(
+                            outcome,
+                        ) => &outcome.final_outcome.status,
+                    };
+                    if matches!(
+                        status,
+                        FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started
+                    ) {
+                        continue;
+                    }
+                    result.notifications.push(RpcSubscriptionNotification {
+                        subscription_id: *subscription_id,
+                        event: RpcSubscriptionEvent::TransactionOutcome { final_execution_outcome },
+                    });
+                    result.finished.push(*subscription_id);
+                }
+                // The transaction hasn't been included in a chunk yet.
+                Ok(None) | Err(RpcTransactionError::UnknownTransaction { .. }) => {}
+                Err(err) => {
+                    tracing::debug!(target: "jsonrpc", ?err, %tx_hash, "Failed to fetch transaction status")
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Returns final blocks newer than `last_final_block_height` in increasing
+/// height order.  If no block has been seen yet, returns only the latest final
+/// block.
+async fn new_final_blocks(
+    handler: &JsonRpcHandler,
+    last_final_block_height: Option<BlockHeight>,
+) -> Result<Vec<BlockView>, RpcBlockError> {
+    let mut block: BlockView =
+        handler.view_client_send(GetBlock(BlockReference::Finality(Finality::Final))).await?;
+    let last_final_block_height = match last_final_block_height {
+        Some(height) => height,
+        None => return Ok(vec![block]),
+    };
+    let mut blocks = Vec::new();
+    while block.header.height > last_final_block_height {
+        let prev_hash = block.header.prev_hash;
+        blocks.push(block);
+        if blocks.len() >= MAX_BLOCKS_PER_POLL {
+            tracing::debug!(
+                target: "jsonrpc",
+                last_final_block_height,
+                "Too many new final blocks, skipping older ones"
+            );
+            break;
+        }
+        block = handler
+            .view_client_send(GetBlock(BlockReference::BlockId(BlockId::Hash(prev_hash))))
+            .await?;
+    }
+    blocks.reverse();
+    Ok(blocks)
+}
+
+/// Returns notifications about given block for all block based subscriptions.
+/// Nothing is returned on error so that the block can be retried as a whole.
+async fn notify_about_block(
+    handler: &JsonRpcHandler,
+    subscriptions: &BTreeMap<SubscriptionId, RpcSubscribeRequest>,
+    block: BlockView,
+) -> Result<Vec<RpcSubscriptionNotification>, RpcStateChangesError> {
+    let mut notifications = Vec::new();
+    let block_hash = block.header.hash;
+    for (subscription_id, subscription) in subscriptions {
+        match subscription {
+            RpcSubscribeRequest::FinalBlocks => {
+                notifications.push(RpcSubscriptionNotification {
+                    subscription_id: *subscription_id,
+                    event: RpcSubscriptionEvent::FinalBlock { block: block.clone() },
+                });
+            }
+            RpcSubscribeRequest::StateChanges { state_changes_request } => {
+                let changes = handler
+                    .view_client_send(GetStateChanges {
+                        block_hash,
+                        state_changes_request: state_changes_request.clone(),
+                    })
+                    .await?;
+                if !changes.is_empty() {
+                    notifications.push(RpcSubscriptionNotification {
+                        subscription_id: *subscription_id,
+                        event: RpcSubscriptionEvent::StateChanges { block_hash, changes },
+                    });
+                }
+            }
+            RpcSubscribeRequest::TransactionOutcome { .. } => {}
+        }
+    }
+    Ok(notifications)
+}
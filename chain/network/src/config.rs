@@ -1,5 +1,6 @@
 use crate::blacklist;
 use crate::concurrency::rate;
+use crate::network_protocol::Compression;
 use crate::network_protocol::PeerAddr;
 use crate::network_protocol::PeerInfo;
 use crate::peer_manager::peer_manager_actor::Event;
@@ -88,6 +89,26 @@ pub struct Tier1 {
     pub enable_outbound: bool,
 }
 
+/// Encryption of TCP connections between peers, see `peer::encryption`.
+///
+/// Inbound encrypted connections are accepted in every mode, so that encryption can be rolled out
+/// gradually: first every node upgrades (keeping `disabled`), then nodes switch to `preferred` and
+/// eventually to `required`, once plaintext connections are no longer needed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportEncryption {
+    /// Outbound connections are not encrypted.
+    #[default]
+    Disabled,
+    /// Outbound connections are encrypted, except for connections to peers which have announced in
+    /// a plaintext `Handshake` (e.g. of an inbound connection) that they don't support encryption.
+    /// A failed encryption handshake never causes a fallback to plaintext, since it can be caused
+    /// by anyone on the path.
+    Preferred,
+    /// All connections are encrypted, inbound plaintext connections are rejected.
+    Required,
+}
+
 /// Validated configuration for the peer-to-peer manager.
 #[derive(Clone)]
 pub struct NetworkConfig {
@@ -150,6 +171,14 @@ pub struct NetworkConfig {
     pub routing_table_update_rate_limit: rate::Limit,
     /// Config of the TIER1 network.
     pub tier1: Option<Tier1>,
+    /// Encryption of TCP connections.
+    pub transport_encryption: TransportEncryption,
+    /// Algorithm used to compress large messages sent to peers which support it.
+    /// `None` disables compression of sent messages. Compressed messages are
+    /// accepted regardless of this setting.
+    pub compression: Option<Compression>,
+    /// Messages smaller than this are never compressed.
+    pub compression_threshold_bytes: usize,
 
     // Whether to ignore tombstones some time after startup.
     //
@@ -281,6 +310,9 @@ impl NetworkConfig {
                 enable_inbound: cfg.experimental.tier1_enable_inbound,
                 enable_outbound: cfg.experimental.tier1_enable_outbound,
             }),
+            transport_encryption: cfg.experimental.transport_encryption,
+            compression: cfg.experimental.compression,
+            compression_threshold_bytes: cfg.experimental.compression_threshold_bytes,
             inbound_disabled: cfg.experimental.inbound_disabled,
             skip_tombstones: if cfg.experimental.skip_sending_tombstones_seconds > 0 {
                 Some(time::Duration::seconds(cfg.experimental.skip_sending_tombstones_seconds))
@@ -349,6 +381,9 @@ impl NetworkConfig {
                 enable_inbound: true,
                 enable_outbound: true,
             }),
+            transport_encryption: TransportEncryption::Disabled,
+            compression: None,
+            compression_threshold_bytes: 0,
             skip_tombstones: None,
             event_sink: Sink::null(),
         }
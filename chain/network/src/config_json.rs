@@ -187,6 +187,12 @@ fn default_tier1_new_connections_per_attempt() -> u64 {
     50
 }
 
+/// Blocks, chunks and state parts are way larger than that,
+/// while compressing small messages isn't worth the CPU time.
+fn default_compression_threshold_bytes() -> usize {
+    16 * 1024
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ExperimentalConfig {
     // If true - don't allow any inbound connections.
@@ -219,6 +225,18 @@ pub struct ExperimentalConfig {
     /// See `near_network::config::Tier1::new_connections_per_attempt`.
     #[serde(default = "default_tier1_new_connections_per_attempt")]
     pub tier1_new_connections_per_attempt: u64,
+
+    /// See `near_network::config::TransportEncryption`.
+    #[serde(default)]
+    pub transport_encryption: crate::config::TransportEncryption,
+
+    /// See `near_network::config::NetworkConfig::compression`.
+    #[serde(default)]
+    pub compression: Option<crate::network_protocol::Compression>,
+
+    /// See `near_network::config::NetworkConfig::compression_threshold_bytes`.
+    #[serde(default = "default_compression_threshold_bytes")]
+    pub compression_threshold_bytes: usize,
 }
 
 impl Default for ExperimentalConfig {
@@ -231,6 +249,9 @@ impl Default for ExperimentalConfig {
             tier1_enable_outbound: default_tier1_enable_outbound(),
             tier1_connect_interval: default_tier1_connect_interval(),
             tier1_new_connections_per_attempt: default_tier1_new_connections_per_attempt(),
+            transport_encryption: Default::default(),
+            compression: None,
+            compression_threshold_bytes: default_compression_threshold_bytes(),
         }
     }
 }
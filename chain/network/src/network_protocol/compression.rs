@@ -0,0 +1,151 @@
+//! Compression of large `PeerMessage`s.
+//!
+//! A message is compressed only if it is encoded with protobuf and the peer has advertised support
+//! for the algorithm in `Handshake::features`. A compressed message is sent as a `PeerMessage`
+//! containing a `Compressed` message, which wraps the original binary encoded `PeerMessage`.
+use crate::network_protocol::proto;
+use crate::stats::metrics;
+use protobuf::Message as _;
+
+/// Compression algorithms which this node is able to decompress.
+pub(crate) const SUPPORTED: &[Compression] = &[Compression::Zstd, Compression::Lz4];
+
+/// Maximal size of a decompressed message. It is the same as the maximal size of a message
+/// received from the network, so that compression doesn't allow to bypass the limit.
+const MAX_RAW_SIZE: usize = 512 * bytesize::MIB as usize;
+
+/// Maximal ratio between the size of a decompressed message and the size of the compressed data.
+/// The decompression buffer is allocated upfront, so without this limit a tiny message claiming
+/// a huge `raw_size` would make the node allocate up to `MAX_RAW_SIZE`. Messages which compress
+/// better than that are sent uncompressed.
+pub(crate) const MAX_COMPRESSION_RATIO: usize = 64;
+
+/// Compression algorithm applied to a `PeerMessage`.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, strum::AsRefStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Compression {
+    Zstd,
+    Lz4,
+}
+
+impl From<Compression> for proto::compressed::Algorithm {
+    fn from(x: Compression) -> Self {
+        match x {
+            Compression::Zstd => Self::Zstd,
+            Compression::Lz4 => Self::Lz4,
+        }
+    }
+}
+
+impl TryFrom<proto::compressed::Algorithm> for Compression {
+    type Error = DecompressError;
+    fn try_from(x: proto::compressed::Algorithm) -> Result<Self, Self::Error> {
+        match x {
+            proto::compressed::Algorithm::Zstd => Ok(Self::Zstd),
+            proto::compressed::Algorithm::Lz4 => Ok(Self::Lz4),
+            proto::compressed::Algorithm::UNKNOWN => Err(DecompressError::UnknownAlgorithm),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DecompressError {
+    #[error("unknown algorithm")]
+    UnknownAlgorithm,
+    #[error("raw_size {got_bytes}B exceeds the limit of {MAX_RAW_SIZE}B")]
+    TooLarge { got_bytes: usize },
+    #[error("raw_size {raw_bytes}B exceeds {MAX_COMPRESSION_RATIO} times the size of the compressed data ({compressed_bytes}B)")]
+    RatioTooLarge { raw_bytes: usize, compressed_bytes: usize },
+    #[error("zstd: {0}")]
+    Zstd(#[source] std::io::Error),
+    #[error("lz4: {0}")]
+    Lz4(#[source] lz4_flex::block::DecompressError),
+    #[error("decompressed {got_bytes}B, want {want_bytes}B")]
+    SizeMismatch { got_bytes: usize, want_bytes: usize },
+    #[error("nested compressed message")]
+    Nested,
+}
+
+/// Compresses a binary encoded protobuf `PeerMessage` of type `msg_type`.
+/// Returns `None` if compression doesn't make the message smaller.
+pub(crate) fn compress(compression: Compression, msg_type: &str, raw: &[u8]) -> Option<Vec<u8>> {
+    let _span = tracing::trace_span!(target: "network", "compress", msg_type, raw_size = raw.len())
+        .entered();
+    let data = match compression {
+        Compression::Zstd => match zstd::bulk::compress(raw, zstd::DEFAULT_COMPRESSION_LEVEL) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::warn!(target: "network", ?err, "zstd compression failed");
+                return None;
+            }
+        },
+        Compression::Lz4 => lz4_flex::block::compress(raw),
+    };
+    if raw.len() > data.len().saturating_mul(MAX_COMPRESSION_RATIO) {
+        // The other side would reject the message.
+        return None;
+    }
+    let msg = proto::PeerMessage {
+        message_type: Some(proto::peer_message::Message_type::Compressed(proto::Compressed {
+            algorithm: proto::compressed::Algorithm::from(compression).into(),
+            raw_size: raw.len() as u32,
+            data,
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    let compressed = msg.write_to_bytes().unwrap();
+    if compressed.len() >= raw.len() {
+        return None;
+    }
+    observe("sent", msg_type, compression, raw.len(), compressed.len());
+    Some(compressed)
+}
+
+/// Decompresses a `Compressed` message. Returns the binary encoded protobuf `PeerMessage`.
+pub(crate) fn decompress(
+    msg: &proto::Compressed,
+) -> Result<(Compression, Vec<u8>), DecompressError> {
+    let compression: Compression =
+        msg.algorithm.enum_value().map_err(|_| DecompressError::UnknownAlgorithm)?.try_into()?;
+    let raw_size = msg.raw_size as usize;
+    if raw_size > MAX_RAW_SIZE {
+        return Err(DecompressError::TooLarge { got_bytes: raw_size });
+    }
+    if raw_size > msg.data.len().saturating_mul(MAX_COMPRESSION_RATIO) {
+        return Err(DecompressError::RatioTooLarge {
+            raw_bytes: raw_size,
+            compressed_bytes: msg.data.len(),
+        });
+    }
+    let raw = match compression {
+        Compression::Zstd => {
+            zstd::bulk::decompress(&msg.data, raw_size).map_err(DecompressError::Zstd)?
+        }
+        Compression::Lz4 => {
+            lz4_flex::block::decompress(&msg.data, raw_size).map_err(DecompressError::Lz4)?
+        }
+    };
+    if raw.len() != raw_size {
+        return Err(DecompressError::SizeMismatch { got_bytes: raw.len(), want_bytes: raw_size });
+    }
+    Ok((compression, raw))
+}
+
+/// Reports sizes of a compressed message, `direction` is either "sent" or "received".
+pub(crate) fn observe(
+    direction: &str,
+    msg_type: &str,
+    compression: Compression,
+    raw_size: usize,
+    compressed_size: usize,
+) {
+    let labels = [direction, msg_type, compression.as_ref()];
+    metrics::PEER_MESSAGE_COMPRESSION_RAW_BYTES.with_label_values(&labels).inc_by(raw_size as u64);
+    metrics::PEER_MESSAGE_COMPRESSION_COMPRESSED_BYTES
+        .with_label_values(&labels)
+        .inc_by(compressed_size as u64);
+}
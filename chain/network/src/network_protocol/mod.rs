@@ -2,9 +2,11 @@
 #[path = "borsh.rs"]
 mod borsh_;
 mod borsh_conv;
+mod compression;
 mod edge;
 mod peer;
 mod proto_conv;
+pub use compression::{Compression, DecompressError};
 pub use edge::*;
 pub use peer::*;
 
@@ -266,6 +268,24 @@ pub struct Handshake {
     pub(crate) partial_edge_info: PartialEdgeInfo,
     /// Account owned by the sender.
     pub(crate) owned_account: Option<SignedOwnedAccount>,
+    /// Optional features of the network protocol supported by the sender.
+    pub(crate) features: ProtocolFeatures,
+}
+
+/// See ProtocolFeatures in network_protocol/network.proto.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct ProtocolFeatures {
+    /// Compression algorithms which the sender is able to decompress.
+    pub(crate) compression: Vec<Compression>,
+    /// Whether the sender accepts encrypted connections.
+    pub(crate) encryption: bool,
+}
+
+impl ProtocolFeatures {
+    /// Features supported by this node.
+    pub(crate) fn supported() -> Self {
+        Self { compression: compression::SUPPORTED.to_vec(), encryption: true }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, strum::IntoStaticStr)]
@@ -336,6 +356,8 @@ pub enum ParsePeerMessageError {
     ProtoDecode(#[source] protobuf::Error),
     #[error("ProtoConv")]
     ProtoConv(#[source] proto_conv::ParsePeerMessageError),
+    #[error("Decompress")]
+    Decompress(#[source] DecompressError),
 }
 
 impl PeerMessage {
@@ -364,16 +386,58 @@ impl PeerMessage {
                 .try_into()
                 .map_err(ParsePeerMessageError::BorshConv)?,
             Encoding::Proto => {
-                let proto_msg: proto::PeerMessage = proto::PeerMessage::parse_from_bytes(data)
+                let mut proto_msg: proto::PeerMessage = proto::PeerMessage::parse_from_bytes(data)
                     .map_err(ParsePeerMessageError::ProtoDecode)?;
+                let mut compressed = None;
+                if let Some(proto::peer_message::Message_type::Compressed(c)) =
+                    &proto_msg.message_type
+                {
+                    let (compression, raw) =
+                        compression::decompress(c).map_err(ParsePeerMessageError::Decompress)?;
+                    proto_msg = proto::PeerMessage::parse_from_bytes(&raw)
+                        .map_err(ParsePeerMessageError::ProtoDecode)?;
+                    if let Some(proto::peer_message::Message_type::Compressed(_)) =
+                        &proto_msg.message_type
+                    {
+                        return Err(ParsePeerMessageError::Decompress(DecompressError::Nested));
+                    }
+                    compressed = Some((compression, raw.len()));
+                }
                 if let Ok(extracted_span_context) = extract_span_context(&proto_msg.trace_context) {
                     span.clone().or_current().add_link(extracted_span_context);
                 }
-                (&proto_msg).try_into().map_err(|err| ParsePeerMessageError::ProtoConv(err))?
+                let msg: PeerMessage =
+                    (&proto_msg).try_into().map_err(|err| ParsePeerMessageError::ProtoConv(err))?;
+                if let Some((compression, raw_size)) = compressed {
+                    compression::observe(
+                        "received",
+                        msg.msg_variant(),
+                        compression,
+                        raw_size,
+                        data.len(),
+                    );
+                }
+                msg
             }
         })
     }
 
+    /// Serializes a message in the given encoding, compressing it if it is encoded with protobuf
+    /// and its size is at least `threshold` bytes. Falls back to the uncompressed message if
+    /// compression doesn't make it smaller.
+    pub(crate) fn serialize_compressed(
+        &self,
+        enc: Encoding,
+        compression: Compression,
+        threshold: usize,
+    ) -> Vec<u8> {
+        let raw = self.serialize(enc);
+        if enc != Encoding::Proto || raw.len() < threshold {
+            return raw;
+        }
+        compression::compress(compression, self.msg_variant(), &raw).unwrap_or(raw)
+    }
+
     pub(crate) fn msg_variant(&self) -> &'static str {
         match self {
             PeerMessage::Routed(routed_msg) => routed_msg.body_variant(),
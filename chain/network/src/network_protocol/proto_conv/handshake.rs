@@ -2,8 +2,8 @@
 use super::*;
 
 use crate::network_protocol::proto;
-use crate::network_protocol::{Handshake, HandshakeFailureReason};
-use crate::network_protocol::{PeerChainInfoV2, PeerInfo};
+use crate::network_protocol::{Compression, Handshake, HandshakeFailureReason};
+use crate::network_protocol::{PeerChainInfoV2, PeerInfo, ProtocolFeatures};
 use near_primitives::block::GenesisId;
 use protobuf::MessageField as MF;
 
@@ -63,6 +63,36 @@ impl TryFrom<&proto::PeerChainInfo> for PeerChainInfoV2 {
 
 //////////////////////////////////////////
 
+impl From<&ProtocolFeatures> for proto::ProtocolFeatures {
+    fn from(x: &ProtocolFeatures) -> Self {
+        Self {
+            compression: x
+                .compression
+                .iter()
+                .map(|c| proto::compressed::Algorithm::from(*c).into())
+                .collect(),
+            encryption: x.encryption,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&proto::ProtocolFeatures> for ProtocolFeatures {
+    fn from(p: &proto::ProtocolFeatures) -> Self {
+        Self {
+            // Algorithms unknown to this node are ignored.
+            compression: p
+                .compression
+                .iter()
+                .filter_map(|c| Compression::try_from(c.enum_value().ok()?).ok())
+                .collect(),
+            encryption: p.encryption,
+        }
+    }
+}
+
+//////////////////////////////////////////
+
 #[derive(thiserror::Error, Debug)]
 pub enum ParseHandshakeError {
     #[error("sender_peer_id {0}")]
@@ -90,6 +120,7 @@ impl From<&Handshake> for proto::Handshake {
             sender_chain_info: MF::some((&x.sender_chain_info).into()),
             partial_edge_info: MF::some((&x.partial_edge_info).into()),
             owned_account: x.owned_account.as_ref().map(Into::into).into(),
+            features: MF::some((&x.features).into()),
             ..Self::default()
         }
     }
@@ -120,6 +151,7 @@ impl TryFrom<&proto::Handshake> for Handshake {
                 .map_err(Self::Error::PartialEdgeInfo)?,
             owned_account: try_from_optional(&p.owned_account)
                 .map_err(Self::Error::OwnedAccount)?,
+            features: p.features.as_ref().map(Into::into).unwrap_or_default(),
         })
     }
 }
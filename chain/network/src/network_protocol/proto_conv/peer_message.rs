@@ -195,6 +195,8 @@ pub enum ParsePeerMessageError {
     RoutedCreatedAtTimestamp(ComponentRange),
     #[error("sync_accounts_data: {0}")]
     SyncAccountsData(ParseVecError<ParseSignedAccountDataError>),
+    #[error("compressed: has to be decompressed before conversion")]
+    Compressed,
 }
 
 impl TryFrom<&proto::PeerMessage> for PeerMessage {
@@ -271,6 +273,8 @@ impl TryFrom<&proto::PeerMessage> for PeerMessage {
             ProtoMT::Challenge(c) => PeerMessage::Challenge(
                 Challenge::try_from_slice(&c.borsh).map_err(Self::Error::Challenge)?,
             ),
+            // Decompression is done by PeerMessage::deserialize.
+            ProtoMT::Compressed(_) => return Err(Self::Error::Compressed),
         })
     }
 }
@@ -60,7 +60,10 @@ fn serialize_deserialize_protobuf_only() {
     let mut clock = time::FakeClock::default();
     let chain = data::Chain::make(&mut clock, &mut rng, 12);
     let msgs = [
-        PeerMessage::Tier1Handshake(data::make_handshake(&mut rng, &chain)),
+        PeerMessage::Tier1Handshake(Handshake {
+            features: ProtocolFeatures::supported(),
+            ..data::make_handshake(&mut rng, &chain)
+        }),
         PeerMessage::SyncAccountsData(SyncAccountsData {
             accounts_data: (0..4)
                 .map(|_| Arc::new(data::make_signed_account_data(&mut rng, &clock.clock())))
@@ -165,3 +168,53 @@ fn serialize_deserialize() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn serialize_deserialize_compressed() {
+    let mut rng = make_rng(4672198450129);
+    let mut clock = time::FakeClock::default();
+    let chain = data::Chain::make(&mut clock, &mut rng, 12);
+    let msgs = [
+        PeerMessage::Block(chain.blocks[5].clone()),
+        PeerMessage::BlockHeaders(chain.get_block_headers()),
+        PeerMessage::Disconnect,
+    ];
+    for compression in [Compression::Zstd, Compression::Lz4] {
+        for m in &msgs {
+            let bytes = m.serialize_compressed(Encoding::Proto, compression, 0);
+            let m2 = PeerMessage::deserialize(Encoding::Proto, &bytes)
+                .with_context(|| format!("{compression:?}: {m}"))
+                .unwrap();
+            assert_eq!(*m, m2);
+        }
+        // Messages below the threshold are sent as is.
+        let m = &msgs[0];
+        assert_eq!(
+            m.serialize(Encoding::Proto),
+            m.serialize_compressed(Encoding::Proto, compression, usize::MAX)
+        );
+        // Borsh encoded messages are never compressed.
+        assert_eq!(
+            m.serialize(Encoding::Borsh),
+            m.serialize_compressed(Encoding::Borsh, compression, 0)
+        );
+    }
+}
+
+#[test]
+fn decompress_rejects_large_ratio() {
+    for compression in [Compression::Zstd, Compression::Lz4] {
+        let msg = proto::Compressed {
+            algorithm: proto::compressed::Algorithm::from(compression).into(),
+            raw_size: (compression::MAX_COMPRESSION_RATIO * 10 + 1) as u32,
+            data: vec![0; 10],
+            ..Default::default()
+        };
+        assert!(matches!(
+            compression::decompress(&msg),
+            Err(DecompressError::RatioTooLarge { .. })
+        ));
+        // Messages compressing too well are not compressed at all.
+        assert_eq!(None, compression::compress(compression, "test", &vec![0; 100_000]));
+    }
+}
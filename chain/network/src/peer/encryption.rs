@@ -0,0 +1,300 @@
+//! Authenticated encryption of peer-to-peer TCP connections.
+//!
+//! Connections are encrypted with the [Noise protocol](https://noiseprotocol.org/noise.html)
+//! `Noise_XX_25519_ChaChaPoly_BLAKE2s`. The static X25519 key of the Noise handshake is generated
+//! per connection and is bound to the `PeerId` of the node by the handshake payload: each side
+//! sends its node public key together with a signature (made with the node key) of its static
+//! X25519 key. Since the XX handshake proves possession of the static key, a successful handshake
+//! authenticates the `PeerId` of the other side.
+//!
+//! Encryption is negotiated before the `Handshake` message is exchanged: the outbound side starts
+//! the connection with [`MAGIC`], which is never a valid length prefix of a plaintext frame (it
+//! exceeds the maximal message size). This way the inbound side can tell encrypted and plaintext
+//! connections apart and keep accepting the latter from nodes which don't support encryption yet.
+//! Such nodes close a connection starting with [`MAGIC`], which is reported as
+//! [`Error::Rejected`]. Since an attacker on the path can close the connection just as well, it is
+//! not a reason to retry in plaintext. Instead, nodes announce encryption support in the
+//! `ProtocolFeatures` of their `Handshake` (see `config::TransportEncryption::Preferred`).
+//!
+//! After the handshake every frame is sent as an encrypted 4-byte length header, followed by the
+//! frame split into chunks fitting into Noise messages. Sizes of the chunks follow from the length,
+//! so no metadata is sent in plaintext.
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, SecretKey, Signature};
+use near_primitives::network::PeerId;
+use std::io;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncWriteExt as _};
+
+/// First 4 bytes sent over an encrypted connection.
+pub(crate) const MAGIC: u32 = u32::MAX;
+
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// Maximal length of a Noise message.
+const NOISE_MAX_MESSAGE_LEN: usize = 65535;
+/// Length of the authentication tag appended to every encrypted Noise message.
+const TAG_LEN: usize = 16;
+/// Maximal length of a frame chunk encrypted into a single Noise message.
+const MAX_CHUNK_LEN: usize = NOISE_MAX_MESSAGE_LEN - TAG_LEN;
+/// Length of the encrypted frame length header.
+const HEADER_LEN: usize = 4 + TAG_LEN;
+/// Domain separator of the signature binding the Noise static key to the node key.
+const STATIC_KEY_SIGNATURE_PREFIX: &[u8] = b"near-network-noise-static-key:";
+
+/// Whether and how to encrypt a connection.
+#[derive(Clone, Debug)]
+pub(crate) enum Plan {
+    /// Don't encrypt the connection.
+    Plaintext,
+    /// Inbound connection: encrypt it if the other side initiates the encryption handshake.
+    Accept { node_key: SecretKey, require: bool },
+    /// Outbound connection: initiate the encryption handshake with the given peer.
+    Initiate { node_key: SecretKey, peer_id: PeerId },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    #[error("IO error: {0}")]
+    IO(#[source] io::Error),
+    #[error("noise: {0}")]
+    Noise(#[from] snow::Error),
+    #[error("peer closed the connection during the encryption handshake")]
+    Rejected,
+    #[error("plaintext connections are not allowed")]
+    PlaintextNotAllowed,
+    #[error("handshake message too large: got {got_bytes}B, want <={NOISE_MAX_MESSAGE_LEN}B")]
+    MessageTooLarge { got_bytes: usize },
+    #[error("invalid handshake payload")]
+    InvalidPayload,
+    #[error("peer authenticated as {got}, want {want}")]
+    PeerIdMismatch { got: PeerId, want: PeerId },
+}
+
+/// Result of the encryption negotiation.
+pub(crate) struct Negotiated {
+    /// Bytes read from a plaintext connection while detecting whether it is encrypted.
+    /// They are the beginning of the first frame.
+    pub prefix: Vec<u8>,
+    /// Encrypted session, `None` if the connection is not encrypted.
+    pub session: Option<Session>,
+}
+
+/// Negotiates encryption of a freshly established connection according to `plan`.
+pub(crate) async fn negotiate(
+    stream: &mut tokio::net::TcpStream,
+    plan: &Plan,
+) -> Result<Negotiated, Error> {
+    match plan {
+        Plan::Plaintext => Ok(Negotiated { prefix: vec![], session: None }),
+        Plan::Accept { node_key, require } => {
+            let mut prefix = [0; 4];
+            stream.read_exact(&mut prefix).await.map_err(Error::IO)?;
+            if u32::from_le_bytes(prefix) != MAGIC {
+                if *require {
+                    return Err(Error::PlaintextNotAllowed);
+                }
+                return Ok(Negotiated { prefix: prefix.to_vec(), session: None });
+            }
+            Ok(Negotiated { prefix: vec![], session: Some(respond(stream, node_key).await?) })
+        }
+        Plan::Initiate { node_key, peer_id } => {
+            stream.write_u32_le(MAGIC).await.map_err(Error::IO)?;
+            Ok(Negotiated {
+                prefix: vec![],
+                session: Some(initiate(stream, node_key, peer_id).await?),
+            })
+        }
+    }
+}
+
+async fn initiate(
+    stream: &mut tokio::net::TcpStream,
+    node_key: &SecretKey,
+    peer_id: &PeerId,
+) -> Result<Session, Error> {
+    let (mut hs, static_key) = new_handshake(true)?;
+    let mut buf = vec![0; NOISE_MAX_MESSAGE_LEN];
+    // -> e
+    let n = hs.write_message(&[], &mut buf)?;
+    write_message(stream, &buf[..n]).await?;
+    // <- e, ee, s, es
+    let msg = read_message(stream).await.map_err(|err| match err {
+        Error::IO(err) if is_closed(&err) => Error::Rejected,
+        err => err,
+    })?;
+    let n = hs.read_message(&msg, &mut buf)?;
+    let got = verify_payload(&buf[..n], hs.get_remote_static())?;
+    if &got != peer_id {
+        return Err(Error::PeerIdMismatch { got, want: peer_id.clone() });
+    }
+    // -> s, se
+    let n = hs.write_message(&make_payload(node_key, &static_key), &mut buf)?;
+    write_message(stream, &buf[..n]).await?;
+    Ok(Session { peer_id: got, transport: Arc::new(hs.into_stateless_transport_mode()?) })
+}
+
+async fn respond(
+    stream: &mut tokio::net::TcpStream,
+    node_key: &SecretKey,
+) -> Result<Session, Error> {
+    let (mut hs, static_key) = new_handshake(false)?;
+    let mut buf = vec![0; NOISE_MAX_MESSAGE_LEN];
+    // -> e
+    let msg = read_message(stream).await?;
+    hs.read_message(&msg, &mut buf)?;
+    // <- e, ee, s, es
+    let n = hs.write_message(&make_payload(node_key, &static_key), &mut buf)?;
+    write_message(stream, &buf[..n]).await?;
+    // -> s, se
+    let msg = read_message(stream).await?;
+    let n = hs.read_message(&msg, &mut buf)?;
+    let peer_id = verify_payload(&buf[..n], hs.get_remote_static())?;
+    Ok(Session { peer_id, transport: Arc::new(hs.into_stateless_transport_mode()?) })
+}
+
+/// Creates a handshake state with a fresh static key. Returns the state and the public static key.
+fn new_handshake(initiator: bool) -> Result<(snow::HandshakeState, Vec<u8>), Error> {
+    let builder = snow::Builder::new(NOISE_PARAMS.parse()?);
+    let keypair = builder.generate_keypair()?;
+    let builder = builder.local_private_key(&keypair.private);
+    let hs = if initiator { builder.build_initiator()? } else { builder.build_responder()? };
+    Ok((hs, keypair.public))
+}
+
+fn is_closed(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset | io::ErrorKind::BrokenPipe
+    )
+}
+
+async fn write_message(stream: &mut tokio::net::TcpStream, msg: &[u8]) -> Result<(), Error> {
+    stream.write_u32_le(msg.len() as u32).await.map_err(Error::IO)?;
+    stream.write_all(msg).await.map_err(Error::IO)
+}
+
+async fn read_message(stream: &mut tokio::net::TcpStream) -> Result<Vec<u8>, Error> {
+    let n = stream.read_u32_le().await.map_err(Error::IO)? as usize;
+    if n > NOISE_MAX_MESSAGE_LEN {
+        return Err(Error::MessageTooLarge { got_bytes: n });
+    }
+    let mut msg = vec![0; n];
+    stream.read_exact(&mut msg).await.map_err(Error::IO)?;
+    Ok(msg)
+}
+
+/// Payload of the handshake messages carrying the static keys.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct HandshakePayload {
+    node_key: PublicKey,
+    /// Signature of the Noise static key made with the node key.
+    signature: Signature,
+}
+
+fn static_key_signature_data(static_key: &[u8]) -> Vec<u8> {
+    [STATIC_KEY_SIGNATURE_PREFIX, static_key].concat()
+}
+
+fn make_payload(node_key: &SecretKey, static_key: &[u8]) -> Vec<u8> {
+    HandshakePayload {
+        node_key: node_key.public_key(),
+        signature: node_key.sign(&static_key_signature_data(static_key)),
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+/// Verifies the handshake payload against the static key of the other side.
+/// Returns the authenticated `PeerId` of the other side.
+fn verify_payload(payload: &[u8], static_key: Option<&[u8]>) -> Result<PeerId, Error> {
+    let static_key = static_key.ok_or(Error::InvalidPayload)?;
+    let payload = HandshakePayload::try_from_slice(payload).map_err(|_| Error::InvalidPayload)?;
+    if !payload.signature.verify(&static_key_signature_data(static_key), &payload.node_key) {
+        return Err(Error::InvalidPayload);
+    }
+    Ok(PeerId::new(payload.node_key))
+}
+
+/// Established encrypted session.
+pub(crate) struct Session {
+    /// Authenticated `PeerId` of the other side.
+    pub peer_id: PeerId,
+    transport: Arc<snow::StatelessTransportState>,
+}
+
+impl Session {
+    /// Splits the session into its sending and receiving halves.
+    pub fn split(self) -> (PeerId, Sender, Receiver) {
+        (
+            self.peer_id,
+            Sender { transport: self.transport.clone(), nonce: 0 },
+            Receiver { transport: self.transport, nonce: 0 },
+        )
+    }
+}
+
+/// Length of a frame of `n` bytes after encryption.
+fn encrypted_len(n: usize) -> usize {
+    HEADER_LEN + n + TAG_LEN * ((n + MAX_CHUNK_LEN - 1) / MAX_CHUNK_LEN)
+}
+
+pub(crate) struct Sender {
+    transport: Arc<snow::StatelessTransportState>,
+    nonce: u64,
+}
+
+impl Sender {
+    /// Encrypts a frame, returns the bytes to be sent over the connection.
+    pub fn encrypt(&mut self, frame: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut out = vec![0; encrypted_len(frame.len())];
+        let mut pos = self.encrypt_message(&(frame.len() as u32).to_le_bytes(), &mut out)?;
+        for chunk in frame.chunks(MAX_CHUNK_LEN) {
+            pos += self.encrypt_message(chunk, &mut out[pos..])?;
+        }
+        Ok(out)
+    }
+
+    fn encrypt_message(&mut self, payload: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        let n = self.transport.write_message(self.nonce, payload, out)?;
+        self.nonce += 1;
+        Ok(n)
+    }
+}
+
+pub(crate) struct Receiver {
+    transport: Arc<snow::StatelessTransportState>,
+    nonce: u64,
+}
+
+impl Receiver {
+    /// Reads and decrypts the length header of the next frame.
+    pub async fn read_len(&mut self, read: &mut (impl AsyncRead + Unpin)) -> Result<usize, Error> {
+        let mut header = [0; HEADER_LEN];
+        read.read_exact(&mut header).await.map_err(Error::IO)?;
+        let mut len = [0; 4];
+        self.decrypt_message(&header, &mut len)?;
+        Ok(u32::from_le_bytes(len) as usize)
+    }
+
+    /// Reads and decrypts a frame of `n` bytes, whose length header has been read already.
+    pub async fn read_frame(
+        &mut self,
+        read: &mut (impl AsyncRead + Unpin),
+        n: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let mut frame = vec![0; n];
+        let mut buf = vec![0; NOISE_MAX_MESSAGE_LEN.min(n + TAG_LEN)];
+        for chunk in frame.chunks_mut(MAX_CHUNK_LEN) {
+            let msg = &mut buf[..chunk.len() + TAG_LEN];
+            read.read_exact(msg).await.map_err(Error::IO)?;
+            self.decrypt_message(msg, chunk)?;
+        }
+        Ok(frame)
+    }
+
+    fn decrypt_message(&mut self, msg: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+        let n = self.transport.read_message(self.nonce, msg, out)?;
+        self.nonce += 1;
+        Ok(n)
+    }
+}
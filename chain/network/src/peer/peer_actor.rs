@@ -1,11 +1,13 @@
 use crate::accounts_data;
 use crate::concurrency::atomic_cell::AtomicCell;
 use crate::concurrency::demux;
+use crate::config::TransportEncryption;
 use crate::network_protocol::{
-    Edge, EdgeState, Encoding, OwnedAccount, ParsePeerMessageError, PartialEdgeInfo,
-    PeerChainInfoV2, PeerIdOrHash, PeerInfo, RawRoutedMessage, RoutedMessageBody, RoutedMessageV2,
-    RoutingTableUpdate, SyncAccountsData,
+    Compression, Edge, EdgeState, Encoding, OwnedAccount, ParsePeerMessageError, PartialEdgeInfo,
+    PeerChainInfoV2, PeerIdOrHash, PeerInfo, ProtocolFeatures, RawRoutedMessage, RoutedMessageBody,
+    RoutedMessageV2, RoutingTableUpdate, SyncAccountsData,
 };
+use crate::peer::encryption;
 use crate::peer::stream;
 use crate::peer::tracker::Tracker;
 use crate::peer_manager::connection;
@@ -134,6 +136,9 @@ pub(crate) struct PeerActor {
     routed_message_cache: LruCache<(PeerId, PeerIdOrHash, Signature), time::Instant>,
     /// Whether we detected support for protocol buffers during handshake.
     protocol_buffers_supported: bool,
+    /// Compression algorithm used for large messages sent to the peer.
+    /// Negotiated during handshake, `None` if compression is disabled or not supported by the peer.
+    compression: Option<Compression>,
     /// Whether the PeerActor should skip protobuf support detection and use
     /// a given encoding right away.
     force_encoding: Option<Encoding>,
@@ -256,6 +261,25 @@ impl PeerActor {
             }
             _ => force_encoding,
         };
+        let node_key = network_state.config.node_key.clone();
+        let encryption = match (&stream.type_, network_state.config.transport_encryption) {
+            (tcp::StreamType::Inbound, mode) => encryption::Plan::Accept {
+                node_key,
+                require: mode == TransportEncryption::Required,
+            },
+            (tcp::StreamType::Outbound { .. }, TransportEncryption::Disabled) => {
+                encryption::Plan::Plaintext
+            }
+            (tcp::StreamType::Outbound { peer_id, .. }, TransportEncryption::Preferred)
+                if network_state.is_plaintext_peer(&clock, peer_id) =>
+            {
+                tracing::debug!(target: "network", %peer_id, "Connecting without encryption");
+                encryption::Plan::Plaintext
+            }
+            (tcp::StreamType::Outbound { peer_id, .. }, _) => {
+                encryption::Plan::Initiate { node_key, peer_id: peer_id.clone() }
+            }
+        };
         let my_node_info = PeerInfo {
             id: network_state.config.node_id(),
             addr: network_state.config.node_addr.clone(),
@@ -271,7 +295,7 @@ impl PeerActor {
                 let peer_addr = stream.peer_addr;
                 let stream_type = stream.type_.clone();
                 let stats = Arc::new(connection::Stats::default());
-                let framed = stream::FramedStream::spawn(ctx, stream, stats.clone());
+                let framed = stream::FramedStream::spawn(ctx, stream, stats.clone(), encryption);
                 Self {
                     closing_reason: None,
                     clock,
@@ -288,6 +312,7 @@ impl PeerActor {
                     stats,
                     routed_message_cache: LruCache::new(ROUTED_MESSAGE_CACHE_SIZE),
                     protocol_buffers_supported: false,
+                    compression: None,
                     force_encoding,
                     peer_info: match &stream_type {
                         tcp::StreamType::Inbound => None,
@@ -366,7 +391,14 @@ impl PeerActor {
             _ => (),
         };
 
-        let bytes = msg.serialize(enc);
+        let bytes = match self.compression {
+            Some(compression) => msg.serialize_compressed(
+                enc,
+                compression,
+                self.network_state.config.compression_threshold_bytes,
+            ),
+            None => msg.serialize(enc),
+        };
         self.tracker.lock().increment_sent(&self.clock, bytes.len() as u64);
         let bytes_len = bytes.len();
         tracing::trace!(target: "network", msg_len = bytes_len);
@@ -407,6 +439,7 @@ impl PeerActor {
                 }
                 .sign(vc.signer.as_ref())
             }),
+            features: ProtocolFeatures::supported(),
         };
         let msg = match spec.tier {
             tcp::Tier::T1 => PeerMessage::Tier1Handshake(handshake),
@@ -440,6 +473,22 @@ impl PeerActor {
         handshake: Handshake,
     ) {
         tracing::debug!(target: "network", "{:?}: Received handshake {:?}", self.my_node_info.id, handshake);
+        // An encrypted connection has been authenticated already,
+        // the handshake has to come from the same peer.
+        if let Some(peer_id) = self.framed.encrypted_peer_id() {
+            if peer_id != &handshake.sender_peer_id {
+                tracing::warn!(target: "network", "PeerId mismatch with the encrypted connection. Disconnecting peer {}", handshake.sender_peer_id);
+                self.stop(ctx, ClosingReason::HandshakeFailed);
+                return;
+            }
+        }
+        let encrypted = self.framed.encrypted_peer_id().is_some();
+        // Compress messages only with an algorithm the peer is able to decompress.
+        self.compression = self
+            .network_state
+            .config
+            .compression
+            .filter(|c| handshake.features.compression.contains(c));
         let cs = match &self.peer_status {
             PeerStatus::Connecting(_, it) => it,
             _ => panic!("process_handshake called in non-connecting state"),
@@ -640,6 +689,16 @@ impl PeerActor {
             .map(move |res, act: &mut PeerActor, ctx| {
                 match res {
                     Ok(()) => {
+                        // The handshake has been verified, including the edge signed by the peer,
+                        // so the announcement comes from the peer itself, see
+                        // `TransportEncryption::Preferred`.
+                        if !encrypted {
+                            if handshake.features.encryption {
+                                act.network_state.unset_plaintext_peer(&handshake.sender_peer_id);
+                            } else {
+                                act.network_state.set_plaintext_peer(&act.clock, handshake.sender_peer_id.clone());
+                            }
+                        }
                         act.peer_info = Some(peer_info).into();
                         act.peer_status = PeerStatus::Ready(conn.clone());
                         // Respond to handshake if it's inbound and connection was consolidated.
@@ -1398,6 +1457,12 @@ impl actix::Handler<stream::Error> for PeerActor {
             }
             // It is expected in a sense that the peer might be just slow.
             stream::Error::Send(stream::SendError::QueueOverflow { .. }) => true,
+            // The connection might have been closed by a peer which doesn't support encryption,
+            // but also by anyone on the path, so it is not a reason to fall back to plaintext.
+            stream::Error::Encryption(encryption::Error::Rejected) => true,
+            // Peer is incompatible or malicious.
+            stream::Error::Encryption(_)
+            | stream::Error::Recv(stream::RecvError::Decryption(_)) => true,
             stream::Error::Recv(stream::RecvError::IO(err))
             | stream::Error::Send(stream::SendError::IO(err)) => match err.kind() {
                 // Connection has been closed.
@@ -1,9 +1,12 @@
+use crate::peer::encryption;
 use crate::peer_manager::connection;
 use crate::stats::metrics;
 use crate::tcp;
 use actix::fut::future::wrap_future;
 use actix::AsyncContext as _;
 use bytesize::{GIB, MIB};
+use near_primitives::network::PeerId;
+use once_cell::sync::OnceCell;
 use std::io;
 use std::net::SocketAddr;
 use std::sync::atomic::Ordering;
@@ -17,7 +20,6 @@ const NETWORK_MESSAGE_MAX_SIZE_BYTES: usize = 512 * MIB as usize;
 /// Maximum capacity of write buffer in bytes.
 const MAX_WRITE_BUFFER_CAPACITY_BYTES: usize = GIB as usize;
 
-type ReadHalf = tokio::io::ReadHalf<tokio::net::TcpStream>;
 type WriteHalf = tokio::io::WriteHalf<tokio::net::TcpStream>;
 
 #[derive(thiserror::Error, Debug)]
@@ -34,6 +36,17 @@ pub(crate) enum RecvError {
     IO(#[source] io::Error),
     #[error("message too large: got {got_bytes}B, want <={want_max_bytes}B")]
     MessageTooLarge { got_bytes: usize, want_max_bytes: usize },
+    #[error("decryption failed: {0}")]
+    Decryption(#[source] encryption::Error),
+}
+
+impl From<encryption::Error> for RecvError {
+    fn from(err: encryption::Error) -> Self {
+        match err {
+            encryption::Error::IO(err) => Self::IO(err),
+            err => Self::Decryption(err),
+        }
+    }
 }
 
 #[derive(actix::Message, PartialEq, Eq, Clone, Debug)]
@@ -54,6 +67,8 @@ pub(crate) enum Error {
     Send(#[source] SendError),
     #[error("recv: {0}")]
     Recv(#[source] RecvError),
+    #[error("encryption: {0}")]
+    Encryption(#[source] encryption::Error),
 }
 
 pub(crate) struct FramedStream<Actor: actix::Actor> {
@@ -61,6 +76,7 @@ pub(crate) struct FramedStream<Actor: actix::Actor> {
     stats: Arc<connection::Stats>,
     send_buf_size_metric: Arc<metrics::IntGaugeGuard>,
     addr: actix::Addr<Actor>,
+    encrypted_peer_id: Arc<OnceCell<PeerId>>,
 }
 
 impl<Actor> FramedStream<Actor>
@@ -69,39 +85,79 @@ where
         + actix::Handler<Error>
         + actix::Handler<Frame>,
 {
+    /// Spawns the send and recv loops. Before they start, encryption of the connection is
+    /// negotiated according to `encryption`. Frames sent in the meantime are queued.
     pub fn spawn(
         ctx: &mut actix::Context<Actor>,
         stream: tcp::Stream,
         stats: Arc<connection::Stats>,
+        encryption: encryption::Plan,
     ) -> Self {
-        let (tcp_recv, tcp_send) = tokio::io::split(stream.stream);
         let (queue_send, queue_recv) = tokio::sync::mpsc::unbounded_channel();
         let send_buf_size_metric = Arc::new(metrics::MetricGuard::new(
             &*metrics::PEER_DATA_WRITE_BUFFER_SIZE,
             vec![stream.peer_addr.to_string()],
         ));
+        let encrypted_peer_id = Arc::new(OnceCell::new());
         ctx.spawn(wrap_future({
             let addr = ctx.address();
             let stats = stats.clone();
             let m = send_buf_size_metric.clone();
+            let encrypted_peer_id = encrypted_peer_id.clone();
             async move {
-                if let Err(err) = Self::run_send_loop(tcp_send, queue_recv, stats, m).await {
-                    addr.do_send(Error::Send(SendError::IO(err)));
-                }
-            }
-        }));
-        ctx.spawn(wrap_future({
-            let addr = ctx.address();
-            let stats = stats.clone();
-            async move {
-                if let Err(err) =
-                    Self::run_recv_loop(stream.peer_addr, tcp_recv, addr.clone(), stats).await
-                {
-                    addr.do_send(Error::Recv(err));
-                }
+                let peer_addr = stream.peer_addr;
+                let mut tcp_stream = stream.stream;
+                let negotiated = match encryption::negotiate(&mut tcp_stream, &encryption).await {
+                    Ok(it) => it,
+                    Err(err) => {
+                        addr.do_send(Error::Encryption(err));
+                        return;
+                    }
+                };
+                let (sender, receiver) = match negotiated.session {
+                    Some(session) => {
+                        let (peer_id, sender, receiver) = session.split();
+                        // Set before the first frame is received.
+                        let _ = encrypted_peer_id.set(peer_id);
+                        (Some(sender), Some(receiver))
+                    }
+                    None => (None, None),
+                };
+                let (tcp_recv, tcp_send) = tokio::io::split(tcp_stream);
+                let tcp_recv = io::Cursor::new(negotiated.prefix).chain(tcp_recv);
+                tokio::join!(
+                    async {
+                        if let Err(err) =
+                            Self::run_send_loop(tcp_send, sender, queue_recv, stats.clone(), m)
+                                .await
+                        {
+                            addr.do_send(Error::Send(SendError::IO(err)));
+                        }
+                    },
+                    async {
+                        if let Err(err) = Self::run_recv_loop(
+                            peer_addr,
+                            tcp_recv,
+                            receiver,
+                            addr.clone(),
+                            stats.clone(),
+                        )
+                        .await
+                        {
+                            addr.do_send(Error::Recv(err));
+                        }
+                    },
+                );
             }
         }));
-        Self { queue_send, stats, send_buf_size_metric, addr: ctx.address() }
+        Self { queue_send, stats, send_buf_size_metric, addr: ctx.address(), encrypted_peer_id }
+    }
+
+    /// `PeerId` of the other side authenticated by the encryption handshake.
+    /// `None` if the connection is not encrypted.
+    /// If the connection is encrypted, it is set before the first frame is received.
+    pub fn encrypted_peer_id(&self) -> Option<&PeerId> {
+        self.encrypted_peer_id.get()
     }
 
     /// Pushes `msg` to the send queue.
@@ -138,7 +194,8 @@ where
     // directly from the stream.
     async fn run_recv_loop(
         peer_addr: SocketAddr,
-        read: ReadHalf,
+        read: impl tokio::io::AsyncRead + Unpin,
+        mut receiver: Option<encryption::Receiver>,
         addr: actix::Addr<Actor>,
         stats: Arc<connection::Stats>,
     ) -> Result<(), RecvError> {
@@ -152,7 +209,10 @@ where
             vec![peer_addr.to_string()],
         );
         loop {
-            let n = read.read_u32_le().await.map_err(RecvError::IO)? as usize;
+            let n = match &mut receiver {
+                Some(receiver) => receiver.read_len(&mut read).await?,
+                None => read.read_u32_le().await.map_err(RecvError::IO)? as usize,
+            };
             if n > NETWORK_MESSAGE_MAX_SIZE_BYTES {
                 return Err(RecvError::MessageTooLarge {
                     got_bytes: n,
@@ -161,9 +221,15 @@ where
             }
             msg_size_metric.observe(n as f64);
             buf_size_metric.set(n as i64);
-            let mut buf = vec![0; n];
             let t = metrics::PEER_MSG_READ_LATENCY.start_timer();
-            read.read_exact(&mut buf[..]).await.map_err(RecvError::IO)?;
+            let buf = match &mut receiver {
+                Some(receiver) => receiver.read_frame(&mut read, n).await?,
+                None => {
+                    let mut buf = vec![0; n];
+                    read.read_exact(&mut buf[..]).await.map_err(RecvError::IO)?;
+                    buf
+                }
+            };
             t.observe_duration();
             buf_size_metric.set(0);
             stats.received_messages.fetch_add(1, Ordering::Relaxed);
@@ -177,6 +243,7 @@ where
     }
     async fn run_send_loop(
         tcp_send: WriteHalf,
+        mut sender: Option<encryption::Sender>,
         mut queue_recv: tokio::sync::mpsc::UnboundedReceiver<Frame>,
         stats: Arc<connection::Stats>,
         buf_size_metric: Arc<metrics::IntGaugeGuard>,
@@ -190,6 +257,11 @@ where
                 // since dropping messages may lead to hard-to-debug high-level issues.
                 if msg.len() > NETWORK_MESSAGE_MAX_SIZE_BYTES {
                     metrics::MessageDropped::InputTooLong.inc_unknown_msg();
+                } else if let Some(sender) = &mut sender {
+                    let encrypted = sender
+                        .encrypt(&msg[..])
+                        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                    writer.write_all(&encrypted[..]).await?;
                 } else {
                     writer.write_u32_le(msg.len() as u32).await?;
                     writer.write_all(&msg[..]).await?;
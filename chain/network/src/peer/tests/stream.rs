@@ -1,10 +1,12 @@
 use crate::actix::ActixSystem;
 use crate::network_protocol::testonly as data;
+use crate::peer::encryption;
 use crate::peer::stream;
 use crate::tcp;
 use crate::testonly::make_rng;
 use actix::Actor as _;
 use actix::ActorContext as _;
+use near_primitives::network::PeerId;
 use rand::Rng as _;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -12,6 +14,7 @@ use tokio::sync::mpsc;
 struct Actor {
     stream: stream::FramedStream<Actor>,
     queue_send: mpsc::UnboundedSender<stream::Frame>,
+    errors_send: mpsc::UnboundedSender<stream::Error>,
 }
 
 impl actix::Actor for Actor {
@@ -29,6 +32,17 @@ impl actix::Handler<SendFrame> for Actor {
     }
 }
 
+#[derive(actix::Message)]
+#[rtype("Option<PeerId>")]
+struct GetEncryptedPeerId;
+
+impl actix::Handler<GetEncryptedPeerId> for Actor {
+    type Result = Option<PeerId>;
+    fn handle(&mut self, _: GetEncryptedPeerId, _ctx: &mut Self::Context) -> Option<PeerId> {
+        self.stream.encrypted_peer_id().cloned()
+    }
+}
+
 impl actix::Handler<stream::Frame> for Actor {
     type Result = ();
     fn handle(&mut self, frame: stream::Frame, _ctx: &mut Self::Context) {
@@ -38,25 +52,29 @@ impl actix::Handler<stream::Frame> for Actor {
 
 impl actix::Handler<stream::Error> for Actor {
     type Result = ();
-    fn handle(&mut self, _err: stream::Error, ctx: &mut Self::Context) {
+    fn handle(&mut self, err: stream::Error, ctx: &mut Self::Context) {
+        let _ = self.errors_send.send(err);
         ctx.stop();
     }
 }
 
 struct Handler {
     queue_recv: mpsc::UnboundedReceiver<stream::Frame>,
+    errors_recv: mpsc::UnboundedReceiver<stream::Error>,
     system: ActixSystem<Actor>,
 }
 
 impl Actor {
-    async fn spawn(s: tcp::Stream) -> Handler {
+    async fn spawn(s: tcp::Stream, encryption: encryption::Plan) -> Handler {
         let (queue_send, queue_recv) = mpsc::unbounded_channel();
+        let (errors_send, errors_recv) = mpsc::unbounded_channel();
         Handler {
             queue_recv,
+            errors_recv,
             system: ActixSystem::spawn(|| {
                 Actor::create(|ctx| {
-                    let stream = stream::FramedStream::spawn(ctx, s, Arc::default());
-                    Self { stream, queue_send }
+                    let stream = stream::FramedStream::spawn(ctx, s, Arc::default(), encryption);
+                    Self { stream, queue_send, errors_send }
                 })
             })
             .await,
@@ -64,18 +82,18 @@ impl Actor {
     }
 }
 
-#[tokio::test]
-async fn send_recv() {
-    let mut rng = make_rng(98324532);
-    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng), tcp::Tier::T2).await;
-    let a1 = Actor::spawn(s1).await;
-    let mut a2 = Actor::spawn(s2).await;
-
+/// Sends random frames from a1 to a2 and checks that they are received intact.
+async fn check_send_recv(
+    rng: &mut impl rand::Rng,
+    a1: &Handler,
+    a2: &mut Handler,
+    max_size: usize,
+) {
     for _ in 0..5 {
         let n = rng.gen_range(1..10);
         let msgs: Vec<_> = (0..n)
             .map(|_| {
-                let size = rng.gen_range(0..10000);
+                let size = rng.gen_range(0..max_size);
                 let mut msg = vec![0; size];
                 rng.fill(&mut msg[..]);
                 stream::Frame(msg)
@@ -90,3 +108,74 @@ async fn send_recv() {
         }
     }
 }
+
+#[tokio::test]
+async fn send_recv() {
+    let mut rng = make_rng(98324532);
+    let (s1, s2) = tcp::Stream::loopback(data::make_peer_id(&mut rng), tcp::Tier::T2).await;
+    let a1 = Actor::spawn(s1, encryption::Plan::Plaintext).await;
+    let mut a2 = Actor::spawn(s2, encryption::Plan::Plaintext).await;
+    check_send_recv(&mut rng, &a1, &mut a2, 10000).await;
+}
+
+#[tokio::test]
+async fn send_recv_encrypted() {
+    let mut rng = make_rng(98324532);
+    let key1 = data::make_secret_key(&mut rng);
+    let key2 = data::make_secret_key(&mut rng);
+    let peer_id1 = PeerId::new(key1.public_key());
+    let peer_id2 = PeerId::new(key2.public_key());
+    let (s1, s2) = tcp::Stream::loopback(peer_id2.clone(), tcp::Tier::T2).await;
+    let mut a1 =
+        Actor::spawn(s1, encryption::Plan::Initiate { node_key: key1, peer_id: peer_id2.clone() })
+            .await;
+    let mut a2 = Actor::spawn(s2, encryption::Plan::Accept { node_key: key2, require: true }).await;
+    // Frames larger than a single Noise message are split into chunks.
+    check_send_recv(&mut rng, &a1, &mut a2, 200000).await;
+    check_send_recv(&mut rng, &a2, &mut a1, 200000).await;
+    let got1 = a1.system.addr.send(GetEncryptedPeerId).await.unwrap();
+    let got2 = a2.system.addr.send(GetEncryptedPeerId).await.unwrap();
+    assert_eq!(Some(peer_id2), got1);
+    assert_eq!(Some(peer_id1), got2);
+}
+
+#[tokio::test]
+async fn accept_plaintext() {
+    let mut rng = make_rng(98324532);
+    let key = data::make_secret_key(&mut rng);
+    let (s1, s2) = tcp::Stream::loopback(PeerId::new(key.public_key()), tcp::Tier::T2).await;
+    let a1 = Actor::spawn(s1, encryption::Plan::Plaintext).await;
+    let mut a2 = Actor::spawn(s2, encryption::Plan::Accept { node_key: key, require: false }).await;
+    check_send_recv(&mut rng, &a1, &mut a2, 10000).await;
+    assert_eq!(None, a2.system.addr.send(GetEncryptedPeerId).await.unwrap());
+}
+
+#[tokio::test]
+async fn require_encryption() {
+    let mut rng = make_rng(98324532);
+    let key = data::make_secret_key(&mut rng);
+    let (s1, s2) = tcp::Stream::loopback(PeerId::new(key.public_key()), tcp::Tier::T2).await;
+    let a1 = Actor::spawn(s1, encryption::Plan::Plaintext).await;
+    let mut a2 = Actor::spawn(s2, encryption::Plan::Accept { node_key: key, require: true }).await;
+    a1.system.addr.send(SendFrame(stream::Frame(vec![1, 2, 3]))).await.unwrap();
+    assert!(matches!(
+        a2.errors_recv.recv().await.unwrap(),
+        stream::Error::Encryption(encryption::Error::PlaintextNotAllowed)
+    ));
+}
+
+#[tokio::test]
+async fn wrong_peer_id() {
+    let mut rng = make_rng(98324532);
+    let key1 = data::make_secret_key(&mut rng);
+    let key2 = data::make_secret_key(&mut rng);
+    let want = data::make_peer_id(&mut rng);
+    let (s1, s2) = tcp::Stream::loopback(want.clone(), tcp::Tier::T2).await;
+    let mut a1 =
+        Actor::spawn(s1, encryption::Plan::Initiate { node_key: key1, peer_id: want }).await;
+    let _a2 = Actor::spawn(s2, encryption::Plan::Accept { node_key: key2, require: true }).await;
+    assert!(matches!(
+        a1.errors_recv.recv().await.unwrap(),
+        stream::Error::Encryption(encryption::Error::PeerIdMismatch { .. })
+    ));
+}
@@ -44,6 +44,14 @@ const IMPORTANT_MESSAGE_RESENT_COUNT: usize = 3;
 /// production of 1 block should fit).
 const RECENT_ROUTED_MESSAGES_CACHE_SIZE: usize = 10000;
 
+/// Size of LRU cache of peers which don't support encrypted connections.
+const PLAINTEXT_PEERS_CACHE_SIZE: usize = 1000;
+
+/// For how long outbound connections to a peer which announced that it doesn't support encryption
+/// are established in plaintext. After that, encryption is attempted again, in case the peer has
+/// been upgraded in the meantime.
+const PLAINTEXT_FALLBACK_TTL: time::Duration = time::Duration::hours(1);
+
 /// How long a peer has to be unreachable, until we prune it from the in-memory graph.
 const PRUNE_UNREACHABLE_PEERS_AFTER: time::Duration = time::Duration::hours(1);
 
@@ -107,6 +115,11 @@ pub(crate) struct NetworkState {
     /// It allows us to determine whether messages arrived faster over TIER1 or TIER2 network.
     pub recent_routed_messages: Mutex<lru::LruCache<CryptoHash, ()>>,
 
+    /// Peers which announced in a plaintext `Handshake` that they don't support encryption,
+    /// together with the time it happened.
+    /// Used with `config::TransportEncryption::Preferred` to fall back to plaintext connections.
+    plaintext_peers: Mutex<lru::LruCache<PeerId, time::Instant>>,
+
     /// Hash of messages that requires routing back to respective previous hop.
     /// Currently unused, as TIER1 messages do not require a response.
     /// Also TIER1 connections are direct by design (except for proxies),
@@ -172,6 +185,7 @@ impl NetworkState {
             recent_routed_messages: Mutex::new(lru::LruCache::new(
                 RECENT_ROUTED_MESSAGES_CACHE_SIZE,
             )),
+            plaintext_peers: Mutex::new(lru::LruCache::new(PLAINTEXT_PEERS_CACHE_SIZE)),
             txns_since_last_block: AtomicUsize::new(0),
             whitelist_nodes,
             max_num_peers: AtomicU32::new(config.max_num_peers),
@@ -183,6 +197,30 @@ impl NetworkState {
         }
     }
 
+    /// Records that the peer has announced that it doesn't support encryption.
+    pub fn set_plaintext_peer(&self, clock: &time::Clock, peer_id: PeerId) {
+        self.plaintext_peers.lock().put(peer_id, clock.now());
+    }
+
+    /// Records that the peer has announced that it supports encryption.
+    pub fn unset_plaintext_peer(&self, peer_id: &PeerId) {
+        self.plaintext_peers.lock().pop(peer_id);
+    }
+
+    /// Whether outbound connections to the peer should fall back to plaintext, because
+    /// it has recently announced that it doesn't support encryption.
+    pub fn is_plaintext_peer(&self, clock: &time::Clock, peer_id: &PeerId) -> bool {
+        let mut plaintext_peers = self.plaintext_peers.lock();
+        let expired = match plaintext_peers.get(peer_id) {
+            Some(rejected_at) => clock.now() - *rejected_at >= PLAINTEXT_FALLBACK_TTL,
+            None => return false,
+        };
+        if expired {
+            plaintext_peers.pop(peer_id);
+        }
+        !expired
+    }
+
     /// Spawn a future on the runtime which has the same lifetime as the NetworkState instance.
     /// In particular if the future contains the NetworkState handler, it will be run until
     /// completion. It is safe to self.spawn(...).await.unwrap(), since runtime will be kept alive
@@ -1,6 +1,6 @@
 use crate::network_protocol::testonly as data;
 use crate::network_protocol::{
-    Encoding, Handshake, PartialEdgeInfo, PeerMessage, EDGE_MIN_TIMESTAMP_NONCE,
+    Encoding, Handshake, PartialEdgeInfo, PeerMessage, ProtocolFeatures, EDGE_MIN_TIMESTAMP_NONCE,
 };
 use crate::peer_manager::testonly::{ActorHandler, Event};
 use crate::peer_manager::{self, peer_manager_actor};
@@ -75,6 +75,7 @@ async fn test_nonces() {
             sender_chain_info: chain.get_peer_chain_info(),
             partial_edge_info: PartialEdgeInfo::new(&peer_id, &pm.cfg.node_id(), test.0, &peer_key),
             owned_account: None,
+            features: Default::default(),
         });
         stream.write(&handshake).await;
         if test.1 {
@@ -169,3 +170,57 @@ async fn test_nonce_refresh() {
 
     assert_eq!(Edge::nonce_to_utc(pm_nonce).unwrap().unwrap(), new_nonce_utc);
 }
+
+// A plaintext handshake announcing no encryption support makes outbound connections to the
+// sender fall back to plaintext only once the handshake has been fully verified.
+#[tokio::test]
+async fn test_plaintext_fallback_requires_verified_handshake() {
+    init_test_logger();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::new(*EDGE_MIN_TIMESTAMP_NONCE + time::Duration::days(2));
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+    let pm = peer_manager::testonly::start(
+        clock.clock(),
+        near_store::db::TestDB::new(),
+        chain.make_config(rng),
+        chain.clone(),
+    )
+    .await;
+
+    let valid_nonce = to_active_nonce(clock.now_utc());
+    let test_cases = [
+        (to_active_nonce(clock.now_utc() - time::Duration::days(1)), false, false, "bad nonce"),
+        (valid_nonce, true, false, "bad edge signature"),
+        (valid_nonce, false, true, "valid handshake"),
+    ];
+    for (nonce, wrong_edge_key, want_plaintext, name) in test_cases {
+        tracing::info!(target: "test", "Running test {name:?}");
+        let stream = tcp::Stream::connect(&pm.peer_info(), tcp::Tier::T2).await.unwrap();
+        let mut stream = stream::Stream::new(Some(Encoding::Proto), stream);
+        let peer_key = data::make_secret_key(rng);
+        let peer_id = PeerId::new(peer_key.public_key());
+        let edge_key = if wrong_edge_key { data::make_secret_key(rng) } else { peer_key.clone() };
+        let handshake = PeerMessage::Tier2Handshake(Handshake {
+            protocol_version: version::PROTOCOL_VERSION,
+            oldest_supported_version: version::PEER_MIN_ALLOWED_PROTOCOL_VERSION,
+            sender_peer_id: peer_id.clone(),
+            target_peer_id: pm.cfg.node_id(),
+            sender_listen_port: Some(24567),
+            sender_chain_info: chain.get_peer_chain_info(),
+            partial_edge_info: PartialEdgeInfo::new(&peer_id, &pm.cfg.node_id(), nonce, &edge_key),
+            owned_account: None,
+            features: ProtocolFeatures { encryption: false, ..ProtocolFeatures::supported() },
+        });
+        stream.write(&handshake).await;
+        match stream.read().await {
+            Ok(PeerMessage::Tier2Handshake { .. }) if want_plaintext => {}
+            Err(err) if !want_plaintext && err.kind() == std::io::ErrorKind::UnexpectedEof => {}
+            got => panic!("got = {got:?}"),
+        }
+        let clock = clock.clock();
+        let plaintext =
+            pm.with_state(move |s| async move { s.is_plaintext_peer(&clock, &peer_id) }).await;
+        assert_eq!(plaintext, want_plaintext, "{name}");
+    }
+}
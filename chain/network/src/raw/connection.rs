@@ -156,6 +156,7 @@ impl Connection {
                 &self.secret_key,
             ),
             owned_account: None,
+            features: Default::default(),
         });
 
         self.write_message(&handshake).await.map_err(ConnectError::IO)?;
@@ -192,6 +192,23 @@ pub(crate) static PEER_MESSAGE_SENT_BY_TYPE_BYTES: Lazy<IntCounterVec> = Lazy::n
     )
     .unwrap()
 });
+pub(crate) static PEER_MESSAGE_COMPRESSION_RAW_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_message_compression_raw_bytes",
+        "Size of compressed messages before compression, by direction, message type and algorithm",
+        &["direction", "type", "algorithm"],
+    )
+    .unwrap()
+});
+pub(crate) static PEER_MESSAGE_COMPRESSION_COMPRESSED_BYTES: Lazy<IntCounterVec> =
+    Lazy::new(|| {
+        try_create_int_counter_vec(
+        "near_peer_message_compression_compressed_bytes",
+        "Size of compressed messages after compression, by direction, message type and algorithm",
+        &["direction", "type", "algorithm"],
+    )
+    .unwrap()
+    });
 pub(crate) static PEER_MESSAGE_SENT_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     try_create_int_counter_vec(
         "near_peer_message_sent_by_type_total",
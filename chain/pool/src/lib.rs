@@ -1,44 +1,88 @@
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
 use crate::types::{PoolIterator, PoolKey, TransactionGroup};
 use borsh::BorshSerialize;
+use near_chain_configs::{TransactionPoolConfig, TransactionPoolEvictionPolicy};
 use near_crypto::PublicKey;
 use near_primitives::epoch_manager::RngSeed;
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::transaction::SignedTransaction;
-use near_primitives::types::AccountId;
+use near_primitives::types::{AccountId, Balance};
 use std::ops::Bound;
 
 mod metrics;
 pub mod types;
 
+/// Result of inserting a transaction into the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertTransactionResult {
+    /// The transaction was inserted, possibly evicting other transactions.
+    Success,
+    /// The transaction is already in the pool.
+    Duplicate,
+    /// The pool is full and the eviction policy doesn't allow evicting anything to make room for
+    /// the transaction.
+    NoSpaceLeft,
+}
+
+/// Bookkeeping of a transaction in the pool.
+struct TransactionInfo {
+    key: PoolKey,
+    signer_id: AccountId,
+    size: u64,
+    /// Position of the transaction in `insertion_order`.
+    seq: u64,
+}
+
 /// Transaction pool: keeps track of transactions that were not yet accepted into the block chain.
 pub struct TransactionPool {
     /// Transactions are grouped by a pair of (account ID, signer public key).
     /// NOTE: It's more efficient on average to keep transactions unsorted and with potentially
     /// conflicting nonce than to create a BTreeMap for every transaction.
     transactions: BTreeMap<PoolKey, Vec<SignedTransaction>>,
-    /// Set of all hashes to quickly check if the given transaction is in the pool.
-    unique_transactions: HashSet<CryptoHash>,
+    /// Keys of all groups in `transactions` ordered by the priority of the group, which is the
+    /// priority fee of its transaction with the lowest nonce, i.e. the one to be included next.
+    priorities: BTreeSet<(Balance, PoolKey)>,
+    /// All transactions in the pool by hash, to quickly check if the given transaction is in
+    /// the pool.
+    unique_transactions: HashMap<CryptoHash, TransactionInfo>,
+    /// Hashes of the transactions in the order they were inserted.
+    insertion_order: BTreeMap<u64, CryptoHash>,
+    /// Sequence number of the next inserted transaction.
+    next_seq: u64,
+    /// Sequence numbers of the transactions of each signer account.
+    signer_transactions: HashMap<AccountId, BTreeSet<u64>>,
+    /// Total size of the transactions in the pool in bytes.
+    total_size: u64,
     /// A uniquely generated key seed to randomize PoolKey order.
     key_seed: RngSeed,
     /// The key after which the pool iterator starts. Doesn't have to be present in the pool.
     last_used_key: PoolKey,
+    config: TransactionPoolConfig,
 }
 
 impl TransactionPool {
-    pub fn new(key_seed: RngSeed) -> Self {
+    pub fn new(key_seed: RngSeed, config: TransactionPoolConfig) -> Self {
         Self {
             key_seed,
             transactions: BTreeMap::new(),
-            unique_transactions: HashSet::new(),
+            priorities: BTreeSet::new(),
+            unique_transactions: HashMap::new(),
+            insertion_order: BTreeMap::new(),
+            next_seq: 0,
+            signer_transactions: HashMap::new(),
+            total_size: 0,
             last_used_key: CryptoHash::default(),
+            config,
         }
     }
 
     pub fn init_metrics() {
         // A `get()` call initializes a metric even if its value is zero.
         metrics::TRANSACTION_POOL_TOTAL.get();
+        metrics::TRANSACTION_POOL_SIZE.get();
+        metrics::TRANSACTION_POOL_EVICTED_TOTAL.get();
+        metrics::TRANSACTION_POOL_REJECTED_TOTAL.get();
     }
 
     fn key(&self, account_id: &AccountId, public_key: &PublicKey) -> PoolKey {
@@ -48,21 +92,191 @@ impl TransactionPool {
         hash(&v)
     }
 
+    fn group_priority(transactions: &[SignedTransaction]) -> Balance {
+        transactions
+            .iter()
+            .map(|tx| (tx.transaction.nonce, std::cmp::Reverse(tx.transaction.priority_fee())))
+            .min()
+            .map_or(0, |(_, std::cmp::Reverse(priority_fee))| priority_fee)
+    }
+
+    /// Inserts a non-empty group of transactions, replacing the group with the same key.
+    fn insert_group(&mut self, key: PoolKey, transactions: Vec<SignedTransaction>) {
+        self.priorities.insert((Self::group_priority(&transactions), key));
+        if let Some(old) = self.transactions.insert(key, transactions) {
+            self.priorities.remove(&(Self::group_priority(&old), key));
+        }
+    }
+
+    fn remove_group(&mut self, key: &PoolKey) -> Option<Vec<SignedTransaction>> {
+        let transactions = self.transactions.remove(key)?;
+        self.priorities.remove(&(Self::group_priority(&transactions), *key));
+        Some(transactions)
+    }
+
+    /// Records a transaction which is about to be added to its group.
+    fn remember_transaction(&mut self, key: PoolKey, signed_transaction: &SignedTransaction) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let signer_id = signed_transaction.transaction.signer_id.clone();
+        let size = signed_transaction.get_size();
+        self.insertion_order.insert(seq, signed_transaction.get_hash());
+        self.signer_transactions.entry(signer_id.clone()).or_default().insert(seq);
+        self.total_size += size;
+        self.unique_transactions
+            .insert(signed_transaction.get_hash(), TransactionInfo { key, signer_id, size, seq });
+        metrics::TRANSACTION_POOL_TOTAL.inc();
+        metrics::TRANSACTION_POOL_SIZE.add(size as i64);
+    }
+
+    /// Removes a transaction which is no longer in any group from the bookkeeping.
+    fn forget_transaction(&mut self, hash: &CryptoHash) {
+        let info = match self.unique_transactions.remove(hash) {
+            Some(info) => info,
+            None => return,
+        };
+        self.insertion_order.remove(&info.seq);
+        if let Some(seqs) = self.signer_transactions.get_mut(&info.signer_id) {
+            seqs.remove(&info.seq);
+            if seqs.is_empty() {
+                self.signer_transactions.remove(&info.signer_id);
+            }
+        }
+        self.total_size -= info.size;
+        metrics::TRANSACTION_POOL_TOTAL.dec();
+        metrics::TRANSACTION_POOL_SIZE.sub(info.size as i64);
+    }
+
+    fn fits(&self, count: usize, total_size: u64, new_transaction_size: u64) -> bool {
+        let count_exceeded = self.config.count_limit.map_or(false, |limit| count >= limit);
+        let size_exceeded =
+            self.config.size_limit.map_or(false, |limit| total_size + new_transaction_size > limit);
+        !count_exceeded && !size_exceeded
+    }
+
+    /// Transactions which may be evicted to make room for the given one, in the order the
+    /// eviction policy picks them.
+    fn eviction_candidates<'a>(
+        &'a self,
+        signed_transaction: &'a SignedTransaction,
+    ) -> Box<dyn Iterator<Item = CryptoHash> + 'a> {
+        match self.config.eviction_policy {
+            TransactionPoolEvictionPolicy::LowestPriorityFee => {
+                // Groups with a lower priority than the new transaction, starting from the lowest
+                // one, each from its highest nonce. Evicting from the highest nonce keeps the
+                // priority of the group until it's gone.
+                let priority_fee = signed_transaction.transaction.priority_fee();
+                Box::new(
+                    self.priorities
+                        .iter()
+                        .take_while(move |(priority, _)| *priority < priority_fee)
+                        .flat_map(move |(_, key)| {
+                            let mut group: Vec<_> = self.transactions[key].iter().collect();
+                            group.sort_by_key(|tx| {
+                                (
+                                    std::cmp::Reverse(tx.transaction.nonce),
+                                    tx.transaction.priority_fee(),
+                                )
+                            });
+                            group.into_iter().map(|tx| tx.get_hash())
+                        }),
+                )
+            }
+            TransactionPoolEvictionPolicy::Oldest => {
+                Box::new(self.insertion_order.values().copied())
+            }
+            TransactionPoolEvictionPolicy::SignerQuota => {
+                // The newest transactions of the signer with the most transactions, as long as it
+                // has more of them than the signer of the new transaction.
+                let signer_id = &signed_transaction.transaction.signer_id;
+                let signer_count = self.signer_transactions.get(signer_id).map_or(0, |s| s.len());
+                let mut evicted: HashMap<&AccountId, usize> = HashMap::new();
+                Box::new(std::iter::from_fn(move || {
+                    let (account_id, seqs, evicted_count) = self
+                        .signer_transactions
+                        .iter()
+                        .filter(|(account_id, _)| *account_id != signer_id)
+                        .map(|(account_id, seqs)| {
+                            (account_id, seqs, evicted.get(account_id).copied().unwrap_or(0))
+                        })
+                        .max_by_key(|(_, seqs, evicted_count)| seqs.len() - evicted_count)?;
+                    if seqs.len() - evicted_count <= signer_count {
+                        return None;
+                    }
+                    let seq = seqs.iter().rev().nth(evicted_count).expect("checked the count");
+                    evicted.insert(account_id, evicted_count + 1);
+                    Some(self.insertion_order[seq])
+                }))
+            }
+        }
+    }
+
+    /// Picks the transactions to evict to make room for the given one according to the
+    /// eviction policy. Returns `None` if the policy doesn't allow evicting enough of them.
+    fn plan_eviction(&self, signed_transaction: &SignedTransaction) -> Option<Vec<CryptoHash>> {
+        let size = signed_transaction.get_size();
+        let mut count = self.len();
+        let mut total_size = self.total_size;
+        let mut evicted = vec![];
+        let mut candidates = self.eviction_candidates(signed_transaction);
+        while !self.fits(count, total_size, size) {
+            let hash = candidates.next()?;
+            count -= 1;
+            total_size -= self.unique_transactions[&hash].size;
+            evicted.push(hash);
+        }
+        Some(evicted)
+    }
+
+    fn evict_transaction(&mut self, hash: &CryptoHash) {
+        let key = self.unique_transactions[hash].key;
+        let mut transactions = self.remove_group(&key).expect("transaction is in the pool");
+        transactions.retain(|tx| &tx.get_hash() != hash);
+        if !transactions.is_empty() {
+            self.insert_group(key, transactions);
+        }
+        self.forget_transaction(hash);
+        metrics::TRANSACTION_POOL_EVICTED_TOTAL.inc();
+    }
+
     /// Insert a signed transaction into the pool that passed validation.
-    pub fn insert_transaction(&mut self, signed_transaction: SignedTransaction) -> bool {
-        if !self.unique_transactions.insert(signed_transaction.get_hash()) {
+    ///
+    /// If the pool is full, transactions are evicted to make room for the new one according to
+    /// the eviction policy. If the policy doesn't allow evicting enough of them, the transaction
+    /// is rejected and the pool is left intact.
+    pub fn insert_transaction(
+        &mut self,
+        signed_transaction: SignedTransaction,
+    ) -> InsertTransactionResult {
+        if self.unique_transactions.contains_key(&signed_transaction.get_hash()) {
             // The hash of this transaction was already seen, skip it.
-            return false;
+            return InsertTransactionResult::Duplicate;
+        }
+        let size = signed_transaction.get_size();
+        if self.config.size_limit.map_or(false, |limit| size > limit) {
+            metrics::TRANSACTION_POOL_REJECTED_TOTAL.inc();
+            return InsertTransactionResult::NoSpaceLeft;
+        }
+        match self.plan_eviction(&signed_transaction) {
+            Some(evicted) => {
+                for hash in evicted {
+                    self.evict_transaction(&hash);
+                }
+            }
+            None => {
+                metrics::TRANSACTION_POOL_REJECTED_TOTAL.inc();
+                return InsertTransactionResult::NoSpaceLeft;
+            }
         }
-        metrics::TRANSACTION_POOL_TOTAL.inc();
 
         let signer_id = &signed_transaction.transaction.signer_id;
         let signer_public_key = &signed_transaction.transaction.public_key;
-        self.transactions
-            .entry(self.key(signer_id, signer_public_key))
-            .or_insert_with(Vec::new)
-            .push(signed_transaction);
-        true
+        let key = self.key(signer_id, signer_public_key);
+        self.remember_transaction(key, &signed_transaction);
+        let mut transactions = self.remove_group(&key).unwrap_or_default();
+        transactions.push(signed_transaction);
+        self.insert_group(key, transactions);
+        InsertTransactionResult::Success
     }
 
     /// Returns a pool iterator wrapper that implements an iterator like trait to iterate over
@@ -77,28 +291,22 @@ impl TransactionPool {
     pub fn remove_transactions(&mut self, transactions: &[SignedTransaction]) {
         let mut grouped_transactions = HashMap::new();
         for tx in transactions {
-            if self.unique_transactions.contains(&tx.get_hash()) {
-                let signer_id = &tx.transaction.signer_id;
-                let signer_public_key = &tx.transaction.public_key;
+            if let Some(info) = self.unique_transactions.get(&tx.get_hash()) {
                 grouped_transactions
-                    .entry(self.key(signer_id, signer_public_key))
+                    .entry(info.key)
                     .or_insert_with(HashSet::new)
                     .insert(tx.get_hash());
             }
         }
         for (key, hashes) in grouped_transactions {
-            let mut remove_entry = false;
-            if let Some(v) = self.transactions.get_mut(&key) {
+            if let Some(mut v) = self.remove_group(&key) {
                 v.retain(|tx| !hashes.contains(&tx.get_hash()));
-                remove_entry = v.is_empty();
-            }
-            if remove_entry {
-                self.transactions.remove(&key);
+                if !v.is_empty() {
+                    self.insert_group(key, v);
+                }
             }
             for hash in &hashes {
-                if self.unique_transactions.remove(&hash) {
-                    metrics::TRANSACTION_POOL_TOTAL.dec();
-                }
+                self.forget_transaction(hash);
             }
         }
     }
@@ -113,6 +321,16 @@ impl TransactionPool {
     pub fn len(&self) -> usize {
         self.unique_transactions.len()
     }
+
+    /// Iterates over all transactions in the pool in no particular order.
+    pub fn transactions(&self) -> impl Iterator<Item = &SignedTransaction> {
+        self.transactions.values().flatten()
+    }
+
+    /// Total size of the transactions in the pool in bytes.
+    pub fn size(&self) -> u64 {
+        self.total_size
+    }
 }
 
 /// PoolIterator is a structure to pull transactions from the pool.
@@ -133,9 +351,10 @@ impl<'a> PoolIteratorWrapper<'a> {
 }
 
 /// The iterator works with the following algorithm:
-/// On next(), the iterator tries to get a transaction group from the pool, sorts transactions in
-/// it, and add it to the back of the sorted groups queue.
-/// Remembers the last used key, so it can continue from the next key.
+/// On next(), the iterator tries to get the transaction group with the highest priority from the
+/// pool, sorts transactions in it, and add it to the back of the sorted groups queue.
+/// Groups with the same priority are taken in the order of their keys. The iterator remembers the
+/// last used key, so it can continue from the next key.
 ///
 /// If the pool is empty, the iterator gets the group from the front of the sorted groups queue.
 ///
@@ -151,23 +370,22 @@ impl<'a> PoolIteratorWrapper<'a> {
 /// And all non-empty group from the sorted groups queue are inserted back into the pool.
 impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
     fn next(&mut self) -> Option<&mut TransactionGroup> {
-        if !self.pool.transactions.is_empty() {
-            let key = *self
+        if let Some(&(priority, _)) = self.pool.priorities.iter().next_back() {
+            // No group has a higher priority, so the range contains only groups with `priority`.
+            let (_, key) = *self
                 .pool
-                .transactions
-                .range((Bound::Excluded(self.pool.last_used_key), Bound::Unbounded))
+                .priorities
+                .range((Bound::Excluded((priority, self.pool.last_used_key)), Bound::Unbounded))
                 .next()
-                .map(|(k, _v)| k)
                 .unwrap_or_else(|| {
                     self.pool
-                        .transactions
-                        .keys()
+                        .priorities
+                        .range((priority, CryptoHash::default())..)
                         .next()
-                        .expect("we've just checked that the map is not empty")
+                        .expect("we've just checked that there is a group with this priority")
                 });
             self.pool.last_used_key = key;
-            let mut transactions =
-                self.pool.transactions.remove(&key).expect("just checked existence");
+            let mut transactions = self.pool.remove_group(&key).expect("just checked existence");
             transactions.sort_by_key(|st| std::cmp::Reverse(st.transaction.nonce));
             self.sorted_groups.push_back(TransactionGroup {
                 key,
@@ -179,9 +397,7 @@ impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
             while let Some(sorted_group) = self.sorted_groups.pop_front() {
                 if sorted_group.transactions.is_empty() {
                     for hash in sorted_group.removed_transaction_hashes {
-                        if self.pool.unique_transactions.remove(&hash) {
-                            metrics::TRANSACTION_POOL_TOTAL.dec();
-                        }
+                        self.pool.forget_transaction(&hash);
                     }
                 } else {
                     self.sorted_groups.push_back(sorted_group);
@@ -200,12 +416,10 @@ impl<'a> Drop for PoolIteratorWrapper<'a> {
     fn drop(&mut self) {
         for group in self.sorted_groups.drain(..) {
             for hash in group.removed_transaction_hashes {
-                if self.pool.unique_transactions.remove(&hash) {
-                    metrics::TRANSACTION_POOL_TOTAL.dec();
-                }
+                self.pool.forget_transaction(&hash);
             }
             if !group.transactions.is_empty() {
-                self.pool.transactions.insert(group.key, group.transactions);
+                self.pool.insert_group(group.key, group.transactions);
             }
         }
     }
@@ -222,7 +436,7 @@ mod tests {
     use near_crypto::{InMemorySigner, KeyType};
 
     use near_primitives::hash::CryptoHash;
-    use near_primitives::types::Balance;
+    use near_primitives::transaction::Transaction;
 
     const TEST_SEED: RngSeed = [3; 32];
 
@@ -249,11 +463,30 @@ mod tests {
             .collect()
     }
 
+    fn generate_transactions_with_priority_fee(
+        signer_id: &str,
+        starting_nonce: u64,
+        end_nonce: u64,
+        priority_fee: Balance,
+    ) -> Vec<SignedTransaction> {
+        generate_transactions(signer_id, signer_id, starting_nonce, end_nonce)
+            .into_iter()
+            .map(|tx| {
+                let signer = InMemorySigner::from_seed(
+                    tx.transaction.signer_id.clone(),
+                    KeyType::ED25519,
+                    signer_id,
+                );
+                Transaction { priority_fee: Some(priority_fee), ..tx.transaction }.sign(&signer)
+            })
+            .collect()
+    }
+
     fn process_txs_to_nonces(
         mut transactions: Vec<SignedTransaction>,
         expected_weight: u32,
     ) -> (Vec<u64>, TransactionPool) {
-        let mut pool = TransactionPool::new(TEST_SEED);
+        let mut pool = TransactionPool::new(TEST_SEED, TransactionPoolConfig::default());
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions {
@@ -364,7 +597,7 @@ mod tests {
             })
             .collect::<Vec<_>>();
 
-        let mut pool = TransactionPool::new(TEST_SEED);
+        let mut pool = TransactionPool::new(TEST_SEED, TransactionPoolConfig::default());
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions.clone() {
@@ -465,4 +698,166 @@ mod tests {
         new_nonces.sort();
         assert_ne!(nonces, new_nonces);
     }
+
+    /// Groups are taken in the order of their priority fees, transactions of each group are
+    /// still ordered by nonce.
+    #[test]
+    fn test_order_priority_fee() {
+        let mut transactions = generate_transactions("alice.near", "alice.near", 1, 5);
+        transactions.extend(generate_transactions_with_priority_fee("bob.near", 1, 5, 10));
+        transactions.extend(generate_transactions_with_priority_fee("carol.near", 1, 5, 5));
+
+        let mut pool = TransactionPool::new(TEST_SEED, TransactionPoolConfig::default());
+        transactions.shuffle(&mut thread_rng());
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        let txs = prepare_transactions(&mut pool, 15);
+        let signers: Vec<_> =
+            txs.iter().take(3).map(|tx| tx.transaction.signer_id.as_str()).collect();
+        assert_eq!(signers, vec!["bob.near", "carol.near", "alice.near"]);
+        let nonces: Vec<_> = txs.iter().map(|tx| tx.transaction.nonce).collect();
+        assert_eq!(nonces, (1..=5).map(|a| vec![a; 3]).flatten().collect::<Vec<u64>>());
+    }
+
+    /// Priority of a group is the priority fee of its transaction with the lowest nonce, not the
+    /// highest fee in the group.
+    #[test]
+    fn test_order_priority_fee_of_next_transaction() {
+        let mut transactions = generate_transactions("alice.near", "alice.near", 1, 1);
+        transactions.extend(generate_transactions_with_priority_fee("alice.near", 2, 2, 10));
+        transactions.extend(generate_transactions_with_priority_fee("bob.near", 1, 1, 5));
+
+        let mut pool = TransactionPool::new(TEST_SEED, TransactionPoolConfig::default());
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        let txs = prepare_transactions(&mut pool, 3);
+        assert_eq!(
+            signers_and_nonces(&txs),
+            vec![("bob.near", 1), ("alice.near", 1), ("alice.near", 2)]
+        );
+    }
+
+    fn signers_and_nonces(transactions: &[SignedTransaction]) -> Vec<(&str, u64)> {
+        transactions
+            .iter()
+            .map(|tx| (tx.transaction.signer_id.as_str(), tx.transaction.nonce))
+            .collect()
+    }
+
+    /// Full pool evicts the transactions with the lowest priority fee to make room for ones
+    /// with a higher fee.
+    #[test]
+    fn test_count_limit_evicts_lowest_priority_fee() {
+        let config = TransactionPoolConfig { count_limit: Some(3), ..Default::default() };
+        let mut pool = TransactionPool::new(TEST_SEED, config);
+        for tx in generate_transactions("alice.near", "alice.near", 1, 3) {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        let tx = generate_transactions("bob.near", "bob.near", 1, 1).remove(0);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::NoSpaceLeft);
+        assert_eq!(pool.len(), 3);
+
+        let tx = generate_transactions_with_priority_fee("bob.near", 1, 1, 1).remove(0);
+        assert_eq!(pool.insert_transaction(tx.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Duplicate);
+        assert_eq!(pool.len(), 3);
+        let txs = prepare_transactions(&mut pool, 3);
+        assert_eq!(
+            signers_and_nonces(&txs),
+            vec![("bob.near", 1), ("alice.near", 1), ("alice.near", 2)]
+        );
+    }
+
+    /// Transactions are evicted until their total size fits the limit.
+    #[test]
+    fn test_size_limit() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 3);
+        let tx_size = transactions[0].get_size();
+        let config = TransactionPoolConfig {
+            size_limit: Some(3 * tx_size),
+            eviction_policy: TransactionPoolEvictionPolicy::Oldest,
+            ..Default::default()
+        };
+        let mut pool = TransactionPool::new(TEST_SEED, config);
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        assert_eq!(pool.size(), 3 * tx_size);
+
+        let tx = generate_transactions("carol.near", "carol.near", 1, 1).remove(0);
+        assert_eq!(tx.get_size(), tx_size);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.size(), 3 * tx_size);
+        let mut txs = prepare_transactions(&mut pool, 3);
+        txs.sort_by_key(|tx| tx.transaction.nonce);
+        assert_eq!(
+            signers_and_nonces(&txs),
+            vec![("carol.near", 1), ("alice.near", 2), ("alice.near", 3)]
+        );
+        assert_eq!(pool.size(), 0);
+
+        // A transaction larger than the limit never fits.
+        let config = TransactionPoolConfig { size_limit: Some(tx_size - 1), ..Default::default() };
+        let mut pool = TransactionPool::new(TEST_SEED, config);
+        let tx = generate_transactions_with_priority_fee("carol.near", 1, 1, 1).remove(0);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::NoSpaceLeft);
+    }
+
+    /// A transaction which doesn't fit even after evicting everything the policy allows is
+    /// rejected without evicting anything.
+    #[test]
+    fn test_rejected_transaction_evicts_nothing() {
+        let alice_tx = generate_transactions("alice.near", "alice.near", 1, 1).remove(0);
+        let bob_tx = generate_transactions_with_priority_fee("bob.near", 1, 1, 10).remove(0);
+        let carol_tx = generate_transactions_with_priority_fee("carol.near", 1, 1, 5).remove(0);
+        assert!(alice_tx.get_size() < carol_tx.get_size());
+        let config = TransactionPoolConfig {
+            size_limit: Some(alice_tx.get_size() + bob_tx.get_size()),
+            ..Default::default()
+        };
+        let mut pool = TransactionPool::new(TEST_SEED, config);
+        assert_eq!(pool.insert_transaction(alice_tx), InsertTransactionResult::Success);
+        assert_eq!(pool.insert_transaction(bob_tx), InsertTransactionResult::Success);
+
+        // Evicting Alice's transaction isn't enough and Bob's one has a higher fee.
+        assert_eq!(pool.insert_transaction(carol_tx), InsertTransactionResult::NoSpaceLeft);
+        let mut txs = prepare_transactions(&mut pool, 3);
+        txs.sort_by_key(|tx| tx.transaction.signer_id.clone());
+        assert_eq!(signers_and_nonces(&txs), vec![("alice.near", 1), ("bob.near", 1)]);
+    }
+
+    /// Signers with the most transactions get their newest transactions evicted first.
+    #[test]
+    fn test_signer_quota_eviction() {
+        let config = TransactionPoolConfig {
+            count_limit: Some(4),
+            eviction_policy: TransactionPoolEvictionPolicy::SignerQuota,
+            ..Default::default()
+        };
+        let mut pool = TransactionPool::new(TEST_SEED, config);
+        let mut transactions = generate_transactions("alice.near", "alice.near", 1, 3);
+        transactions.extend(generate_transactions("bob.near", "bob.near", 1, 1));
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+
+        // Alice already has the most transactions.
+        let tx = generate_transactions("alice.near", "alice.near", 4, 4).remove(0);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::NoSpaceLeft);
+        let tx = generate_transactions("bob.near", "bob.near", 2, 2).remove(0);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        // Both have two transactions now.
+        let tx = generate_transactions("bob.near", "bob.near", 3, 3).remove(0);
+        assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::NoSpaceLeft);
+
+        let mut txs = prepare_transactions(&mut pool, 4);
+        txs.sort_by_key(|tx| (tx.transaction.signer_id.clone(), tx.transaction.nonce));
+        assert_eq!(
+            signers_and_nonces(&txs),
+            vec![("alice.near", 1), ("alice.near", 2), ("bob.near", 1), ("bob.near", 2)]
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use near_o11y::metrics::IntGauge;
+use near_o11y::metrics::{IntCounter, IntGauge};
 use once_cell::sync::Lazy;
 
 pub static TRANSACTION_POOL_TOTAL: Lazy<IntGauge> = Lazy::new(|| {
@@ -8,3 +8,27 @@ pub static TRANSACTION_POOL_TOTAL: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static TRANSACTION_POOL_SIZE: Lazy<IntGauge> = Lazy::new(|| {
+    near_o11y::metrics::try_create_int_gauge(
+        "near_transaction_pool_size",
+        "Total size in bytes of transactions currently in the pools tracked by the node",
+    )
+    .unwrap()
+});
+
+pub static TRANSACTION_POOL_EVICTED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    near_o11y::metrics::try_create_int_counter(
+        "near_transaction_pool_evicted_total",
+        "Number of transactions evicted from full transaction pools",
+    )
+    .unwrap()
+});
+
+pub static TRANSACTION_POOL_REJECTED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    near_o11y::metrics::try_create_int_counter(
+        "near_transaction_pool_rejected_total",
+        "Number of transactions rejected because the transaction pool was full",
+    )
+    .unwrap()
+});
@@ -3,7 +3,8 @@ use near_primitives::transaction::SignedTransaction;
 
 /// Trait acts like an iterator. It iterates over transactions groups by returning mutable
 /// references to them. Each transaction group implements a draining iterator to pull transactions.
-/// The order of the transaction groups is round robin scheduling.
+/// Transaction groups are ordered by the priority fee of their transaction with the lowest nonce,
+/// groups with the same priority are scheduled round robin.
 /// When this iterator is dropped the remaining transactions are returned back to the pool.
 pub trait PoolIterator {
     fn next(&mut self) -> Option<&mut TransactionGroup>;
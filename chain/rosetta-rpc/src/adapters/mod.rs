@@ -419,6 +419,10 @@ impl From<NearActions> for Vec<crate::models::Operation> {
                     );
                     operations.push(deploy_contract_operation);
                 }
+
+                // Delegated actions are executed in a separate receipt and
+                // have no Rosetta representation yet.
+                near_primitives::transaction::Action::Delegate(_) => {}
             }
         }
         operations
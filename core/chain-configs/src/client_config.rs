@@ -1,6 +1,8 @@
 //! Chain Client Configuration
 use std::cmp::max;
 use std::cmp::min;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
@@ -24,6 +26,11 @@ pub const MIN_GC_NUM_EPOCHS_TO_KEEP: u64 = 3;
 /// Default number of epochs for which we keep store data
 pub const DEFAULT_GC_NUM_EPOCHS_TO_KEEP: u64 = 5;
 
+/// Columns which can be kept for more epochs than other store data, see
+/// `GCConfig::gc_num_epochs_to_keep_per_column`.
+pub const GC_DELAYABLE_COLUMNS: &[&str] =
+    &["Transactions", "Receipts", "TransactionResultForBlock", "OutcomeIds"];
+
 /// Configuration for garbage collection.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct GCConfig {
@@ -40,6 +47,15 @@ pub struct GCConfig {
     /// Number of epochs for which we keep store data.
     #[serde(default = "default_gc_num_epochs_to_keep")]
     pub gc_num_epochs_to_keep: u64,
+
+    /// Number of epochs for which data in particular columns is kept, e.g.
+    /// `{"TransactionResultForBlock": 30}`.  Data is kept for at least
+    /// `gc_num_epochs_to_keep` epochs so only longer retention has effect.
+    /// Only transactions, receipts and their outcomes can be kept for longer,
+    /// see [`GC_DELAYABLE_COLUMNS`]; other columns are rejected when the
+    /// config is loaded.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub gc_num_epochs_to_keep_per_column: BTreeMap<String, u64>,
 }
 
 impl Default for GCConfig {
@@ -48,6 +64,7 @@ impl Default for GCConfig {
             gc_blocks_limit: 2,
             gc_fork_clean_step: 100,
             gc_num_epochs_to_keep: DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+            gc_num_epochs_to_keep_per_column: BTreeMap::new(),
         }
     }
 }
@@ -68,6 +85,93 @@ impl GCConfig {
     pub fn gc_num_epochs_to_keep(&self) -> u64 {
         max(MIN_GC_NUM_EPOCHS_TO_KEEP, self.gc_num_epochs_to_keep)
     }
+
+    /// Number of epochs for which data in column `col_name` is kept.
+    pub fn gc_num_epochs_to_keep_for_column(&self, col_name: &str) -> u64 {
+        let num_epochs = self.gc_num_epochs_to_keep_per_column.get(col_name).copied();
+        max(self.gc_num_epochs_to_keep(), num_epochs.unwrap_or_default())
+    }
+}
+
+/// Which transaction is evicted to make room for a new one when the
+/// transaction pool of a shard is full.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionPoolEvictionPolicy {
+    /// The transaction with the highest nonce from the group with the lowest
+    /// priority fee.  The new transaction is rejected unless its priority fee
+    /// is higher than that.
+    #[default]
+    LowestPriorityFee,
+    /// The transaction which has been in the pool for the longest time.
+    Oldest,
+    /// The newest transaction of the signer account with the most
+    /// transactions in the pool, so that a single account can't take the
+    /// whole pool.  The new transaction is rejected if its signer already has
+    /// at least as many transactions in the pool as any other account.
+    SignerQuota,
+}
+
+/// Limits of the transaction pool of each shard and how often it is saved to
+/// the database.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransactionPoolConfig {
+    /// Maximum number of transactions in the pool.  None is no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count_limit: Option<usize>,
+    /// Maximum total size of transactions in the pool in bytes.  None is no
+    /// limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_limit: Option<u64>,
+    #[serde(default)]
+    pub eviction_policy: TransactionPoolEvictionPolicy,
+    /// How often the pool is saved to the database so that pending
+    /// transactions survive restarts.  The pool is also saved on shutdown.
+    #[serde(default = "default_transaction_pool_snapshot_period")]
+    pub snapshot_period: Duration,
+}
+
+impl Default for TransactionPoolConfig {
+    fn default() -> Self {
+        Self {
+            count_limit: None,
+            size_limit: None,
+            eviction_policy: TransactionPoolEvictionPolicy::default(),
+            snapshot_period: Duration::from_secs(60),
+        }
+    }
+}
+
+fn default_transaction_pool_snapshot_period() -> Duration {
+    TransactionPoolConfig::default().snapshot_period
+}
+
+/// Location of state parts dumped outside of the nodes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExternalStorageLocation {
+    /// Directory on the local filesystem, possibly a mounted network one.
+    Filesystem { root_dir: PathBuf },
+    /// Bucket in an S3-compatible object store (e.g. MinIO) accessed over
+    /// HTTP(S) with path-style URLs.  Requests aren’t signed so the bucket
+    /// must allow anonymous reads (and writes when dumping).
+    S3 { endpoint: String, bucket: String },
+}
+
+/// Configuration of state sync from an external storage.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateSyncConfig {
+    /// If set, the node dumps parts of the state of the shards it tracks at
+    /// the beginning of each epoch to this location.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dump: Option<ExternalStorageLocation>,
+    /// If set, the node downloads state parts from this location during
+    /// state sync instead of requesting them from peers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_from: Option<ExternalStorageLocation>,
+    /// When syncing from an external storage, request every other attempt to
+    /// fetch a part from peers.  Useful when the dump may be incomplete.
+    #[serde(default)]
+    pub fallback_to_peers: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -106,6 +210,8 @@ pub struct ClientConfig {
     pub header_sync_expected_height_per_second: u64,
     /// How long to wait for a response during state sync
     pub state_sync_timeout: Duration,
+    /// Dumping and syncing state parts using an external storage.
+    pub state_sync: StateSyncConfig,
     /// Minimum number of peers to start syncing.
     pub min_num_peers: usize,
     /// Period between logging summary information.
@@ -158,6 +264,8 @@ pub struct ClientConfig {
     /// genesis file.  The value only affects the RPCs without influencing the
     /// protocol thus changing it per-node doesn’t affect the blockchain.
     pub max_gas_burnt_view: Option<Gas>,
+    /// Limits of the transaction pool of each shard.
+    pub transaction_pool: TransactionPoolConfig,
     /// Re-export storage layer statistics as prometheus metrics.
     pub enable_statistics_export: bool,
     /// Number of threads to execute background migration work in client.
@@ -200,6 +308,7 @@ impl ClientConfig {
             header_sync_progress_timeout: Duration::from_secs(2),
             header_sync_stall_ban_timeout: Duration::from_secs(30),
             state_sync_timeout: Duration::from_secs(TEST_STATE_SYNC_TIMEOUT),
+            state_sync: StateSyncConfig::default(),
             header_sync_expected_height_per_second: 1,
             min_num_peers: 1,
             log_summary_period: Duration::from_secs(10),
@@ -228,6 +337,7 @@ impl ClientConfig {
             view_client_throttle_period: Duration::from_secs(1),
             trie_viewer_state_size_limit: None,
             max_gas_burnt_view: None,
+            transaction_pool: TransactionPoolConfig::default(),
             enable_statistics_export: true,
             client_background_migration_threads: 1,
         }
@@ -21,7 +21,7 @@ use smart_default::SmartDefault;
 use tracing::warn;
 
 use crate::genesis_validate::validate_genesis;
-use near_primitives::epoch_manager::{AllEpochConfig, EpochConfig};
+use near_primitives::epoch_manager::{AllEpochConfig, EpochConfig, ShardLayoutUpgrade};
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::validator_stake::ValidatorStake;
 use near_primitives::{
@@ -160,6 +160,11 @@ pub struct GenesisConfig {
     #[serde(default = "default_shard_layout")]
     #[default(ShardLayout::v0_single_shard())]
     pub shard_layout: ShardLayout,
+    /// Shard layouts to switch to once the chain upgrades to the given
+    /// protocol versions.  Only meant for test networks, to rehearse
+    /// resharding without a dedicated protocol upgrade.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shard_layout_upgrades: Vec<ShardLayoutUpgrade>,
     #[serde(default = "default_num_chunk_only_producer_seats")]
     #[default(300)]
     pub num_chunk_only_producer_seats: NumSeats,
@@ -222,7 +227,8 @@ impl From<&GenesisConfig> for AllEpochConfig {
     fn from(genesis_config: &GenesisConfig) -> Self {
         let initial_epoch_config = EpochConfig::from(genesis_config);
         let epoch_config =
-            Self::new(genesis_config.use_production_config(), initial_epoch_config.clone());
+            Self::new(genesis_config.use_production_config(), initial_epoch_config.clone())
+                .with_shard_layout_upgrades(genesis_config.shard_layout_upgrades.clone());
         epoch_config
     }
 }
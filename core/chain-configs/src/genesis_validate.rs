@@ -129,6 +129,30 @@ impl<'a> GenesisValidator<'a> {
             self.genesis_config.gas_price_adjustment_rate < Rational32::from_integer(1),
             "Gas price adjustment rate must be less than 1"
         );
+
+        let mut protocol_version = self.genesis_config.protocol_version;
+        let mut shard_layout = &self.genesis_config.shard_layout;
+        for upgrade in &self.genesis_config.shard_layout_upgrades {
+            assert!(
+                upgrade.protocol_version > protocol_version,
+                "Shard layout upgrades must be scheduled at increasing protocol versions higher than the genesis one"
+            );
+            let next_shard_layout = &upgrade.shard_layout;
+            assert!(
+                next_shard_layout.version() > shard_layout.version(),
+                "Shard layout versions must increase"
+            );
+            let num_shards = shard_layout.num_shards();
+            assert!(
+                (0..num_shards)
+                    .all(|shard_id| next_shard_layout.get_split_shard_ids(shard_id).is_some())
+                    && next_shard_layout.get_split_shard_ids(num_shards).is_none(),
+                "Shard layout {} must split the shards of the previous layout",
+                next_shard_layout.version()
+            );
+            protocol_version = upgrade.protocol_version;
+            shard_layout = next_shard_layout;
+        }
     }
 }
 
@@ -139,6 +163,8 @@ mod test {
     use crate::GenesisRecords;
     use near_crypto::{KeyType, PublicKey};
     use near_primitives::account::{AccessKey, Account};
+    use near_primitives::epoch_manager::ShardLayoutUpgrade;
+    use near_primitives::shard_layout::ShardLayout;
     use near_primitives::types::AccountInfo;
 
     const VALID_ED25519_RISTRETTO_KEY: &str = "ed25519:KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7";
@@ -248,4 +274,25 @@ mod test {
         ]);
         validate_genesis(&Genesis::new(config, records));
     }
+
+    #[test]
+    #[should_panic(expected = "Shard layout 1 must split the shards of the previous layout")]
+    fn test_shard_layout_upgrade_without_split_map() {
+        let mut config = GenesisConfig::default();
+        config.validators = vec![AccountInfo {
+            account_id: "test".parse().unwrap(),
+            public_key: VALID_ED25519_RISTRETTO_KEY.parse().unwrap(),
+            amount: 10,
+        }];
+        config.total_supply = 110;
+        config.shard_layout_upgrades = vec![ShardLayoutUpgrade {
+            protocol_version: config.protocol_version + 1,
+            shard_layout: ShardLayout::v1(vec![], vec!["test".parse().unwrap()], None, 1),
+        }];
+        let records = GenesisRecords(vec![StateRecord::Account {
+            account_id: "test".parse().unwrap(),
+            account: create_account(),
+        }]);
+        validate_genesis(&Genesis::new(config, records));
+    }
 }
@@ -3,7 +3,11 @@ use std::io;
 use std::io::Write;
 use std::path::Path;
 
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
 use crate::{PublicKey, SecretKey};
 
@@ -20,28 +24,214 @@ pub struct KeyFile {
     pub secret_key: SecretKey,
 }
 
+/// Key file whose secret key is encrypted with a key derived from
+/// a passphrase.  Account id and public key are kept in plaintext so that
+/// they can be read without knowing the passphrase.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedKeyFile {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    encrypted_secret_key: EncryptedSecretKey,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct EncryptedSecretKey {
+    kdf: Kdf,
+    cipher: Cipher,
+    /// Base58-encoded nonce.
+    nonce: String,
+    /// Base58-encoded secret key in its string form, encrypted with the key
+    /// derived from the passphrase.  Account id and public key are used as
+    /// associated data, so they can't be swapped.
+    ciphertext: String,
+}
+
+/// Function deriving the encryption key from the passphrase.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+enum Kdf {
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        /// Base58-encoded salt.
+        salt: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Cipher {
+    XChaCha20Poly1305,
+}
+
+/// scrypt parameters recommended for interactive logins, which take about
+/// 100ms to derive a key on a modern machine.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Upper bounds on scrypt parameters read from a key file, so that a crafted
+/// file can't make the node allocate lots of memory or spin for hours.
+/// Memory used by scrypt is `128 * r * 2^log_n` bytes.
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+const MAX_SCRYPT_P: u32 = 16;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn decode_bs58(data: &str) -> io::Result<Vec<u8>> {
+    bs58::decode(data).into_vec().map_err(|_| invalid_data("invalid base58 in key file"))
+}
+
+impl Kdf {
+    fn derive_key(&self, passphrase: &str) -> io::Result<Zeroizing<[u8; 32]>> {
+        match self {
+            Kdf::Scrypt { log_n, r, p, salt } => {
+                let memory = 1u64
+                    .checked_shl(u32::from(*log_n))
+                    .and_then(|n| n.checked_mul(128 * u64::from(*r)));
+                if !memory.map_or(false, |memory| memory <= MAX_SCRYPT_MEMORY) || *p > MAX_SCRYPT_P
+                {
+                    return Err(invalid_data("scrypt parameters in key file are too large"));
+                }
+                let params = scrypt::Params::new(*log_n, *r, *p)
+                    .map_err(|_| invalid_data("invalid scrypt parameters"))?;
+                let mut key = Zeroizing::new([0u8; 32]);
+                scrypt::scrypt(passphrase.as_bytes(), &decode_bs58(salt)?, &params, &mut *key)
+                    .map_err(|_| invalid_data("invalid scrypt key length"))?;
+                Ok(key)
+            }
+        }
+    }
+}
+
+fn associated_data(account_id: &AccountId, public_key: &PublicKey) -> Vec<u8> {
+    format!("{}:{}", account_id, public_key).into_bytes()
+}
+
 impl KeyFile {
     pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
         let data = serde_json::to_string_pretty(self)?;
-        let mut file = Self::create(path)?;
+        let mut file = create(path)?;
         file.write_all(data.as_bytes())
     }
 
-    #[cfg(unix)]
-    fn create(path: &Path) -> io::Result<File> {
-        use std::os::unix::fs::OpenOptionsExt;
-        std::fs::File::options().mode(0o600).write(true).create(true).open(path)
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
     }
 
-    #[cfg(not(unix))]
-    fn create(path: &Path) -> io::Result<File> {
-        std::fs::File::create(path)
+    /// Reads a key file which may be either plaintext or encrypted.
+    /// Passphrase is required only in the latter case.
+    pub fn from_file_with_passphrase(path: &Path, passphrase: Option<&str>) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+        if value.get("encrypted_secret_key").is_none() {
+            return Ok(serde_json::from_value(value)?);
+        }
+        let passphrase = passphrase.ok_or_else(|| {
+            invalid_data("key file is encrypted but no passphrase has been provided")
+        })?;
+        serde_json::from_value::<EncryptedKeyFile>(value)?.decrypt(passphrase)
+    }
+
+    /// Returns whether the file at given path is an encrypted key file.
+    pub fn is_encrypted(path: &Path) -> io::Result<bool> {
+        let content = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+        Ok(value.get("encrypted_secret_key").is_some())
+    }
+
+    pub fn encrypt(&self, passphrase: &str) -> EncryptedKeyFile {
+        self.encrypt_with_scrypt(passphrase, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+    }
+
+    fn encrypt_with_scrypt(&self, passphrase: &str, log_n: u8, r: u32, p: u32) -> EncryptedKeyFile {
+        let mut salt = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        let mut nonce = XNonce::default();
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let kdf = Kdf::Scrypt { log_n, r, p, salt: bs58::encode(salt).into_string() };
+        let key = kdf.derive_key(passphrase).expect("scrypt parameters are valid");
+        let secret_key = Zeroizing::new(self.secret_key.to_string());
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&*key))
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: secret_key.as_bytes(),
+                    aad: &associated_data(&self.account_id, &self.public_key),
+                },
+            )
+            .expect("encryption with XChaCha20Poly1305 never fails");
+        EncryptedKeyFile {
+            account_id: self.account_id.clone(),
+            public_key: self.public_key.clone(),
+            encrypted_secret_key: EncryptedSecretKey {
+                kdf,
+                cipher: Cipher::XChaCha20Poly1305,
+                nonce: bs58::encode(nonce).into_string(),
+                ciphertext: bs58::encode(ciphertext).into_string(),
+            },
+        }
+    }
+}
+
+impl EncryptedKeyFile {
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        let mut file = create(path)?;
+        file.write_all(data.as_bytes())
     }
 
     pub fn from_file(path: &Path) -> io::Result<Self> {
         let content = std::fs::read_to_string(path)?;
         Ok(serde_json::from_str(&content)?)
     }
+
+    pub fn decrypt(&self, passphrase: &str) -> io::Result<KeyFile> {
+        let encrypted = &self.encrypted_secret_key;
+        let key = encrypted.kdf.derive_key(passphrase)?;
+        let nonce = decode_bs58(&encrypted.nonce)?;
+        if nonce.len() != XNonce::default().len() {
+            return Err(invalid_data("invalid nonce length in key file"));
+        }
+        let secret_key = match encrypted.cipher {
+            Cipher::XChaCha20Poly1305 => XChaCha20Poly1305::new(Key::from_slice(&*key)).decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &decode_bs58(&encrypted.ciphertext)?,
+                    aad: &associated_data(&self.account_id, &self.public_key),
+                },
+            ),
+        }
+        .map(Zeroizing::new)
+        .map_err(|_| invalid_data("failed to decrypt key file; is the passphrase correct?"))?;
+        let secret_key: SecretKey = std::str::from_utf8(&secret_key)
+            .ok()
+            .and_then(|secret_key| secret_key.parse().ok())
+            .ok_or_else(|| invalid_data("invalid secret key in key file"))?;
+        if secret_key.public_key() != self.public_key {
+            return Err(invalid_data("secret key doesn't match public key in key file"));
+        }
+        Ok(KeyFile {
+            account_id: self.account_id.clone(),
+            public_key: self.public_key.clone(),
+            secret_key,
+        })
+    }
+}
+
+#[cfg(unix)]
+fn create(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::File::options().mode(0o600).write(true).create(true).truncate(true).open(path)
+}
+
+#[cfg(not(unix))]
+fn create(path: &Path) -> io::Result<File> {
+    std::fs::File::create(path)
 }
 
 #[cfg(test)]
@@ -110,4 +300,45 @@ mod test {
         let inner_msg = err.into_inner().unwrap().to_string();
         assert!(inner_msg.contains("duplicate field"));
     }
+
+    #[test]
+    fn test_encrypted() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let plain_path = tmp.path().join("plain");
+        let encrypted_path = tmp.path().join("encrypted");
+        std::fs::write(&plain_path, KEY_FILE_CONTENTS).unwrap();
+
+        let key = KeyFile::from_file(&plain_path).unwrap();
+        // Use cheap scrypt parameters to keep the test fast.
+        let encrypted = key.encrypt_with_scrypt("passphrase", 4, 8, 1);
+        encrypted.write_to_file(&encrypted_path).unwrap();
+        assert!(!std::fs::read_to_string(&encrypted_path).unwrap().contains(SECRET_KEY));
+        assert!(!KeyFile::is_encrypted(&plain_path).unwrap());
+        assert!(KeyFile::is_encrypted(&encrypted_path).unwrap());
+        assert_eq!(encrypted, EncryptedKeyFile::from_file(&encrypted_path).unwrap());
+
+        let check = |key: KeyFile| {
+            assert_eq!(ACCOUNT_ID, key.account_id.to_string());
+            assert_eq!(SECRET_KEY, key.secret_key.to_string());
+        };
+        check(encrypted.decrypt("passphrase").unwrap());
+        check(KeyFile::from_file_with_passphrase(&encrypted_path, Some("passphrase")).unwrap());
+        check(KeyFile::from_file_with_passphrase(&plain_path, None).unwrap());
+        assert!(KeyFile::from_file_with_passphrase(&encrypted_path, None).is_err());
+        assert!(encrypted.decrypt("wrong passphrase").is_err());
+
+        // Account id is authenticated.
+        let mut tampered = encrypted.clone();
+        tampered.account_id = "other".parse().unwrap();
+        assert!(tampered.decrypt("passphrase").is_err());
+
+        // Expensive scrypt parameters are rejected before deriving the key.
+        for (log_n, r, p) in [(31, 8, 1), (255, 8, 1), (20, 1024, 1), (4, 8, 1000)] {
+            let mut expensive = encrypted.clone();
+            expensive.encrypted_secret_key.kdf =
+                Kdf::Scrypt { log_n, r, p, salt: bs58::encode([0u8; 32]).into_string() };
+            let err = expensive.decrypt("passphrase").err().unwrap();
+            assert!(err.to_string().contains("too large"), "{err}");
+        }
+    }
 }
@@ -563,6 +563,60 @@ impl Signature {
         }
     }
 
+    /// Verifies a batch of `(signature, data, public_key)` triples.  Returns
+    /// for each of them whether it is valid.
+    ///
+    /// ED25519 signatures are checked together with a single multiscalar
+    /// multiplication, which is considerably cheaper than checking them one
+    /// by one.  If the batch doesn't verify, every signature in it is checked
+    /// with `verify` to find the invalid ones.  Other signatures are always
+    /// checked individually.
+    ///
+    /// The random coefficients of the batch equation are derived from the
+    /// batch itself, so the result is the same on every node.  It is however
+    /// not strictly equivalent to `verify`: a signature whose `R` was altered
+    /// by a small-order point may pass in a batch while failing on its own.
+    /// Such a signature can only be derived from a valid signature of the same
+    /// data by the same key, so it doesn't allow forgeries, but switching
+    /// between the two methods must be done with a protocol upgrade.
+    pub fn verify_batch(items: &[(&Signature, &[u8], &PublicKey)]) -> Vec<bool> {
+        let mut result = vec![false; items.len()];
+        let mut batch = vec![];
+        let mut messages = vec![];
+        let mut signatures = vec![];
+        let mut public_keys = vec![];
+        for (i, (signature, data, public_key)) in items.iter().enumerate() {
+            match (signature, public_key) {
+                (Signature::ED25519(signature), PublicKey::ED25519(public_key))
+                    if has_canonical_r(signature) =>
+                {
+                    // A public key which isn't on the curve never verifies.
+                    if let Ok(public_key) = ed25519_dalek::PublicKey::from_bytes(&public_key.0) {
+                        batch.push(i);
+                        messages.push(*data);
+                        signatures.push(*signature);
+                        public_keys.push(public_key);
+                    }
+                }
+                _ => result[i] = signature.verify(data, public_key),
+            }
+        }
+        if batch.is_empty() {
+            return result;
+        }
+        if ed25519_dalek::verify_batch(&messages, &signatures, &public_keys).is_ok() {
+            for i in batch {
+                result[i] = true;
+            }
+        } else {
+            for i in batch {
+                let (signature, data, public_key) = items[i];
+                result[i] = signature.verify(data, public_key);
+            }
+        }
+        result
+    }
+
     pub fn key_type(&self) -> KeyType {
         match self {
             Signature::ED25519(_) => KeyType::ED25519,
@@ -571,6 +625,18 @@ impl Signature {
     }
 }
 
+/// Checks that `R` of the signature is a canonical encoding of a point.
+/// `verify` compares `R` byte by byte so it rejects non-canonical encodings,
+/// while the batch equation operates on decompressed points and would accept
+/// them.
+fn has_canonical_r(signature: &ed25519_dalek::Signature) -> bool {
+    let mut r = [0u8; 32];
+    r.copy_from_slice(&signature.to_bytes()[..32]);
+    curve25519_dalek::edwards::CompressedEdwardsY(r)
+        .decompress()
+        .map_or(false, |point| point.compress().0 == r)
+}
+
 impl Default for Signature {
     fn default() -> Self {
         Signature::empty(KeyType::ED25519)
@@ -760,6 +826,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_verify_batch() {
+        let data: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 32]).collect();
+        let keys: Vec<SecretKey> = (0..10)
+            .map(|i| {
+                SecretKey::from_random(if i == 7 { KeyType::SECP256K1 } else { KeyType::ED25519 })
+            })
+            .collect();
+        let public_keys: Vec<PublicKey> = keys.iter().map(|key| key.public_key()).collect();
+        let mut signatures: Vec<Signature> =
+            keys.iter().zip(&data).map(|(key, data)| key.sign(data)).collect();
+        let verify = |signatures: &[Signature]| -> Vec<bool> {
+            let items: Vec<_> = signatures
+                .iter()
+                .zip(&data)
+                .zip(&public_keys)
+                .map(|((signature, data), public_key)| (signature, data.as_slice(), public_key))
+                .collect();
+            Signature::verify_batch(&items)
+        };
+        assert_eq!(verify(&signatures), vec![true; 10]);
+
+        // Invalid signatures are pinpointed.
+        signatures.swap(2, 3);
+        signatures[5] = keys[6].sign(&data[5]);
+        let mut expected = vec![true; 10];
+        expected[2] = false;
+        expected[3] = false;
+        expected[5] = false;
+        assert_eq!(verify(&signatures), expected);
+        assert_eq!(Signature::verify_batch(&[]), Vec::<bool>::new());
+    }
+
     #[test]
     fn test_json_serialize_ed25519() {
         let sk = SecretKey::from_seed(KeyType::ED25519, "test");
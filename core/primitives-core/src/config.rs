@@ -260,6 +260,9 @@ impl VMLimitConfig {
 pub struct ViewConfig {
     /// If specified, defines max burnt gas per view method.
     pub max_gas_burnt: Gas,
+    /// Whether host function calls made by the contract are recorded.
+    #[serde(default)]
+    pub trace: bool,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -470,6 +473,7 @@ pub enum ActionCosts {
     new_action_receipt,
     new_data_receipt_base,
     new_data_receipt_byte,
+    delegate,
 }
 
 impl ExtCosts {
@@ -12,7 +12,7 @@ use strum::IntoEnumIterator;
 pub struct DataArray(Box<[u64; Self::LEN]>);
 
 impl DataArray {
-    pub const LEN: usize = if cfg!(feature = "protocol_feature_ed25519_verify") { 72 } else { 70 };
+    pub const LEN: usize = if cfg!(feature = "protocol_feature_ed25519_verify") { 73 } else { 71 };
 }
 
 impl Index<usize> for DataArray {
@@ -260,6 +260,10 @@ impl Cost {
             Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_base } => 70,
             #[cfg(feature = "protocol_feature_ed25519_verify")]
             Cost::ExtCost { ext_cost_kind: ExtCosts::ed25519_verify_byte } => 71,
+            #[cfg(feature = "protocol_feature_ed25519_verify")]
+            Cost::ActionCost { action_cost_kind: ActionCosts::delegate } => 72,
+            #[cfg(not(feature = "protocol_feature_ed25519_verify"))]
+            Cost::ActionCost { action_cost_kind: ActionCosts::delegate } => 70,
         }
     }
 }
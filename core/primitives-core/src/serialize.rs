@@ -88,6 +88,53 @@ fn test_option_base64_format() {
     assert_round_trip("{\"field\":null}", Test { field: None });
 }
 
+pub mod vec_base64_format {
+    use serde::de;
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{from_base64, to_base64};
+
+    pub fn serialize<S, T>(data: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        let mut seq = serializer.serialize_seq(Some(data.len()))?;
+        for bytes in data {
+            seq.serialize_element(&to_base64(bytes))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        let strings: Vec<String> = Vec::deserialize(deserializer)?;
+        strings
+            .iter()
+            .map(|s| {
+                from_base64(s).map_err(|err| de::Error::custom(err.to_string())).map(Into::into)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_vec_base64_format() {
+    #[derive(PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+    struct Test {
+        #[serde(with = "vec_base64_format")]
+        field: Vec<Vec<u8>>,
+    }
+
+    assert_round_trip("{\"field\":[\"Zm9v\",\"\"]}", Test { field: vec![b"foo".to_vec(), vec![]] });
+    assert_round_trip("{\"field\":[]}", Test { field: vec![] });
+    assert_de_error::<Test>("{\"field\":[null]}");
+}
+
 /// Serialises number as a string; deserialises either as a string or number.
 ///
 /// This format works for `u64`, `u128`, `Option<u64>` and `Option<u128>` types.
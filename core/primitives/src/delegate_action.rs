@@ -0,0 +1,141 @@
+//! Delegate actions allow a relayer to pay for the gas of actions signed by
+//! another account, e.g. to onboard users who don't hold any tokens yet.
+//!
+//! The user signs a `DelegateAction` with one of their access keys and hands
+//! it to the relayer, which wraps it in a transaction sent to the user's
+//! account.  When the transaction's receipt is executed, the delegated
+//! actions are sent to their receiver as if they originated from the user.
+use std::io::{Error, ErrorKind};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+
+use crate::hash::CryptoHash;
+use crate::transaction::Action;
+use crate::types::{AccountId, BlockHeight, Nonce};
+
+/// Prefix of the signed data, see `DelegateAction::get_hash`.
+const SIGNED_DATA_PREFIX: u32 = (1 << 30) + 366;
+
+/// Actions signed by `sender_id` to be executed on `receiver_id`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DelegateAction {
+    /// Account which signed the delegated actions.
+    pub sender_id: AccountId,
+    /// Receiver of the delegated actions.
+    pub receiver_id: AccountId,
+    /// Actions to execute.  Delegate actions can't be nested.
+    pub actions: Vec<NonDelegateAction>,
+    /// Must be larger than the nonce of the access key identified by
+    /// `public_key`, which is set to this nonce once the action is executed.
+    pub nonce: Nonce,
+    /// The action is rejected once the chain is past this height.
+    pub max_block_height: BlockHeight,
+    /// Public key of the access key used to sign the action.
+    pub public_key: PublicKey,
+}
+
+impl DelegateAction {
+    pub fn get_actions(&self) -> Vec<Action> {
+        self.actions.iter().map(|action| action.0.clone()).collect()
+    }
+
+    /// Hash of the data signed by `sender_id`.
+    ///
+    /// Borsh serialization of the action is prefixed with a `u32` which
+    /// would be the length of the transaction's `signer_id` if it were a
+    /// transaction.  Account ids are much shorter than that, so a signed
+    /// delegate action can never be replayed as a transaction.
+    pub fn get_hash(&self) -> CryptoHash {
+        let mut bytes = SIGNED_DATA_PREFIX.to_le_bytes().to_vec();
+        BorshSerialize::serialize(self, &mut bytes).expect("Failed to serialize");
+        CryptoHash::hash_bytes(&bytes)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SignedDelegateAction {
+    pub delegate_action: DelegateAction,
+    pub signature: Signature,
+}
+
+impl SignedDelegateAction {
+    pub fn verify(&self) -> bool {
+        let delegate_action = &self.delegate_action;
+        self.signature.verify(delegate_action.get_hash().as_ref(), &delegate_action.public_key)
+    }
+}
+
+/// An action which is not a `Delegate` action.
+#[derive(BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(try_from = "Action", into = "Action")]
+pub struct NonDelegateAction(Action);
+
+impl From<NonDelegateAction> for Action {
+    fn from(action: NonDelegateAction) -> Self {
+        action.0
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("delegate action can't contain another delegate action")]
+pub struct IsDelegateAction;
+
+impl TryFrom<Action> for NonDelegateAction {
+    type Error = IsDelegateAction;
+
+    fn try_from(action: Action) -> Result<Self, Self::Error> {
+        match action {
+            Action::Delegate(_) => Err(IsDelegateAction),
+            action => Ok(Self(action)),
+        }
+    }
+}
+
+impl BorshDeserialize for NonDelegateAction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        <Action as BorshDeserialize>::deserialize(buf)?
+            .try_into()
+            .map_err(|err: IsDelegateAction| Error::new(ErrorKind::InvalidData, err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransferAction;
+    use near_crypto::{InMemorySigner, KeyType, Signer};
+
+    fn delegate_action(actions: Vec<Action>) -> DelegateAction {
+        DelegateAction {
+            sender_id: "alice.near".parse().unwrap(),
+            receiver_id: "bob.near".parse().unwrap(),
+            actions: actions.into_iter().map(NonDelegateAction).collect(),
+            nonce: 1,
+            max_block_height: 100,
+            public_key: PublicKey::empty(KeyType::ED25519),
+        }
+    }
+
+    #[test]
+    fn test_nested_delegate_action() {
+        let signer =
+            InMemorySigner::from_seed("alice.near".parse().unwrap(), KeyType::ED25519, "a");
+        let mut inner = delegate_action(vec![Action::Transfer(TransferAction { deposit: 1 })]);
+        inner.public_key = signer.public_key();
+        let signed = SignedDelegateAction {
+            signature: signer.sign(inner.get_hash().as_ref()),
+            delegate_action: inner,
+        };
+        assert!(signed.verify());
+
+        let bytes = signed.delegate_action.try_to_vec().unwrap();
+        assert_eq!(DelegateAction::try_from_slice(&bytes).unwrap(), signed.delegate_action);
+
+        // Nested delegate actions can be serialized but not deserialized.
+        let outer = delegate_action(vec![Action::Delegate(Box::new(signed))]);
+        let bytes = outer.try_to_vec().unwrap();
+        assert!(DelegateAction::try_from_slice(&bytes).is_err());
+    }
+}
@@ -77,6 +77,14 @@ impl ShardConfig {
     }
 }
 
+/// Shard layout which replaces the genesis one starting from the given
+/// protocol version.  Meant for rehearsing resharding on test networks.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShardLayoutUpgrade {
+    pub protocol_version: ProtocolVersion,
+    pub shard_layout: ShardLayout,
+}
+
 /// AllEpochConfig manages protocol configs that might be changing throughout epochs (hence EpochConfig).
 /// The main function in AllEpochConfig is ::for_protocol_version which takes a protocol version
 /// and returns the EpochConfig that should be used for this protocol version.
@@ -87,11 +95,19 @@ pub struct AllEpochConfig {
     use_production_config: bool,
     /// EpochConfig from genesis
     genesis_epoch_config: EpochConfig,
+    /// Shard layout changes scheduled in genesis, sorted by protocol version.
+    shard_layout_upgrades: Vec<ShardLayoutUpgrade>,
 }
 
 impl AllEpochConfig {
     pub fn new(use_production_config: bool, genesis_epoch_config: EpochConfig) -> Self {
-        Self { use_production_config, genesis_epoch_config }
+        Self { use_production_config, genesis_epoch_config, shard_layout_upgrades: vec![] }
+    }
+
+    pub fn with_shard_layout_upgrades(mut self, mut upgrades: Vec<ShardLayoutUpgrade>) -> Self {
+        upgrades.sort_by_key(|upgrade| upgrade.protocol_version);
+        self.shard_layout_upgrades = upgrades;
+        self
     }
 
     pub fn for_protocol_version(&self, protocol_version: ProtocolVersion) -> EpochConfig {
@@ -126,6 +142,18 @@ impl AllEpochConfig {
                 config.validator_max_kickout_stake_perc = 30;
             }
         }
+        let upgrade = self
+            .shard_layout_upgrades
+            .iter()
+            .rev()
+            .find(|upgrade| upgrade.protocol_version <= protocol_version);
+        if let Some(upgrade) = upgrade {
+            config.shard_layout = upgrade.shard_layout.clone();
+            let num_shards = config.shard_layout.num_shards() as usize;
+            config.num_block_producer_seats_per_shard =
+                vec![config.num_block_producer_seats; num_shards];
+            config.avg_hidden_validator_seats_per_shard = vec![0; num_shards];
+        }
         config
     }
 }
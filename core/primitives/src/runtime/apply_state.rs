@@ -41,4 +41,7 @@ pub struct ApplyState {
     pub migration_data: Arc<MigrationData>,
     /// Flags for migrations indicating whether they can be applied at this block
     pub migration_flags: MigrationFlags,
+    /// Whether signatures of the transactions are verified.  Only disabled when
+    /// simulating transactions, which don't have to be signed.
+    pub verify_signatures: bool,
 }
@@ -1,4 +1,4 @@
-use std::cmp::Ordering::Greater;
+use std::cmp::Ordering::{self, Greater};
 use std::{fmt, str};
 
 use serde::{Deserialize, Serialize};
@@ -96,7 +96,19 @@ pub struct ShardLayoutV1 {
 
 #[derive(Debug)]
 pub enum ShardLayoutError {
-    InvalidShardIdError { shard_id: ShardId },
+    InvalidShardIdError {
+        shard_id: ShardId,
+    },
+    /// The shard is a fixed shard or belongs to a V0 layout, so it can't be
+    /// split by boundary accounts.
+    UnsplittableShardError {
+        shard_id: ShardId,
+    },
+    /// The account doesn't belong to the shard being split or is already a
+    /// boundary account.
+    InvalidBoundaryAccountError {
+        account_id: AccountId,
+    },
 }
 
 impl ShardLayout {
@@ -163,6 +175,48 @@ impl ShardLayout {
         )
     }
 
+    /// Returns the next shard layout in which `shard_id` is split into
+    /// `new_boundary_accounts.len() + 1` shards at the given accounts while
+    /// the other shards stay the same.  Shards after the split one are
+    /// renumbered accordingly.
+    pub fn split_shard(
+        &self,
+        shard_id: ShardId,
+        new_boundary_accounts: Vec<AccountId>,
+    ) -> Result<ShardLayout, ShardLayoutError> {
+        if shard_id >= self.num_shards() {
+            return Err(ShardLayoutError::InvalidShardIdError { shard_id });
+        }
+        let v1 = match self {
+            Self::V1(v1) if shard_id as usize >= v1.fixed_shards.len() => v1,
+            _ => return Err(ShardLayoutError::UnsplittableShardError { shard_id }),
+        };
+        let mut boundary_accounts = v1.boundary_accounts.clone();
+        for account_id in new_boundary_accounts {
+            if account_id_to_shard_id(&account_id, self) != shard_id
+                || boundary_accounts.contains(&account_id)
+            {
+                return Err(ShardLayoutError::InvalidBoundaryAccountError { account_id });
+            }
+            boundary_accounts.push(account_id);
+        }
+        let num_new_shards = (boundary_accounts.len() - v1.boundary_accounts.len()) as ShardId;
+        boundary_accounts.sort();
+        let shards_split_map = (0..self.num_shards())
+            .map(|parent_shard_id| match parent_shard_id.cmp(&shard_id) {
+                Ordering::Less => vec![parent_shard_id],
+                Ordering::Equal => (shard_id..=shard_id + num_new_shards).collect(),
+                Ordering::Greater => vec![parent_shard_id + num_new_shards],
+            })
+            .collect();
+        Ok(ShardLayout::v1(
+            v1.fixed_shards.clone(),
+            boundary_accounts,
+            Some(shards_split_map),
+            v1.version + 1,
+        ))
+    }
+
     /// Given a parent shard id, return the shard uids for the shards in the current shard layout that
     /// are split from this parent shard. If this shard layout has no parent shard layout, return None
     pub fn get_split_shard_uids(&self, parent_shard_id: ShardId) -> Option<Vec<ShardUId>> {
@@ -509,4 +563,34 @@ mod tests {
         assert_eq!(account_id_to_shard_id(&"goo".parse().unwrap(), &shard_layout), 6);
         assert_eq!(account_id_to_shard_id(&"zoo".parse().unwrap(), &shard_layout), 7);
     }
+
+    #[test]
+    fn test_split_shard() {
+        let shard_layout =
+            ShardLayout::v1(vec!["aurora".parse().unwrap()], vec!["foo".parse().unwrap()], None, 1);
+        let split = shard_layout
+            .split_shard(1, vec!["def".parse().unwrap(), "bar".parse().unwrap()])
+            .unwrap();
+        assert_eq!(
+            split,
+            ShardLayout::v1(
+                vec!["aurora".parse().unwrap()],
+                vec!["bar", "def", "foo"].into_iter().map(|s| s.parse().unwrap()).collect(),
+                Some(vec![vec![0], vec![1, 2, 3], vec![4]]),
+                2,
+            )
+        );
+        for (account_id, shard_id) in [("abc", 1), ("bar", 2), ("dd", 2), ("def", 3), ("zoo", 4)] {
+            let account_id = account_id.parse().unwrap();
+            assert_eq!(account_id_to_shard_id(&account_id, &split), shard_id);
+            let parent_shard_id = account_id_to_shard_id(&account_id, &shard_layout);
+            assert_eq!(split.get_parent_shard_id(shard_id).unwrap(), parent_shard_id);
+        }
+
+        // Fixed shards can't be split and boundaries must be within the split shard.
+        assert!(shard_layout.split_shard(0, vec!["bar".parse().unwrap()]).is_err());
+        assert!(shard_layout.split_shard(1, vec!["goo".parse().unwrap()]).is_err());
+        assert!(shard_layout.split_shard(2, vec!["foo".parse().unwrap()]).is_err());
+        assert!(shard_layout.split_shard(1, vec!["x.aurora".parse().unwrap()]).is_err());
+    }
 }
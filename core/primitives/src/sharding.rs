@@ -207,8 +207,7 @@ impl ShardChunkHeaderV3 {
             tx_root,
             validator_proposals,
         });
-        let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_chunk_hash(&hash);
+        let (hash, signature) = signer.sign_chunk_header_inner(&inner);
         Self { inner, height_included: 0, signature, hash }
     }
 }
@@ -10,6 +10,7 @@ use near_o11y::pretty;
 use near_primitives_core::profile::ProfileData;
 
 use crate::account::AccessKey;
+use crate::delegate_action::SignedDelegateAction;
 use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::MerklePath;
@@ -18,7 +19,7 @@ use crate::types::{AccountId, Balance, Gas, Nonce};
 
 pub type LogEntry = String;
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub struct Transaction {
     /// An account on which behalf transaction is signed
     pub signer_id: AccountId,
@@ -34,6 +35,10 @@ pub struct Transaction {
     pub block_hash: CryptoHash,
     /// A list of actions to be applied
     pub actions: Vec<Action>,
+    /// Amount burnt on top of the gas costs in exchange for being picked
+    /// sooner from the transaction pool during congestion.
+    #[serde(default, with = "dec_format", skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<Balance>,
 }
 
 impl Transaction {
@@ -42,6 +47,55 @@ impl Transaction {
         let bytes = self.try_to_vec().expect("Failed to deserialize");
         (hash(&bytes), bytes.len() as u64)
     }
+
+    pub fn priority_fee(&self) -> Balance {
+        self.priority_fee.unwrap_or(0)
+    }
+}
+
+/// First byte of the serialization of transactions with a priority fee.
+///
+/// Transactions without a priority fee are serialized the same way they were
+/// before priority fees were introduced, so that their hashes don't change.
+/// Such serialization starts with the length of `signer_id` as a `u32` and
+/// account ids are at least two characters long, so the first byte is never
+/// equal to this tag.
+const TRANSACTION_V1_TAG: u8 = 1;
+
+impl BorshSerialize for Transaction {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if self.priority_fee.is_some() {
+            BorshSerialize::serialize(&TRANSACTION_V1_TAG, writer)?;
+        }
+        BorshSerialize::serialize(&self.signer_id, writer)?;
+        BorshSerialize::serialize(&self.public_key, writer)?;
+        BorshSerialize::serialize(&self.nonce, writer)?;
+        BorshSerialize::serialize(&self.receiver_id, writer)?;
+        BorshSerialize::serialize(&self.block_hash, writer)?;
+        BorshSerialize::serialize(&self.actions, writer)?;
+        if let Some(priority_fee) = &self.priority_fee {
+            BorshSerialize::serialize(priority_fee, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Transaction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let is_v1 = buf.first() == Some(&TRANSACTION_V1_TAG);
+        if is_v1 {
+            *buf = &buf[1..];
+        }
+        Ok(Self {
+            signer_id: BorshDeserialize::deserialize(buf)?,
+            public_key: BorshDeserialize::deserialize(buf)?,
+            nonce: BorshDeserialize::deserialize(buf)?,
+            receiver_id: BorshDeserialize::deserialize(buf)?,
+            block_hash: BorshDeserialize::deserialize(buf)?,
+            actions: BorshDeserialize::deserialize(buf)?,
+            priority_fee: if is_v1 { Some(BorshDeserialize::deserialize(buf)?) } else { None },
+        })
+    }
 }
 
 #[derive(
@@ -68,12 +122,20 @@ pub enum Action {
     AddKey(AddKeyAction),
     DeleteKey(DeleteKeyAction),
     DeleteAccount(DeleteAccountAction),
+    /// Actions signed by another account, executed as if sent by it.  Gas
+    /// and deposits of the inner actions are paid by the transaction signer.
+    Delegate(Box<SignedDelegateAction>),
 }
 
 impl Action {
     pub fn get_prepaid_gas(&self) -> Gas {
         match self {
             Action::FunctionCall(a) => a.gas,
+            Action::Delegate(a) => a
+                .delegate_action
+                .get_actions()
+                .iter()
+                .fold(0, |gas: Gas, action| gas.saturating_add(action.get_prepaid_gas())),
             _ => 0,
         }
     }
@@ -81,6 +143,11 @@ impl Action {
         match self {
             Action::FunctionCall(a) => a.deposit,
             Action::Transfer(a) => a.deposit,
+            Action::Delegate(a) => {
+                a.delegate_action.get_actions().iter().fold(0, |deposit: Balance, action| {
+                    deposit.saturating_add(action.get_deposit_balance())
+                })
+            }
             _ => 0,
         }
     }
@@ -437,6 +504,20 @@ pub fn verify_transaction_signature(
     public_keys.iter().any(|key| transaction.signature.verify(hash, key))
 }
 
+/// Verifies signatures of all the transactions as a batch, which is much
+/// cheaper than verifying them one by one.  Returns for each transaction
+/// whether its signature is valid, so that it can be validated later with
+/// `verify_signature` set to `false`.
+pub fn verify_transaction_signatures(transactions: &[SignedTransaction]) -> Vec<bool> {
+    let hashes: Vec<_> = transactions.iter().map(|tx| tx.get_hash()).collect();
+    let items: Vec<_> = transactions
+        .iter()
+        .zip(&hashes)
+        .map(|(tx, hash)| (&tx.signature, hash.as_ref(), &tx.transaction.public_key))
+        .collect();
+    Signature::verify_batch(&items)
+}
+
 /// A more compact struct, just for storage.
 #[derive(Clone, BorshSerialize, BorshDeserialize)]
 pub struct ExecutionOutcomeWithProof {
@@ -463,6 +544,7 @@ mod tests {
             receiver_id: "test".parse().unwrap(),
             block_hash: Default::default(),
             actions: vec![],
+            priority_fee: None,
         }
         .sign(&signer);
         let wrong_public_key = PublicKey::from_seed(KeyType::ED25519, "wrong");
@@ -477,6 +559,33 @@ mod tests {
         assert!(verify_transaction_signature(&decoded_tx, &valid_keys));
     }
 
+    #[test]
+    fn test_verify_transaction_signatures() {
+        let signer = InMemorySigner::from_random("test".parse().unwrap(), KeyType::ED25519);
+        let transactions: Vec<_> = (0..3)
+            .map(|nonce| {
+                Transaction {
+                    signer_id: "test".parse().unwrap(),
+                    public_key: signer.public_key(),
+                    nonce,
+                    receiver_id: "test".parse().unwrap(),
+                    block_hash: Default::default(),
+                    actions: vec![],
+                    priority_fee: None,
+                }
+                .sign(&signer)
+            })
+            .collect();
+        assert_eq!(verify_transaction_signatures(&transactions), vec![true, true, true]);
+
+        let mut invalid = transactions.clone();
+        invalid[1] = SignedTransaction::new(
+            transactions[0].signature.clone(),
+            transactions[1].transaction.clone(),
+        );
+        assert_eq!(verify_transaction_signatures(&invalid), vec![true, false, true]);
+    }
+
     /// This test is change checker for a reason - we don't expect transaction format to change.
     /// If it does - you MUST update all of the dependencies: like nearlib and other clients.
     #[test]
@@ -515,6 +624,7 @@ mod tests {
                     beneficiary_id: "123".parse().unwrap(),
                 }),
             ],
+            priority_fee: None,
         };
         let signed_tx = SignedTransaction::new(Signature::empty(KeyType::ED25519), transaction);
         let new_signed_tx =
@@ -526,6 +636,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_serialize_transaction_with_priority_fee() {
+        let public_key: PublicKey = "22skMptHjFWNyuEWY22ftn2AbLPSYpmYwGJRGwpNHbTV".parse().unwrap();
+        let mut transaction = Transaction {
+            signer_id: "test.near".parse().unwrap(),
+            public_key,
+            nonce: 1,
+            receiver_id: "123".parse().unwrap(),
+            block_hash: Default::default(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 123 })],
+            priority_fee: None,
+        };
+        let v0_bytes = transaction.try_to_vec().unwrap();
+
+        transaction.priority_fee = Some(1_000);
+        let bytes = transaction.try_to_vec().unwrap();
+        assert_eq!(bytes[0], TRANSACTION_V1_TAG);
+        assert_eq!(bytes.len(), v0_bytes.len() + 1 + 16);
+        assert_eq!(Transaction::try_from_slice(&bytes).unwrap(), transaction);
+
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert_eq!(json["priority_fee"], "1000");
+        transaction.priority_fee = None;
+        assert_eq!(Transaction::try_from_slice(&v0_bytes).unwrap(), transaction);
+        let json = serde_json::to_value(&transaction).unwrap();
+        assert!(json.get("priority_fee").is_none());
+    }
+
     #[test]
     fn test_outcome_to_hashes() {
         let outcome = ExecutionOutcome {
@@ -397,6 +397,15 @@ pub mod trie_key_parsers {
         })
     }
 
+    /// Returns the common prefix of `TrieKey::Account` keys of all accounts
+    /// whose ids start with `account_id_prefix`.
+    pub fn get_raw_prefix_for_accounts(account_id_prefix: &str) -> Vec<u8> {
+        let mut res = Vec::with_capacity(col::ACCOUNT.len() + account_id_prefix.len());
+        res.push(col::ACCOUNT);
+        res.extend(account_id_prefix.as_bytes());
+        res
+    }
+
     pub fn get_raw_prefix_for_access_keys(account_id: &AccountId) -> Vec<u8> {
         let mut res = Vec::with_capacity(col::ACCESS_KEY.len() * 2 + account_id.len());
         res.push(col::ACCESS_KEY);
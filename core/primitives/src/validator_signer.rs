@@ -7,10 +7,15 @@ use crate::block::{Approval, ApprovalInner, BlockHeader};
 use crate::challenge::ChallengeBody;
 use crate::hash::CryptoHash;
 use crate::network::{AnnounceAccount, PeerId};
-use crate::sharding::ChunkHash;
+use crate::sharding::{ChunkHash, ShardChunkHeaderInner, ShardChunkHeaderV3};
 use crate::telemetry::TelemetryInfo;
 use crate::types::{AccountId, BlockHeight, EpochId};
 
+#[cfg(unix)]
+pub mod remote;
+#[cfg(unix)]
+pub use remote::RemoteValidatorSigner;
+
 /// Validator signer that is used to sign blocks and approvals.
 pub trait ValidatorSigner: Sync + Send {
     /// Account id of the given validator.
@@ -33,6 +38,14 @@ pub trait ValidatorSigner: Sync + Send {
     /// Signs given inner of the chunk header.
     fn sign_chunk_hash(&self, chunk_hash: &ChunkHash) -> Signature;
 
+    /// Signs hash of the chunk header with given inner.  Unlike `sign_chunk_hash`, it exposes
+    /// the height and the shard of the chunk to the signer (see `RemoteValidatorSigner`).
+    fn sign_chunk_header_inner(&self, inner: &ShardChunkHeaderInner) -> (ChunkHash, Signature) {
+        let hash = ShardChunkHeaderV3::compute_hash(inner);
+        let signature = self.sign_chunk_hash(&hash);
+        (hash, signature)
+    }
+
     /// Signs approval of given parent hash and reference hash.
     fn sign_approval(&self, inner: &ApprovalInner, target_height: BlockHeight) -> Signature;
 
@@ -159,9 +172,12 @@ impl InMemoryValidatorSigner {
         self.signer.public_key()
     }
 
+    pub fn from_signer(signer: InMemorySigner) -> Self {
+        Self { account_id: signer.account_id.clone(), signer: Arc::new(signer) }
+    }
+
     pub fn from_file(path: &Path) -> std::io::Result<Self> {
-        let signer = InMemorySigner::from_file(path)?;
-        Ok(Self { account_id: signer.account_id.clone(), signer: Arc::new(signer) })
+        InMemorySigner::from_file(path).map(Self::from_signer)
     }
 }
 
@@ -0,0 +1,272 @@
+//! Validator signer which delegates signing to a separate process.
+//!
+//! The signing process (see `tools/remote-signer`) holds the validator key,
+//! so that it doesn't need to be stored on the disk of the node.  The node
+//! connects to it over a Unix socket and sends borsh-serialized
+//! `SignRequest`s, each prefixed with its length as a little-endian `u32`,
+//! to which the signer replies with a `SignResponse` framed the same way.
+//!
+//! The signer is expected to refuse signing two different blocks, two
+//! different approvals or two different chunks of the same shard at the same
+//! height.  Since `ValidatorSigner` methods
+//! are infallible, a refusal (or a failure to reach the signer) is logged and
+//! an empty signature is returned instead.  Messages signed that way are
+//! rejected by other nodes, which is exactly what we want rather than
+//! crashing the node.
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, Signature};
+
+use crate::block::ApprovalInner;
+use crate::block_header::BlockHeader;
+use crate::challenge::ChallengeBody;
+use crate::hash::CryptoHash;
+use crate::network::PeerId;
+use crate::sharding::{ChunkHash, ShardChunkHeaderInner, ShardChunkHeaderV3};
+use crate::telemetry::TelemetryInfo;
+use crate::types::{AccountId, BlockHeight, EpochId};
+use crate::validator_signer::ValidatorSigner;
+
+/// Maximal size of a request or a response.  Challenges contain whole chunks
+/// so they can get pretty large.
+const MAX_FRAME_SIZE: usize = 64 * bytesize::MIB as usize;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SignRequest {
+    /// Asks for the account id and the public key of the validator.
+    ValidatorInfo,
+    /// JSON serialized telemetry info.
+    Telemetry {
+        content: String,
+    },
+    BlockHeaderParts {
+        prev_hash: CryptoHash,
+        inner_lite: Vec<u8>,
+        inner_rest: Vec<u8>,
+    },
+    ChunkHash(ChunkHash),
+    Approval {
+        inner: ApprovalInner,
+        target_height: BlockHeight,
+    },
+    Challenge(Box<ChallengeBody>),
+    AccountAnnounce {
+        account_id: AccountId,
+        peer_id: PeerId,
+        epoch_id: EpochId,
+    },
+    AccountKeyPayload(Vec<u8>),
+    VrfWithProof(Vec<u8>),
+    /// Chunk header to be signed, from which the signer computes the chunk hash.
+    ChunkHeaderInner(Box<ShardChunkHeaderInner>),
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SignResponse {
+    ValidatorInfo {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    Signature(Signature),
+    VrfWithProof {
+        value: near_crypto::vrf::Value,
+        proof: near_crypto::vrf::Proof,
+    },
+    /// The signer refused to sign, e.g. because it would be a double sign.
+    Refused(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RemoteSignerError {
+    #[error("IO: {0}")]
+    IO(#[from] io::Error),
+    #[error("signer refused to sign: {0}")]
+    Refused(String),
+    #[error("unexpected response: {0:?}")]
+    UnexpectedResponse(SignResponse),
+}
+
+/// Writes a length-prefixed borsh-serialized message.
+pub fn write_frame(w: &mut impl Write, msg: &impl BorshSerialize) -> io::Result<()> {
+    let data = msg.try_to_vec()?;
+    if data.len() > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "message too large"));
+    }
+    w.write_all(&(data.len() as u32).to_le_bytes())?;
+    w.write_all(&data)?;
+    w.flush()
+}
+
+/// Reads a length-prefixed borsh-serialized message.
+pub fn read_frame<T: BorshDeserialize>(r: &mut impl Read) -> io::Result<T> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message too large"));
+    }
+    let mut data = vec![0u8; len];
+    r.read_exact(&mut data)?;
+    T::try_from_slice(&data)
+}
+
+fn open(socket_path: &Path, timeout: Duration) -> io::Result<UnixStream> {
+    let stream = UnixStream::connect(socket_path)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(stream)
+}
+
+/// Signer which forwards requests to a signing process listening on a Unix socket.
+pub struct RemoteValidatorSigner {
+    socket_path: PathBuf,
+    timeout: Duration,
+    account_id: AccountId,
+    public_key: PublicKey,
+    /// Connection to the signer, re-established lazily after a failure.
+    conn: Mutex<Option<UnixStream>>,
+}
+
+impl RemoteValidatorSigner {
+    /// Connects to the signer and fetches the identity of the validator.
+    pub fn connect(socket_path: &Path, timeout: Duration) -> Result<Self, RemoteSignerError> {
+        let mut stream = open(socket_path, timeout)?;
+        write_frame(&mut stream, &SignRequest::ValidatorInfo)?;
+        match read_frame(&mut stream)? {
+            SignResponse::ValidatorInfo { account_id, public_key } => Ok(Self {
+                socket_path: socket_path.to_path_buf(),
+                timeout,
+                account_id,
+                public_key,
+                conn: Mutex::new(Some(stream)),
+            }),
+            resp => Err(RemoteSignerError::UnexpectedResponse(resp)),
+        }
+    }
+
+    fn request(&self, req: &SignRequest) -> Result<SignResponse, RemoteSignerError> {
+        let mut conn = self.conn.lock().unwrap();
+        // Retry once with a fresh connection, in case the signer has been restarted.
+        for attempt in 0..2 {
+            if conn.is_none() {
+                *conn = Some(open(&self.socket_path, self.timeout)?);
+            }
+            let stream = conn.as_mut().unwrap();
+            match write_frame(stream, req).and_then(|()| read_frame::<SignResponse>(stream)) {
+                Ok(SignResponse::Refused(reason)) => {
+                    return Err(RemoteSignerError::Refused(reason))
+                }
+                Ok(resp) => return Ok(resp),
+                Err(err) => {
+                    *conn = None;
+                    if attempt > 0 {
+                        return Err(err.into());
+                    }
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    fn sign(&self, req: SignRequest) -> Signature {
+        match self.request(&req) {
+            Ok(SignResponse::Signature(signature)) => return signature,
+            Ok(resp) => {
+                tracing::error!(target: "remote_signer", ?resp, "Unexpected response from signer")
+            }
+            Err(err) => tracing::error!(target: "remote_signer", %err, "Failed to sign"),
+        }
+        Signature::empty(self.public_key.key_type())
+    }
+}
+
+impl ValidatorSigner for RemoteValidatorSigner {
+    fn validator_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign_telemetry(&self, info: &TelemetryInfo) -> serde_json::Value {
+        let mut value = serde_json::to_value(info).expect("Telemetry must serialize to JSON");
+        let content = serde_json::to_string(&value).expect("Telemetry must serialize to JSON");
+        value["signature"] = self.sign(SignRequest::Telemetry { content }).to_string().into();
+        value
+    }
+
+    fn sign_block_header_parts(
+        &self,
+        prev_hash: CryptoHash,
+        inner_lite: &[u8],
+        inner_rest: &[u8],
+    ) -> (CryptoHash, Signature) {
+        let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
+        let signature = self.sign(SignRequest::BlockHeaderParts {
+            prev_hash,
+            inner_lite: inner_lite.to_vec(),
+            inner_rest: inner_rest.to_vec(),
+        });
+        (hash, signature)
+    }
+
+    fn sign_chunk_hash(&self, chunk_hash: &ChunkHash) -> Signature {
+        self.sign(SignRequest::ChunkHash(chunk_hash.clone()))
+    }
+
+    fn sign_chunk_header_inner(&self, inner: &ShardChunkHeaderInner) -> (ChunkHash, Signature) {
+        let hash = ShardChunkHeaderV3::compute_hash(inner);
+        (hash, self.sign(SignRequest::ChunkHeaderInner(Box::new(inner.clone()))))
+    }
+
+    fn sign_approval(&self, inner: &ApprovalInner, target_height: BlockHeight) -> Signature {
+        self.sign(SignRequest::Approval { inner: inner.clone(), target_height })
+    }
+
+    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature) {
+        let hash = CryptoHash::hash_borsh(challenge_body);
+        (hash, self.sign(SignRequest::Challenge(Box::new(challenge_body.clone()))))
+    }
+
+    fn sign_account_announce(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> Signature {
+        self.sign(SignRequest::AccountAnnounce {
+            account_id: account_id.clone(),
+            peer_id: peer_id.clone(),
+            epoch_id: epoch_id.clone(),
+        })
+    }
+
+    fn sign_account_key_payload(&self, proto_bytes: &[u8]) -> Signature {
+        self.sign(SignRequest::AccountKeyPayload(proto_bytes.to_vec()))
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
+        match self.request(&SignRequest::VrfWithProof(data.to_vec())) {
+            Ok(SignResponse::VrfWithProof { value, proof }) => return (value, proof),
+            Ok(resp) => {
+                tracing::error!(target: "remote_signer", ?resp, "Unexpected response from signer")
+            }
+            Err(err) => tracing::error!(target: "remote_signer", %err, "Failed to compute VRF"),
+        }
+        // An all-zeros proof never verifies.
+        (near_crypto::vrf::Value([0; 32]), near_crypto::vrf::Proof([0; 64]))
+    }
+
+    fn write_to_file(&self, _path: &Path) -> std::io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "remote signer doesn't expose its key"))
+    }
+}
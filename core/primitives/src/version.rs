@@ -147,6 +147,18 @@ pub enum ProtocolFeature {
     Ed25519Verify,
     #[cfg(feature = "protocol_feature_reject_blocks_with_outdated_protocol_version")]
     RejectBlocksWithOutdatedProtocolVersions,
+    /// Verify signatures of all transactions in a chunk as a batch, see
+    /// `Signature::verify_batch` for how it differs from verifying them one by one.
+    #[cfg(feature = "protocol_feature_batch_signature_verification")]
+    BatchSignatureVerification,
+    /// Delegate actions allow a relayer to pay for actions signed by another
+    /// account, see `near_primitives::delegate_action`.
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    DelegateAction,
+    /// Transactions may specify a priority fee which is burnt on top of the
+    /// gas costs, see `Transaction::priority_fee`.
+    #[cfg(feature = "protocol_feature_priority_fee")]
+    PriorityFee,
 }
 
 /// Both, outgoing and incoming tcp connections to peers, will be rejected if `peer's`
@@ -161,7 +173,7 @@ const STABLE_PROTOCOL_VERSION: ProtocolVersion = 57;
 /// Largest protocol version supported by the current binary.
 pub const PROTOCOL_VERSION: ProtocolVersion = if cfg!(feature = "nightly_protocol") {
     // On nightly, pick big enough version to support all features.
-    132
+    135
 } else {
     // Enable all stable features.
     STABLE_PROTOCOL_VERSION
@@ -234,6 +246,12 @@ impl ProtocolFeature {
             ProtocolFeature::Ed25519Verify => 131,
             #[cfg(feature = "protocol_feature_reject_blocks_with_outdated_protocol_version")]
             ProtocolFeature::RejectBlocksWithOutdatedProtocolVersions => 132,
+            #[cfg(feature = "protocol_feature_batch_signature_verification")]
+            ProtocolFeature::BatchSignatureVerification => 133,
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            ProtocolFeature::DelegateAction => 134,
+            #[cfg(feature = "protocol_feature_priority_fee")]
+            ProtocolFeature::PriorityFee => 135,
         }
     }
 }
@@ -5,6 +5,7 @@
 //! from the source structure in the relevant `From<SourceStruct>` impl.
 use std::collections::HashMap;
 use std::fmt;
+use std::num::NonZeroU64;
 use std::ops::Range;
 use std::sync::Arc;
 
@@ -26,6 +27,7 @@ use crate::block_header::{
 };
 use crate::challenge::{Challenge, ChallengesResult};
 use crate::contract::ContractCode;
+use crate::delegate_action::{DelegateAction, SignedDelegateAction};
 use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{combine_hash, MerklePath};
@@ -33,7 +35,7 @@ use crate::network::PeerId;
 use crate::profile::Cost;
 use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum};
 use crate::runtime::config::RuntimeConfig;
-use crate::serialize::{base64_format, dec_format, option_base64_format};
+use crate::serialize::{base64_format, dec_format, option_base64_format, vec_base64_format};
 use crate::sharding::{
     ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderInnerV2,
     ShardChunkHeaderV3,
@@ -218,12 +220,81 @@ pub struct ViewStateResult {
     // set in the request) was deprecated in 1.30.  Add
     // `#[serde(skip(Vec::if_empty))` at 1.33 or something.
     pub proof: Vec<Arc<[u8]>>,
+    /// Set if the query was paginated and there are more values to read.  Pass
+    /// it as `start_after` to get the next page.
+    #[serde(
+        default,
+        rename = "next_start_after_base64",
+        with = "option_base64_format",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_start_after: Option<Vec<u8>>,
+}
+
+/// Balance of an account listed by [`QueryRequest::ViewAccountsWithPrefix`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct AccountBalanceView {
+    pub account_id: AccountId,
+    #[serde(with = "dec_format")]
+    pub amount: Balance,
+    #[serde(with = "dec_format")]
+    pub locked: Balance,
+}
+
+/// A page of accounts listed by [`QueryRequest::ViewAccountsWithPrefix`],
+/// ordered by account id.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct AccountsWithPrefixView {
+    pub accounts: Vec<AccountBalanceView>,
+    /// Set if there may be more accounts with the prefix.  Pass it as
+    /// `start_after` to get the next page.
+    pub next_start_after: Option<AccountId>,
+}
+
+/// State of an account at a block in which the account was changed.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct AccountHistoryEntryView {
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    /// `None` if the account has been deleted in this block.
+    pub account: Option<AccountView>,
+    /// Contract state under the requested key prefix, if it was requested and
+    /// the account exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ViewStateResult>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
 pub struct CallResult {
     pub result: Vec<u8>,
     pub logs: Vec<String>,
+    /// Host functions called by the contract, if the call was traced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<CallTraceView>,
+}
+
+/// Host function calls made by a contract during a traced function call.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct CallTraceView {
+    pub calls: Vec<HostFunctionCallView>,
+    /// Whether some calls or storage keys were left out because the trace
+    /// reached its size limit.
+    pub truncated: bool,
+}
+
+/// Host function call made by a contract during a traced function call.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct HostFunctionCallView {
+    pub name: String,
+    /// Arguments of the call, e.g. `key_len: 3, key_ptr: 1024, register_id: 0`.
+    pub args: String,
+    pub gas_burnt: Gas,
+    /// Storage keys read by the call.
+    #[serde(rename = "storage_reads_base64", with = "vec_base64_format")]
+    pub storage_reads: Vec<StoreKey>,
+    /// Storage keys written or removed by the call.
+    #[serde(rename = "storage_writes_base64", with = "vec_base64_format")]
+    pub storage_writes: Vec<StoreKey>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -269,6 +340,7 @@ pub enum QueryResponseKind {
     CallResult(CallResult),
     AccessKey(AccessKeyView),
     AccessKeyList(AccessKeyList),
+    ViewAccountsWithPrefix(AccountsWithPrefixView),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -286,6 +358,20 @@ pub enum QueryRequest {
         prefix: StoreKey,
         #[serde(default, skip_serializing_if = "is_false")]
         include_proof: bool,
+        /// If set, only keys greater than this one are returned.  Setting
+        /// either this or `limit` makes the query paginated: instead of
+        /// failing for accounts with large state, a single page of values is
+        /// returned along with a continuation cursor.
+        #[serde(
+            default,
+            rename = "start_after_base64",
+            with = "option_base64_format",
+            skip_serializing_if = "Option::is_none"
+        )]
+        start_after: Option<Vec<u8>>,
+        /// Maximum number of values returned in a page.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<NonZeroU64>,
     },
     ViewAccessKey {
         account_id: AccountId,
@@ -299,6 +385,17 @@ pub enum QueryRequest {
         method_name: String,
         #[serde(rename = "args_base64", with = "base64_format")]
         args: FunctionArgs,
+        /// If set, host functions called by the contract are returned along
+        /// with the result.
+        #[serde(default, skip_serializing_if = "is_false")]
+        trace: bool,
+    },
+    /// Lists accounts whose ids start with the prefix together with their
+    /// balances.  The result is paginated, see [`AccountsWithPrefixView`].
+    ViewAccountsWithPrefix {
+        account_id_prefix: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start_after: Option<AccountId>,
     },
 }
 
@@ -995,7 +1092,7 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockView {
     pub author: AccountId,
     pub header: BlockHeaderView,
@@ -1073,6 +1170,10 @@ pub enum ActionView {
     DeleteAccount {
         beneficiary_id: AccountId,
     },
+    Delegate {
+        delegate_action: DelegateAction,
+        signature: Signature,
+    },
 }
 
 impl From<Action> for ActionView {
@@ -1101,6 +1202,10 @@ impl From<Action> for ActionView {
             Action::DeleteAccount(action) => {
                 ActionView::DeleteAccount { beneficiary_id: action.beneficiary_id }
             }
+            Action::Delegate(action) => ActionView::Delegate {
+                delegate_action: action.delegate_action,
+                signature: action.signature,
+            },
         }
     }
 }
@@ -1130,6 +1235,9 @@ impl TryFrom<ActionView> for Action {
             ActionView::DeleteAccount { beneficiary_id } => {
                 Action::DeleteAccount(DeleteAccountAction { beneficiary_id })
             }
+            ActionView::Delegate { delegate_action, signature } => {
+                Action::Delegate(Box::new(SignedDelegateAction { delegate_action, signature }))
+            }
         })
     }
 }
@@ -1143,6 +1251,8 @@ pub struct SignedTransactionView {
     pub actions: Vec<ActionView>,
     pub signature: Signature,
     pub hash: CryptoHash,
+    #[serde(default, with = "dec_format", skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<Balance>,
 }
 
 impl From<SignedTransaction> for SignedTransactionView {
@@ -1161,6 +1271,7 @@ impl From<SignedTransaction> for SignedTransactionView {
                 .collect(),
             signature: signed_tx.signature,
             hash,
+            priority_fee: signed_tx.transaction.priority_fee,
         }
     }
 }
@@ -1485,6 +1596,15 @@ pub struct FinalExecutionOutcomeWithReceiptView {
     pub receipts: Vec<ReceiptView>,
 }
 
+/// Final execution outcome of a simulated transaction and all of its receipts,
+/// together with the changes it would make to the state.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimulatedTransactionView {
+    #[serde(flatten)]
+    pub final_outcome: FinalExecutionOutcomeWithReceiptView,
+    pub state_changes: StateChangesView,
+}
+
 pub mod validator_stake_view {
     use crate::types::validator_stake::ValidatorStake;
     use borsh::{BorshDeserialize, BorshSerialize};
@@ -1774,7 +1894,7 @@ pub struct GasPriceView {
 ///
 /// [serializable view]: ./index.html
 /// [`StateChangesRequest`]: ../types/struct.StateChangesRequest.html
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequestView {
     AccountChanges {
@@ -2080,6 +2200,9 @@ pub struct ActionCreationConfigView {
 
     /// Base cost of deleting an account.
     pub delete_account_cost: Fee,
+
+    /// Base cost of a delegate action.
+    pub delegate_cost: Fee,
 }
 
 /// Describes the cost of creating an access key.
@@ -2145,6 +2268,7 @@ impl From<RuntimeConfig> for RuntimeConfigView {
                     },
                     delete_key_cost: config.fees.fee(ActionCosts::delete_key).clone(),
                     delete_account_cost: config.fees.fee(ActionCosts::delete_account).clone(),
+                    delegate_cost: config.fees.fee(ActionCosts::delegate).clone(),
                 },
                 storage_usage_config: StorageUsageConfigView {
                     num_bytes_account: config.fees.storage_usage_config.num_bytes_account,
@@ -2202,6 +2326,7 @@ impl From<RuntimeConfigView> for RuntimeConfig {
                     ActionCosts::new_action_receipt => config.transaction_costs.action_receipt_creation_config.clone(),
                     ActionCosts::new_data_receipt_base => config.transaction_costs.data_receipt_creation_config.base_cost.clone(),
                     ActionCosts::new_data_receipt_byte => config.transaction_costs.data_receipt_creation_config.cost_per_byte.clone(),
+                    ActionCosts::delegate => config.transaction_costs.action_creation_config.delegate_cost.clone(),
 
                 },
             },
@@ -128,6 +128,145 @@ pub fn update_cold_head<D: Database>(
     return Ok(());
 }
 
+/// Prefix of keys in [`DBCol::BlockMisc`] of cold storage under which progress
+/// of [`copy_all_data_to_cold`] is saved.  The full key is the prefix followed
+/// by the name of the copied column.
+pub const COLD_MIGRATION_CHECKPOINT_PREFIX: &[u8] = b"COLD_MIGRATION_CHECKPOINT:";
+
+/// Progress of copying a single column by [`copy_all_data_to_cold`].
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub enum ColdMigrationCheckpoint {
+    /// All keys up to and including given one have been copied.
+    InProgress(StoreKey),
+    /// The whole column has been copied.
+    Done,
+}
+
+fn cold_migration_checkpoint_key(col: DBCol) -> StoreKey {
+    join_two_keys(COLD_MIGRATION_CHECKPOINT_PREFIX, <&str>::from(col).as_bytes())
+}
+
+/// Returns progress of copying given column by [`copy_all_data_to_cold`] or
+/// `None` if copying of the column hasn’t started yet.
+pub fn get_cold_migration_checkpoint<D: Database>(
+    cold_db: &ColdDB<D>,
+    col: DBCol,
+) -> io::Result<Option<ColdMigrationCheckpoint>> {
+    cold_db
+        .get_raw_bytes(DBCol::BlockMisc, &cold_migration_checkpoint_key(col))?
+        .map(|value| ColdMigrationCheckpoint::try_from_slice(&value))
+        .transpose()
+}
+
+/// Copies all the data of cold columns from hot store to cold storage.
+///
+/// Unlike [`update_cold_db`] which copies data block by block, this copies
+/// whole columns at once and is meant for the initial population of cold
+/// storage of an existing archival node.  Data is written in batches of
+/// roughly `batch_size` bytes.  Together with each batch a checkpoint is saved
+/// in cold storage so if copying is interrupted, calling the function again
+/// resumes from where it stopped.  Columns which have already been copied are
+/// skipped.
+///
+/// After each batch is written, its last entry is read back from cold storage
+/// and compared with the value from hot store.
+///
+/// This doesn’t update HEAD of cold storage.  Once this returns, caller should
+/// call [`update_cold_head`] with final head of hot store.
+pub fn copy_all_data_to_cold<D: Database>(
+    cold_db: &ColdDB<D>,
+    hot_store: &Store,
+    batch_size: usize,
+) -> io::Result<()> {
+    for col in DBCol::iter() {
+        if col.is_cold() {
+            copy_column_to_cold(cold_db, hot_store, col, batch_size)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_column_to_cold<D: Database>(
+    cold_db: &ColdDB<D>,
+    hot_store: &Store,
+    col: DBCol,
+    batch_size: usize,
+) -> io::Result<()> {
+    let _span = tracing::info_span!(target: "store", "copy column to cold db", %col).entered();
+
+    let lower_bound = match get_cold_migration_checkpoint(cold_db, col)? {
+        Some(ColdMigrationCheckpoint::Done) => {
+            tracing::info!(target: "store", col = %col, "Column has already been copied");
+            return Ok(());
+        }
+        // The smallest key greater than the last copied one.
+        Some(ColdMigrationCheckpoint::InProgress(last_key)) => Some(join_two_keys(&last_key, &[0])),
+        None => None,
+    };
+
+    let mut transaction = DBTransaction::new();
+    let mut transaction_size = 0;
+    let mut last_entry = None;
+    let mut copied_keys = 0u64;
+    let mut copied_bytes = 0u64;
+    for item in hot_store.iter_range(col, lower_bound.as_deref(), None) {
+        let (key, value) = item?;
+        transaction_size += key.len() + value.len();
+        copied_keys += 1;
+        copied_bytes += (key.len() + value.len()) as u64;
+        transaction.set(col, key.to_vec(), value.to_vec());
+        last_entry = Some((key, value));
+        if transaction_size >= batch_size {
+            let (key, value) = last_entry.take().unwrap();
+            let checkpoint = ColdMigrationCheckpoint::InProgress(key.to_vec());
+            let entry = Some((&key[..], &value[..]));
+            write_cold_migration_batch(cold_db, col, transaction, checkpoint, entry)?;
+            tracing::info!(target: "store", col = %col, copied_keys, copied_bytes, last_key = ?key,
+                           "Copied batch to cold db");
+            transaction = DBTransaction::new();
+            transaction_size = 0;
+        }
+    }
+    let last_entry = last_entry.as_ref().map(|(key, value)| (&key[..], &value[..]));
+    write_cold_migration_batch(
+        cold_db,
+        col,
+        transaction,
+        ColdMigrationCheckpoint::Done,
+        last_entry,
+    )?;
+    tracing::info!(target: "store", col = %col, copied_keys, copied_bytes,
+                   "Finished copying column to cold db");
+    Ok(())
+}
+
+/// Writes a batch of copied data together with a checkpoint to cold storage and
+/// verifies that `last_entry` has been written correctly.
+fn write_cold_migration_batch<D: Database>(
+    cold_db: &ColdDB<D>,
+    col: DBCol,
+    mut transaction: DBTransaction,
+    checkpoint: ColdMigrationCheckpoint,
+    last_entry: Option<(&[u8], &[u8])>,
+) -> io::Result<()> {
+    transaction.set(DBCol::BlockMisc, cold_migration_checkpoint_key(col), checkpoint.try_to_vec()?);
+    cold_db.write(transaction)?;
+    if let Some((key, value)) = last_entry {
+        let copied = if col.is_rc() {
+            cold_db.get_with_rc_stripped(col, key)?
+        } else {
+            cold_db.get_raw_bytes(col, key)?
+        };
+        if copied.as_deref() != Some(value) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{col}: value of {key:?} in cold db doesn’t match hot db"),
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub fn test_cold_genesis_update<D: Database>(
     cold_db: &ColdDB<D>,
     hot_store: &Store,
@@ -390,9 +529,15 @@ impl StoreWithCache<'_> {
 
 #[cfg(test)]
 mod test {
-    use super::{combine_keys, StoreKey};
+    use super::{
+        cold_migration_checkpoint_key, combine_keys, copy_all_data_to_cold,
+        get_cold_migration_checkpoint, ColdMigrationCheckpoint, StoreKey,
+    };
     use crate::columns::DBKeyType;
+    use crate::{DBCol, DBTransaction, Database, Temperature};
+    use borsh::BorshSerialize;
     use std::collections::{HashMap, HashSet};
+    use strum::IntoEnumIterator;
 
     #[test]
     fn test_combine_keys() {
@@ -454,4 +599,47 @@ mod test {
             HashSet::<StoreKey>::from_iter(vec![vec![]])
         );
     }
+
+    /// Tests that copying of a column resumes after the key saved in the
+    /// checkpoint and that all columns end up marked as copied.
+    #[test]
+    fn test_copy_all_data_to_cold_resumes() {
+        let storage = crate::test_utils::create_test_node_storage_with_cold();
+        let hot_store = storage.get_store(Temperature::Hot);
+        let cold_db = &**storage.cold_db().unwrap();
+
+        let keys = [[1u8; 32], [2; 32], [3; 32]];
+        let mut store_update = hot_store.store_update();
+        for key in &keys {
+            store_update.insert(DBCol::Block, key, key);
+            store_update.increment_refcount(DBCol::State, &[&[0u8; 8][..], &key[..]].concat(), key);
+        }
+        store_update.commit().unwrap();
+
+        // Pretend the first block has already been copied.
+        let mut transaction = DBTransaction::new();
+        transaction.set(
+            DBCol::BlockMisc,
+            cold_migration_checkpoint_key(DBCol::Block),
+            ColdMigrationCheckpoint::InProgress(keys[0].to_vec()).try_to_vec().unwrap(),
+        );
+        cold_db.write(transaction).unwrap();
+
+        copy_all_data_to_cold(cold_db, &hot_store, 1).unwrap();
+
+        assert_eq!(None, cold_db.get_raw_bytes(DBCol::Block, &keys[0]).unwrap());
+        for key in &keys[1..] {
+            let value = cold_db.get_raw_bytes(DBCol::Block, key).unwrap();
+            assert_eq!(Some(&key[..]), value.as_deref());
+        }
+        for key in &keys {
+            let value =
+                cold_db.get_with_rc_stripped(DBCol::State, &[&[0u8; 8][..], &key[..]].concat());
+            assert_eq!(Some(&key[..]), value.unwrap().as_deref());
+        }
+        for col in DBCol::iter().filter(DBCol::is_cold) {
+            let checkpoint = get_cold_migration_checkpoint(cold_db, col).unwrap();
+            assert_eq!(Some(ColdMigrationCheckpoint::Done), checkpoint, "{col}");
+        }
+    }
 }
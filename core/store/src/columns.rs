@@ -16,7 +16,16 @@ use std::fmt;
 /// deprecation.  Make sure to add `#[strum(serialize = "OriginalName")]`
 /// attribute in front of the variant when you deprecate a column.
 #[derive(
-    PartialEq, Copy, Clone, Debug, Hash, Eq, enum_map::Enum, strum::EnumIter, strum::IntoStaticStr,
+    PartialEq,
+    Copy,
+    Clone,
+    Debug,
+    Hash,
+    Eq,
+    enum_map::Enum,
+    strum::EnumIter,
+    strum::EnumString,
+    strum::IntoStaticStr,
 )]
 pub enum DBCol {
     /// Column to indicate which version of database this is.
@@ -242,6 +251,17 @@ pub enum DBCol {
     /// *Rows*: OutcomeId (CryptoHash) || BlockHash (CryptoHash)
     /// *Column type*: ExecutionOutcomeWithProof
     TransactionResultForBlock,
+    /// Transactions which were in the transaction pool when it was last saved,
+    /// so that they can be re-added to the pool after a restart.
+    /// - *Rows*: ShardId
+    /// - *Column type*: Vec<SignedTransaction>
+    TransactionPool,
+    /// Keys of rows whose garbage collection is delayed because their column
+    /// is kept for longer than other block data, see
+    /// `GCConfig::gc_num_epochs_to_keep_per_column`.
+    /// - *Rows*: head height at which the rows are garbage collected (u64, big-endian) + column name
+    /// - *Column type*: Vec<Vec<u8>>
+    GCDelayedKeys,
     /// Flat state contents. Used to get `ValueRef` by trie key faster than doing a trie lookup.
     /// - *Rows*: trie key (Vec<u8>)
     /// - *Column type*: ValueRef
@@ -456,6 +476,8 @@ impl DBCol {
             DBCol::HeaderHashesByHeight => &[DBKeyType::BlockHeight],
             DBCol::StateChangesForSplitStates => &[DBKeyType::BlockHash, DBKeyType::ShardId],
             DBCol::TransactionResultForBlock => &[DBKeyType::OutcomeId, DBKeyType::BlockHash],
+            DBCol::TransactionPool => &[DBKeyType::ShardId],
+            DBCol::GCDelayedKeys => &[DBKeyType::BlockHeight, DBKeyType::ColumnId],
             #[cfg(feature = "protocol_feature_flat_state")]
             DBCol::FlatState => &[DBKeyType::TrieKey],
             #[cfg(feature = "protocol_feature_flat_state")]
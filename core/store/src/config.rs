@@ -11,6 +11,13 @@ pub struct StoreConfig {
     /// database.
     pub path: Option<std::path::PathBuf>,
 
+    /// Storage engine holding the database.  Defaults to RocksDB.
+    ///
+    /// The engine cannot be changed for an existing database.  Cold storage
+    /// supports RocksDB only.
+    #[serde(skip_serializing_if = "StoreBackend::is_default")]
+    pub backend: StoreBackend,
+
     /// Collect internal storage layer statistics.
     /// Minor performance impact is expected.
     pub enable_statistics: bool,
@@ -89,6 +96,27 @@ pub struct StoreConfig {
     pub background_migration_threads: usize,
 }
 
+/// Storage engine used by the database.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    /// [RocksDB](https://rocksdb.org/), an LSM-tree based store.
+    #[default]
+    RocksDB,
+    /// [redb](https://docs.rs/redb), an embedded B-tree based store.  It
+    /// doesn’t suffer from compaction stalls but is less battle-tested and
+    /// doesn’t allow opening the database in read-only mode while the node is
+    /// running.
+    Redb,
+}
+
+impl StoreBackend {
+    /// Checks whether the object equals its default value.
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
 pub enum MigrationSnapshot {
@@ -164,6 +192,7 @@ impl Default for StoreConfig {
     fn default() -> Self {
         Self {
             path: None,
+            backend: StoreBackend::RocksDB,
             enable_statistics: false,
             enable_statistics_export: true,
 
@@ -4,15 +4,21 @@ use crate::DBCol;
 
 #[cfg(feature = "cold_store")]
 mod colddb;
+mod redb;
 pub mod refcount;
 pub(crate) mod rocksdb;
 mod slice;
+#[cfg(feature = "cold_store")]
+mod splitdb;
 mod testdb;
 
 #[cfg(feature = "cold_store")]
 pub use self::colddb::ColdDB;
+pub use self::redb::RedbDB;
 pub use self::rocksdb::RocksDB;
 pub use self::slice::DBSlice;
+#[cfg(feature = "cold_store")]
+pub use self::splitdb::SplitDB;
 pub use self::testdb::TestDB;
 
 pub const HEAD_KEY: &[u8; 4] = b"HEAD";
@@ -117,6 +123,18 @@ pub trait Database: Sync + Send {
     /// are returned in lexicographical order sorted by the key.
     fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a>;
 
+    /// Iterate over items in given column whose keys are in given range.
+    ///
+    /// Lower bound is inclusive and upper bound is exclusive; a missing bound
+    /// means the range is unbounded on that side.  Reference counts are
+    /// handled the same way as in [`Self::iter`].
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&'a [u8]>,
+        upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a>;
+
     /// Iterate over items in given column bypassing reference count decoding if
     /// any.
     ///
@@ -74,6 +74,15 @@ impl<D> ColdDB<D> {
             false
         }
     }
+
+    /// Checks whether prefix iteration is supported for the column.
+    ///
+    /// Those are the cold columns which are prefix-iterated when reading the
+    /// data (state changes and transaction outcomes).  Their keys and values
+    /// are stored in cold database the same way as in hot one.
+    pub(crate) fn supports_iter_prefix(col: DBCol) -> bool {
+        matches!(col, DBCol::StateChanges | DBCol::TransactionResultForBlock)
+    }
 }
 
 impl<D: Database> ColdDB<D> {
@@ -150,23 +159,31 @@ impl<D: Database> super::Database for ColdDB<D> {
 
     /// Iterates over values in a given column whose key has given prefix.
     ///
-    /// This is only implemented for StateChanges column and will panic if used
-    /// for any other column.
+    /// This is only implemented for columns for which
+    /// [`ColdDB::supports_iter_prefix`] returns true and will panic if used for
+    /// any other column.
     fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
-        // We only ever call iter_prefix on DBCol::StateChanges so we don’t need
-        // to worry about implementing it for any other column.
-        assert_eq!(
-            DBCol::StateChanges,
-            col,
-            "iter_prefix on cold storage is supported for StateChanges only; \
-             tried to iterate over {col}"
+        assert!(
+            Self::supports_iter_prefix(col),
+            "iter_prefix on cold storage is supported for StateChanges and \
+             TransactionResultForBlock only; tried to iterate over {col}"
         );
-        // StateChanges is neither reference counted, nor do we do any
-        // adjustments to that column’s keys so we can pass the iter_prefix
-        // request directly to the underlying database.
+        // Those columns are neither reference counted, nor do we do any
+        // adjustments to their keys so we can pass the iter_prefix request
+        // directly to the underlying database.
         self.cold.iter_prefix(col, key_prefix)
     }
 
+    /// Unimplemented; always panics.
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        _lower_bound: Option<&'a [u8]>,
+        _upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a> {
+        panic!("iter_range on cold storage is not supported; tried to iterate over {col}")
+    }
+
     /// Unimplemented; always panics.
     fn iter_raw_bytes<'a>(&'a self, _column: DBCol) -> DBIterator<'a> {
         // We’re actually never call iter_raw_bytes on cold store.
@@ -0,0 +1,450 @@
+use std::io;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+use ::redb::ReadableTable;
+use strum::IntoEnumIterator;
+
+use crate::config::Mode;
+use crate::db::rocksdb::snapshot::{Snapshot, SnapshotError};
+use crate::db::{refcount, DBIterator, DBOp, DBSlice, DBTransaction, Database};
+use crate::{metadata, DBCol, StoreConfig, StoreStatistics};
+
+/// Name of the database file inside of the database directory.
+const DB_FILE_NAME: &str = "data.redb";
+
+/// Number of entries read in a single read transaction when iterating over
+/// a column.
+const ITER_BATCH_SIZE: usize = 1024;
+
+type Table<'a> = ::redb::TableDefinition<'a, &'static [u8], &'static [u8]>;
+
+/// Database backed by [redb](https://docs.rs/redb), an embedded copy-on-write
+/// B-tree store.
+///
+/// Every column is stored in a separate table named after the [`DBCol`]
+/// variant.  Unlike RocksDB, redb has no merge operators so updates of
+/// reference counted columns are merged when the transaction is written.
+/// Since there’s also no compaction, values whose reference count drops to
+/// zero are removed immediately.
+///
+/// Only hot (i.e. RPC or archival) storage is supported.
+pub struct RedbDB {
+    db: ::redb::Database,
+    mode: Mode,
+}
+
+impl RedbDB {
+    /// Opens the database.
+    ///
+    /// `path` specifies the database directory.  It’s assumed that it has
+    /// been resolved based on configuration in `store_config` and thus path
+    /// configuration in `store_config` is ignored.
+    ///
+    /// Note that redb doesn’t support opening a database which is already
+    /// opened by another process, even in read-only mode.  In read-only mode,
+    /// writing to the database fails.
+    pub fn open(path: &Path, store_config: &StoreConfig, mode: Mode) -> io::Result<Self> {
+        let file = Self::file_path(path);
+        let exists = file.is_file();
+        let mut builder = ::redb::Builder::new();
+        builder.set_cache_size(store_config.col_state_cache_size.as_u64() as usize);
+        let db = if exists {
+            if mode.must_create() {
+                return Err(other_error(format!("{}: database already exists", file.display())));
+            }
+            builder.open(&file)
+        } else {
+            if !mode.can_create() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{}: database does not exist", file.display()),
+                ));
+            }
+            std::fs::create_dir_all(path)?;
+            builder.create(&file)
+        }
+        .map_err(into_other)?;
+        let db = Self { db, mode };
+        if mode.read_write() {
+            db.create_tables()?;
+        }
+        Ok(db)
+    }
+
+    /// Returns metadata of the database or `None` if the db doesn’t exist.
+    pub(crate) fn get_metadata(
+        path: &Path,
+        config: &StoreConfig,
+    ) -> io::Result<Option<metadata::DbMetadata>> {
+        if !Self::file_path(path).is_file() {
+            return Ok(None);
+        }
+        let db = Self::open(path, config, Mode::ReadOnly)?;
+        Some(metadata::DbMetadata::read(&db)).transpose()
+    }
+
+    /// Creates a migration snapshot of the database by copying the database
+    /// file.
+    pub(crate) fn snapshot(path: &Path, config: &StoreConfig) -> Result<Snapshot, SnapshotError> {
+        Snapshot::new_with(path, config, |snapshot_path| {
+            std::fs::create_dir_all(snapshot_path)?;
+            std::fs::copy(Self::file_path(path), Self::file_path(snapshot_path)).map(drop)
+        })
+    }
+
+    fn file_path(path: &Path) -> PathBuf {
+        path.join(DB_FILE_NAME)
+    }
+
+    /// Makes sure tables for all the columns exist.
+    fn create_tables(&self) -> io::Result<()> {
+        let txn = self.db.begin_write().map_err(into_other)?;
+        for col in DBCol::iter() {
+            txn.open_table(table_def(col)).map_err(into_other)?;
+        }
+        txn.commit().map_err(into_other)
+    }
+
+    /// Reads up to [`ITER_BATCH_SIZE`] entries of a column starting at
+    /// `lower` bound and stopping at first key which doesn’t start with
+    /// `prefix` or isn’t less than `upper` bound.
+    ///
+    /// Returns the entries and whether the end of the iteration has been
+    /// reached.
+    fn read_batch(
+        &self,
+        col: DBCol,
+        lower: Bound<&[u8]>,
+        prefix: &[u8],
+        upper: Option<&[u8]>,
+    ) -> io::Result<(Vec<(Box<[u8]>, Box<[u8]>)>, bool)> {
+        let txn = self.db.begin_read().map_err(into_other)?;
+        let table = match open_read_table(&txn, col)? {
+            Some(table) => table,
+            None => return Ok((Vec::new(), true)),
+        };
+        let mut batch = Vec::new();
+        let range = (lower, Bound::Unbounded);
+        for item in table.range::<&[u8]>(range).map_err(into_other)? {
+            let (key, value) = item.map_err(into_other)?;
+            let key = key.value();
+            if !key.starts_with(prefix) || upper.map_or(false, |upper| key >= upper) {
+                return Ok((batch, true));
+            }
+            batch.push((key.into(), value.value().into()));
+            if batch.len() == ITER_BATCH_SIZE {
+                return Ok((batch, false));
+            }
+        }
+        Ok((batch, true))
+    }
+}
+
+/// Iterator over entries of a column.
+///
+/// redb iterators borrow the read transaction so instead of keeping one open
+/// for the whole iteration, entries are read in batches each using a new read
+/// transaction.  This means that, unlike with RocksDB, the iteration does not
+/// observe a consistent snapshot of the database if there are concurrent
+/// writes.
+struct RedbIterator<'a> {
+    db: &'a RedbDB,
+    col: DBCol,
+    /// Inclusive lower bound of the iteration.
+    start: &'a [u8],
+    prefix: &'a [u8],
+    /// Exclusive upper bound of the iteration, if any.
+    upper: Option<&'a [u8]>,
+    /// Last key of the most recently read batch or `None` if iteration hasn’t
+    /// started yet.
+    last_key: Option<Box<[u8]>>,
+    batch: std::vec::IntoIter<(Box<[u8]>, Box<[u8]>)>,
+    done: bool,
+}
+
+impl<'a> RedbIterator<'a> {
+    fn new(db: &'a RedbDB, col: DBCol, prefix: &'a [u8]) -> Self {
+        Self::with_bounds(db, col, prefix, prefix, None)
+    }
+
+    fn range(db: &'a RedbDB, col: DBCol, lower: &'a [u8], upper: Option<&'a [u8]>) -> Self {
+        Self::with_bounds(db, col, lower, &[], upper)
+    }
+
+    fn with_bounds(
+        db: &'a RedbDB,
+        col: DBCol,
+        start: &'a [u8],
+        prefix: &'a [u8],
+        upper: Option<&'a [u8]>,
+    ) -> Self {
+        let batch = Vec::new().into_iter();
+        Self { db, col, start, prefix, upper, last_key: None, batch, done: false }
+    }
+}
+
+impl<'a> Iterator for RedbIterator<'a> {
+    type Item = io::Result<(Box<[u8]>, Box<[u8]>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch.len() == 0 && !self.done {
+            let lower = match &self.last_key {
+                Some(key) => Bound::Excluded(&key[..]),
+                None => Bound::Included(self.start),
+            };
+            match self.db.read_batch(self.col, lower, self.prefix, self.upper) {
+                Ok((batch, done)) => {
+                    self.done = done;
+                    if let Some((key, _)) = batch.last() {
+                        self.last_key = Some(key.clone());
+                    }
+                    self.batch = batch.into_iter();
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.batch.next().map(Ok)
+    }
+}
+
+impl Database for RedbDB {
+    fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> io::Result<Option<DBSlice<'_>>> {
+        let txn = self.db.begin_read().map_err(into_other)?;
+        let table = match open_read_table(&txn, col)? {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+        let value = table.get(key).map_err(into_other)?;
+        Ok(value.map(|value| DBSlice::from_vec(value.value().to_vec())))
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        refcount::iter_with_rc_logic(col, RedbIterator::new(self, col, &[]))
+    }
+
+    fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
+        refcount::iter_with_rc_logic(col, RedbIterator::new(self, col, key_prefix))
+    }
+
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&'a [u8]>,
+        upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a> {
+        let iter = RedbIterator::range(self, col, lower_bound.unwrap_or(&[]), upper_bound);
+        refcount::iter_with_rc_logic(col, iter)
+    }
+
+    fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        Box::new(RedbIterator::new(self, col, &[]))
+    }
+
+    fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+        if self.mode.read_only() {
+            return Err(other_error("database opened in read-only mode".to_string()));
+        }
+        let txn = self.db.begin_write().map_err(into_other)?;
+        for op in transaction.ops {
+            match op {
+                DBOp::Set { col, key, value } => {
+                    let mut table = txn.open_table(table_def(col)).map_err(into_other)?;
+                    table.insert(&key[..], &value[..]).map_err(into_other)?;
+                }
+                DBOp::Insert { col, key, value } => {
+                    let mut table = txn.open_table(table_def(col)).map_err(into_other)?;
+                    if cfg!(debug_assertions) {
+                        let old_value = table
+                            .get(&key[..])
+                            .map_err(into_other)?
+                            .map(|old_value| old_value.value().to_vec());
+                        if let Some(old_value) = old_value {
+                            super::assert_no_overwrite(col, &key, &value, &old_value)
+                        }
+                    }
+                    table.insert(&key[..], &value[..]).map_err(into_other)?;
+                }
+                DBOp::UpdateRefcount { col, key, value } => {
+                    let mut table = txn.open_table(table_def(col)).map_err(into_other)?;
+                    let existing = table
+                        .get(&key[..])
+                        .map_err(into_other)?
+                        .map(|existing| existing.value().to_vec());
+                    let merged = refcount::refcount_merge(existing.as_deref(), [&value[..]]);
+                    if merged.is_empty() {
+                        table.remove(&key[..]).map_err(into_other)?;
+                    } else {
+                        debug_assert!(
+                            refcount::decode_value_with_rc(&merged).1 > 0,
+                            "Inserting value with non-positive refcount"
+                        );
+                        table.insert(&key[..], &merged[..]).map_err(into_other)?;
+                    }
+                }
+                DBOp::Delete { col, key } => {
+                    let mut table = txn.open_table(table_def(col)).map_err(into_other)?;
+                    table.remove(&key[..]).map_err(into_other)?;
+                }
+                DBOp::DeleteAll { col } => {
+                    txn.delete_table(table_def(col)).map_err(into_other)?;
+                    txn.open_table(table_def(col)).map_err(into_other)?;
+                }
+            }
+        }
+        txn.commit().map_err(into_other)
+    }
+
+    /// Does nothing since redb makes transactions durable when they’re
+    /// committed.
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Does nothing since redb doesn’t need compaction.
+    fn compact(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get_store_statistics(&self) -> Option<StoreStatistics> {
+        None
+    }
+}
+
+/// Returns definition of the table holding given column.
+fn table_def(col: DBCol) -> Table<'static> {
+    ::redb::TableDefinition::new(<&str>::from(col))
+}
+
+/// Opens table holding given column for reading.
+///
+/// Returns `None` if the table doesn’t exist which may happen if the database
+/// has been opened in read-only mode and was created by an older version
+/// which didn’t have the column.
+fn open_read_table<'txn>(
+    txn: &'txn ::redb::ReadTransaction,
+    col: DBCol,
+) -> io::Result<Option<::redb::ReadOnlyTable<'txn, &'static [u8], &'static [u8]>>> {
+    match txn.open_table(table_def(col)) {
+        Ok(table) => Ok(Some(table)),
+        Err(::redb::TableError::TableDoesNotExist(_)) => Ok(None),
+        Err(err) => Err(into_other(err)),
+    }
+}
+
+fn other_error(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, msg)
+}
+
+fn into_other(error: impl std::fmt::Display) -> io::Error {
+    other_error(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::refcount::add_positive_refcount;
+
+    fn open(dir: &tempfile::TempDir) -> RedbDB {
+        RedbDB::open(dir.path(), &StoreConfig::test_config(), Mode::ReadWrite).unwrap()
+    }
+
+    #[test]
+    fn test_refcount_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open(&dir);
+        let one = std::num::NonZeroU32::new(1).unwrap();
+        let value = add_positive_refcount(b"value", one);
+        let mut transaction = DBTransaction::new();
+        transaction.update_refcount(DBCol::State, b"key".to_vec(), value.clone());
+        transaction.update_refcount(DBCol::State, b"key".to_vec(), value);
+        db.write(transaction).unwrap();
+        assert_eq!(
+            db.get_with_rc_stripped(DBCol::State, b"key").unwrap().as_deref(),
+            Some(&b"value"[..])
+        );
+
+        for _ in 0..2 {
+            let mut transaction = DBTransaction::new();
+            transaction.update_refcount(
+                DBCol::State,
+                b"key".to_vec(),
+                refcount::encode_negative_refcount(one),
+            );
+            db.write(transaction).unwrap();
+        }
+        assert_eq!(db.get_raw_bytes(DBCol::State, b"key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_iter_prefix_across_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open(&dir);
+        let mut transaction = DBTransaction::new();
+        for i in 0..(ITER_BATCH_SIZE as u32 * 2 + 10) {
+            transaction.set(DBCol::BlockMisc, [b"a", &i.to_be_bytes()[..]].concat(), vec![1]);
+        }
+        transaction.set(DBCol::BlockMisc, b"b".to_vec(), vec![2]);
+        db.write(transaction).unwrap();
+
+        let keys = db.iter_prefix(DBCol::BlockMisc, b"a").map(|item| item.unwrap().0);
+        let keys = keys.collect::<Vec<_>>();
+        assert_eq!(keys.len(), ITER_BATCH_SIZE * 2 + 10);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(db.iter(DBCol::BlockMisc).count(), ITER_BATCH_SIZE * 2 + 11);
+
+        let lower = [b"a", &5u32.to_be_bytes()[..]].concat();
+        let upper = [b"a", &(ITER_BATCH_SIZE as u32 + 5).to_be_bytes()[..]].concat();
+        assert_eq!(
+            db.iter_range(DBCol::BlockMisc, Some(&lower), Some(&upper)).count(),
+            ITER_BATCH_SIZE
+        );
+        assert_eq!(
+            db.iter_range(DBCol::BlockMisc, Some(&upper), None).count(),
+            ITER_BATCH_SIZE + 6
+        );
+
+        let mut transaction = DBTransaction::new();
+        transaction.delete_all(DBCol::BlockMisc);
+        db.write(transaction).unwrap();
+        assert_eq!(db.iter(DBCol::BlockMisc).count(), 0);
+    }
+
+    #[test]
+    fn test_opener() {
+        let dir = tempfile::tempdir().unwrap();
+        let config =
+            StoreConfig { backend: crate::StoreBackend::Redb, ..StoreConfig::test_config() };
+        let opener = crate::NodeStorage::opener(dir.path(), &config, None);
+        let store = opener.open().unwrap().get_store(crate::Temperature::Hot);
+        let mut update = store.store_update();
+        update.set(DBCol::BlockMisc, b"key", b"value");
+        update.commit().unwrap();
+        drop(store);
+
+        assert!(dir.path().join("data").join(DB_FILE_NAME).is_file());
+        let store = opener.open_in_mode(Mode::ReadOnly).unwrap().get_store(crate::Temperature::Hot);
+        assert_eq!(store.get(DBCol::BlockMisc, b"key").unwrap().as_deref(), Some(&b"value"[..]));
+    }
+
+    #[test]
+    fn test_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut transaction = DBTransaction::new();
+        transaction.set(DBCol::BlockMisc, b"key".to_vec(), b"value".to_vec());
+        open(&dir).write(transaction).unwrap();
+
+        let config = StoreConfig::test_config();
+        let db = RedbDB::open(dir.path(), &config, Mode::ReadOnly).unwrap();
+        assert_eq!(
+            db.get_raw_bytes(DBCol::BlockMisc, b"key").unwrap().as_deref(),
+            Some(&b"value"[..])
+        );
+        assert!(db.write(DBTransaction::new()).is_err());
+        drop(db);
+        assert!(RedbDB::open(dir.path(), &config, Mode::Create).is_err());
+    }
+}
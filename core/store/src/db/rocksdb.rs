@@ -220,6 +220,26 @@ impl RocksDB {
         let iter = self.db.iterator_cf_opt(cf_handle, read_options, IteratorMode::Start);
         RocksDBIterator(iter)
     }
+
+    /// Returns iterator over raw bytes of entries in given column whose keys
+    /// are within given bounds.
+    fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> RocksDBIterator<'a> {
+        let cf_handle = self.cf_handle(col).unwrap();
+        let mut read_options = rocksdb_read_options();
+        if let Some(lower_bound) = lower_bound {
+            read_options.set_iterate_lower_bound(lower_bound);
+        }
+        if let Some(upper_bound) = upper_bound {
+            read_options.set_iterate_upper_bound(upper_bound);
+        }
+        let iter = self.db.iterator_cf_opt(cf_handle, read_options, IteratorMode::Start);
+        RocksDBIterator(iter)
+    }
 }
 
 struct RocksDBIterator<'a>(rocksdb::DBIteratorWithThreadMode<'a, DB>);
@@ -285,6 +305,16 @@ impl Database for RocksDB {
         refcount::iter_with_rc_logic(col, iter)
     }
 
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&'a [u8]>,
+        upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a> {
+        let iter = self.iter_raw_bytes_range(col, lower_bound, upper_bound);
+        refcount::iter_with_rc_logic(col, iter)
+    }
+
     fn write(&self, transaction: DBTransaction) -> io::Result<()> {
         let mut batch = WriteBatch::default();
         for op in transaction.ops {
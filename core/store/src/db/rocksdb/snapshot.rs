@@ -81,6 +81,23 @@ impl Snapshot {
         db_path: &std::path::Path,
         config: &crate::StoreConfig,
         temp: Temperature,
+    ) -> Result<Self, SnapshotError> {
+        Self::new_with(db_path, config, |snapshot_path| {
+            let db = super::RocksDB::open(db_path, config, crate::Mode::ReadWriteExisting, temp)?;
+            let cp = Checkpoint::new(&db.db).map_err(super::into_other)?;
+            cp.create_checkpoint(snapshot_path).map_err(super::into_other)
+        })
+    }
+
+    /// Possibly creates a new snapshot using given function.
+    ///
+    /// Works like [`Self::new`] but rather than creating a RocksDB checkpoint
+    /// calls `create` with path at which the snapshot should be created.  This
+    /// allows other database backends to use the same snapshot logic.
+    pub(crate) fn new_with(
+        db_path: &std::path::Path,
+        config: &crate::StoreConfig,
+        create: impl FnOnce(&std::path::Path) -> io::Result<()>,
     ) -> Result<Self, SnapshotError> {
         let snapshot_path = match config.migration_snapshot.get_path(db_path) {
             Some(snapshot_path) => snapshot_path,
@@ -93,9 +110,7 @@ impl Snapshot {
             return Err(SnapshotError::AlreadyExists(snapshot_path));
         }
 
-        let db = super::RocksDB::open(db_path, config, crate::Mode::ReadWriteExisting, temp)?;
-        let cp = Checkpoint::new(&db.db).map_err(super::into_other)?;
-        cp.create_checkpoint(&snapshot_path)?;
+        create(&snapshot_path)?;
 
         Ok(Self(Some(snapshot_path)))
     }
@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::sync::Arc;
+
+use crate::db::{ColdDB, DBIterator, DBSlice, DBTransaction, Database};
+use crate::{DBCol, StoreStatistics};
+
+/// A database which reads from hot storage falling back to cold storage.
+///
+/// Once data is garbage collected from hot storage it’s only available in the
+/// cold one.  To let split-storage node serve archival reads (e.g. historical
+/// RPC queries) without having to know temperature of the data, this reads
+/// from hot storage first and, for columns which are copied to cold storage
+/// (see [`DBCol::is_cold`]), from cold storage if the value is missing.
+///
+/// Writes, flushes and compactions go to hot storage only.  Iterating is done
+/// over hot storage only as well with the exception of prefix iteration over
+/// [`DBCol::StateChanges`] and [`DBCol::TransactionResultForBlock`] (which is
+/// the only iteration cold storage supports for a cold column) where entries
+/// from both databases are merged.
+pub struct SplitDB<D = crate::db::RocksDB> {
+    hot: Arc<dyn Database>,
+    cold: Arc<ColdDB<D>>,
+}
+
+impl<D> SplitDB<D> {
+    pub fn new(hot: Arc<dyn Database>, cold: Arc<ColdDB<D>>) -> Self {
+        Self { hot, cold }
+    }
+}
+
+impl<D: Database> Database for SplitDB<D> {
+    fn get_raw_bytes(&self, col: DBCol, key: &[u8]) -> std::io::Result<Option<DBSlice<'_>>> {
+        match self.hot.get_raw_bytes(col, key)? {
+            None if col.is_cold() => self.cold.get_raw_bytes(col, key),
+            value => Ok(value),
+        }
+    }
+
+    fn get_with_rc_stripped(&self, col: DBCol, key: &[u8]) -> std::io::Result<Option<DBSlice<'_>>> {
+        assert!(col.is_rc());
+        match self.hot.get_with_rc_stripped(col, key)? {
+            None if col.is_cold() => self.cold.get_with_rc_stripped(col, key),
+            value => Ok(value),
+        }
+    }
+
+    fn iter<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        self.hot.iter(col)
+    }
+
+    fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
+        if ColdDB::<D>::supports_iter_prefix(col) {
+            let hot = self.hot.iter_prefix(col, key_prefix);
+            let cold = self.cold.iter_prefix(col, key_prefix);
+            Box::new(MergeIterator { hot: hot.peekable(), cold: cold.peekable() })
+        } else {
+            self.hot.iter_prefix(col, key_prefix)
+        }
+    }
+
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&'a [u8]>,
+        upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a> {
+        self.hot.iter_range(col, lower_bound, upper_bound)
+    }
+
+    fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a> {
+        self.hot.iter_raw_bytes(col)
+    }
+
+    fn write(&self, transaction: DBTransaction) -> std::io::Result<()> {
+        self.hot.write(transaction)
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.hot.flush()
+    }
+
+    fn compact(&self) -> std::io::Result<()> {
+        self.hot.compact()
+    }
+
+    fn get_store_statistics(&self) -> Option<StoreStatistics> {
+        self.hot.get_store_statistics()
+    }
+}
+
+/// Merges two iterators sorted by key into one.
+///
+/// If both iterators return the same key, the entry from hot storage is used.
+struct MergeIterator<'a> {
+    hot: Peekable<DBIterator<'a>>,
+    cold: Peekable<DBIterator<'a>>,
+}
+
+impl<'a> Iterator for MergeIterator<'a> {
+    type Item = std::io::Result<(Box<[u8]>, Box<[u8]>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.hot.peek(), self.cold.peek()) {
+            (Some(Ok((hot_key, _))), Some(Ok((cold_key, _)))) => hot_key.cmp(cold_key),
+            // Errors are returned as soon as they are encountered.
+            (Some(Err(_)), _) | (_, None) => Ordering::Less,
+            (_, Some(Err(_))) | (None, _) => Ordering::Greater,
+        };
+        match order {
+            Ordering::Less => self.hot.next(),
+            Ordering::Greater => self.cold.next(),
+            Ordering::Equal => {
+                self.cold.next();
+                self.hot.next()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::TestDB;
+
+    const HASH: [u8; 32] = [1; 32];
+
+    fn create_test_db() -> (Arc<dyn Database>, SplitDB<TestDB>) {
+        let hot = TestDB::new();
+        let cold = Arc::new(ColdDB::new(hot.clone(), TestDB::default()));
+        (hot.clone(), SplitDB::new(hot, cold))
+    }
+
+    fn write(db: &dyn Database, col: DBCol, key: &[u8], value: &[u8]) {
+        let mut transaction = DBTransaction::new();
+        transaction.set(col, key.to_vec(), value.to_vec());
+        db.write(transaction).unwrap();
+    }
+
+    fn get(db: &dyn Database, col: DBCol, key: &[u8]) -> Option<Vec<u8>> {
+        db.get_raw_bytes(col, key).unwrap().map(|value| value.to_vec())
+    }
+
+    /// Tests that reads of cold columns fall back to cold storage.
+    #[test]
+    fn test_get_falls_back_to_cold() {
+        let (hot, db) = create_test_db();
+        write(&*db.cold, DBCol::Block, &HASH, b"cold");
+        assert_eq!(Some(b"cold".to_vec()), get(&db, DBCol::Block, &HASH));
+
+        // Hot storage takes precedence.
+        write(&*hot, DBCol::Block, &HASH, b"hot");
+        assert_eq!(Some(b"hot".to_vec()), get(&db, DBCol::Block, &HASH));
+
+        // Writes go to hot storage only.
+        write(&db, DBCol::Chunks, &HASH, b"hot");
+        assert_eq!(Some(b"hot".to_vec()), get(&*hot, DBCol::Chunks, &HASH));
+        assert_eq!(None, get(&*db.cold, DBCol::Chunks, &HASH));
+
+        // Reference counted values read from cold storage have their reference
+        // count reintroduced.
+        let state_key = [&[0u8; 8][..], &HASH[..]].concat();
+        write(&*db.cold, DBCol::State, &state_key, b"state");
+        let value = db.get_with_rc_stripped(DBCol::State, &state_key).unwrap();
+        assert_eq!(Some(&b"state"[..]), value.as_deref());
+    }
+
+    /// Tests that iterating over StateChanges merges both databases.
+    #[test]
+    fn test_iter_prefix_merges_state_changes() {
+        let (hot, db) = create_test_db();
+        write(&*db.cold, DBCol::StateChanges, b"a1", b"cold");
+        write(&*db.cold, DBCol::StateChanges, b"a2", b"cold");
+        write(&*hot, DBCol::StateChanges, b"a2", b"hot");
+        write(&*hot, DBCol::StateChanges, b"a3", b"hot");
+        write(&*hot, DBCol::StateChanges, b"b1", b"hot");
+
+        let got = db
+            .iter_prefix(DBCol::StateChanges, b"a")
+            .map(|item| {
+                let (key, value) = item.unwrap();
+                (key.to_vec(), value.to_vec())
+            })
+            .collect::<Vec<_>>();
+        let want = vec![
+            (b"a1".to_vec(), b"cold".to_vec()),
+            (b"a2".to_vec(), b"hot".to_vec()),
+            (b"a3".to_vec(), b"hot".to_vec()),
+        ];
+        assert_eq!(want, got);
+    }
+
+    /// Tests that transaction outcomes garbage collected from hot storage are
+    /// still found when iterating by outcome id.
+    #[test]
+    fn test_iter_prefix_falls_back_to_cold_for_outcomes() {
+        let (hot, db) = create_test_db();
+        let outcome_id = [2u8; 32];
+        let cold_key = [&outcome_id[..], &HASH[..]].concat();
+        let hot_key = [&outcome_id[..], &[3u8; 32][..]].concat();
+        write(&*db.cold, DBCol::TransactionResultForBlock, &cold_key, b"cold");
+
+        let iter_outcomes = || {
+            db.iter_prefix(DBCol::TransactionResultForBlock, &outcome_id)
+                .map(|item| {
+                    let (key, value) = item.unwrap();
+                    (key.to_vec(), value.to_vec())
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![(cold_key.clone(), b"cold".to_vec())], iter_outcomes());
+
+        write(&*hot, DBCol::TransactionResultForBlock, &hot_key, b"hot");
+        let want = vec![(cold_key, b"cold".to_vec()), (hot_key, b"hot".to_vec())];
+        assert_eq!(want, iter_outcomes());
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::io;
+use std::ops::Bound;
 use std::sync::{Arc, RwLock};
 
 use crate::db::{refcount, DBIterator, DBOp, DBSlice, DBTransaction, Database};
@@ -47,6 +48,21 @@ impl Database for TestDB {
         refcount::iter_with_rc_logic(col, iterator.into_iter())
     }
 
+    fn iter_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&'a [u8]>,
+        upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a> {
+        let lower = lower_bound.map_or(Bound::Unbounded, |key| Bound::Included(key.to_vec()));
+        let upper = upper_bound.map_or(Bound::Unbounded, |key| Bound::Excluded(key.to_vec()));
+        let iterator = self.db.read().unwrap()[col]
+            .range((lower, upper))
+            .map(|(k, v)| Ok((k.clone().into_boxed_slice(), v.clone().into_boxed_slice())))
+            .collect::<Vec<io::Result<_>>>();
+        refcount::iter_with_rc_logic(col, iterator.into_iter())
+    }
+
     fn write(&self, transaction: DBTransaction) -> io::Result<()> {
         let mut db = self.db.write().unwrap();
         for op in transaction.ops {
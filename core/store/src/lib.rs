@@ -34,8 +34,8 @@ pub use crate::trie::update::{TrieUpdate, TrieUpdateIterator, TrieUpdateValuePtr
 pub use crate::trie::{
     estimator, split_state, ApplyStatePartResult, KeyForStateChanges, KeyLookupMode, NibbleSlice,
     PartialStorage, PrefetchApi, PrefetchError, RawTrieNode, RawTrieNodeWithSize, ShardTries, Trie,
-    TrieAccess, TrieCache, TrieCachingStorage, TrieChanges, TrieConfig, TrieDBStorage, TrieStorage,
-    WrappedTrieChanges,
+    TrieAccess, TrieCache, TrieCachingStorage, TrieChanges, TrieConfig, TrieDBStorage,
+    TrieMemoryOverlayStorage, TrieStorage, WrappedTrieChanges,
 };
 pub use flat_state::FlatStateDelta;
 
@@ -53,7 +53,7 @@ mod sync_utils;
 pub mod test_utils;
 mod trie;
 
-pub use crate::config::{Mode, StoreConfig};
+pub use crate::config::{Mode, StoreBackend, StoreConfig};
 pub use crate::opener::{StoreMigrator, StoreOpener, StoreOpenerError};
 
 /// Specifies temperature of a storage.
@@ -120,13 +120,12 @@ impl NodeStorage {
         )
     }
 
-    /// Constructs new object backed by given database.
-    fn from_rocksdb(
-        hot_storage: crate::db::RocksDB,
+    /// Constructs new object backed by given databases.
+    fn from_dbs(
+        hot_storage: Arc<dyn Database>,
         #[cfg(feature = "cold_store")] cold_storage: Option<crate::db::RocksDB>,
         #[cfg(not(feature = "cold_store"))] cold_storage: Option<std::convert::Infallible>,
     ) -> Self {
-        let hot_storage = Arc::new(hot_storage);
         #[cfg(feature = "cold_store")]
         let cold_storage = cold_storage
             .map(|cold_db| Arc::new(crate::db::ColdDB::new(hot_storage.clone(), cold_db)));
@@ -190,6 +189,21 @@ impl<D: Database + 'static> NodeStorage<D> {
         }
     }
 
+    /// Returns a store which reads from hot storage falling back to cold
+    /// storage for data which is no longer in the hot one.
+    ///
+    /// This lets code serving archival reads (e.g. view client) access all the
+    /// data without having to know its temperature.  See
+    /// [`crate::db::SplitDB`] for details.  Returns `None` if the node has no
+    /// cold storage.
+    #[cfg(feature = "cold_store")]
+    pub fn get_split_store(&self) -> Option<Store> {
+        self.cold_storage.as_ref().map(|cold_storage| {
+            let split = crate::db::SplitDB::new(self.hot_storage.clone(), cold_storage.clone());
+            Store { storage: Arc::new(split) }
+        })
+    }
+
     /// Returns underlying database for given temperature.
     ///
     /// With (currently unimplemented) cold storage, this allows accessing
@@ -315,6 +329,15 @@ impl Store {
         self.storage.iter_prefix(column, key_prefix)
     }
 
+    pub fn iter_range<'a>(
+        &'a self,
+        column: DBCol,
+        lower_bound: Option<&'a [u8]>,
+        upper_bound: Option<&'a [u8]>,
+    ) -> DBIterator<'a> {
+        self.storage.iter_range(column, lower_bound, upper_bound)
+    }
+
     pub fn iter_prefix_ser<'a, T: BorshDeserialize>(
         &'a self,
         column: DBCol,
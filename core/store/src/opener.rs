@@ -1,13 +1,16 @@
+use std::sync::Arc;
+
 use crate::db::rocksdb::snapshot::{Snapshot, SnapshotError, SnapshotRemoveError};
 use crate::db::rocksdb::RocksDB;
+use crate::db::{Database, RedbDB};
 use crate::metadata::{
     set_store_metadata, set_store_version, DbKind, DbMetadata, DbVersion, DB_VERSION,
 };
-use crate::{Mode, NodeStorage, StoreConfig, Temperature};
+use crate::{Mode, NodeStorage, StoreBackend, StoreConfig, Temperature};
 
 #[derive(Debug, thiserror::Error)]
 pub enum StoreOpenerError {
-    /// I/O or RocksDB-level error while opening or accessing the database.
+    /// I/O or database-level error while opening or accessing the database.
     #[error("{0}")]
     IO(#[from] std::io::Error),
 
@@ -142,7 +145,10 @@ pub struct StoreOpener<'a> {
     migrator: Option<&'a dyn StoreMigrator>,
 }
 
-/// Opener for a single RocksDB instance.
+/// Opener for a single database instance.
+///
+/// The database is opened with the backend specified by
+/// [`StoreConfig::backend`].
 struct DBOpener<'a> {
     /// Path to the database.
     ///
@@ -282,6 +288,46 @@ impl<'a> StoreOpener<'a> {
         }
     }
 
+    /// Creates cold storage next to an existing archival database.
+    ///
+    /// This is the first step of migrating an archival node to split storage.
+    /// The existing database must be an [`DbKind::Archive`] database at the
+    /// current version and the cold database must not exist yet.  The
+    /// existing database becomes [`DbKind::Hot`] and the new one
+    /// [`DbKind::Cold`] so that afterwards the storage can be opened with
+    /// [`Self::open`] as usual.  Populating the cold database is left to the
+    /// caller (see [`crate::cold_storage::copy_all_data_to_cold`]).
+    #[cfg(feature = "cold_store")]
+    pub fn create_cold_for_archive(&self) -> Result<crate::NodeStorage, StoreOpenerError> {
+        let cold = self.cold.as_ref().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::Other, "cold storage is not configured")
+        })?;
+        let hot_meta = self.hot.get_metadata()?.ok_or(StoreOpenerError::DbDoesNotExist)?;
+        if cold.get_metadata()?.is_some() {
+            return Err(StoreOpenerError::DbAlreadyExists);
+        }
+        if hot_meta.version != DB_VERSION {
+            return Err(StoreOpenerError::DbVersionMismatch {
+                got: hot_meta.version,
+                want: DB_VERSION,
+            });
+        }
+        if hot_meta.kind != Some(DbKind::Archive) {
+            return Err(StoreOpenerError::DbKindMismatch {
+                which: "Hot",
+                got: hot_meta.kind,
+                want: DbKind::Archive,
+            });
+        }
+        tracing::info!(target: "near", path=%cold.path.display(),
+                       "Creating cold database for an archival database");
+        let hot = self.hot.open_db(Mode::ReadWriteExisting)?.into_database();
+        let cold = cold_db_opener::into_cold_db(cold.create()?)?;
+        let storage = NodeStorage::from_dbs(hot, Some(cold));
+        set_store_metadata(&storage, DbMetadata { version: DB_VERSION, kind: None })?;
+        Ok(storage)
+    }
+
     fn open_existing(
         &self,
         mode: Mode,
@@ -289,7 +335,7 @@ impl<'a> StoreOpener<'a> {
     ) -> Result<crate::NodeStorage, StoreOpenerError> {
         let snapshots = self.apply_migrations(mode, metadata)?;
         tracing::info!(target: "near", path=%self.path().display(),
-                       "Opening an existing RocksDB database");
+                       backend=?self.hot.config.backend, "Opening an existing database");
         let (storage, hot_meta, cold_meta) = self.open_storage(mode, DB_VERSION)?;
         if let Some(_cold_meta) = cold_meta {
             assert!(cfg!(feature = "cold_store"));
@@ -338,10 +384,14 @@ impl<'a> StoreOpener<'a> {
 
     fn open_new(&self) -> Result<crate::NodeStorage, StoreOpenerError> {
         tracing::info!(target: "near", path=%self.path().display(),
-                       "Creating a new RocksDB database");
-        let hot = self.hot.create()?;
-        let cold = self.cold.as_ref().map(|db| db.create()).transpose()?;
-        let storage = NodeStorage::from_rocksdb(hot, cold);
+                       backend=?self.hot.config.backend, "Creating a new database");
+        let hot = self.hot.create()?.into_database();
+        let cold = self
+            .cold
+            .as_ref()
+            .map(|db| db.create().and_then(cold_db_opener::into_cold_db))
+            .transpose()?;
+        let storage = NodeStorage::from_dbs(hot, cold);
         set_store_metadata(
             &storage,
             DbMetadata { version: DB_VERSION, kind: self.expected_kind.or(Some(DbKind::RPC)) },
@@ -421,7 +471,7 @@ impl<'a> StoreOpener<'a> {
         let (cold, cold_meta) =
             match self.cold.as_ref().map(|opener| opener.open(mode, want_version)).transpose()? {
                 None => (None, None),
-                Some((db, meta)) => (Some(db), Some(meta)),
+                Some((db, meta)) => (Some(cold_db_opener::into_cold_db(db)?), Some(meta)),
             };
 
         // Those are mostly sanity checks.  If any of those conditions fails
@@ -450,12 +500,12 @@ impl<'a> StoreOpener<'a> {
             )
         })?;
 
-        Ok((NodeStorage::from_rocksdb(hot, cold), hot_meta, cold_meta))
+        Ok((NodeStorage::from_dbs(hot.into_database(), cold), hot_meta, cold_meta))
     }
 }
 
 impl<'a> DBOpener<'a> {
-    /// Constructs new opener for a single RocksDB builder.
+    /// Constructs new opener for a single database.
     ///
     /// The path to the database is resolved based on the path in config with
     /// given home_dir as base directory for resolving relative paths.
@@ -475,7 +525,10 @@ impl<'a> DBOpener<'a> {
     /// introduced, the kind is returned as `None`.  Otherwise, it’s also
     /// fetched and if it’s not there error is returned.
     fn get_metadata(&self) -> std::io::Result<Option<DbMetadata>> {
-        RocksDB::get_metadata(&self.path, self.config)
+        match self.config.backend {
+            StoreBackend::RocksDB => RocksDB::get_metadata(&self.path, self.config),
+            StoreBackend::Redb => RedbDB::get_metadata(&self.path, self.config),
+        }
     }
 
     /// Opens the database in given mode checking expected version and kind.
@@ -494,9 +547,9 @@ impl<'a> DBOpener<'a> {
     /// new version.
     ///
     /// Use [`Self::create`] to create a new database.
-    fn open(&self, mode: Mode, want_version: DbVersion) -> std::io::Result<(RocksDB, DbMetadata)> {
-        let db = RocksDB::open(&self.path, &self.config, mode, self.temp)?;
-        let metadata = DbMetadata::read(&db)?;
+    fn open(&self, mode: Mode, want_version: DbVersion) -> std::io::Result<(OpenedDB, DbMetadata)> {
+        let db = self.open_db(mode)?;
+        let metadata = DbMetadata::read(db.as_database())?;
         if want_version != metadata.version {
             let msg = format!("unexpected DbVersion {}; expected {want_version}", metadata.version);
             Err(std::io::Error::new(std::io::ErrorKind::Other, msg))
@@ -506,13 +559,52 @@ impl<'a> DBOpener<'a> {
     }
 
     /// Creates a new database.
-    fn create(&self) -> std::io::Result<RocksDB> {
-        RocksDB::open(&self.path, &self.config, Mode::Create, self.temp)
+    fn create(&self) -> std::io::Result<OpenedDB> {
+        self.open_db(Mode::Create)
+    }
+
+    /// Opens the database with the configured backend.
+    fn open_db(&self, mode: Mode) -> std::io::Result<OpenedDB> {
+        match self.config.backend {
+            StoreBackend::RocksDB => {
+                RocksDB::open(&self.path, &self.config, mode, self.temp).map(OpenedDB::RocksDB)
+            }
+            StoreBackend::Redb if self.temp != Temperature::Hot => Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "redb backend is not supported for cold storage",
+            )),
+            StoreBackend::Redb => RedbDB::open(&self.path, &self.config, mode).map(OpenedDB::Redb),
+        }
     }
 
     /// Creates a new snapshot for the database.
     fn snapshot(&self) -> Result<Snapshot, SnapshotError> {
-        Snapshot::new(&self.path, &self.config, self.temp)
+        match self.config.backend {
+            StoreBackend::RocksDB => Snapshot::new(&self.path, &self.config, self.temp),
+            StoreBackend::Redb => RedbDB::snapshot(&self.path, &self.config),
+        }
+    }
+}
+
+/// A database opened by [`DBOpener`].
+enum OpenedDB {
+    RocksDB(RocksDB),
+    Redb(RedbDB),
+}
+
+impl OpenedDB {
+    fn as_database(&self) -> &dyn Database {
+        match self {
+            Self::RocksDB(db) => db,
+            Self::Redb(db) => db,
+        }
+    }
+
+    fn into_database(self) -> Arc<dyn Database> {
+        match self {
+            Self::RocksDB(db) => Arc::new(db),
+            Self::Redb(db) => Arc::new(db),
+        }
     }
 }
 
@@ -547,6 +639,16 @@ use cold_db_opener::ColdDBOpener;
 #[cfg(feature = "cold_store")]
 mod cold_db_opener {
     pub(super) type ColdDBOpener<'a> = super::DBOpener<'a>;
+
+    /// Extracts RocksDB instance used as cold storage.
+    ///
+    /// [`super::DBOpener`] refuses to open cold storage with other backends.
+    pub(super) fn into_cold_db(db: super::OpenedDB) -> std::io::Result<super::RocksDB> {
+        match db {
+            super::OpenedDB::RocksDB(db) => Ok(db),
+            super::OpenedDB::Redb(_) => unreachable!(),
+        }
+    }
 }
 
 #[cfg(not(feature = "cold_store"))]
@@ -577,5 +679,11 @@ mod cold_db_opener {
         }
     }
 
+    pub(super) fn into_cold_db(
+        db: std::convert::Infallible,
+    ) -> std::io::Result<std::convert::Infallible> {
+        match db {}
+    }
+
     pub(super) type ColdDBOpener<'a> = OpenerImpl;
 }
@@ -370,6 +370,34 @@ impl TrieStorage for TrieMemoryPartialStorage {
     }
 }
 
+/// Storage which serves the given nodes from memory and reads everything else
+/// from the underlying storage.  Allows reading a trie at a state root whose
+/// changes haven't been written to DB, e.g. to apply several chunks on top of
+/// each other and discard the result.
+pub struct TrieMemoryOverlayStorage {
+    pub(crate) storage: Box<dyn TrieStorage>,
+    pub(crate) nodes: HashMap<CryptoHash, Arc<[u8]>>,
+}
+
+impl TrieMemoryOverlayStorage {
+    pub fn new(storage: Box<dyn TrieStorage>, nodes: HashMap<CryptoHash, Arc<[u8]>>) -> Self {
+        Self { storage, nodes }
+    }
+}
+
+impl TrieStorage for TrieMemoryOverlayStorage {
+    fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
+        match self.nodes.get(hash) {
+            Some(value) => Ok(value.clone()),
+            None => self.storage.retrieve_raw_bytes(hash),
+        }
+    }
+
+    fn get_trie_nodes_count(&self) -> TrieNodesCount {
+        self.storage.get_trie_nodes_count()
+    }
+}
+
 /// Storage for reading State nodes and values from DB which caches reads.
 pub struct TrieCachingStorage {
     pub(crate) store: Store,
@@ -201,7 +201,9 @@ mod nodes_counter_tests {
 mod trie_storage_tests {
     use super::*;
     use crate::test_utils::{create_test_store, create_tries};
-    use crate::trie::trie_storage::{TrieCache, TrieCachingStorage, TrieDBStorage};
+    use crate::trie::trie_storage::{
+        TrieCache, TrieCachingStorage, TrieDBStorage, TrieMemoryOverlayStorage,
+    };
     use crate::trie::TrieRefcountChange;
     use crate::{Store, TrieChanges, TrieConfig};
     use assert_matches::assert_matches;
@@ -306,6 +308,37 @@ mod trie_storage_tests {
         assert_eq!(count_delta.mem_reads, 1);
     }
 
+    /// Check that a trie can be read at a root whose changes are only kept in
+    /// memory, and that the overlay doesn't hide nodes which are in the store.
+    #[test]
+    fn test_retrieve_overlay() {
+        let tries = create_tries();
+        let shard_uid = ShardUId::single_shard();
+        let changes =
+            vec![(b"a".to_vec(), Some(b"1".to_vec())), (b"b".to_vec(), Some(b"2".to_vec()))];
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+
+        let trie = tries.get_view_trie_for_shard(shard_uid, root);
+        let trie_changes = trie.update(vec![(b"a".to_vec(), Some(b"3".to_vec()))]).unwrap();
+        let nodes = trie_changes
+            .insertions()
+            .iter()
+            .map(|change| (*change.hash(), Arc::from(change.payload())))
+            .collect();
+        let storage = tries.get_view_trie_for_shard(shard_uid, root).storage;
+        let trie = Trie::new(
+            Box::new(TrieMemoryOverlayStorage::new(storage, nodes)),
+            trie_changes.new_root,
+            None,
+        );
+        assert_eq!(trie.get(b"a").unwrap(), Some(b"3".to_vec()));
+        assert_eq!(trie.get(b"b").unwrap(), Some(b"2".to_vec()));
+
+        // Nothing has been written to the store.
+        let trie = tries.get_view_trie_for_shard(shard_uid, trie_changes.new_root);
+        assert_matches!(trie.get(b"a"), Err(_));
+    }
+
     /// Check that positions of item and costs of its retrieval are returned correctly.
     #[test]
     fn test_counter_with_caching() {
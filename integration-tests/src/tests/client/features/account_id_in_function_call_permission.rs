@@ -61,6 +61,7 @@ fn test_account_id_in_function_call_permission_upgrade() {
         })],
         nonce: 0,
         block_hash: CryptoHash::default(),
+        priority_fee: None,
     };
 
     // Run the transaction, it should pass as we don't do validation at this protocol version.
@@ -129,6 +130,7 @@ fn test_very_long_account_id() {
         })],
         nonce: 0,
         block_hash: tip.last_block_hash,
+        priority_fee: None,
     }
     .sign(&signer);
 
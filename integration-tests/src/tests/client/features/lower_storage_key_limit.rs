@@ -77,6 +77,7 @@ fn protocol_upgrade() {
 
         nonce: 0,
         block_hash: CryptoHash::default(),
+        priority_fee: None,
     };
 
     // Run transaction writing storage key exceeding the limit. Check that execution succeeds.
@@ -137,6 +138,7 @@ fn protocol_upgrade() {
 
             nonce: 0,
             block_hash: CryptoHash::default(),
+            priority_fee: None,
         };
         let tip = env.clients[0].chain.head().unwrap();
         let signed_tx =
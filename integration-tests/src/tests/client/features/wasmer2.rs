@@ -51,6 +51,7 @@ fn test_wasmer2_upgrade() {
 
         nonce: 0,
         block_hash: CryptoHash::default(),
+        priority_fee: None,
     };
 
     // Run the transaction & collect the logs.
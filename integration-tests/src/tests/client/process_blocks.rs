@@ -1070,6 +1070,7 @@ fn test_process_invalid_tx() {
             receiver_id: "test".parse().unwrap(),
             block_hash: *env.clients[0].chain.genesis().hash(),
             actions: vec![],
+            priority_fee: None,
         },
     );
     for i in 1..12 {
@@ -1088,6 +1089,7 @@ fn test_process_invalid_tx() {
             receiver_id: "test".parse().unwrap(),
             block_hash: hash(&[1]),
             actions: vec![],
+            priority_fee: None,
         },
     );
     assert_eq!(
@@ -1096,6 +1098,41 @@ fn test_process_invalid_tx() {
     );
 }
 
+/// Transactions processed together get the same responses as when processed one by one,
+/// whether or not their signatures are verified as a batch.
+#[test]
+fn test_process_txs() {
+    init_test_logger();
+    let genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    let mut env = TestEnv::builder(ChainGenesis::new(&genesis))
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build();
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+    let txs: Vec<_> = (1..=3)
+        .map(|nonce| {
+            SignedTransaction::send_money(
+                nonce,
+                "test0".parse().unwrap(),
+                "test1".parse().unwrap(),
+                &signer,
+                100,
+                genesis_hash,
+            )
+        })
+        .collect();
+    let mut invalid = txs[1].clone();
+    invalid.signature = txs[0].signature.clone();
+    assert_eq!(
+        env.clients[0].process_txs(vec![txs[0].clone(), invalid, txs[2].clone()], false, false),
+        vec![
+            ProcessTxResponse::ValidTx,
+            ProcessTxResponse::InvalidTx(InvalidTxError::InvalidSignature),
+            ProcessTxResponse::ValidTx,
+        ]
+    );
+}
+
 /// If someone produce a block with Utc::now() + 1 min, we should produce a block with valid timestamp
 #[test]
 fn test_time_attack() {
@@ -3478,6 +3515,7 @@ mod contract_precompilation_tests {
                 "log_something",
                 &[],
                 &mut logs,
+                None,
                 &MockEpochInfoProvider::default(),
             )
             .unwrap();
@@ -82,6 +82,7 @@ fn setup_network_node(
         config.validator.as_ref().map(|v| v.account_id()),
         chain_genesis.clone(),
         runtime.clone(),
+        None,
         network_adapter.clone(),
         client_config,
         adv,
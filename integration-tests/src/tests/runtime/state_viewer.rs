@@ -8,7 +8,7 @@ use near_primitives::{
     serialize::to_base64,
     trie_key::trie_key_parsers,
     types::{AccountId, StateRoot},
-    views::{StateItem, ViewApplyState},
+    views::{AccountsWithPrefixView, StateItem, ViewApplyState},
 };
 use near_primitives::{
     test_utils::MockEpochInfoProvider,
@@ -119,6 +119,7 @@ fn test_view_call() {
         "run_test",
         &[],
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
 
@@ -147,6 +148,7 @@ fn test_view_call_try_changing_storage() {
         "run_test_with_storage_change",
         &[],
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
     let err = result.unwrap_err();
@@ -179,6 +181,7 @@ fn test_view_call_with_args() {
         "sum_with_input",
         &args,
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
     assert_eq!(view_call_result.unwrap(), 3u64.to_le_bytes().to_vec());
@@ -199,8 +202,9 @@ fn assert_view_state(
         .map(|(key, value)| StateItem { key: key.to_vec(), value: value.to_vec(), proof: vec![] })
         .collect::<Vec<_>>();
 
-    let view_state =
-        |include_proof| trie_viewer.view_state(&state_update, &alice, prefix, include_proof);
+    let view_state = |include_proof| {
+        trie_viewer.view_state(&state_update, &alice, prefix, include_proof, None, None)
+    };
 
     // Test without proof
     let result = view_state(false).unwrap();
@@ -359,10 +363,128 @@ fn test_view_state_too_large() {
         &Account::new(0, 0, CryptoHash::default(), 50_001),
     );
     let trie_viewer = TrieViewer::new(Some(50_000), None);
-    let result = trie_viewer.view_state(&state_update, &alice_account(), b"", false);
+    let result = trie_viewer.view_state(&state_update, &alice_account(), b"", false, None, None);
     assert!(matches!(result, Err(errors::ViewStateError::AccountStateTooLarge { .. })));
 }
 
+#[test]
+fn test_view_accounts_with_prefix() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let shard_uid = TEST_SHARD_UID;
+    let mut state_update = tries.new_trie_update(shard_uid, root);
+    for (account_id, amount) in
+        [("dan.near", 1), ("dana.near", 2), ("daniel.near", 3), ("dave.near", 4)]
+    {
+        set_account(
+            &mut state_update,
+            account_id.parse().unwrap(),
+            &Account::new(amount, 0, CryptoHash::default(), 100),
+        );
+    }
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().0;
+    let mut db_changes = tries.store_update();
+    let new_root = tries.apply_all(&trie_changes, shard_uid, &mut db_changes);
+    db_changes.commit().unwrap();
+    let state_update = tries.new_trie_update(shard_uid, new_root);
+
+    let ids = |view: &AccountsWithPrefixView| {
+        view.accounts.iter().map(|account| account.account_id.to_string()).collect::<Vec<_>>()
+    };
+
+    let trie_viewer = TrieViewer::default();
+    let result = trie_viewer.view_accounts_with_prefix(&state_update, "dan", None).unwrap();
+    assert_eq!(ids(&result), ["dan.near", "dana.near", "daniel.near"]);
+    assert_eq!(result.accounts[1].amount, 2);
+    assert_eq!(result.next_start_after, None);
+
+    let start_after: AccountId = "dana.near".parse().unwrap();
+    let result =
+        trie_viewer.view_accounts_with_prefix(&state_update, "da", Some(&start_after)).unwrap();
+    assert_eq!(ids(&result), ["daniel.near", "dave.near"]);
+
+    let result = trie_viewer.view_accounts_with_prefix(&state_update, "x", None).unwrap();
+    assert!(result.accounts.is_empty());
+
+    // With a tiny state size limit every page holds a single account.
+    let trie_viewer = TrieViewer::new(Some(1), None);
+    let mut start_after = None;
+    let mut listed = vec![];
+    loop {
+        let result = trie_viewer
+            .view_accounts_with_prefix(&state_update, "da", start_after.as_ref())
+            .unwrap();
+        assert!(result.accounts.len() <= 1);
+        listed.extend(ids(&result));
+        match result.next_start_after {
+            Some(cursor) => start_after = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(listed, ["dan.near", "dana.near", "daniel.near", "dave.near"]);
+}
+
+#[test]
+fn test_view_state_pagination() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let shard_uid = TEST_SHARD_UID;
+    let mut state_update = tries.new_trie_update(shard_uid, root);
+    set_account(
+        &mut state_update,
+        alice_account(),
+        &Account::new(0, 0, CryptoHash::default(), 50_001),
+    );
+    for key in ["a1", "a2", "a3", "a4", "a5", "b1"] {
+        state_update.set(
+            TrieKey::ContractData { account_id: alice_account(), key: key.as_bytes().to_vec() },
+            b"value".to_vec(),
+        );
+    }
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().0;
+    let mut db_changes = tries.store_update();
+    let new_root = tries.apply_all(&trie_changes, shard_uid, &mut db_changes);
+    db_changes.commit().unwrap();
+    let state_update = tries.new_trie_update(shard_uid, new_root);
+
+    let trie_viewer = TrieViewer::new(Some(50_000), None);
+    let view_state = |start_after: Option<&[u8]>, limit: Option<u64>| {
+        let limit = limit.map(|limit| std::num::NonZeroU64::new(limit).unwrap());
+        let result = trie_viewer
+            .view_state(&state_update, &alice_account(), b"a", false, start_after, limit)
+            .unwrap();
+        let keys = result
+            .values
+            .iter()
+            .map(|item| String::from_utf8(item.key.clone()).unwrap())
+            .collect::<Vec<_>>();
+        (keys, result.next_start_after)
+    };
+
+    // Unpaginated queries still fail for large accounts.
+    let result = trie_viewer.view_state(&state_update, &alice_account(), b"a", false, None, None);
+    assert!(matches!(result, Err(errors::ViewStateError::AccountStateTooLarge { .. })));
+
+    assert_eq!(view_state(None, Some(2)), (vec!["a1".into(), "a2".into()], Some(b"a2".to_vec())));
+    assert_eq!(
+        view_state(Some(b"a2"), Some(2)),
+        (vec!["a3".into(), "a4".into()], Some(b"a4".to_vec()))
+    );
+    assert_eq!(view_state(Some(b"a4"), Some(2)), (vec!["a5".into()], None));
+    assert_eq!(view_state(Some(b"a35"), None), (vec!["a4".into(), "a5".into()], None));
+    assert_eq!(view_state(Some(b"a5"), None), (vec![], None));
+    // Cursors before the prefix start from the beginning.
+    assert_eq!(view_state(Some(b""), Some(1)), (vec!["a1".into()], Some(b"a1".to_vec())));
+
+    // Pages are also bounded by the state size limit.
+    let trie_viewer = TrieViewer::new(Some(1), None);
+    let result = trie_viewer
+        .view_state(&state_update, &alice_account(), b"", false, Some(b"a4"), None)
+        .unwrap();
+    assert_eq!(result.values.len(), 1);
+    assert_eq!(result.next_start_after, Some(b"a5".to_vec()));
+}
+
 #[test]
 fn test_view_state_with_large_contract() {
     let (_, tries, root) = get_runtime_and_trie();
@@ -375,7 +497,7 @@ fn test_view_state_with_large_contract() {
     );
     state_update.set(TrieKey::ContractCode { account_id: alice_account() }, contract_code);
     let trie_viewer = TrieViewer::new(Some(50_000), None);
-    let result = trie_viewer.view_state(&state_update, &alice_account(), b"", false);
+    let result = trie_viewer.view_state(&state_update, &alice_account(), b"", false, None, None);
     assert!(result.is_ok());
 }
 
@@ -401,6 +523,7 @@ fn test_log_when_panic() {
             "panic_after_logging",
             &[],
             &mut logs,
+            None,
             &MockEpochInfoProvider::default(),
         )
         .unwrap_err();
@@ -17,7 +17,7 @@ use tracing::{info, warn};
 
 use near_chain_configs::{
     get_initial_supply, ClientConfig, GCConfig, Genesis, GenesisConfig, GenesisValidationMode,
-    LogSummaryStyle,
+    LogSummaryStyle, StateSyncConfig, TransactionPoolConfig, GC_DELAYABLE_COLUMNS,
 };
 use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
 #[cfg(feature = "json_rpc")]
@@ -35,6 +35,8 @@ use near_primitives::types::{
     NumShards, ShardId,
 };
 use near_primitives::utils::{generate_random_string, get_num_seats_per_shard};
+#[cfg(unix)]
+use near_primitives::validator_signer::RemoteValidatorSigner;
 use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
 use near_primitives::version::PROTOCOL_VERSION;
 #[cfg(feature = "rosetta_rpc")]
@@ -131,6 +133,9 @@ pub const GENESIS_CONFIG_FILENAME: &str = "genesis.json";
 pub const NODE_KEY_FILE: &str = "node_key.json";
 pub const VALIDATOR_KEY_FILE: &str = "validator_key.json";
 
+/// Environment variable holding the passphrase of encrypted key files.
+pub const KEY_PASSPHRASE_ENV: &str = "NEAR_KEY_PASSPHRASE";
+
 pub const MAINNET_TELEMETRY_URL: &str = "https://explorer.mainnet.near.org/api/nodes";
 pub const NETWORK_TELEMETRY_URL: &str = "https://explorer.{}.near.org/api/nodes";
 
@@ -201,6 +206,21 @@ fn default_trie_viewer_state_size_limit() -> Option<u64> {
     Some(50_000)
 }
 
+fn default_remote_signer_timeout() -> Duration {
+    Duration::from_secs(1)
+}
+
+/// Connection to a separate process holding the validator key, see
+/// `near_primitives::validator_signer::RemoteValidatorSigner`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RemoteSignerConfig {
+    /// Unix socket the signer listens on, relative to the home directory.
+    pub socket: PathBuf,
+    /// Timeout of a single signing request.
+    #[serde(default = "default_remote_signer_timeout")]
+    pub timeout: Duration,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Consensus {
     /// Minimum number of peers to start syncing.
@@ -291,6 +311,10 @@ pub struct Config {
     pub genesis_file: String,
     pub genesis_records_file: Option<String>,
     pub validator_key_file: String,
+    /// If set, the validator key is held by a remote signer instead of being
+    /// read from `validator_key_file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<RemoteSignerConfig>,
     pub node_key_file: String,
     #[cfg(feature = "json_rpc")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -317,9 +341,16 @@ pub struct Config {
     pub view_client_throttle_period: Duration,
     #[serde(default = "default_trie_viewer_state_size_limit")]
     pub trie_viewer_state_size_limit: Option<u64>,
+    /// Dumping and syncing state parts using an external storage.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub state_sync: StateSyncConfig,
     /// If set, overrides value in genesis configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_gas_burnt_view: Option<Gas>,
+    /// Limits of the transaction pool of each shard.  By default the pool is
+    /// unbounded.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub transaction_pool: TransactionPoolConfig,
     /// Different parameters to configure underlying storage.
     pub store: near_store::StoreConfig,
     /// Different parameters to configure underlying cold storage.
@@ -344,12 +375,17 @@ fn is_false(value: &bool) -> bool {
     !*value
 }
 
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
             genesis_file: GENESIS_CONFIG_FILENAME.to_string(),
             genesis_records_file: None,
             validator_key_file: VALIDATOR_KEY_FILE.to_string(),
+            remote_signer: None,
             node_key_file: NODE_KEY_FILE.to_string(),
             #[cfg(feature = "json_rpc")]
             rpc: Some(RpcConfig::default()),
@@ -368,7 +404,9 @@ impl Default for Config {
             view_client_threads: default_view_client_threads(),
             view_client_throttle_period: default_view_client_throttle_period(),
             trie_viewer_state_size_limit: default_trie_viewer_state_size_limit(),
+            state_sync: StateSyncConfig::default(),
             max_gas_burnt_view: None,
+            transaction_pool: TransactionPoolConfig::default(),
             db_migration_snapshot_path: None,
             use_db_migration_snapshot: None,
             store: near_store::StoreConfig::default(),
@@ -587,6 +625,7 @@ impl NearConfig {
                     .consensus
                     .header_sync_expected_height_per_second,
                 state_sync_timeout: config.consensus.state_sync_timeout,
+                state_sync: config.state_sync,
                 min_num_peers: config.consensus.min_num_peers,
                 log_summary_period: Duration::from_secs(10),
                 produce_empty_blocks: config.consensus.produce_empty_blocks,
@@ -612,6 +651,7 @@ impl NearConfig {
                 view_client_throttle_period: config.view_client_throttle_period,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
                 max_gas_burnt_view: config.max_gas_burnt_view,
+                transaction_pool: config.transaction_pool,
                 enable_statistics_export: config.store.enable_statistics_export,
                 client_background_migration_threads: config.store.background_migration_threads,
             },
@@ -1262,6 +1302,7 @@ pub fn download_config(url: &str, path: &Path) -> Result<(), FileDownloadError>
 struct NodeKeyFile {
     account_id: String,
     public_key: PublicKey,
+    #[serde(alias = "private_key")]
     secret_key: near_crypto::SecretKey,
 }
 
@@ -1272,6 +1313,22 @@ impl NodeKeyFile {
         file.read_to_string(&mut content)?;
         Ok(serde_json::from_str(&content)?)
     }
+
+    /// Reads node key which may be encrypted.  Plaintext node keys are read
+    /// as `NodeKeyFile` since they may have an empty account id.
+    fn load(path: &Path, passphrase: Option<&str>) -> std::io::Result<KeyFile> {
+        if KeyFile::is_encrypted(path)? {
+            KeyFile::from_file_with_passphrase(path, passphrase)
+        } else {
+            Self::from_file(path).map(KeyFile::from)
+        }
+    }
+}
+
+/// Reads a node or validator key file which may be encrypted.  Unlike
+/// `KeyFile::from_file`, it accepts node key files with an empty account id.
+pub fn read_key_file(path: &Path, passphrase: Option<&str>) -> std::io::Result<KeyFile> {
+    NodeKeyFile::load(path, passphrase)
 }
 
 impl From<NodeKeyFile> for KeyFile {
@@ -1290,23 +1347,61 @@ impl From<NodeKeyFile> for KeyFile {
     }
 }
 
+/// Reads the passphrase of encrypted key files from given file if any or from
+/// the `NEAR_KEY_PASSPHRASE` environment variable otherwise.
+pub fn key_passphrase(passphrase_file: Option<&Path>) -> anyhow::Result<Option<String>> {
+    if let Some(path) = passphrase_file {
+        let passphrase = fs::read_to_string(path)
+            .with_context(|| format!("Failed reading passphrase from {}", path.display()))?;
+        return Ok(Some(passphrase.trim_end_matches(&['\r', '\n'][..]).to_string()));
+    }
+    Ok(std::env::var(KEY_PASSPHRASE_ENV).ok())
+}
+
+/// Loads config from given directory.  Encrypted key files are decrypted with
+/// passphrase from the `NEAR_KEY_PASSPHRASE` environment variable.
 pub fn load_config(
     dir: &Path,
     genesis_validation: GenesisValidationMode,
+) -> anyhow::Result<NearConfig> {
+    load_config_with_passphrase(dir, genesis_validation, key_passphrase(None)?.as_deref())
+}
+
+pub fn load_config_with_passphrase(
+    dir: &Path,
+    genesis_validation: GenesisValidationMode,
+    key_passphrase: Option<&str>,
 ) -> anyhow::Result<NearConfig> {
     let config = Config::from_file(&dir.join(CONFIG_FILENAME))?;
     let genesis_file = dir.join(&config.genesis_file);
     let validator_file = dir.join(&config.validator_key_file);
-    let validator_signer = if validator_file.exists() {
-        let signer = InMemoryValidatorSigner::from_file(&validator_file).with_context(|| {
-            format!("Failed initializing validator signer from {}", validator_file.display())
-        })?;
+    let validator_signer = if let Some(remote_signer) = &config.remote_signer {
+        #[cfg(unix)]
+        {
+            let socket = dir.join(&remote_signer.socket);
+            let signer = RemoteValidatorSigner::connect(&socket, remote_signer.timeout)
+                .with_context(|| {
+                    format!("Failed connecting to remote signer at {}", socket.display())
+                })?;
+            Some(Arc::new(signer) as Arc<dyn ValidatorSigner>)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = remote_signer;
+            anyhow::bail!("remote_signer is supported only on Unix");
+        }
+    } else if validator_file.exists() {
+        let signer = KeyFile::from_file_with_passphrase(&validator_file, key_passphrase)
+            .map(|key| InMemoryValidatorSigner::from_signer(key.into()))
+            .with_context(|| {
+                format!("Failed initializing validator signer from {}", validator_file.display())
+            })?;
         Some(Arc::new(signer) as Arc<dyn ValidatorSigner>)
     } else {
         None
     };
     let node_key_path = dir.join(&config.node_key_file);
-    let network_signer = NodeKeyFile::from_file(&node_key_path).with_context(|| {
+    let network_signer = NodeKeyFile::load(&node_key_path, key_passphrase).with_context(|| {
         format!("Failed reading node key file from {}", node_key_path.display())
     })?;
 
@@ -1323,8 +1418,16 @@ pub fn load_config(
         anyhow::ensure!(!config.tracked_shards.is_empty(),
                         "Validator must track all shards. Please change `tracked_shards` field in config.json to be any non-empty vector");
     }
+    for col_name in config.gc.gc_num_epochs_to_keep_per_column.keys() {
+        anyhow::ensure!(
+            GC_DELAYABLE_COLUMNS.contains(&col_name.as_str()),
+            "Column {} in `gc.gc_num_epochs_to_keep_per_column` field of config.json can't be kept for longer than other data, only {} can",
+            col_name,
+            GC_DELAYABLE_COLUMNS.join(", ")
+        );
+    }
 
-    NearConfig::new(config, genesis, network_signer.into(), validator_signer)
+    NearConfig::new(config, genesis, network_signer, validator_signer)
 }
 
 pub fn load_test_config(seed: &str, port: u16, genesis: Genesis) -> NearConfig {
@@ -1416,9 +1519,19 @@ fn test_config_from_file() {
         // values is probably not worth it but there may be some other defaults
         // we want to ensure that they happen.
         let want_gc = if has_gc {
-            GCConfig { gc_blocks_limit: 42, gc_fork_clean_step: 420, gc_num_epochs_to_keep: 24 }
+            GCConfig {
+                gc_blocks_limit: 42,
+                gc_fork_clean_step: 420,
+                gc_num_epochs_to_keep: 24,
+                ..GCConfig::default()
+            }
         } else {
-            GCConfig { gc_blocks_limit: 2, gc_fork_clean_step: 100, gc_num_epochs_to_keep: 5 }
+            GCConfig {
+                gc_blocks_limit: 2,
+                gc_fork_clean_step: 100,
+                gc_num_epochs_to_keep: 5,
+                ..GCConfig::default()
+            }
         };
         assert_eq!(want_gc, config.gc);
 
@@ -1429,6 +1542,47 @@ fn test_config_from_file() {
     }
 }
 
+/// Tests that only transactions, receipts and their outcomes can be kept for
+/// longer than other data.
+#[test]
+fn test_gc_num_epochs_to_keep_per_column() {
+    let temp_dir = tempdir().unwrap();
+    init_configs(
+        &temp_dir.path(),
+        Some("localnet"),
+        None,
+        Some("seed1"),
+        1,
+        false,
+        None,
+        false,
+        None,
+        None,
+        false,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    let config_path = temp_dir.path().join(CONFIG_FILENAME);
+    let set_gc_columns = |columns: &[&str]| {
+        let mut config = Config::from_file(&config_path).unwrap();
+        config.gc.gc_num_epochs_to_keep_per_column =
+            columns.iter().map(|col_name| (col_name.to_string(), 30)).collect();
+        config.write_to_file(&config_path).unwrap();
+    };
+
+    set_gc_columns(&["TransactionResultForBlock", "OutcomeIds", "Receipts"]);
+    let config = load_config(temp_dir.path(), GenesisValidationMode::UnsafeFast).unwrap();
+    assert_eq!(config.client_config.gc.gc_num_epochs_to_keep_for_column("OutcomeIds"), 30);
+
+    for col_name in ["Block", "State", "NoSuchColumn"] {
+        set_gc_columns(&["TransactionResultForBlock", col_name]);
+        let err = load_config(temp_dir.path(), GenesisValidationMode::UnsafeFast).unwrap_err();
+        assert!(err.to_string().contains(col_name), "{err}");
+    }
+}
+
 #[test]
 fn test_create_testnet_configs() {
     let num_shards = 4;
@@ -1492,3 +1646,27 @@ fn test_create_testnet_configs() {
     assert_eq!(genesis.config.validators.len() as u64, num_shards);
     assert_eq!(genesis.config.shard_layout.num_shards(), num_shards);
 }
+
+#[test]
+fn test_read_key_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("node_key.json");
+    let secret_key = near_crypto::SecretKey::from_random(KeyType::ED25519);
+    // Old node key files have an empty account id.
+    std::fs::write(
+        &path,
+        serde_json::json!({
+            "account_id": "",
+            "public_key": secret_key.public_key(),
+            "secret_key": secret_key,
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let key = read_key_file(&path, None).unwrap();
+    assert_eq!(secret_key, key.secret_key);
+
+    key.encrypt("passphrase").write_to_file(&path).unwrap();
+    assert_eq!(secret_key, read_key_file(&path, Some("passphrase")).unwrap().secret_key);
+    assert!(read_key_file(&path, None).is_err());
+}
@@ -25,6 +25,7 @@ mod metrics;
 pub mod migrations;
 mod runtime;
 mod shard_tracker;
+pub mod state_sync;
 
 pub fn get_default_home() -> PathBuf {
     if let Ok(near_home) = std::env::var("NEAR_HOME") {
@@ -183,10 +184,25 @@ pub fn start_with_config_and_synchronization(
     let network_adapter = Arc::new(NetworkRecipient::default());
     let adv = near_client::adversarial::Controls::new(config.client_config.archive);
 
+    // With split storage, view client reads chain data through hot storage
+    // into cold one so that it can serve archival data which has already
+    // been garbage collected from hot storage.
+    #[cfg(feature = "cold_store")]
+    let view_chain_store = store.get_split_store();
+    #[cfg(not(feature = "cold_store"))]
+    let view_chain_store = None;
+
+    let state_sync_dump_handle = state_sync::spawn_state_sync_dump(
+        &config,
+        store.get_store(Temperature::Hot),
+        runtime.clone(),
+    );
+
     let view_client = start_view_client(
         config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
         chain_genesis.clone(),
         runtime.clone(),
+        view_chain_store,
         network_adapter.clone(),
         config.client_config.clone(),
         adv.clone(),
@@ -244,12 +260,10 @@ pub fn start_with_config_and_synchronization(
 
     trace!(target: "diagnostic", key="log", "Starting NEAR node with diagnostic activated");
 
-    Ok(NearNode {
-        client: client_actor,
-        view_client,
-        rpc_servers,
-        arbiters: vec![client_arbiter_handle],
-    })
+    let mut arbiters = vec![client_arbiter_handle];
+    arbiters.extend(state_sync_dump_handle);
+
+    Ok(NearNode { client: client_actor, view_client, rpc_servers, arbiters })
 }
 
 pub struct RecompressOpts {
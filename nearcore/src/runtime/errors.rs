@@ -83,6 +83,18 @@ impl QueryError {
         }
     }
 
+    pub fn from_view_accounts_error(
+        error: node_runtime::state_viewer::errors::ViewAccountsError,
+        block_height: near_primitives::types::BlockHeight,
+        block_hash: near_primitives::hash::CryptoHash,
+    ) -> Self {
+        match error {
+            node_runtime::state_viewer::errors::ViewAccountsError::InternalError {
+                error_message,
+            } => Self::InternalError { error_message, block_height, block_hash },
+        }
+    }
+
     pub fn from_view_access_key_error(
         error: node_runtime::state_viewer::errors::ViewAccessKeyError,
         block_height: near_primitives::types::BlockHeight,
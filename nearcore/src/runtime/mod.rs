@@ -5,7 +5,9 @@ use crate::NearConfig;
 use borsh::ser::BorshSerialize;
 use borsh::BorshDeserialize;
 use errors::FromStateViewerErrors;
-use near_chain::types::{ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo};
+use near_chain::types::{
+    ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, SimulateTransactionResult,
+};
 use near_chain::{Error, RuntimeAdapter};
 use near_chain_configs::{
     Genesis, GenesisConfig, ProtocolConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
@@ -24,7 +26,7 @@ use near_primitives::epoch_manager::block_info::BlockInfo;
 use near_primitives::epoch_manager::EpochConfig;
 use near_primitives::errors::{InvalidTxError, RuntimeError, StorageError};
 use near_primitives::hash::{hash, CryptoHash};
-use near_primitives::receipt::Receipt;
+use near_primitives::receipt::{Receipt, ReceiptEnum};
 use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
 use near_primitives::sandbox::state_patch::SandboxStatePatch;
@@ -35,6 +37,7 @@ use near_primitives::state_part::PartId;
 use near_primitives::state_record::{state_record_to_account_id, StateRecord};
 use near_primitives::syncing::{get_num_state_parts, STATE_PART_MEMORY_LIMIT};
 use near_primitives::transaction::SignedTransaction;
+use near_primitives::trie_key::TrieKey;
 use near_primitives::types::validator_stake::ValidatorStakeIter;
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, CompiledContractCache, EpochHeight, EpochId,
@@ -43,8 +46,8 @@ use near_primitives::types::{
 };
 use near_primitives::version::ProtocolVersion;
 use near_primitives::views::{
-    AccessKeyInfoView, CallResult, QueryRequest, QueryResponse, QueryResponseKind, ViewApplyState,
-    ViewStateResult,
+    AccessKeyInfoView, AccountsWithPrefixView, CallResult, CallTraceView, QueryRequest,
+    QueryResponse, QueryResponseKind, ViewApplyState, ViewStateResult,
 };
 use near_store::flat_state::ChainAccessForFlatStorage;
 use near_store::flat_state::{
@@ -52,9 +55,10 @@ use near_store::flat_state::{
 };
 use near_store::split_state::get_delayed_receipts;
 use near_store::{
-    get_genesis_hash, get_genesis_state_roots, set_genesis_hash, set_genesis_state_roots,
-    ApplyStatePartResult, DBCol, PartialStorage, ShardTries, Store, StoreCompiledContractCache,
-    StoreUpdate, Trie, TrieConfig, WrappedTrieChanges,
+    get_delayed_receipt_indices, get_genesis_hash, get_genesis_state_roots, set, set_genesis_hash,
+    set_genesis_state_roots, ApplyStatePartResult, DBCol, PartialStorage, ShardTries, Store,
+    StoreCompiledContractCache, StoreUpdate, Trie, TrieConfig, TrieMemoryOverlayStorage,
+    TrieUpdate, WrappedTrieChanges,
 };
 use near_vm_runner::precompile_contract;
 use node_runtime::adapter::ViewRuntimeAdapter;
@@ -64,9 +68,12 @@ use node_runtime::{
     validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
     ValidatorAccountsUpdate,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::num::NonZeroU64;
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::{Arc, RwLockReadGuard, RwLockWriteGuard};
 use std::time::Instant;
 use tracing::{debug, error, info, warn};
@@ -75,6 +82,9 @@ pub mod errors;
 
 const STATE_DUMP_FILE: &str = "state_dump";
 const GENESIS_ROOTS_FILE: &str = "genesis_roots";
+/// Maximum number of consecutive blocks in which the receipts of a simulated
+/// transaction are executed before the simulation is aborted.
+const MAX_SIMULATED_BLOCKS: u64 = 128;
 
 /// Defines Nightshade state transition and validator rotation.
 /// TODO: this possibly should be merged with the runtime cargo or at least reconciled on the interfaces.
@@ -496,6 +506,7 @@ impl NightshadeRuntime {
                 is_first_block_of_version,
                 is_first_block_with_chunk_of_version,
             },
+            verify_signatures: true,
         };
 
         let instant = Instant::now();
@@ -854,6 +865,8 @@ impl RuntimeAdapter for NightshadeRuntime {
                     // Verifying the transaction is on the same chain and hasn't expired yet.
                     if chain_validate(&tx) {
                         // Verifying the validity of the transaction based on the current state.
+                        // Signatures have been verified already when the transactions were added
+                        // to the pool (as a batch where possible, see `Client::process_txs`).
                         match verify_and_charge_transaction(
                             runtime_config,
                             &mut state_update,
@@ -1102,8 +1115,9 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
-            QueryRequest::CallFunction { account_id, method_name, args } => {
+            QueryRequest::CallFunction { account_id, method_name, args, trace } => {
                 let mut logs = vec![];
+                let mut call_trace = CallTraceView::default();
                 let (epoch_height, current_protocol_version) = {
                     let epoch_manager = self.epoch_manager.read();
                     let epoch_info = epoch_manager.get_epoch_info(epoch_id).map_err(|err| {
@@ -1130,6 +1144,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                         method_name,
                         args.as_ref(),
                         &mut logs,
+                        trace.then(|| &mut call_trace),
                         &self.epoch_manager,
                         current_protocol_version,
                     )
@@ -1138,12 +1153,13 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::CallResult(CallResult {
                         result: call_function_result,
                         logs,
+                        trace: trace.then(|| call_trace),
                     }),
                     block_height,
                     block_hash: *block_hash,
                 })
             }
-            QueryRequest::ViewState { account_id, prefix, include_proof } => {
+            QueryRequest::ViewState { account_id, prefix, include_proof, start_after, limit } => {
                 let view_state_result = self
                     .view_state(
                         &shard_uid,
@@ -1151,6 +1167,8 @@ impl RuntimeAdapter for NightshadeRuntime {
                         account_id,
                         prefix.as_ref(),
                         *include_proof,
+                        start_after.as_deref(),
+                        *limit,
                     )
                     .map_err(|err| {
                         near_chain::near_chain_primitives::error::QueryError::from_view_state_error(
@@ -1165,6 +1183,27 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
+            QueryRequest::ViewAccountsWithPrefix { account_id_prefix, start_after } => {
+                let accounts = self
+                    .view_accounts_with_prefix(
+                        &shard_uid,
+                        *state_root,
+                        account_id_prefix,
+                        start_after.as_ref(),
+                    )
+                    .map_err(|err| {
+                        near_chain::near_chain_primitives::error::QueryError::from_view_accounts_error(
+                            err,
+                            block_height,
+                            *block_hash,
+                        )
+                    })?;
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::ViewAccountsWithPrefix(accounts),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
             QueryRequest::ViewAccessKeyList { account_id } => {
                 let access_key_list =
                     self.view_access_keys(&shard_uid, *state_root, account_id).map_err(|err| {
@@ -1207,6 +1246,191 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        block_height: BlockHeight,
+        block_timestamp: u64,
+        block_hash: &CryptoHash,
+        gas_price: Balance,
+        random_seed: CryptoHash,
+        state_roots: &HashMap<ShardId, StateRoot>,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<SimulateTransactionResult, Error> {
+        // State roots are given for the shards of the block, while the chunks
+        // are applied in the blocks following it.
+        let shard_layout = self.get_shard_layout(&self.get_epoch_id(block_hash)?)?;
+        let epoch_id = self.get_epoch_id_from_prev_block(block_hash)?;
+        let current_protocol_version = self.get_epoch_protocol_version(&epoch_id)?;
+        let mut apply_state = ApplyState {
+            block_height: block_height + 1,
+            prev_block_hash: *block_hash,
+            block_hash: CryptoHash::default(),
+            epoch_id,
+            epoch_height: self.get_epoch_height_from_prev_block(block_hash)?,
+            gas_price,
+            block_timestamp,
+            // Without a gas limit none of the receipts gets delayed.  Receipts
+            // which are already delayed are skipped, see below.
+            gas_limit: None,
+            random_seed,
+            current_protocol_version,
+            config: self.runtime_config_store.get_config(current_protocol_version).clone(),
+            cache: Some(Box::new(StoreCompiledContractCache::new(&self.store))),
+            is_new_chunk: true,
+            migration_data: Arc::clone(&self.migration_data),
+            migration_flags: MigrationFlags::default(),
+            verify_signatures: verify_signature,
+        };
+
+        let signer_shard_id =
+            account_id_to_shard_id(&transaction.transaction.signer_id, &shard_layout);
+        let mut state_roots = state_roots.clone();
+        // Trie nodes inserted by the chunks applied so far, which are never
+        // written to the store.
+        let mut trie_nodes: HashMap<ShardId, HashMap<CryptoHash, Arc<[u8]>>> = HashMap::new();
+        // Ids of receipts and data produced by the transaction, to tell them
+        // apart from other receipts executed in the same chunks.
+        let mut receipt_ids = HashSet::from([transaction.get_hash()]);
+        let mut data_ids = HashSet::new();
+        let mut transactions = vec![transaction.clone()];
+        let mut receipts: Vec<Receipt> = vec![];
+        let mut result =
+            SimulateTransactionResult { outcomes: vec![], receipts: vec![], state_changes: vec![] };
+        while !transactions.is_empty() || !receipts.is_empty() {
+            if apply_state.block_height > block_height + MAX_SIMULATED_BLOCKS {
+                return Err(Error::Other(format!(
+                    "transaction {} hasn't been executed in {} blocks",
+                    transaction.get_hash(),
+                    MAX_SIMULATED_BLOCKS
+                )));
+            }
+            let mut receipts_by_shard: BTreeMap<ShardId, Vec<Receipt>> = BTreeMap::new();
+            if !transactions.is_empty() {
+                receipts_by_shard.entry(signer_shard_id).or_default();
+            }
+            for receipt in receipts.drain(..) {
+                let shard_id = account_id_to_shard_id(&receipt.receiver_id, &shard_layout);
+                receipts_by_shard.entry(shard_id).or_default().push(receipt);
+            }
+
+            for (shard_id, shard_receipts) in receipts_by_shard {
+                let state_root =
+                    state_roots.get_mut(&shard_id).ok_or(Error::InvalidShardId(shard_id))?;
+                let shard_uid = ShardUId::from_shard_id_and_layout(shard_id, &shard_layout);
+                let overlay_trie = |nodes: &HashMap<CryptoHash, Arc<[u8]>>, root: StateRoot| {
+                    let storage = self.tries.get_view_trie_for_shard(shard_uid, root).storage;
+                    Trie::new(
+                        Box::new(TrieMemoryOverlayStorage::new(storage, nodes.clone())),
+                        root,
+                        None,
+                    )
+                };
+                let nodes = match trie_nodes.entry(shard_id) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        // Receipts delayed before the transaction would be
+                        // executed first, however many blocks it takes, so
+                        // they're removed from the queue instead.
+                        let nodes = entry.insert(HashMap::new());
+                        let mut state_update =
+                            TrieUpdate::new(Rc::new(overlay_trie(nodes, *state_root)));
+                        let mut indices = get_delayed_receipt_indices(&state_update)?;
+                        if indices.first_index < indices.next_available_index {
+                            indices.first_index = indices.next_available_index;
+                            set(&mut state_update, TrieKey::DelayedReceiptIndices, &indices);
+                            state_update.commit(StateChangeCause::UpdatedDelayedReceipts);
+                            let (trie_changes, _) = state_update.finalize()?;
+                            *state_root = trie_changes.new_root;
+                            nodes.extend(
+                                trie_changes
+                                    .insertions()
+                                    .iter()
+                                    .map(|change| (*change.hash(), Arc::from(change.payload()))),
+                            );
+                        }
+                        nodes
+                    }
+                };
+                let trie = overlay_trie(nodes, *state_root);
+                let shard_transactions = if shard_id == signer_shard_id {
+                    std::mem::take(&mut transactions)
+                } else {
+                    vec![]
+                };
+                let apply_result = self
+                    .runtime
+                    .apply(
+                        trie,
+                        &None,
+                        &apply_state,
+                        &shard_receipts,
+                        &shard_transactions,
+                        &self.epoch_manager,
+                        SandboxStatePatch::default(),
+                    )
+                    .map_err(|err| match err {
+                        RuntimeError::StorageError(err) => Error::StorageError(err),
+                        RuntimeError::ValidatorError(err) => err.into(),
+                        err => Error::Other(err.to_string()),
+                    })?;
+
+                *state_root = apply_result.state_root;
+                nodes.extend(
+                    apply_result
+                        .trie_changes
+                        .insertions()
+                        .iter()
+                        .map(|change| (*change.hash(), Arc::from(change.payload()))),
+                );
+                for outcome in apply_result.outcomes {
+                    if receipt_ids.contains(&outcome.id) {
+                        receipt_ids.extend(outcome.outcome.receipt_ids.iter().cloned());
+                        result.outcomes.push(outcome);
+                    }
+                }
+                for receipt in apply_result.outgoing_receipts {
+                    match &receipt.receipt {
+                        ReceiptEnum::Action(action_receipt)
+                            if receipt_ids.contains(&receipt.receipt_id) =>
+                        {
+                            data_ids.extend(
+                                action_receipt.output_data_receivers.iter().map(|r| r.data_id),
+                            );
+                            result.receipts.push(receipt.clone());
+                        }
+                        ReceiptEnum::Data(data_receipt)
+                            if data_ids.contains(&data_receipt.data_id) =>
+                        {
+                            receipt_ids.insert(receipt.receipt_id);
+                        }
+                        _ => continue,
+                    }
+                    receipts.push(receipt);
+                }
+                for mut state_changes in apply_result.state_changes {
+                    state_changes.changes.retain(|change| match &change.cause {
+                        StateChangeCause::TransactionProcessing { tx_hash } => {
+                            receipt_ids.contains(tx_hash)
+                        }
+                        StateChangeCause::ActionReceiptProcessingStarted { receipt_hash }
+                        | StateChangeCause::ActionReceiptGasReward { receipt_hash }
+                        | StateChangeCause::ReceiptProcessing { receipt_hash }
+                        | StateChangeCause::PostponedReceipt { receipt_hash } => {
+                            receipt_ids.contains(receipt_hash)
+                        }
+                        _ => false,
+                    });
+                    if !state_changes.changes.is_empty() {
+                        result.state_changes.push(state_changes);
+                    }
+                }
+            }
+            apply_state.block_height += 1;
+        }
+        Ok(result)
+    }
+
     /// Returns StorageError when storage is inconsistent.
     /// This is possible with the used isolation level + running ViewClient in a separate thread
     /// `block_hash` is a block whose `prev_state_root` is `state_root`
@@ -1478,6 +1702,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        trace: Option<&mut CallTraceView>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, node_runtime::state_viewer::errors::CallFunctionError> {
@@ -1499,6 +1724,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             method_name,
             args,
             logs,
+            trace,
             epoch_info_provider,
         )
     }
@@ -1532,9 +1758,29 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         account_id: &AccountId,
         prefix: &[u8],
         include_proof: bool,
+        start_after: Option<&[u8]>,
+        limit: Option<NonZeroU64>,
     ) -> Result<ViewStateResult, node_runtime::state_viewer::errors::ViewStateError> {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
-        self.trie_viewer.view_state(&state_update, account_id, prefix, include_proof)
+        self.trie_viewer.view_state(
+            &state_update,
+            account_id,
+            prefix,
+            include_proof,
+            start_after,
+            limit,
+        )
+    }
+
+    fn view_accounts_with_prefix(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        account_id_prefix: &str,
+        start_after: Option<&AccountId>,
+    ) -> Result<AccountsWithPrefixView, node_runtime::state_viewer::errors::ViewAccountsError> {
+        let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
+        self.trie_viewer.view_accounts_with_prefix(&state_update, account_id_prefix, start_after)
     }
 }
 
@@ -1549,12 +1795,15 @@ mod test {
 
     use crate::config::{GenesisExt, TESTING_INIT_BALANCE, TESTING_INIT_STAKE};
     use near_chain_configs::DEFAULT_GC_NUM_EPOCHS_TO_KEEP;
-    use near_crypto::{InMemorySigner, KeyType, Signer};
+    use near_crypto::{InMemorySigner, KeyType, Signature, Signer};
     use near_epoch_manager::EpochManagerAdapter;
     use near_o11y::testonly::init_test_logger;
     use near_primitives::block::Tip;
     use near_primitives::challenge::SlashedValidator;
-    use near_primitives::transaction::{Action, DeleteAccountAction, StakeAction, TransferAction};
+    use near_primitives::receipt::DelayedReceiptIndices;
+    use near_primitives::transaction::{
+        Action, DeleteAccountAction, ExecutionStatus, StakeAction, TransferAction,
+    };
     use near_primitives::types::{
         BlockHeightDelta, Nonce, ValidatorId, ValidatorInfoIdentifier, ValidatorKickoutReason,
     };
@@ -1567,7 +1816,6 @@ mod test {
 
     use super::*;
 
-    use near_primitives::trie_key::TrieKey;
     use primitive_types::U256;
 
     fn stake(
@@ -3038,6 +3286,137 @@ mod test {
         assert_eq!(env.last_proposals[0].stake(), TESTING_INIT_STAKE - 10);
     }
 
+    /// Simulated transaction has its receipts executed on other shards, while
+    /// the state stays the same.
+    #[test]
+    fn test_simulate_transaction() {
+        let validators: Vec<AccountId> = vec!["test1".parse().unwrap(), "test2".parse().unwrap()];
+        let mut env = TestEnv::new(vec![validators.clone(), validators.clone()], 4, false);
+        env.step(vec![vec![], vec![]], vec![true, true], ChallengesResult::default());
+        let signer = InMemorySigner::from_seed(
+            validators[0].clone(),
+            KeyType::ED25519,
+            validators[0].as_ref(),
+        );
+        let transaction = SignedTransaction::send_money(
+            1,
+            validators[0].clone(),
+            validators[1].clone(),
+            &signer,
+            100,
+            CryptoHash::default(),
+        );
+        let state_roots = env
+            .state_roots
+            .iter()
+            .enumerate()
+            .map(|(shard_id, state_root)| (shard_id as ShardId, *state_root))
+            .collect();
+        let simulate = |transaction: &SignedTransaction, verify_signature: bool| {
+            env.runtime.simulate_transaction(
+                env.head.height,
+                env.time,
+                &env.head.last_block_hash,
+                env.runtime.genesis_config.min_gas_price,
+                CryptoHash::default(),
+                &state_roots,
+                transaction,
+                verify_signature,
+            )
+        };
+
+        let result = simulate(&transaction, true).unwrap();
+        assert_eq!(result.outcomes.len(), 2);
+        assert_eq!(result.outcomes[0].id, transaction.get_hash());
+        assert_eq!(result.outcomes[0].outcome.receipt_ids, vec![result.outcomes[1].id]);
+        assert!(matches!(result.outcomes[1].outcome.status, ExecutionStatus::SuccessValue(_)));
+        assert!(result.state_changes.iter().any(|state_changes| state_changes.trie_key
+            == TrieKey::Account { account_id: validators[1].clone() }));
+        let account = env.view_account(&validators[1]);
+        assert_eq!(account.amount, TESTING_INIT_BALANCE - TESTING_INIT_STAKE);
+
+        // Transactions without a signature can only be simulated when the
+        // signature isn't verified.
+        let unsigned_transaction = SignedTransaction::new(
+            Signature::empty(KeyType::ED25519),
+            transaction.transaction.clone(),
+        );
+        assert!(simulate(&unsigned_transaction, true).is_err());
+        let result = simulate(&unsigned_transaction, false).unwrap();
+        assert_eq!(result.outcomes.len(), 2);
+    }
+
+    /// Checks that receipts delayed before the simulated transaction aren't
+    /// executed in the simulation.
+    #[test]
+    fn test_simulate_transaction_skips_delayed_receipts() {
+        let validators: Vec<AccountId> = vec!["test1".parse().unwrap(), "test2".parse().unwrap()];
+        let mut env = TestEnv::new(vec![validators.clone(), validators.clone()], 4, false);
+        env.step(vec![vec![], vec![]], vec![true, true], ChallengesResult::default());
+        let signer = InMemorySigner::from_seed(
+            validators[0].clone(),
+            KeyType::ED25519,
+            validators[0].as_ref(),
+        );
+        let transaction = SignedTransaction::send_money(
+            1,
+            validators[0].clone(),
+            validators[1].clone(),
+            &signer,
+            100,
+            CryptoHash::default(),
+        );
+        let mut state_roots: HashMap<ShardId, StateRoot> = env
+            .state_roots
+            .iter()
+            .enumerate()
+            .map(|(shard_id, state_root)| (shard_id as ShardId, *state_root))
+            .collect();
+
+        // Delay a refund to the receiver of the transaction.
+        let epoch_id = env.runtime.get_epoch_id_from_prev_block(&env.head.last_block_hash).unwrap();
+        let shard_layout = env.runtime.get_shard_layout(&epoch_id).unwrap();
+        let shard_id = account_id_to_shard_id(&validators[1], &shard_layout);
+        let shard_uid = ShardUId::from_shard_id_and_layout(shard_id, &shard_layout);
+        let mut state_update = env.runtime.tries.new_trie_update(shard_uid, state_roots[&shard_id]);
+        let delayed_receipt = Receipt::new_balance_refund(&validators[1], 1000);
+        set(&mut state_update, TrieKey::DelayedReceipt { index: 0 }, &delayed_receipt);
+        let indices = DelayedReceiptIndices { first_index: 0, next_available_index: 1 };
+        set(&mut state_update, TrieKey::DelayedReceiptIndices, &indices);
+        state_update.commit(StateChangeCause::UpdatedDelayedReceipts);
+        let (trie_changes, _) = state_update.finalize().unwrap();
+        let mut store_update = env.runtime.store.store_update();
+        let state_root = env.runtime.tries.apply_all(&trie_changes, shard_uid, &mut store_update);
+        store_update.commit().unwrap();
+        state_roots.insert(shard_id, state_root);
+
+        let result = env
+            .runtime
+            .simulate_transaction(
+                env.head.height,
+                env.time,
+                &env.head.last_block_hash,
+                env.runtime.genesis_config.min_gas_price,
+                CryptoHash::default(),
+                &state_roots,
+                &transaction,
+                true,
+            )
+            .unwrap();
+        assert_eq!(result.outcomes.len(), 2);
+        assert!(matches!(result.outcomes[1].outcome.status, ExecutionStatus::SuccessValue(_)));
+        let trie_key = TrieKey::Account { account_id: validators[1].clone() };
+        let account_change = result
+            .state_changes
+            .iter()
+            .filter(|state_changes| state_changes.trie_key == trie_key)
+            .flat_map(|state_changes| state_changes.changes.iter())
+            .last()
+            .unwrap();
+        let account = Account::try_from_slice(account_change.data.as_ref().unwrap()).unwrap();
+        assert_eq!(account.amount(), TESTING_INIT_BALANCE - TESTING_INIT_STAKE + 100);
+    }
+
     #[test]
     fn test_insufficient_stake() {
         let num_nodes = 2;
@@ -0,0 +1,134 @@
+//! Dumping state parts to an external storage.
+//!
+//! A node configured with `state_sync.dump` writes parts of the state of the
+//! shards it tracks, as of the beginning of each epoch, to the configured
+//! location.  Other nodes can then download the parts from there during state
+//! sync (see `near_client::sync::external`) instead of requesting them from
+//! peers.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_rt::{Arbiter, ArbiterHandle};
+use near_chain::types::RuntimeAdapter;
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_chain_configs::ExternalStorageLocation;
+use near_client::sync::external::{external_storage_location, ExternalConnection};
+use near_primitives::hash::CryptoHash;
+use near_primitives::state_part::PartId;
+use near_primitives::syncing::get_num_state_parts;
+use near_primitives::types::{AccountId, EpochId};
+use near_store::Store;
+
+use crate::NearConfig;
+
+/// How often to check whether a new epoch has started.
+const CHECK_PERIOD: Duration = Duration::from_secs(10);
+
+/// Starts dumping state parts in a new arbiter if configured to.
+pub fn spawn_state_sync_dump(
+    config: &NearConfig,
+    store: Store,
+    runtime: Arc<dyn RuntimeAdapter>,
+) -> Option<ArbiterHandle> {
+    let location = config.client_config.state_sync.dump.as_ref()?;
+    tracing::info!(target: "state_sync_dump", ?location, "Spawning state sync dump");
+    let dumper = StateSyncDumper {
+        chain_id: config.client_config.chain_id.clone(),
+        me: config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
+        chain_store: ChainStore::new(
+            store,
+            config.genesis.config.genesis_height,
+            config.client_config.save_trie_changes,
+        ),
+        runtime,
+        location: location.clone(),
+    };
+    let arbiter = Arbiter::new();
+    assert!(arbiter.spawn(dumper.run()));
+    Some(arbiter.handle())
+}
+
+struct StateSyncDumper {
+    chain_id: String,
+    me: Option<AccountId>,
+    chain_store: ChainStore,
+    runtime: Arc<dyn RuntimeAdapter>,
+    location: ExternalStorageLocation,
+}
+
+impl StateSyncDumper {
+    async fn run(mut self) {
+        let conn = ExternalConnection::new(&self.location);
+        let mut last_dumped_epoch = None;
+        loop {
+            match self.dump_current_epoch(&conn, &last_dumped_epoch).await {
+                Ok(Some(epoch_id)) => last_dumped_epoch = Some(epoch_id),
+                Ok(None) => {}
+                // Parts which have been dumped already are skipped on retry.
+                Err(err) => tracing::warn!(target: "state_sync_dump", ?err, "Failed to dump state"),
+            }
+            tokio::time::sleep(CHECK_PERIOD).await;
+        }
+    }
+
+    /// Dumps state parts as of the beginning of the current epoch unless
+    /// they have been dumped already.  Returns id of the epoch if the parts
+    /// have been dumped.
+    async fn dump_current_epoch(
+        &mut self,
+        conn: &ExternalConnection,
+        last_dumped_epoch: &Option<EpochId>,
+    ) -> anyhow::Result<Option<EpochId>> {
+        let head = match self.chain_store.final_head() {
+            Ok(head) => head,
+            // The node hasn’t finalised any block yet.
+            Err(_) => return Ok(None),
+        };
+        if last_dumped_epoch.as_ref() == Some(&head.epoch_id) {
+            return Ok(None);
+        }
+        let sync_hash = *self.runtime.get_block_info(&head.last_block_hash)?.epoch_first_block();
+        let sync_block = self.chain_store.get_block(&sync_hash)?;
+        let sync_prev_hash = *sync_block.header().prev_hash();
+        if sync_prev_hash == CryptoHash::default() {
+            // Nothing to sync to in the first epoch.
+            return Ok(Some(head.epoch_id));
+        }
+        let sync_prev_block = self.chain_store.get_block(&sync_prev_hash)?;
+        let epoch_id = sync_block.header().epoch_id().clone();
+
+        for (shard_id, chunk) in sync_prev_block.chunks().iter().enumerate() {
+            let shard_id = shard_id as u64;
+            if !self.runtime.cares_about_shard(self.me.as_ref(), &sync_prev_hash, shard_id, true) {
+                continue;
+            }
+            let state_root = chunk.prev_state_root();
+            let state_root_node =
+                self.runtime.get_state_root_node(shard_id, &sync_prev_hash, &state_root)?;
+            let num_parts = get_num_state_parts(state_root_node.memory_usage);
+            tracing::info!(target: "state_sync_dump", ?epoch_id, %sync_hash, shard_id, num_parts, "Dumping state parts");
+            for part_id in 0..num_parts {
+                let key = external_storage_location(
+                    &self.chain_id,
+                    &epoch_id,
+                    shard_id,
+                    part_id,
+                    num_parts,
+                );
+                if conn.exists(&key).await? {
+                    continue;
+                }
+                let state_part = self.runtime.obtain_state_part(
+                    shard_id,
+                    &sync_prev_hash,
+                    &state_root,
+                    PartId::new(part_id, num_parts),
+                )?;
+                conn.put(&key, state_part).await?;
+            }
+        }
+        tracing::info!(target: "state_sync_dump", ?epoch_id, %sync_hash, "Dumped state parts");
+        Ok(Some(head.epoch_id))
+    }
+}
@@ -84,6 +84,7 @@ impl NeardCmd {
 
         match neard_cmd.subcmd {
             NeardSubCommand::Init(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::Keys(cmd) => cmd.run(&home_dir)?,
             NeardSubCommand::Localnet(cmd) => cmd.run(&home_dir),
             NeardSubCommand::Run(cmd) => cmd.run(
                 &home_dir,
@@ -172,6 +173,8 @@ pub(super) enum NeardSubCommand {
     /// Sets up local configuration with all necessary files (validator key, node key, genesis and
     /// config)
     Localnet(LocalnetCmd),
+    /// Manages encryption of node and validator key files.
+    Keys(KeysCmd),
     /// View DB state.
     #[clap(name = "view-state", alias = "view_state")]
     StateViewer(StateViewerCommand),
@@ -270,6 +273,13 @@ pub(super) struct InitCmd {
     /// from genesis configuration will be taken.
     #[clap(long)]
     max_gas_burnt_view: Option<Gas>,
+    /// Encrypt generated node and validator keys with passphrase read from
+    /// ‘--key-passphrase-file’ or from NEAR_KEY_PASSPHRASE environment variable.
+    #[clap(long)]
+    encrypt_keys: bool,
+    /// File holding passphrase for ‘--encrypt-keys’.
+    #[clap(long)]
+    key_passphrase_file: Option<PathBuf>,
 }
 
 /// Warns if unsupported build of the executable is used on mainnet or testnet.
@@ -335,6 +345,21 @@ impl InitCmd {
             self.max_gas_burnt_view,
         ) {
             error!("Failed to initialize configs: {:#}", e);
+            return;
+        }
+
+        if self.encrypt_keys {
+            let result = required_key_passphrase(self.key_passphrase_file.as_deref()).and_then(
+                |passphrase| {
+                    for path in default_key_files(home_dir)? {
+                        encrypt_key_file(&path, &passphrase)?;
+                    }
+                    Ok(())
+                },
+            );
+            if let Err(e) = result {
+                error!("Failed to encrypt keys: {:#}", e);
+            }
         }
     }
 }
@@ -382,6 +407,10 @@ pub(super) struct RunCmd {
     /// configuration will be taken.
     #[clap(long)]
     max_gas_burnt_view: Option<Gas>,
+    /// File holding passphrase of encrypted node and validator key files.  If
+    /// not given, NEAR_KEY_PASSPHRASE environment variable is used.
+    #[clap(long)]
+    key_passphrase_file: Option<PathBuf>,
 }
 
 impl RunCmd {
@@ -393,7 +422,14 @@ impl RunCmd {
         o11y_opts: &near_o11y::Options,
     ) {
         // Load configs from home.
-        let mut near_config = nearcore::config::load_config(&home_dir, genesis_validation)
+        let mut near_config = nearcore::config::key_passphrase(self.key_passphrase_file.as_deref())
+            .and_then(|passphrase| {
+                nearcore::config::load_config_with_passphrase(
+                    &home_dir,
+                    genesis_validation,
+                    passphrase.as_deref(),
+                )
+            })
             .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
 
         check_release_build(&near_config.client_config.chain_id);
@@ -532,6 +568,159 @@ async fn wait_for_interrupt_signal(home_dir: &Path, mut rx_crash: Receiver<()>)
     }
 }
 
+#[derive(Parser)]
+pub(super) struct KeysCmd {
+    /// Key files to operate on.  By default, node and validator key files
+    /// configured in config.json are used.
+    #[clap(long = "key-file")]
+    key_files: Vec<PathBuf>,
+    /// File holding passphrase of encrypted key files.  If not given,
+    /// NEAR_KEY_PASSPHRASE environment variable is used.
+    #[clap(long)]
+    passphrase_file: Option<PathBuf>,
+    #[clap(subcommand)]
+    subcmd: KeysSubCommand,
+}
+
+#[derive(Parser)]
+enum KeysSubCommand {
+    /// Encrypts plaintext key files.
+    Encrypt,
+    /// Decrypts encrypted key files.
+    Decrypt,
+    /// Re-encrypts key files with a new passphrase.
+    Rotate {
+        /// File holding the new passphrase.  If not given,
+        /// NEAR_NEW_KEY_PASSPHRASE environment variable is used.
+        #[clap(long)]
+        new_passphrase_file: Option<PathBuf>,
+    },
+    /// Prints account ids and public keys of key files.  Doesn't need the
+    /// passphrase.
+    PublicKeys,
+}
+
+/// Environment variable holding the new passphrase for `neard keys rotate`.
+const NEW_KEY_PASSPHRASE_ENV: &str = "NEAR_NEW_KEY_PASSPHRASE";
+
+/// Account id and public key are stored in plaintext in both plaintext and
+/// encrypted key files.
+#[derive(serde::Deserialize)]
+struct PublicKeyFile {
+    account_id: String,
+    public_key: near_crypto::PublicKey,
+}
+
+/// Returns node and validator key files configured in config.json which exist.
+fn default_key_files(home_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let config =
+        nearcore::config::Config::from_file(&home_dir.join(nearcore::config::CONFIG_FILENAME))?;
+    Ok([config.node_key_file, config.validator_key_file]
+        .into_iter()
+        .map(|file| home_dir.join(file))
+        .filter(|path| path.exists())
+        .collect())
+}
+
+fn required_key_passphrase(passphrase_file: Option<&Path>) -> anyhow::Result<String> {
+    nearcore::config::key_passphrase(passphrase_file)?.with_context(|| {
+        format!(
+            "Passphrase must be given either in a file or in {} environment variable",
+            nearcore::config::KEY_PASSPHRASE_ENV
+        )
+    })
+}
+
+/// Replaces the key file via a temporary file, so that the key is never lost
+/// if writing gets interrupted.
+fn replace_key_file(
+    path: &Path,
+    write: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    write(&tmp_path)
+        .and_then(|()| std::fs::rename(&tmp_path, path))
+        .with_context(|| format!("Failed writing {}", path.display()))
+}
+
+fn encrypt_key_file(path: &Path, passphrase: &str) -> anyhow::Result<()> {
+    if near_crypto::KeyFile::is_encrypted(path)? {
+        info!(target: "neard", "{} is already encrypted", path.display());
+        return Ok(());
+    }
+    let key = nearcore::config::read_key_file(path, None)
+        .with_context(|| format!("Failed reading {}", path.display()))?;
+    let encrypted = key.encrypt(passphrase);
+    replace_key_file(path, |path| encrypted.write_to_file(path))?;
+    info!(target: "neard", "Encrypted {}", path.display());
+    Ok(())
+}
+
+impl KeysCmd {
+    pub(super) fn run(self, home_dir: &Path) -> anyhow::Result<()> {
+        let key_files =
+            if self.key_files.is_empty() { default_key_files(home_dir)? } else { self.key_files };
+        let passphrase = || required_key_passphrase(self.passphrase_file.as_deref());
+        let read_key = |path: &Path, passphrase: &str| {
+            nearcore::config::read_key_file(path, Some(passphrase))
+                .with_context(|| format!("Failed reading {}", path.display()))
+        };
+        match &self.subcmd {
+            KeysSubCommand::Encrypt => {
+                let passphrase = passphrase()?;
+                for path in &key_files {
+                    encrypt_key_file(path, &passphrase)?;
+                }
+            }
+            KeysSubCommand::Decrypt => {
+                let passphrase = passphrase()?;
+                for path in &key_files {
+                    let key = read_key(path, &passphrase)?;
+                    replace_key_file(path, |path| key.write_to_file(path))?;
+                    info!(target: "neard", "Decrypted {}", path.display());
+                }
+            }
+            KeysSubCommand::Rotate { new_passphrase_file } => {
+                let passphrase = passphrase()?;
+                let new_passphrase = match new_passphrase_file {
+                    Some(_) => required_key_passphrase(new_passphrase_file.as_deref())?,
+                    None => std::env::var(NEW_KEY_PASSPHRASE_ENV).with_context(|| {
+                        format!(
+                            "New passphrase must be given either in a file or in {} environment variable",
+                            NEW_KEY_PASSPHRASE_ENV
+                        )
+                    })?,
+                };
+                for path in &key_files {
+                    let encrypted = read_key(path, &passphrase)?.encrypt(&new_passphrase);
+                    replace_key_file(path, |path| encrypted.write_to_file(path))?;
+                    info!(target: "neard", "Re-encrypted {}", path.display());
+                }
+            }
+            KeysSubCommand::PublicKeys => {
+                for path in &key_files {
+                    let file = File::open(path)
+                        .with_context(|| format!("Failed reading {}", path.display()))?;
+                    let key: PublicKeyFile = serde_json::from_reader(BufReader::new(file))?;
+                    let kind = if near_crypto::KeyFile::is_encrypted(path)? {
+                        "encrypted"
+                    } else {
+                        "plaintext"
+                    };
+                    println!(
+                        "{}: {} {} ({})",
+                        path.display(),
+                        key.account_id,
+                        key.public_key,
+                        kind
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Parser)]
 pub(super) struct LocalnetCmd {
     /// Number of non-validators to initialize the localnet with.
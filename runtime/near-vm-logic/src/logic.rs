@@ -11,6 +11,7 @@ use near_primitives::checked_feature;
 use near_primitives::config::ViewConfig;
 use near_primitives::runtime::fees::RuntimeFeesConfig;
 use near_primitives::version::is_implicit_account_creation_enabled;
+use near_primitives::views::{CallTraceView, HostFunctionCallView};
 use near_primitives_core::config::ExtCosts::*;
 use near_primitives_core::config::{ActionCosts, ExtCosts, VMConfig};
 use near_primitives_core::profile::ProfileData;
@@ -68,6 +69,84 @@ pub struct VMLogic<'a> {
 
     /// Handles the receipts generated through execution.
     receipt_manager: ReceiptManager,
+
+    /// Host function calls made so far, if tracing is enabled by the view
+    /// config.
+    trace: Option<Trace>,
+}
+
+/// Maximum number of host function calls recorded when tracing a call.
+pub(crate) const MAX_TRACE_CALLS: usize = 10_000;
+/// Maximum total size of names, arguments and storage keys of host function
+/// calls recorded when tracing a call.
+pub(crate) const MAX_TRACE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Host function calls recorded when tracing a call.  Once the trace reaches
+/// [`MAX_TRACE_CALLS`] calls or [`MAX_TRACE_BYTES`] bytes, calls and storage
+/// keys which don't fit are left out and the trace is marked as truncated.
+#[derive(Default)]
+struct Trace {
+    view: CallTraceView,
+    /// Total size of names, arguments and storage keys recorded so far.
+    bytes: usize,
+    /// Whether the host function being called was recorded, i.e. whether
+    /// storage keys it accesses are added to the last recorded call.
+    recording: bool,
+}
+
+impl Trace {
+    /// Reserves `len` bytes of the trace, marking it as truncated if they
+    /// don't fit.
+    fn reserve(&mut self, len: usize) -> bool {
+        if self.bytes + len > MAX_TRACE_BYTES {
+            self.view.truncated = true;
+            return false;
+        }
+        self.bytes += len;
+        true
+    }
+
+    fn start_call(&mut self, name: &str, args: String) {
+        if self.view.calls.len() >= MAX_TRACE_CALLS {
+            self.view.truncated = true;
+            self.recording = false;
+            return;
+        }
+        self.recording = self.reserve(name.len() + args.len());
+        if self.recording {
+            self.view.calls.push(HostFunctionCallView {
+                name: name.to_string(),
+                args,
+                gas_burnt: 0,
+                storage_reads: vec![],
+                storage_writes: vec![],
+            });
+        }
+    }
+
+    fn end_call(&mut self, gas_burnt: Gas) {
+        if std::mem::take(&mut self.recording) {
+            if let Some(call) = self.view.calls.last_mut() {
+                call.gas_burnt = gas_burnt;
+            }
+        }
+    }
+
+    fn storage_read(&mut self, key: &[u8]) {
+        if self.recording && self.reserve(key.len()) {
+            if let Some(call) = self.view.calls.last_mut() {
+                call.storage_reads.push(key.to_vec().into());
+            }
+        }
+    }
+
+    fn storage_write(&mut self, key: &[u8]) {
+        if self.recording && self.reserve(key.len()) {
+            if let Some(call) = self.view.calls.last_mut() {
+                call.storage_writes.push(key.to_vec().into());
+            }
+        }
+    }
 }
 
 /// Promises API allows to create a DAG-structure that defines dependencies between smart contract
@@ -139,9 +218,13 @@ impl<'a> VMLogic<'a> {
         let current_account_balance = context.account_balance + context.attached_deposit;
         let current_storage_usage = context.storage_usage;
         let max_gas_burnt = match context.view_config {
-            Some(ViewConfig { max_gas_burnt: max_gas_burnt_view }) => max_gas_burnt_view,
+            Some(ViewConfig { max_gas_burnt: max_gas_burnt_view, .. }) => max_gas_burnt_view,
             None => config.limit_config.max_gas_burnt,
         };
+        let trace = match context.view_config {
+            Some(ViewConfig { trace: true, .. }) => Some(Trace::default()),
+            _ => None,
+        };
 
         let current_account_locked_balance = context.account_locked_balance;
         let gas_counter = GasCounter::new(
@@ -169,6 +252,7 @@ impl<'a> VMLogic<'a> {
             total_log_length: 0,
             current_protocol_version,
             receipt_manager: ReceiptManager::default(),
+            trace,
         }
     }
 
@@ -182,6 +266,49 @@ impl<'a> VMLogic<'a> {
         &self.receipt_manager.action_receipts
     }
 
+    /// Whether host function calls are recorded.
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Calls host function `func` named `name` with arguments `args`.  The
+    /// call, the gas it burnt and storage keys it accessed are recorded if
+    /// tracing is enabled and the trace isn't full.
+    pub fn trace_host_function<T>(
+        &mut self,
+        name: &str,
+        args: &[(&str, u64)],
+        func: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let trace = match self.trace.as_mut() {
+            Some(trace) => trace,
+            None => return func(self),
+        };
+        let args = args.iter().map(|(arg, value)| format!("{}: {}", arg, value));
+        trace.start_call(name, args.collect::<Vec<_>>().join(", "));
+        let burnt_gas = self.gas_counter.burnt_gas();
+        let result = func(self);
+        let gas_burnt = self.gas_counter.burnt_gas() - burnt_gas;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.end_call(gas_burnt);
+        }
+        result
+    }
+
+    /// Records storage key read by the traced host function call.
+    fn trace_storage_read(trace: &mut Option<Trace>, key: &[u8]) {
+        if let Some(trace) = trace {
+            trace.storage_read(key);
+        }
+    }
+
+    /// Records storage key written or removed by the traced host function call.
+    fn trace_storage_write(trace: &mut Option<Trace>, key: &[u8]) {
+        if let Some(trace) = trace {
+            trace.storage_write(key);
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn receipt_manager(&self) -> &ReceiptManager {
         &self.receipt_manager
@@ -2360,6 +2487,7 @@ impl<'a> VMLogic<'a> {
         }
         self.gas_counter.pay_per(storage_write_key_byte, key.len() as u64)?;
         self.gas_counter.pay_per(storage_write_value_byte, value.len() as u64)?;
+        Self::trace_storage_write(&mut self.trace, &key);
         let nodes_before = self.ext.get_trie_nodes_count();
         // For storage write, we need to first perform a read on the key to calculate the TTN cost.
         // This storage_get must be performed through trie instead of through FlatStorage
@@ -2458,6 +2586,7 @@ impl<'a> VMLogic<'a> {
             .into());
         }
         self.gas_counter.pay_per(storage_read_key_byte, key.len() as u64)?;
+        Self::trace_storage_read(&mut self.trace, &key);
         let nodes_before = self.ext.get_trie_nodes_count();
         #[cfg(feature = "protocol_feature_flat_state")]
         let read = self.ext.storage_get(&key, StorageGetMode::FlatStorage);
@@ -2524,6 +2653,7 @@ impl<'a> VMLogic<'a> {
             .into());
         }
         self.gas_counter.pay_per(storage_remove_key_byte, key.len() as u64)?;
+        Self::trace_storage_write(&mut self.trace, &key);
         let nodes_before = self.ext.get_trie_nodes_count();
         // To delete a key, we need to first perform a read on the key to calculate the TTN cost.
         // This storage_get must be performed through trie instead of through FlatStorage
@@ -2591,6 +2721,7 @@ impl<'a> VMLogic<'a> {
             .into());
         }
         self.gas_counter.pay_per(storage_has_key_byte, key.len() as u64)?;
+        Self::trace_storage_read(&mut self.trace, &key);
         let nodes_before = self.ext.get_trie_nodes_count();
         let res = self.ext.storage_has_key(&key);
         let nodes_delta = self.ext.get_trie_nodes_count() - nodes_before;
@@ -2747,6 +2878,7 @@ impl<'a> VMLogic<'a> {
             profile,
             action_receipts: self.receipt_manager.action_receipts,
             aborted: None,
+            trace: self.trace.map(|trace| trace.view).unwrap_or_default(),
         }
     }
 
@@ -2866,6 +2998,8 @@ pub struct VMOutcome {
     pub profile: ProfileData,
     pub action_receipts: Vec<(AccountId, ReceiptMetadata)>,
     pub aborted: Option<FunctionCallError>,
+    /// Host function calls made by the contract, if tracing was enabled.
+    pub trace: CallTraceView,
 }
 
 impl VMOutcome {
@@ -2898,6 +3032,7 @@ impl VMOutcome {
             profile: ProfileData::default(),
             action_receipts: Vec::new(),
             aborted: Some(error),
+            trace: CallTraceView::default(),
         }
     }
 
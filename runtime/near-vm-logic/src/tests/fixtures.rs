@@ -18,7 +18,10 @@ pub fn get_context(input: Vec<u8>, is_view: bool) -> VMContext {
         prepaid_gas: 10_u64.pow(14),
         random_seed: vec![],
         view_config: match is_view {
-            true => Some(ViewConfig { max_gas_burnt: VMLimitConfig::test().max_gas_burnt }),
+            true => Some(ViewConfig {
+                max_gas_burnt: VMLimitConfig::test().max_gas_burnt,
+                trace: false,
+            }),
             false => None,
         },
         output_data_receivers: vec![],
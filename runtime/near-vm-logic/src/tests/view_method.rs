@@ -1,3 +1,4 @@
+use crate::logic::{MAX_TRACE_BYTES, MAX_TRACE_CALLS};
 use crate::tests::fixtures::get_context;
 use crate::tests::vm_logic_builder::VMLogicBuilder;
 
@@ -48,3 +49,78 @@ fn test_allowed_view_method() {
     let mut logic = logic_builder.build(context.clone());
     assert_eq!(logic.block_index().unwrap(), context.block_height);
 }
+
+#[test]
+fn test_traced_view_method() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut context = get_context(vec![], true);
+    context.view_config.as_mut().unwrap().trace = true;
+    let mut logic = logic_builder.build(context);
+
+    logic.wrapped_internal_write_register(1, b"foo").unwrap();
+    let burnt_gas = logic.gas_counter().burnt_gas();
+    let args = [("key_len", u64::MAX), ("key_ptr", 1), ("register_id", 0)];
+    let found = logic
+        .trace_host_function("storage_read", &args, |logic| logic.storage_read(u64::MAX, 1, 0))
+        .unwrap();
+    assert_eq!(found, 0);
+    let burnt_gas = logic.gas_counter().burnt_gas() - burnt_gas;
+
+    let outcome = logic.compute_outcome_and_distribute_gas();
+    assert_eq!(outcome.trace.calls.len(), 1);
+    assert!(!outcome.trace.truncated);
+    let call = &outcome.trace.calls[0];
+    assert_eq!(call.name, "storage_read");
+    assert_eq!(call.args, "key_len: 18446744073709551615, key_ptr: 1, register_id: 0");
+    assert_eq!(call.gas_burnt, burnt_gas);
+    assert!(call.gas_burnt > 0);
+    assert_eq!(call.storage_reads, vec![b"foo".to_vec().into()]);
+    assert!(call.storage_writes.is_empty());
+}
+
+#[test]
+fn test_untraced_view_method() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], true));
+
+    logic.wrapped_internal_write_register(1, b"foo").unwrap();
+    let args = [("key_len", u64::MAX), ("key_ptr", 1), ("register_id", 0)];
+    logic
+        .trace_host_function("storage_read", &args, |logic| logic.storage_read(u64::MAX, 1, 0))
+        .unwrap();
+
+    assert!(logic.compute_outcome_and_distribute_gas().trace.calls.is_empty());
+}
+
+#[test]
+fn test_traced_view_method_call_limit() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut context = get_context(vec![], true);
+    context.view_config.as_mut().unwrap().trace = true;
+    let mut logic = logic_builder.build(context);
+
+    for _ in 0..MAX_TRACE_CALLS + 1 {
+        logic.trace_host_function("block_index", &[], |logic| logic.block_index()).unwrap();
+    }
+
+    let outcome = logic.compute_outcome_and_distribute_gas();
+    assert_eq!(outcome.trace.calls.len(), MAX_TRACE_CALLS);
+    assert!(outcome.trace.truncated);
+}
+
+#[test]
+fn test_traced_view_method_byte_limit() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut context = get_context(vec![], true);
+    context.view_config.as_mut().unwrap().trace = true;
+    let mut logic = logic_builder.build(context);
+
+    let name = "a".repeat(MAX_TRACE_BYTES - 10);
+    logic.trace_host_function(&name, &[], |logic| logic.block_index()).unwrap();
+    logic.trace_host_function("block_index", &[], |logic| logic.block_index()).unwrap();
+
+    let outcome = logic.compute_outcome_and_distribute_gas();
+    assert_eq!(outcome.trace.calls.len(), 1);
+    assert_eq!(outcome.trace.calls[0].name, name);
+    assert!(outcome.trace.truncated);
+}
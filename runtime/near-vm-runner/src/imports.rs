@@ -281,7 +281,12 @@ pub(crate) mod wasmer {
                         Some(tracing::trace_span!(target: "host-function", stringify!($func)).entered())
                     };
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(ctx.data as *mut VMLogic<'_>) };
-                    logic.$func( $( $arg_name, )* )
+                    if !IS_GAS && logic.is_tracing() {
+                        let args = [$( (stringify!($arg_name), $arg_name as u64) ),*];
+                        logic.trace_host_function(stringify!($func), &args, |logic| logic.$func( $( $arg_name, )* ))
+                    } else {
+                        logic.$func( $( $arg_name, )* )
+                    }
                 }
 
                 ns.insert(stringify!($func), wasmer_runtime::func!($func));
@@ -385,7 +390,17 @@ pub(crate) mod wasmer2 {
                             // lifetime and so it is safe to dereference the `env` pointer which is
                             // known to be derived from a valid `&'vmlogic mut VMLogic<'_>` in the
                             // first place.
-                            unsafe { (*env).$func( $( $arg_name, )* ) }
+                            let logic = unsafe { &mut *env };
+                            if !IS_GAS && logic.is_tracing() {
+                                let args = [$( (stringify!($arg_name), $arg_name as u64) ),*];
+                                logic.trace_host_function(
+                                    stringify!($func),
+                                    &args,
+                                    |logic| logic.$func( $( $arg_name, )* ),
+                                )
+                            } else {
+                                logic.$func( $( $arg_name, )* )
+                            }
                         }));
                         // We want to ensure that the only kind of error that host function calls
                         // return are VMLogicError. This is important because we later attempt to
@@ -505,7 +520,13 @@ pub(crate) mod wasmtime {
                         crate::wasmtime_runner::CALLER.with(|runner_caller| *runner_caller.borrow_mut() = std::mem::transmute(caller));
                     }
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(data as *mut VMLogic<'_>) };
-                    match logic.$func( $( $arg_name as $arg_type, )* ) {
+                    let result = if !IS_GAS && logic.is_tracing() {
+                        let args = [$( (stringify!($arg_name), $arg_name as u64) ),*];
+                        logic.trace_host_function(stringify!($func), &args, |logic| logic.$func( $( $arg_name as $arg_type, )* ))
+                    } else {
+                        logic.$func( $( $arg_name as $arg_type, )* )
+                    };
+                    match result {
                         Ok(result) => Ok(result as ($( $returns ),* ) ),
                         Err(err) => {
                             // Wasmtime doesn't have proper mechanism for wrapping custom errors
@@ -68,6 +68,8 @@ fn runtime_fees_config(cost_table: &CostTable) -> anyhow::Result<RuntimeFeesConf
             ActionCosts::new_action_receipt => fee(Cost::ActionReceiptCreation)?,
             ActionCosts::new_data_receipt_base => fee(Cost::DataReceiptCreationBase)?,
             ActionCosts::new_data_receipt_byte => fee(Cost::DataReceiptCreationPerByte)?,
+            // Not estimated yet, keep the configured value.
+            ActionCosts::delegate => actual_fees_config.fee(ActionCosts::delegate).clone(),
         },
         ..actual_fees_config.clone()
     };
@@ -1,4 +1,6 @@
-use crate::config::{safe_add_gas, RuntimeConfig};
+use crate::config::{
+    delegate_receipt_cost, safe_add_gas, total_prepaid_gas, total_send_fees, RuntimeConfig,
+};
 use crate::ext::{ExternalError, RuntimeExt};
 use crate::{metrics, ActionResult, ApplyState};
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -7,7 +9,8 @@ use near_primitives::account::{AccessKey, AccessKeyPermission, Account};
 use near_primitives::checked_feature;
 use near_primitives::config::ViewConfig;
 use near_primitives::contract::ContractCode;
-use near_primitives::errors::{ActionError, ActionErrorKind, RuntimeError};
+use near_primitives::delegate_action::{DelegateAction, SignedDelegateAction};
+use near_primitives::errors::{ActionError, ActionErrorKind, InvalidAccessKeyError, RuntimeError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use near_primitives::runtime::config::AccountCreationConfig;
@@ -32,7 +35,7 @@ use near_vm_errors::{
     VMRunnerError,
 };
 use near_vm_logic::types::PromiseResult;
-use near_vm_logic::{VMContext, VMOutcome};
+use near_vm_logic::{ActionCosts, VMContext, VMOutcome};
 use near_vm_runner::precompile_contract;
 
 /// Runs given function call with given context / apply state.
@@ -624,6 +627,176 @@ pub(crate) fn action_add_key(
     Ok(())
 }
 
+pub(crate) fn apply_delegate_action(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    action_receipt: &ActionReceipt,
+    sender_id: &AccountId,
+    signed_delegate_action: &SignedDelegateAction,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    let delegate_action = &signed_delegate_action.delegate_action;
+
+    if !signed_delegate_action.verify() {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidSignature.into());
+        return Ok(());
+    }
+    if &delegate_action.sender_id != sender_id {
+        result.result = Err(ActionErrorKind::DelegateActionSenderDoesNotMatchTxReceiver {
+            sender_id: delegate_action.sender_id.clone(),
+            receiver_id: sender_id.clone(),
+        }
+        .into());
+        return Ok(());
+    }
+    if apply_state.block_height > delegate_action.max_block_height {
+        result.result = Err(ActionErrorKind::DelegateActionExpired.into());
+        return Ok(());
+    }
+
+    validate_delegate_action_key(state_update, apply_state, delegate_action, result)?;
+    if result.result.is_err() {
+        // Validation failed. Need to return Ok() because this is not a runtime error.
+        // "result.result" will be return to the User as the action execution result.
+        return Ok(());
+    }
+
+    // The new receipt is sent on behalf of `sender_id`, but it's still paid
+    // for by the signer of the transaction, which gets the refunds.
+    let actions = delegate_action.get_actions();
+    let new_receipt = Receipt {
+        predecessor_id: sender_id.clone(),
+        receiver_id: delegate_action.receiver_id.clone(),
+        receipt_id: CryptoHash::default(),
+        receipt: ReceiptEnum::Action(ActionReceipt {
+            signer_id: action_receipt.signer_id.clone(),
+            signer_public_key: action_receipt.signer_public_key.clone(),
+            gas_price: action_receipt.gas_price,
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions: actions.clone(),
+        }),
+    };
+
+    // Gas for sending the new receipt is burnt right away.  Gas for executing
+    // it is used, to be burnt or refunded when the receipt is executed.
+    let fees = &apply_state.config.fees;
+    let sender_is_receiver = delegate_action.sender_id == delegate_action.receiver_id;
+    let send_fees = safe_add_gas(
+        fees.fee(ActionCosts::new_action_receipt).send_fee(sender_is_receiver),
+        total_send_fees(
+            fees,
+            sender_is_receiver,
+            &actions,
+            &delegate_action.receiver_id,
+            apply_state.current_protocol_version,
+        )?,
+    )?;
+    let receipt_cost =
+        delegate_receipt_cost(fees, delegate_action, apply_state.current_protocol_version)?;
+    let prepaid_gas = total_prepaid_gas(&actions)?;
+    result.gas_burnt = safe_add_gas(result.gas_burnt, send_fees)?;
+    result.gas_used = safe_add_gas(result.gas_used, safe_add_gas(receipt_cost, prepaid_gas)?)?;
+
+    result.new_receipts.push(new_receipt);
+
+    Ok(())
+}
+
+/// Checks the access key used to sign the delegate action the same way the
+/// access key of a transaction is checked, and updates its nonce.
+fn validate_delegate_action_key(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    delegate_action: &DelegateAction,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    let sender_id = &delegate_action.sender_id;
+    let public_key = &delegate_action.public_key;
+    let mut access_key = match get_access_key(state_update, sender_id, public_key)? {
+        Some(access_key) => access_key,
+        None => {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::AccessKeyNotFound {
+                    account_id: sender_id.clone(),
+                    public_key: public_key.clone(),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+    };
+
+    if delegate_action.nonce <= access_key.nonce {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidNonce {
+            delegate_nonce: delegate_action.nonce,
+            ak_nonce: access_key.nonce,
+        }
+        .into());
+        return Ok(());
+    }
+    let upper_bound = apply_state.block_height
+        * near_primitives::account::AccessKey::ACCESS_KEY_NONCE_RANGE_MULTIPLIER;
+    if delegate_action.nonce >= upper_bound {
+        result.result = Err(ActionErrorKind::DelegateActionNonceTooLarge {
+            delegate_nonce: delegate_action.nonce,
+            upper_bound,
+        }
+        .into());
+        return Ok(());
+    }
+
+    if let AccessKeyPermission::FunctionCall(ref function_call_permission) = access_key.permission {
+        let actions = delegate_action.get_actions();
+        let function_call = match actions.as_slice() {
+            [Action::FunctionCall(function_call)] => function_call,
+            _ => {
+                result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                    InvalidAccessKeyError::RequiresFullAccess,
+                )
+                .into());
+                return Ok(());
+            }
+        };
+        if function_call.deposit > 0 {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::DepositWithFunctionCall,
+            )
+            .into());
+            return Ok(());
+        }
+        if delegate_action.receiver_id.as_ref() != function_call_permission.receiver_id {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::ReceiverMismatch {
+                    tx_receiver: delegate_action.receiver_id.clone(),
+                    ak_receiver: function_call_permission.receiver_id.clone(),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+        if !function_call_permission.method_names.is_empty()
+            && function_call_permission
+                .method_names
+                .iter()
+                .all(|method_name| &function_call.method_name != method_name)
+        {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::MethodNameMismatch {
+                    method_name: function_call.method_name.clone(),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+    }
+
+    access_key.nonce = delegate_action.nonce;
+    set_access_key(state_update, sender_id.clone(), public_key.clone(), &access_key);
+
+    Ok(())
+}
+
 pub(crate) fn check_actor_permissions(
     action: &Action,
     account: &Option<Account>,
@@ -656,7 +829,10 @@ pub(crate) fn check_actor_permissions(
                 .into());
             }
         }
-        Action::CreateAccount(_) | Action::FunctionCall(_) | Action::Transfer(_) => (),
+        Action::CreateAccount(_)
+        | Action::FunctionCall(_)
+        | Action::Transfer(_)
+        | Action::Delegate(_) => (),
     };
     Ok(())
 }
@@ -723,7 +899,8 @@ pub(crate) fn check_account_existence(
         | Action::Stake(_)
         | Action::AddKey(_)
         | Action::DeleteKey(_)
-        | Action::DeleteAccount(_) => {
+        | Action::DeleteAccount(_)
+        | Action::Delegate(_) => {
             if account.is_none() {
                 return Err(ActionErrorKind::AccountDoesNotExist {
                     account_id: account_id.clone(),
@@ -7,7 +7,8 @@ use near_primitives::types::{
     AccountId, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, MerkleHash,
 };
 use near_primitives::version::ProtocolVersion;
-use near_primitives::views::ViewStateResult;
+use near_primitives::views::{AccountsWithPrefixView, CallTraceView, ViewStateResult};
+use std::num::NonZeroU64;
 
 /// Adapter for querying runtime.
 pub trait ViewRuntimeAdapter {
@@ -39,6 +40,7 @@ pub trait ViewRuntimeAdapter {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        trace: Option<&mut CallTraceView>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, crate::state_viewer::errors::CallFunctionError>;
@@ -65,5 +67,15 @@ pub trait ViewRuntimeAdapter {
         account_id: &AccountId,
         prefix: &[u8],
         include_proof: bool,
+        start_after: Option<&[u8]>,
+        limit: Option<NonZeroU64>,
     ) -> Result<ViewStateResult, crate::state_viewer::errors::ViewStateError>;
+
+    fn view_accounts_with_prefix(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        account_id_prefix: &str,
+        start_after: Option<&AccountId>,
+    ) -> Result<AccountsWithPrefixView, crate::state_viewer::errors::ViewAccountsError>;
 }
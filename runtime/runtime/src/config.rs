@@ -6,6 +6,7 @@ use num_traits::cast::ToPrimitive;
 use num_traits::pow::Pow;
 
 use near_primitives::account::AccessKeyPermission;
+use near_primitives::delegate_action::DelegateAction;
 use near_primitives::errors::IntegerOverflowError;
 // Just re-exporting RuntimeConfig for backwards compatibility.
 pub use near_primitives::num_rational::Rational;
@@ -125,6 +126,7 @@ pub fn total_send_fees(
             DeleteAccount(_) => {
                 config.fee(ActionCosts::delete_account).send_fee(sender_is_receiver)
             }
+            Delegate(_) => config.fee(ActionCosts::delegate).send_fee(sender_is_receiver),
         };
         result = safe_add_gas(result, delta)?;
     }
@@ -176,6 +178,7 @@ pub fn exec_fee(
         },
         DeleteKey(_) => config.fee(ActionCosts::delete_key).exec_fee(),
         DeleteAccount(_) => config.fee(ActionCosts::delete_account).exec_fee(),
+        Delegate(_) => config.fee(ActionCosts::delegate).exec_fee(),
     }
 }
 
@@ -230,7 +233,9 @@ pub fn tx_cost(
             current_protocol_version,
         )?,
     )?;
-    let burnt_amount = safe_gas_to_balance(gas_price, gas_burnt)?;
+    // The priority fee is burnt together with the gas.
+    let burnt_amount =
+        safe_add_balance(safe_gas_to_balance(gas_price, gas_burnt)?, transaction.priority_fee())?;
     let remaining_gas_amount = safe_gas_to_balance(receipt_gas_price, gas_remaining)?;
     let mut total_cost = safe_add_balance(burnt_amount, remaining_gas_amount)?;
     total_cost = safe_add_balance(total_cost, total_deposit(&transaction.actions)?)?;
@@ -246,11 +251,50 @@ pub fn total_prepaid_exec_fees(
 ) -> Result<Gas, IntegerOverflowError> {
     let mut result = 0;
     for action in actions {
-        let delta = exec_fee(config, action, receiver_id, current_protocol_version);
+        let mut delta = exec_fee(config, action, receiver_id, current_protocol_version);
+        if let Action::Delegate(signed_delegate_action) = action {
+            let delegate_cost = delegate_receipt_cost(
+                config,
+                &signed_delegate_action.delegate_action,
+                current_protocol_version,
+            )?;
+            delta = safe_add_gas(delta, delegate_cost)?;
+        }
         result = safe_add_gas(result, delta)?;
     }
     Ok(result)
 }
+
+/// Gas needed to send and execute the receipt created by a delegate action,
+/// excluding the gas prepaid for its function calls.
+pub fn delegate_receipt_cost(
+    config: &RuntimeFeesConfig,
+    delegate_action: &DelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    let actions = delegate_action.get_actions();
+    let sender_is_receiver = delegate_action.sender_id == delegate_action.receiver_id;
+    let send_fees = safe_add_gas(
+        config.fee(ActionCosts::new_action_receipt).send_fee(sender_is_receiver),
+        total_send_fees(
+            config,
+            sender_is_receiver,
+            &actions,
+            &delegate_action.receiver_id,
+            current_protocol_version,
+        )?,
+    )?;
+    let exec_fees = safe_add_gas(
+        config.fee(ActionCosts::new_action_receipt).exec_fee(),
+        total_prepaid_exec_fees(
+            config,
+            &actions,
+            &delegate_action.receiver_id,
+            current_protocol_version,
+        )?,
+    )?;
+    safe_add_gas(send_fees, exec_fees)
+}
 /// Get the total sum of deposits for given actions.
 pub fn total_deposit(actions: &[Action]) -> Result<Balance, IntegerOverflowError> {
     let mut total_balance: Balance = 0;
@@ -30,8 +30,8 @@ use near_primitives::{
     },
     state_record::StateRecord,
     transaction::{
-        Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, LogEntry,
-        SignedTransaction,
+        verify_transaction_signatures, Action, ExecutionOutcome, ExecutionOutcomeWithId,
+        ExecutionStatus, LogEntry, SignedTransaction,
     },
     trie_key::TrieKey,
     types::{
@@ -215,11 +215,13 @@ impl Runtime {
     /// `ExecutionOutcomeWithId` for the transaction.
     /// In case of an error, returns either `InvalidTxError` if the transaction verification failed
     /// or a `StorageError` wrapped into `RuntimeError`.
+    /// The signature isn't checked again if `signature_verified` is set.
     fn process_transaction(
         &self,
         state_update: &mut TrieUpdate,
         apply_state: &ApplyState,
         signed_transaction: &SignedTransaction,
+        signature_verified: bool,
         stats: &mut ApplyStats,
     ) -> Result<(Receipt, ExecutionOutcomeWithId), RuntimeError> {
         let _span = tracing::debug_span!(target: "runtime", "process_transaction", tx_hash = %signed_transaction.get_hash()).entered();
@@ -230,7 +232,7 @@ impl Runtime {
             state_update,
             apply_state.gas_price,
             signed_transaction,
-            true,
+            !signature_verified,
             Some(apply_state.block_height),
             apply_state.current_protocol_version,
         ) {
@@ -443,6 +445,16 @@ impl Runtime {
                     apply_state.current_protocol_version,
                 )?;
             }
+            Action::Delegate(signed_delegate_action) => {
+                apply_delegate_action(
+                    state_update,
+                    apply_state,
+                    action_receipt,
+                    account_id,
+                    signed_delegate_action,
+                    &mut result,
+                )?;
+            }
         };
         Ok(result)
     }
@@ -1228,11 +1240,26 @@ impl Runtime {
         // limit
         let mut total_gas_burnt = gas_used_for_migrations;
 
-        for signed_transaction in transactions {
+        // Transactions whose signature failed batch verification are verified again when
+        // processed, so that the chunk fails with the same error as before.
+        let signatures_verified = if !apply_state.verify_signatures {
+            vec![true; transactions.len()]
+        } else if checked_feature!(
+            "protocol_feature_batch_signature_verification",
+            BatchSignatureVerification,
+            apply_state.current_protocol_version
+        ) {
+            verify_transaction_signatures(transactions)
+        } else {
+            vec![false; transactions.len()]
+        };
+        for (signed_transaction, signature_verified) in transactions.iter().zip(signatures_verified)
+        {
             let (receipt, outcome_with_id) = self.process_transaction(
                 &mut state_update,
                 apply_state,
                 signed_transaction,
+                signature_verified,
                 &mut stats,
             )?;
             if receipt.receiver_id == signed_transaction.transaction.signer_id {
@@ -1497,6 +1524,7 @@ mod tests {
     use near_crypto::{InMemorySigner, KeyType, Signer};
     use near_primitives::account::AccessKey;
     use near_primitives::contract::ContractCode;
+    use near_primitives::delegate_action::{DelegateAction, SignedDelegateAction};
     use near_primitives::hash::hash;
     use near_primitives::shard_layout::ShardUId;
     use near_primitives::test_utils::{account_new, MockEpochInfoProvider};
@@ -1625,6 +1653,7 @@ mod tests {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            verify_signatures: true,
         };
 
         (runtime, tries, root, apply_state, signer, MockEpochInfoProvider::default())
@@ -2409,6 +2438,65 @@ mod tests {
         assert_eq!(final_account_state.storage_usage(), 0);
     }
 
+    #[test]
+    fn test_delegate_action() {
+        let (runtime, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+
+        let delegate_action = DelegateAction {
+            sender_id: alice_account(),
+            receiver_id: bob_account(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 100 }).try_into().unwrap()],
+            nonce: 1,
+            max_block_height: apply_state.block_height,
+            public_key: signer.public_key(),
+        };
+        let signed_delegate_action = |signature| {
+            Action::Delegate(Box::new(SignedDelegateAction {
+                delegate_action: delegate_action.clone(),
+                signature,
+            }))
+        };
+        let apply = |action| {
+            let receipts =
+                create_receipts_with_actions(alice_account(), signer.clone(), vec![action]);
+            runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                    &None,
+                    &apply_state,
+                    &receipts,
+                    &[],
+                    &epoch_info_provider,
+                    Default::default(),
+                )
+                .unwrap()
+        };
+
+        let result =
+            apply(signed_delegate_action(signer.sign(delegate_action.get_hash().as_ref())));
+        assert_eq!(result.outcomes[0].outcome.status, ExecutionStatus::SuccessValue(vec![]));
+        let receipt = &result.outgoing_receipts[0];
+        assert_eq!(receipt.predecessor_id, alice_account());
+        assert_eq!(receipt.receiver_id, bob_account());
+        match &receipt.receipt {
+            ReceiptEnum::Action(action_receipt) => {
+                assert_eq!(action_receipt.signer_id, alice_account());
+                assert_eq!(action_receipt.actions, delegate_action.get_actions());
+            }
+            ReceiptEnum::Data(_) => panic!("expected an action receipt"),
+        }
+
+        let result = apply(signed_delegate_action(signer.sign(b"something else")));
+        assert_eq!(
+            result.outcomes[0].outcome.status,
+            ExecutionStatus::Failure(TxExecutionError::ActionError(ActionError {
+                index: Some(0),
+                kind: ActionErrorKind::DelegateActionInvalidSignature,
+            }))
+        );
+    }
+
     #[test]
     fn test_contract_precompilation() {
         let initial_balance = to_yocto(1_000_000);
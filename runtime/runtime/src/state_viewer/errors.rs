@@ -42,6 +42,12 @@ pub enum ViewStateError {
     InternalError { error_message: String },
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ViewAccountsError {
+    #[error("Internal error: #{error_message}")]
+    InternalError { error_message: String },
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum CallFunctionError {
     #[error("Account ID \"{requested_account_id}\" is invalid")]
@@ -99,3 +105,9 @@ impl From<near_primitives::errors::StorageError> for CallFunctionError {
         Self::InternalError { error_message: storage_error.to_string() }
     }
 }
+
+impl From<near_primitives::errors::StorageError> for ViewAccountsError {
+    fn from(storage_error: near_primitives::errors::StorageError) -> Self {
+        Self::InternalError { error_message: storage_error.to_string() }
+    }
+}
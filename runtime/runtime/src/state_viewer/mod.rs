@@ -13,13 +13,16 @@ use near_primitives::{
         migration_data::{MigrationData, MigrationFlags},
     },
     transaction::FunctionCallAction,
-    trie_key::trie_key_parsers,
+    trie_key::{trie_key_parsers, TrieKey},
     types::{AccountId, EpochInfoProvider, Gas},
-    views::{StateItem, ViewApplyState, ViewStateResult},
+    views::{
+        AccountBalanceView, AccountsWithPrefixView, CallTraceView, StateItem, ViewApplyState,
+        ViewStateResult,
+    },
 };
 use near_store::{get_access_key, get_account, get_code, TrieUpdate};
 use near_vm_logic::{ReturnData, ViewConfig};
-use std::{str, sync::Arc, time::Instant};
+use std::{num::NonZeroU64, str, sync::Arc, time::Instant};
 use tracing::debug;
 
 pub mod errors;
@@ -113,20 +116,31 @@ impl TrieViewer {
         access_keys
     }
 
+    /// Returns contract state of the account under the given key prefix.
+    ///
+    /// If either `start_after` or `limit` is given the query is paginated:
+    /// values are listed starting after `start_after` and a page ends once it
+    /// holds `limit` values or the state size limit is reached.  In that case
+    /// `next_start_after` of the result is set to the last returned key.
+    /// Unpaginated queries fail for accounts whose state exceeds the size
+    /// limit.
     pub fn view_state(
         &self,
         state_update: &TrieUpdate,
         account_id: &AccountId,
         prefix: &[u8],
         include_proof: bool,
+        start_after: Option<&[u8]>,
+        limit: Option<NonZeroU64>,
     ) -> Result<ViewStateResult, errors::ViewStateError> {
+        let paginated = start_after.is_some() || limit.is_some();
         match get_account(state_update, account_id)? {
             Some(account) => {
                 let code_len = get_code(state_update, account_id, Some(account.code_hash()))?
                     .map(|c| c.code().len() as u64)
                     .unwrap_or_default();
                 if let Some(limit) = self.state_size_limit {
-                    if account.storage_usage().saturating_sub(code_len) > limit {
+                    if !paginated && account.storage_usage().saturating_sub(code_len) > limit {
                         return Err(errors::ViewStateError::AccountStateTooLarge {
                             requested_account_id: account_id.clone(),
                         });
@@ -143,11 +157,36 @@ impl TrieViewer {
         let mut values = vec![];
         let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
         let acc_sep_len = query.len() - prefix.len();
+        let start_after = start_after
+            .map(|key| trie_key_parsers::get_raw_prefix_for_contract_data(account_id, key));
         let mut iter = state_update.trie().iter()?;
         iter.remember_visited_nodes(include_proof);
-        iter.seek_prefix(&query)?;
+        match &start_after {
+            Some(start_key) if *start_key > query => iter.seek(start_key)?,
+            _ => iter.seek_prefix(&query)?,
+        }
+        let mut next_start_after = None;
+        let mut size = 0u64;
         for item in &mut iter {
             let (key, value) = item?;
+            if !key.starts_with(&query) {
+                break;
+            }
+            if start_after.as_ref() == Some(&key) {
+                continue;
+            }
+            if paginated {
+                let entry_size = (key.len() + value.len()) as u64;
+                let page_full = limit.map_or(false, |limit| values.len() as u64 >= limit.get())
+                    || self
+                        .state_size_limit
+                        .map_or(false, |limit| !values.is_empty() && size + entry_size > limit);
+                if page_full {
+                    next_start_after = values.last().map(|item: &StateItem| item.key.clone());
+                    break;
+                }
+                size += entry_size;
+            }
             values.push(StateItem {
                 key: key[acc_sep_len..].to_vec(),
                 value: value,
@@ -155,7 +194,65 @@ impl TrieViewer {
             });
         }
         let proof = iter.into_visited_nodes();
-        Ok(ViewStateResult { values, proof })
+        Ok(ViewStateResult { values, proof, next_start_after })
+    }
+
+    /// Lists accounts whose ids start with `account_id_prefix` in order of
+    /// their ids, starting after `start_after` if given.
+    ///
+    /// The size of listed trie entries is bounded by the state size limit.  If
+    /// the limit is hit, `next_start_after` of the result is set to the last
+    /// listed account so that the listing can be continued.
+    pub fn view_accounts_with_prefix(
+        &self,
+        state_update: &TrieUpdate,
+        account_id_prefix: &str,
+        start_after: Option<&AccountId>,
+    ) -> Result<AccountsWithPrefixView, errors::ViewAccountsError> {
+        let prefix = trie_key_parsers::get_raw_prefix_for_accounts(account_id_prefix);
+        let start_key = match start_after {
+            Some(account_id) => {
+                TrieKey::Account { account_id: account_id.clone() }.to_vec().max(prefix.clone())
+            }
+            None => prefix.clone(),
+        };
+
+        let mut iter = state_update.trie().iter()?;
+        iter.seek(&start_key)?;
+        let mut accounts: Vec<AccountBalanceView> = vec![];
+        let mut size = 0u64;
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let account_id =
+                trie_key_parsers::parse_account_id_from_account_key(&key).map_err(|err| {
+                    errors::ViewAccountsError::InternalError { error_message: err.to_string() }
+                })?;
+            if Some(&account_id) == start_after {
+                continue;
+            }
+            let entry_size = (key.len() + value.len()) as u64;
+            if let Some(limit) = self.state_size_limit {
+                if !accounts.is_empty() && size + entry_size > limit {
+                    let next_start_after = accounts.last().map(|view| view.account_id.clone());
+                    return Ok(AccountsWithPrefixView { accounts, next_start_after });
+                }
+            }
+            size += entry_size;
+            let account = Account::try_from_slice(&value).map_err(|err| {
+                errors::ViewAccountsError::InternalError {
+                    error_message: format!("Failed to deserialize account {}: {}", account_id, err),
+                }
+            })?;
+            accounts.push(AccountBalanceView {
+                account_id,
+                amount: account.amount(),
+                locked: account.locked(),
+            });
+        }
+        Ok(AccountsWithPrefixView { accounts, next_start_after: None })
     }
 
     pub fn call_function(
@@ -166,6 +263,7 @@ impl TrieViewer {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        trace: Option<&mut CallTraceView>,
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<Vec<u8>, errors::CallFunctionError> {
         let now = Instant::now();
@@ -208,6 +306,7 @@ impl TrieViewer {
             is_new_chunk: false,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            verify_signatures: true,
         };
         let action_receipt = ActionReceipt {
             signer_id: originator_id.clone(),
@@ -223,7 +322,7 @@ impl TrieViewer {
             gas: self.max_gas_burnt_view,
             deposit: 0,
         };
-        let outcome = execute_function_call(
+        let mut outcome = execute_function_call(
             &apply_state,
             &mut runtime_ext,
             &mut account,
@@ -234,9 +333,12 @@ impl TrieViewer {
             &empty_hash,
             config,
             true,
-            Some(ViewConfig { max_gas_burnt: self.max_gas_burnt_view }),
+            Some(ViewConfig { max_gas_burnt: self.max_gas_burnt_view, trace: trace.is_some() }),
         )
         .map_err(|e| errors::CallFunctionError::InternalError { error_message: e.to_string() })?;
+        if let Some(trace) = trace {
+            *trace = std::mem::take(&mut outcome.trace);
+        }
         let elapsed = now.elapsed();
         let time_ms =
             (elapsed.as_secs() as f64 / 1_000.0) + f64::from(elapsed.subsec_nanos()) / 1_000_000.0;
@@ -3,6 +3,7 @@ use near_primitives::runtime::get_insufficient_storage_stake;
 use near_primitives::{
     account::AccessKeyPermission,
     config::VMLimitConfig,
+    delegate_action::DelegateAction,
     errors::{
         ActionsValidationError, InvalidAccessKeyError, InvalidTxError, ReceiptValidationError,
         RuntimeError,
@@ -55,6 +56,33 @@ pub fn validate_transaction(
         .into());
     }
 
+    if !checked_feature!(
+        "protocol_feature_delegate_action",
+        DelegateAction,
+        current_protocol_version
+    ) && transaction.actions.iter().any(|action| matches!(action, Action::Delegate(_)))
+    {
+        return Err(InvalidTxError::ActionsValidation(
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "DelegateAction".to_string(),
+                version: current_protocol_version,
+            },
+        )
+        .into());
+    }
+
+    if !checked_feature!("protocol_feature_priority_fee", PriorityFee, current_protocol_version)
+        && transaction.priority_fee.is_some()
+    {
+        return Err(InvalidTxError::ActionsValidation(
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "PriorityFee".to_string(),
+                version: current_protocol_version,
+            },
+        )
+        .into());
+    }
+
     validate_actions(&config.wasm_config.limit_config, &transaction.actions)
         .map_err(InvalidTxError::ActionsValidation)?;
 
@@ -275,6 +303,7 @@ fn validate_data_receipt(
 /// - Checks limits if applicable.
 /// - Checks that the total number of actions doesn't exceed the limit.
 /// - Validates each individual action.
+/// - Checks that there is at most one delegate action.
 /// - Checks that the total prepaid gas doesn't exceed the limit.
 pub(crate) fn validate_actions(
     limit_config: &VMLimitConfig,
@@ -287,6 +316,7 @@ pub(crate) fn validate_actions(
         });
     }
 
+    let mut found_delegate_action = false;
     let mut iter = actions.iter().peekable();
     while let Some(action) = iter.next() {
         if let Action::DeleteAccount(_) = action {
@@ -294,6 +324,12 @@ pub(crate) fn validate_actions(
                 return Err(ActionsValidationError::DeleteActionMustBeFinal);
             }
         }
+        if let Action::Delegate(_) = action {
+            if found_delegate_action {
+                return Err(ActionsValidationError::DelegateActionMustBeOnlyOne);
+            }
+            found_delegate_action = true;
+        }
         validate_action(limit_config, action)?;
     }
 
@@ -323,9 +359,19 @@ pub fn validate_action(
         Action::AddKey(a) => validate_add_key_action(limit_config, a),
         Action::DeleteKey(_) => Ok(()),
         Action::DeleteAccount(_) => Ok(()),
+        Action::Delegate(a) => validate_delegate_action(limit_config, &a.delegate_action),
     }
 }
 
+/// Validates `DelegateAction`. Checks the inner actions the same way as the
+/// actions of a receipt, since they are sent in a new receipt.
+fn validate_delegate_action(
+    limit_config: &VMLimitConfig,
+    action: &DelegateAction,
+) -> Result<(), ActionsValidationError> {
+    validate_actions(limit_config, &action.get_actions())
+}
+
 /// Validates `DeployContractAction`. Checks that the given contract size doesn't exceed the limit.
 fn validate_deploy_contract_action(
     limit_config: &VMLimitConfig,
@@ -453,12 +499,14 @@ fn test_truncate_string() {
 mod tests {
     use std::sync::Arc;
 
-    use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
+    use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
     use near_primitives::account::{AccessKey, Account, FunctionCallPermission};
+    use near_primitives::delegate_action::SignedDelegateAction;
     use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::test_utils::account_new;
     use near_primitives::transaction::{
-        CreateAccountAction, DeleteAccountAction, DeleteKeyAction, StakeAction, TransferAction,
+        CreateAccountAction, DeleteAccountAction, DeleteKeyAction, StakeAction, Transaction,
+        TransferAction,
     };
     use near_primitives::types::{AccountId, Balance, MerkleHash, StateChangeCause};
     use near_primitives::version::PROTOCOL_VERSION;
@@ -598,6 +646,66 @@ mod tests {
         assert_eq!(access_key.nonce, 1);
     }
 
+    #[test]
+    fn test_validate_transaction_priority_fee() {
+        let config = RuntimeConfig::test();
+        let (signer, mut state_update, gas_price) =
+            setup_common(TESTING_INIT_BALANCE, 0, Some(AccessKey::full_access()));
+
+        let priority_fee = 10u128.pow(20);
+        let mut transaction = Transaction::new(
+            alice_account(),
+            signer.public_key(),
+            bob_account(),
+            1,
+            CryptoHash::default(),
+        )
+        .transfer(100);
+        transaction.priority_fee = Some(priority_fee);
+        let transaction = transaction.sign(&*signer);
+
+        if !checked_feature!("protocol_feature_priority_fee", PriorityFee, PROTOCOL_VERSION) {
+            assert_err_both_validations(
+                &config,
+                &mut state_update,
+                gas_price,
+                &transaction,
+                InvalidTxError::ActionsValidation(
+                    ActionsValidationError::UnsupportedProtocolFeature {
+                        protocol_feature: "PriorityFee".to_string(),
+                        version: PROTOCOL_VERSION,
+                    },
+                )
+                .into(),
+            );
+            return;
+        }
+        let verification_result = verify_and_charge_transaction(
+            &config,
+            &mut state_update,
+            gas_price,
+            &transaction,
+            true,
+            None,
+            PROTOCOL_VERSION,
+        )
+        .expect("valid transaction");
+        // The priority fee is burnt on top of the gas.
+        assert_eq!(
+            verification_result.burnt_amount,
+            Balance::from(verification_result.gas_burnt) * gas_price + priority_fee
+        );
+        let account = get_account(&state_update, &alice_account()).unwrap().unwrap();
+        assert_eq!(
+            account.amount(),
+            TESTING_INIT_BALANCE
+                - Balance::from(verification_result.gas_remaining)
+                    * verification_result.receipt_gas_price
+                - verification_result.burnt_amount
+                - 100
+        );
+    }
+
     #[test]
     fn test_validate_transaction_invalid_signature() {
         let config = RuntimeConfig::test();
@@ -1382,8 +1490,59 @@ mod tests {
         );
     }
 
+    fn delegate_action(actions: Vec<Action>) -> Action {
+        Action::Delegate(Box::new(SignedDelegateAction {
+            delegate_action: DelegateAction {
+                sender_id: alice_account(),
+                receiver_id: bob_account(),
+                actions: actions.into_iter().map(|action| action.try_into().unwrap()).collect(),
+                nonce: 1,
+                max_block_height: 100,
+                public_key: PublicKey::empty(KeyType::ED25519),
+            },
+            signature: Signature::empty(KeyType::ED25519),
+        }))
+    }
+
+    #[test]
+    fn test_validate_delegate_action_must_be_only_one() {
+        let transfer = Action::Transfer(TransferAction { deposit: 1 });
+        assert_eq!(
+            validate_actions(
+                &VMLimitConfig::test(),
+                &[delegate_action(vec![transfer.clone()]), delegate_action(vec![transfer])]
+            )
+            .expect_err("Expected an error"),
+            ActionsValidationError::DelegateActionMustBeOnlyOne,
+        );
+    }
+
     // Individual actions
 
+    #[test]
+    fn test_validate_action_valid_delegate() {
+        validate_action(
+            &VMLimitConfig::test(),
+            &delegate_action(vec![Action::Transfer(TransferAction { deposit: 1 })]),
+        )
+        .expect("valid action");
+    }
+
+    #[test]
+    fn test_validate_action_invalid_delegate_inner_action() {
+        let function_call = Action::FunctionCall(FunctionCallAction {
+            method_name: "new".to_string(),
+            args: vec![],
+            gas: 0,
+            deposit: 0,
+        });
+        assert_eq!(
+            validate_action(&VMLimitConfig::test(), &delegate_action(vec![function_call]))
+                .expect_err("Expected an error"),
+            ActionsValidationError::FunctionCallZeroAttachedGas,
+        );
+    }
+
     #[test]
     fn test_validate_action_valid_create_account() {
         validate_action(&VMLimitConfig::test(), &Action::CreateAccount(CreateAccountAction {}))
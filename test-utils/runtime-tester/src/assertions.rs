@@ -0,0 +1,210 @@
+//! Expectations which can be attached to a [`Scenario`](crate::Scenario) and
+//! are verified once all of its blocks are produced.
+
+use near_client::test_utils::TestEnv;
+use near_primitives::serialize::{base64_format, dec_format};
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::types::{AccountId, Balance, Gas};
+use near_primitives::views::{
+    ExecutionStatusView, FinalExecutionOutcomeView, FinalExecutionStatus, QueryRequest,
+    QueryResponseKind,
+};
+use serde::{Deserialize, Serialize};
+
+/// Expected status of a transaction or of a receipt.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedStatus {
+    /// Execution succeeded, whatever the returned value.
+    Success,
+    /// Execution succeeded and returned given value (encoded in base64).
+    SuccessValue(#[serde(with = "base64_format")] Vec<u8>),
+    /// Execution failed, whatever the error.
+    Failure,
+}
+
+impl ExpectedStatus {
+    fn matches_final(&self, status: &FinalExecutionStatus) -> bool {
+        match (self, status) {
+            (Self::Success, FinalExecutionStatus::SuccessValue(_)) => true,
+            (Self::SuccessValue(expected), FinalExecutionStatus::SuccessValue(value)) => {
+                expected == value
+            }
+            (Self::Failure, FinalExecutionStatus::Failure(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn matches_receipt(&self, status: &ExecutionStatusView) -> bool {
+        match (self, status) {
+            (
+                Self::Success,
+                ExecutionStatusView::SuccessValue(_) | ExecutionStatusView::SuccessReceiptId(_),
+            ) => true,
+            (Self::SuccessValue(expected), ExecutionStatusView::SuccessValue(value)) => {
+                expected == value
+            }
+            (Self::Failure, ExecutionStatusView::Failure(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Expected outcome of a single transaction.
+///
+/// All fields are optional; unset fields are not checked.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TransactionAssertion {
+    /// Final status of the transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ExpectedStatus>,
+    /// Statuses of the receipts produced by the transaction, in the order in
+    /// which they are listed in `receipts_outcome` of the final outcome.
+    /// Refund receipts are not included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipts: Option<Vec<ExpectedStatus>>,
+    /// Lower bound on gas burnt by the transaction and all its receipts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_gas_burnt: Option<Gas>,
+    /// Upper bound on gas burnt by the transaction and all its receipts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_gas_burnt: Option<Gas>,
+}
+
+impl TransactionAssertion {
+    /// Checks the final outcome of the transaction, returning description of
+    /// every violated expectation.
+    pub fn check(&self, outcome: &FinalExecutionOutcomeView) -> Vec<String> {
+        let mut failures = vec![];
+        if let Some(status) = &self.status {
+            if !status.matches_final(&outcome.status) {
+                failures.push(format!("expected status {:?}, got {:?}", status, outcome.status));
+            }
+        }
+        if let Some(receipts) = &self.receipts {
+            let actual: Vec<&ExecutionStatusView> =
+                outcome.receipts_outcome.iter().map(|receipt| &receipt.outcome.status).collect();
+            if receipts.len() != actual.len()
+                || receipts
+                    .iter()
+                    .zip(&actual)
+                    .any(|(expected, got)| !expected.matches_receipt(got))
+            {
+                failures
+                    .push(format!("expected receipt statuses {:?}, got {:?}", receipts, actual));
+            }
+        }
+        let gas_burnt: Gas = std::iter::once(&outcome.transaction_outcome)
+            .chain(outcome.receipts_outcome.iter())
+            .map(|outcome| outcome.outcome.gas_burnt)
+            .sum();
+        if let Some(min_gas_burnt) = self.min_gas_burnt {
+            if gas_burnt < min_gas_burnt {
+                failures
+                    .push(format!("burnt {} gas, expected at least {}", gas_burnt, min_gas_burnt));
+            }
+        }
+        if let Some(max_gas_burnt) = self.max_gas_burnt {
+            if gas_burnt > max_gas_burnt {
+                failures
+                    .push(format!("burnt {} gas, expected at most {}", gas_burnt, max_gas_burnt));
+            }
+        }
+        failures
+    }
+}
+
+/// Expectation about the state after all blocks of the scenario are
+/// produced.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StateAssertion {
+    /// Checks liquid balance of an account.  Both bounds are inclusive.
+    Balance {
+        account_id: AccountId,
+        #[serde(default, with = "dec_format", skip_serializing_if = "Option::is_none")]
+        min: Option<Balance>,
+        #[serde(default, with = "dec_format", skip_serializing_if = "Option::is_none")]
+        max: Option<Balance>,
+    },
+    /// Checks value stored by the contract under given key.  Key and value
+    /// are encoded in base64.  `null` value means the key must be absent.
+    StorageValue {
+        account_id: AccountId,
+        #[serde(with = "base64_format")]
+        key: Vec<u8>,
+        #[serde(default, with = "near_primitives::serialize::option_base64_format")]
+        value: Option<Vec<u8>>,
+    },
+}
+
+impl StateAssertion {
+    /// Checks the assertion against the state as of the head of the chain.
+    pub fn check(&self, env: &TestEnv) -> Result<(), String> {
+        match self {
+            StateAssertion::Balance { account_id, min, max } => {
+                let request = QueryRequest::ViewAccount { account_id: account_id.clone() };
+                let amount = match query(env, &request)? {
+                    QueryResponseKind::ViewAccount(account) => account.amount,
+                    _ => return Err("unexpected response to account query".to_string()),
+                };
+                if min.map_or(false, |min| amount < min) || max.map_or(false, |max| amount > max) {
+                    return Err(format!(
+                        "balance of {} is {}, expected to be within [{}, {}]",
+                        account_id,
+                        amount,
+                        min.map_or("0".to_string(), |min| min.to_string()),
+                        max.map_or("inf".to_string(), |max| max.to_string()),
+                    ));
+                }
+            }
+            StateAssertion::StorageValue { account_id, key, value } => {
+                let request = QueryRequest::ViewState {
+                    account_id: account_id.clone(),
+                    prefix: key.clone().into(),
+                    include_proof: false,
+                    start_after: None,
+                    limit: None,
+                };
+                let values = match query(env, &request)? {
+                    QueryResponseKind::ViewState(result) => result.values,
+                    _ => return Err("unexpected response to state query".to_string()),
+                };
+                let actual =
+                    values.into_iter().find(|item| &item.key == key).map(|item| item.value);
+                if &actual != value {
+                    return Err(format!(
+                        "value of {} under key {:?} is {:?}, expected {:?}",
+                        account_id, key, actual, value
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn query(env: &TestEnv, request: &QueryRequest) -> Result<QueryResponseKind, String> {
+    let chain = &env.clients[0].chain;
+    let head = chain.head().map_err(|err| err.to_string())?;
+    let last_block = chain.get_block(&head.last_block_hash).map_err(|err| err.to_string())?;
+    // The state after the chunks of the last block are applied, so that
+    // changes made in the last block of the scenario are visible.
+    let shard_uid = ShardUId::single_shard();
+    let chunk_extra =
+        chain.get_chunk_extra(&head.last_block_hash, &shard_uid).map_err(|err| err.to_string())?;
+    env.clients[0]
+        .runtime_adapter
+        .query(
+            shard_uid,
+            chunk_extra.state_root(),
+            last_block.header().height(),
+            last_block.header().raw_timestamp(),
+            last_block.header().prev_hash(),
+            last_block.header().hash(),
+            last_block.header().epoch_id(),
+            request,
+        )
+        .map(|response| response.kind)
+        .map_err(|err| err.to_string())
+}
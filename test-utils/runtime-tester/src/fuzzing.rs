@@ -45,7 +45,14 @@ impl Arbitrary<'_> for Scenario {
         while blocks.len() < MAX_BLOCKS && u.len() > BlockConfig::size_hint(0).0 {
             blocks.push(BlockConfig::arbitrary(u, &mut scope)?);
         }
-        Ok(Scenario { network_config, runtime_config, blocks, use_in_memory_store: true })
+        Ok(Scenario {
+            network_config,
+            runtime_config,
+            blocks,
+            use_in_memory_store: true,
+            contracts: vec![],
+            assertions: vec![],
+        })
     }
 
     fn size_hint(_depth: usize) -> (usize, Option<usize>) {
@@ -111,6 +118,7 @@ impl TransactionConfig {
                 receiver_id: receiver_account.id,
                 signer: scope.full_access_signer(u, &signer_account)?,
                 actions: vec![Action::Transfer(TransferAction { deposit: amount })],
+                expected_outcome: None,
             })
         });
 
@@ -132,6 +140,7 @@ impl TransactionConfig {
                 receiver_id: signer_account.id,
                 signer,
                 actions: vec![Action::Stake(StakeAction { stake: amount, public_key })],
+                expected_outcome: None,
             })
         });
          */
@@ -164,6 +173,7 @@ impl TransactionConfig {
                     }),
                     Action::Transfer(TransferAction { deposit: NEAR_BASE }),
                 ],
+                expected_outcome: None,
             })
         });
 
@@ -192,6 +202,7 @@ impl TransactionConfig {
                     actions: vec![Action::DeleteAccount(DeleteAccountAction {
                         beneficiary_id: beneficiary_id.id,
                     })],
+                    expected_outcome: None,
                 })
             });
         }
@@ -217,6 +228,7 @@ impl TransactionConfig {
                 actions: vec![Action::DeployContract(DeployContractAction {
                     code: scope.available_contracts[contract_id].code.clone(),
                 })],
+                expected_outcome: None,
             })
         });
 
@@ -255,6 +267,7 @@ impl TransactionConfig {
                     receiver_id: receiver_account.id.clone(),
                     signer,
                     actions: vec![],
+                    expected_outcome: None,
                 });
             }
 
@@ -275,6 +288,7 @@ impl TransactionConfig {
                 receiver_id: receiver_account.id.clone(),
                 signer,
                 actions,
+                expected_outcome: None,
             })
         });
 
@@ -296,6 +310,7 @@ impl TransactionConfig {
                     scope.usize_id(&signer_account),
                     nonce,
                 )?)],
+                expected_outcome: None,
             })
         });
 
@@ -313,6 +328,7 @@ impl TransactionConfig {
                     receiver_id: signer_account.id.clone(),
                     signer,
                     actions: vec![],
+                    expected_outcome: None,
                 });
             }
 
@@ -324,6 +340,7 @@ impl TransactionConfig {
                 receiver_id: signer_account.id.clone(),
                 signer,
                 actions: vec![Action::DeleteKey(DeleteKeyAction { public_key })],
+                expected_outcome: None,
             })
         });
 
@@ -1,10 +1,15 @@
 #![doc = include_str!("../README.md")]
 
+pub mod assertions;
 pub mod fuzzing;
 pub mod run_test;
 pub mod scenario_builder;
 
-pub use crate::run_test::{BlockConfig, NetworkConfig, RuntimeConfig, Scenario, TransactionConfig};
+pub use crate::assertions::{ExpectedStatus, StateAssertion, TransactionAssertion};
+pub use crate::run_test::{
+    BlockConfig, ContractCode, ContractConfig, ContractFixture, NetworkConfig, RuntimeConfig,
+    Scenario, TransactionConfig,
+};
 pub use crate::scenario_builder::ScenarioBuilder;
 
 #[test]
@@ -28,6 +33,8 @@ fn scenario_smoke_test() {
         },
         blocks: Vec::new(),
         use_in_memory_store: true,
+        contracts: vec![],
+        assertions: vec![],
     };
 
     for h in 1..5 {
@@ -44,6 +51,7 @@ fn scenario_smoke_test() {
                 receiver_id,
                 signer,
                 actions: vec![Action::Transfer(TransferAction { deposit: 10 })],
+                expected_outcome: None,
             }
         };
         block.transactions.push(transaction);
@@ -0,0 +1,56 @@
+//! Runs all JSON scenarios from given directories and reports the ones which
+//! failed.
+
+use clap::Parser;
+use runtime_tester::Scenario;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+struct Cli {
+    /// Directories with scenario files.  Every file with `.json` extension is
+    /// treated as a scenario.
+    #[clap(required = true)]
+    dirs: Vec<PathBuf>,
+}
+
+/// Loads and runs a single scenario, including its assertions.
+fn run_scenario(path: &Path) -> Result<(), String> {
+    let scenario = Scenario::from_file(path).map_err(|err| format!("invalid scenario: {}", err))?;
+    // Let a panicking scenario be reported rather than abort the whole run.
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| scenario.run().result))
+        .map_err(|_| "scenario panicked".to_string())?
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let mut paths = vec![];
+    for dir in &cli.dirs {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "json") {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+
+    let mut failed = 0;
+    for path in &paths {
+        match run_scenario(path) {
+            Ok(()) => println!("ok     {}", path.display()),
+            Err(err) => {
+                failed += 1;
+                println!("FAILED {}\n{}", path.display(), err);
+            }
+        }
+    }
+
+    println!("{} scenarios: {} passed, {} failed", paths.len(), paths.len() - failed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} scenarios failed", failed);
+    }
+    Ok(())
+}
@@ -1,16 +1,18 @@
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
-use near_chain::{Block, ChainGenesis, Provenance};
+use near_chain::{Block, Chain, ChainGenesis, ChainStoreAccess, Provenance};
 use near_chain_configs::Genesis;
 use near_client::test_utils::TestEnv;
 use near_client_primitives::types::Error;
 use near_crypto::InMemorySigner;
-use near_primitives::hash::CryptoHash;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{Action, SignedTransaction};
 use near_primitives::types::{AccountId, BlockHeight, BlockHeightDelta, Gas, Nonce};
+use near_primitives::views::FinalExecutionOutcomeView;
 use near_store::test_utils::create_test_store;
 use nearcore::TrackedConfig;
 use nearcore::{config::GenesisExt, NightshadeRuntime};
@@ -18,6 +20,8 @@ use nearcore::{config::GenesisExt, NightshadeRuntime};
 use near_primitives::runtime::config_store::RuntimeConfigStore;
 use serde::{Deserialize, Serialize};
 
+use crate::assertions::{StateAssertion, TransactionAssertion};
+
 pub struct ScenarioResult<T, E> {
     pub result: std::result::Result<T, E>,
 
@@ -28,8 +32,18 @@ pub struct ScenarioResult<T, E> {
 }
 
 impl Scenario {
+    /// Loads scenario from a JSON file.  Relative paths of contract files
+    /// are resolved against the directory containing the scenario.
     pub fn from_file(path: &Path) -> io::Result<Scenario> {
-        serde_json::from_str::<Scenario>(&std::fs::read_to_string(path)?).map_err(io::Error::from)
+        let mut scenario = serde_json::from_str::<Scenario>(&std::fs::read_to_string(path)?)
+            .map_err(io::Error::from)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for contract in &mut scenario.contracts {
+            if let ContractCode::File(file) = &mut contract.code {
+                *file = dir.join(&*file);
+            }
+        }
+        Ok(scenario)
     }
 
     pub fn run(&self) -> ScenarioResult<RuntimeStats, Error> {
@@ -42,6 +56,7 @@ impl Scenario {
             self.runtime_config.max_total_prepaid_gas;
         genesis.config.epoch_length = self.runtime_config.epoch_length;
         genesis.config.gas_limit = self.runtime_config.gas_limit;
+        let deployed = self.deploy_contracts(&mut genesis);
         let runtime_config_store = RuntimeConfigStore::with_one_config(runtime_config);
 
         let (tempdir, store) = if self.use_in_memory_store {
@@ -64,10 +79,37 @@ impl Scenario {
             ))])
             .build();
 
-        let result = self.process_blocks(&mut env);
+        let result = deployed
+            .and_then(|()| self.process_blocks(&mut env))
+            .and_then(|stats| self.check_assertions(&env, &stats).map(|()| stats));
         ScenarioResult { result, homedir: tempdir, env }
     }
 
+    /// Adds code of the scenario contracts to the genesis records.
+    fn deploy_contracts(&self, genesis: &mut Genesis) -> Result<(), Error> {
+        let records = genesis.force_read_records().as_mut();
+        for contract in &self.contracts {
+            let code = contract.code.load()?;
+            let account = records.iter_mut().find_map(|record| match record {
+                StateRecord::Account { account_id, account }
+                    if account_id == &contract.account_id =>
+                {
+                    Some(account)
+                }
+                _ => None,
+            });
+            let account = account.ok_or_else(|| {
+                Error::Other(format!(
+                    "Cannot deploy contract to unknown account {}",
+                    contract.account_id
+                ))
+            })?;
+            account.set_code_hash(hash(&code));
+            records.push(StateRecord::Contract { account_id: contract.account_id.clone(), code });
+        }
+        Ok(())
+    }
+
     fn process_blocks(&self, env: &mut TestEnv) -> Result<RuntimeStats, Error> {
         let mut last_block = env.clients[0].chain.get_block_by_height(0).unwrap();
 
@@ -96,6 +138,66 @@ impl Scenario {
 
         Ok(runtime_stats)
     }
+
+    /// Verifies expected transaction outcomes and state assertions, returning
+    /// an error describing all the failed ones.
+    fn check_assertions(&self, env: &TestEnv, stats: &RuntimeStats) -> Result<(), Error> {
+        let mut failures = vec![];
+        for (block, block_stats) in self.blocks.iter().zip(&stats.blocks_stats) {
+            for (tx, tx_hash) in block.transactions.iter().zip(&block_stats.tx_hashes) {
+                let assertion = match &tx.expected_outcome {
+                    Some(assertion) => assertion,
+                    None => continue,
+                };
+                let chain = &env.clients[0].chain;
+                match chain
+                    .get_final_transaction_result(tx_hash)
+                    .and_then(|outcome| without_refunds(chain, outcome))
+                {
+                    Ok(outcome) => {
+                        failures.extend(assertion.check(&outcome).into_iter().map(|failure| {
+                            format!(
+                                "transaction {} at height {}: {}",
+                                tx_hash, block.height, failure
+                            )
+                        }))
+                    }
+                    Err(err) => failures.push(format!(
+                        "transaction {} at height {}: no outcome: {}",
+                        tx_hash, block.height, err
+                    )),
+                }
+            }
+        }
+        for assertion in &self.assertions {
+            if let Err(failure) = assertion.check(env) {
+                failures.push(failure);
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Other(format!("Scenario assertions failed:\n{}", failures.join("\n"))))
+        }
+    }
+}
+
+/// Drops outcomes of refund receipts, which are sent by the system account,
+/// so that assertions only list the receipts created by the transaction.
+fn without_refunds(
+    chain: &Chain,
+    mut outcome: FinalExecutionOutcomeView,
+) -> Result<FinalExecutionOutcomeView, near_chain::Error> {
+    let mut receipts_outcome = vec![];
+    for receipt_outcome in outcome.receipts_outcome {
+        // Local receipts aren't saved, but they're never refunds.
+        let receipt = chain.store().get_receipt(&receipt_outcome.id)?;
+        if !receipt.map_or(false, |receipt| receipt.predecessor_id.is_system()) {
+            receipts_outcome.push(receipt_outcome);
+        }
+    }
+    outcome.receipts_outcome = receipts_outcome;
+    Ok(outcome)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -104,6 +206,12 @@ pub struct Scenario {
     pub runtime_config: RuntimeConfig,
     pub blocks: Vec<BlockConfig>,
     pub use_in_memory_store: bool,
+    /// Contracts deployed in genesis.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contracts: Vec<ContractConfig>,
+    /// Assertions about the state checked after all blocks are produced.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<StateAssertion>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -118,6 +226,34 @@ pub struct RuntimeConfig {
     pub epoch_length: BlockHeightDelta,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContractConfig {
+    pub account_id: AccountId,
+    pub code: ContractCode,
+}
+
+/// Source of a contract code.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractCode {
+    /// One of the contracts from `near-test-contracts`.
+    Fixture(ContractFixture),
+    /// Path to a compiled wasm file.
+    File(PathBuf),
+}
+
+/// Contracts from `near-test-contracts` which can be referenced by name.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractFixture {
+    TrivialContract,
+    RsContract,
+    BaseRsContract,
+    TsContract,
+    FuzzingContract,
+    SmallestRsContract,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct BlockConfig {
     pub height: BlockHeight,
@@ -131,6 +267,10 @@ pub struct TransactionConfig {
     pub receiver_id: AccountId,
     pub signer: InMemorySigner,
     pub actions: Vec<Action>,
+    /// Expected outcome of the transaction, checked after all blocks are
+    /// produced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_outcome: Option<TransactionAssertion>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -157,6 +297,34 @@ impl BlockConfig {
     }
 }
 
+impl ContractCode {
+    fn load(&self) -> Result<Vec<u8>, Error> {
+        Ok(match self {
+            ContractCode::Fixture(ContractFixture::TrivialContract) => {
+                near_test_contracts::trivial_contract().to_vec()
+            }
+            ContractCode::Fixture(ContractFixture::RsContract) => {
+                near_test_contracts::rs_contract().to_vec()
+            }
+            ContractCode::Fixture(ContractFixture::BaseRsContract) => {
+                near_test_contracts::base_rs_contract().to_vec()
+            }
+            ContractCode::Fixture(ContractFixture::TsContract) => {
+                near_test_contracts::ts_contract().to_vec()
+            }
+            ContractCode::Fixture(ContractFixture::FuzzingContract) => {
+                near_test_contracts::fuzzing_contract().to_vec()
+            }
+            ContractCode::Fixture(ContractFixture::SmallestRsContract) => {
+                near_test_contracts::smallest_rs_contract().to_vec()
+            }
+            ContractCode::File(path) => std::fs::read(path).map_err(|err| {
+                Error::Other(format!("Failed to read contract {}: {}", path.display(), err))
+            })?,
+        })
+    }
+}
+
 impl TransactionConfig {
     fn to_signed_transaction(&self, last_block: &Block) -> SignedTransaction {
         SignedTransaction::from_actions(
@@ -183,7 +351,9 @@ mod test {
     use std::path::Path;
     use std::time::{Duration, Instant};
 
+    use near_crypto::KeyType;
     use near_o11y::testonly::init_test_logger;
+    use near_primitives::transaction::TransferAction;
     use tracing::info;
 
     #[test]
@@ -205,4 +375,82 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_scenario_assertions() {
+        init_test_logger();
+        let path = Path::new("./scenarios/trivial_contract_call.json");
+        let mut scenario = Scenario::from_file(path).unwrap();
+        scenario.run().result.unwrap();
+
+        scenario.blocks[1].transactions[0].expected_outcome.as_mut().unwrap().max_gas_burnt =
+            Some(1);
+        scenario.assertions.push(StateAssertion::Balance {
+            account_id: "test1".parse().unwrap(),
+            min: None,
+            max: Some(0),
+        });
+        let err = scenario.run().result.unwrap_err().to_string();
+        assert!(err.contains("expected at most 1"), "{}", err);
+        assert!(err.contains("balance of test1"), "{}", err);
+    }
+
+    /// Tests that state assertions see the changes made in the last block of
+    /// the scenario.
+    #[test]
+    fn test_scenario_assertions_after_last_block() {
+        init_test_logger();
+        let test0: AccountId = "test0".parse().unwrap();
+        let test1: AccountId = "test1".parse().unwrap();
+        let amount = 10u128.pow(30);
+        let mut scenario = Scenario {
+            network_config: NetworkConfig { seeds: vec![test0.to_string(), test1.to_string()] },
+            runtime_config: RuntimeConfig {
+                max_total_prepaid_gas: 300 * 10u64.pow(12),
+                gas_limit: 10u64.pow(15),
+                epoch_length: 500,
+            },
+            blocks: (1..=5).map(BlockConfig::at_height).collect(),
+            use_in_memory_store: true,
+            contracts: vec![],
+            assertions: vec![],
+        };
+        scenario.blocks[0].transactions.push(TransactionConfig {
+            nonce: 1,
+            signer_id: test0.clone(),
+            receiver_id: test1.clone(),
+            signer: InMemorySigner::from_seed(test0.clone(), KeyType::ED25519, test0.as_ref()),
+            actions: vec![Action::Transfer(TransferAction { deposit: amount })],
+            expected_outcome: None,
+        });
+
+        // End the scenario with the block in which the transfer is received.
+        let result = scenario.run();
+        let tx_hash = result.result.unwrap().blocks_stats[0].tx_hashes[0];
+        let chain = &result.env.clients[0].chain;
+        let outcome = chain.get_final_transaction_result(&tx_hash).unwrap();
+        let block_hash = outcome.receipts_outcome[0].block_hash;
+        let height = chain.get_block_header(&block_hash).unwrap().height();
+        scenario.blocks.retain(|block| block.height <= height);
+
+        let mut genesis = Genesis::test(vec![test0, test1.clone()], 1);
+        let balance = genesis
+            .force_read_records()
+            .as_mut()
+            .iter()
+            .find_map(|record| match record {
+                StateRecord::Account { account_id, account } if account_id == &test1 => {
+                    Some(account.amount())
+                }
+                _ => None,
+            })
+            .unwrap()
+            + amount;
+        scenario.assertions.push(StateAssertion::Balance {
+            account_id: test1,
+            min: Some(balance),
+            max: Some(balance),
+        });
+        scenario.run().result.unwrap();
+    }
 }
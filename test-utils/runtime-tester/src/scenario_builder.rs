@@ -61,6 +61,8 @@ impl ScenarioBuilder {
                 runtime_config,
                 blocks: vec![],
                 use_in_memory_store: true,
+                contracts: vec![],
+                assertions: vec![],
             },
         }
     }
@@ -128,6 +130,7 @@ impl ScenarioBuilder {
             receiver_id: receiver_id,
             signer,
             actions,
+            expected_outcome: None,
         });
 
         self.nonce += 1
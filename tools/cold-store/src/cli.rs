@@ -1,8 +1,15 @@
+use near_chain::types::ChainConfig;
+use near_chain::{Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode, RuntimeAdapter};
 use near_primitives::block::Tip;
-use near_store::{DBCol, NodeStorage, Temperature, FINAL_HEAD_KEY, HEAD_KEY};
+use near_store::cold_storage::{copy_all_data_to_cold, update_cold_head};
+use near_store::{
+    DBCol, NodeStorage, StoreOpener, StoreOpenerError, Temperature, FINAL_HEAD_KEY, HEAD_KEY,
+};
+use nearcore::{NearConfig, NightshadeRuntime};
 
 use clap::Parser;
 use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Parser)]
 pub struct ColdStoreCommand {
@@ -17,6 +24,30 @@ enum SubCommand {
     Open,
     /// Open NodeStorage and print cold head, hot head and hot final head.
     Head,
+    /// Migrate an existing archival node to hot and cold storage.
+    ///
+    /// Creates the cold database if it doesn’t exist yet, copies all cold
+    /// columns into it and sets cold head to the final head of hot storage.
+    /// Finally, garbage collects hot storage down to the configured GC window.
+    /// The copying is resumable: if the command is interrupted, running it
+    /// again continues from the last saved checkpoint.  The node must not be
+    /// running during the migration.
+    Migrate(MigrateCmd),
+}
+
+#[derive(Parser)]
+struct MigrateCmd {
+    /// Approximate size in bytes of a single batch of data written to cold
+    /// storage.  A checkpoint is saved after every batch.
+    #[clap(long, default_value_t = 500_000_000)]
+    batch_size: usize,
+    /// Don’t garbage collect hot storage once the data is copied.
+    #[clap(long)]
+    skip_trim: bool,
+    /// Maximum number of blocks garbage collected from hot storage in a single
+    /// step of trimming.
+    #[clap(long, default_value_t = 1000)]
+    gc_blocks_limit: u64,
 }
 
 impl ColdStoreCommand {
@@ -31,11 +62,86 @@ impl ColdStoreCommand {
             &near_config.config.store,
             near_config.config.cold_store.as_ref(),
         );
-        let store = opener.open().unwrap_or_else(|e| panic!("Error opening storage: {:#}", e));
+        let open = || opener.open().unwrap_or_else(|e| panic!("Error opening storage: {:#}", e));
         match self.subcmd {
-            SubCommand::Open => check_open(&store),
-            SubCommand::Head => print_heads(&store),
+            SubCommand::Open => check_open(&open()),
+            SubCommand::Head => print_heads(&open()),
+            SubCommand::Migrate(cmd) => cmd.run(home_dir, &near_config, &opener),
+        }
+    }
+}
+
+impl MigrateCmd {
+    fn run(self, home_dir: &Path, near_config: &NearConfig, opener: &StoreOpener) {
+        let store = match opener.open() {
+            // An archival database without the cold one.  This is the
+            // expected state when starting the migration.
+            Err(StoreOpenerError::HotColdExistenceMismatch) => opener.create_cold_for_archive(),
+            result => result,
+        }
+        .unwrap_or_else(|e| panic!("Error opening storage: {:#}", e));
+        let hot_store = store.get_store(Temperature::Hot);
+        let cold_db = store.cold_db().unwrap();
+
+        let cold_head = store
+            .get_store(Temperature::Cold)
+            .get_ser::<Tip>(DBCol::BlockMisc, HEAD_KEY)
+            .unwrap_or_else(|e| panic!("Error reading cold head: {:#}", e));
+        if let Some(cold_head) = cold_head {
+            println!("Cold storage has already been populated up to {}", cold_head.height);
+        } else {
+            let final_head = hot_store
+                .get_ser::<Tip>(DBCol::BlockMisc, FINAL_HEAD_KEY)
+                .unwrap_or_else(|e| panic!("Error reading hot final head: {:#}", e))
+                .expect("hot storage has no final head");
+            println!("Copying data to cold storage");
+            copy_all_data_to_cold(cold_db, &hot_store, self.batch_size)
+                .unwrap_or_else(|e| panic!("Error copying data to cold storage: {:#}", e));
+            update_cold_head(cold_db, &hot_store, &final_head.height)
+                .unwrap_or_else(|e| panic!("Error updating cold head: {:#}", e));
+            println!("Cold storage populated up to {}", final_head.height);
+        }
+
+        if !self.skip_trim {
+            trim_hot_store(home_dir, near_config, &store, self.gc_blocks_limit);
+        }
+    }
+}
+
+/// Garbage collects hot storage down to the configured GC window.
+///
+/// Archival node never garbage collects its database so this runs regular
+/// garbage collection until the tail stops advancing.  Note that archival
+/// nodes don’t save `TrieChanges` so unreferenced `State` entries of old
+/// blocks are left in place.
+fn trim_hot_store(home_dir: &Path, near_config: &NearConfig, store: &NodeStorage, limit: u64) {
+    let hot_store = store.get_store(Temperature::Hot);
+    let runtime = Arc::new(NightshadeRuntime::from_config(home_dir, hot_store, near_config));
+    let mut chain = Chain::new(
+        runtime.clone(),
+        &ChainGenesis::new(&near_config.genesis),
+        DoomslugThresholdMode::TwoThirds,
+        ChainConfig {
+            save_trie_changes: near_config.client_config.save_trie_changes,
+            background_migration_threads: 1,
+        },
+    )
+    .unwrap_or_else(|e| panic!("Error opening chain: {:#}", e));
+    let gc_config = near_chain_configs::GCConfig {
+        gc_blocks_limit: limit,
+        ..near_config.client_config.gc.clone()
+    };
+    let mut tail = chain.store().tail().unwrap();
+    loop {
+        chain
+            .clear_data(runtime.get_tries(), &gc_config)
+            .unwrap_or_else(|e| panic!("Error garbage collecting hot storage: {:#}", e));
+        let new_tail = chain.store().tail().unwrap();
+        if new_tail == tail {
+            break;
         }
+        println!("Hot storage tail is at {}", new_tail);
+        tail = new_tail;
     }
 }
 
@@ -252,6 +252,7 @@ pub fn setup_mock_node(
         None,
         chain_genesis.clone(),
         client_runtime,
+        None,
         network_adapter.clone(),
         config.client_config.clone(),
         adv,
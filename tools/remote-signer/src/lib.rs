@@ -0,0 +1,312 @@
+//! Reference implementation of a signer serving `RemoteValidatorSigner`.
+//!
+//! The signer holds the validator key loaded from a local key file and
+//! refuses to sign two different blocks, two different approvals or two
+//! different chunks of the same shard at the same height, or anything at a
+//! height lower than the last signed one.  The last signed block, approval and
+//! chunks are persisted in a state file before the signature is returned, so
+//! that the protection survives restarts.  Bare chunk hashes are not signed,
+//! since their height can't be checked; chunk headers from before
+//! `ShardChunkHeaderV3` can't be produced with this signer.
+use std::collections::BTreeMap;
+use std::io;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use borsh::BorshDeserialize;
+use near_crypto::{InMemorySigner, Signer};
+use near_primitives::block::{Approval, BlockHeader};
+use near_primitives::block_header::BlockHeaderInnerLite;
+use near_primitives::hash::CryptoHash;
+use near_primitives::sharding::ShardChunkHeaderV3;
+use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::validator_signer::remote::{
+    read_frame, write_frame, SignRequest, SignResponse,
+};
+use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+
+/// Message signed at some height.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Signed {
+    pub height: BlockHeight,
+    /// Hash of the signed block or of the signed approval data.
+    pub hash: CryptoHash,
+}
+
+/// Last signed messages, persisted in the state file.
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct SignerState {
+    pub last_block: Option<Signed>,
+    pub last_approval: Option<Signed>,
+    /// Last signed chunk of each shard.
+    #[serde(default)]
+    pub last_chunks: BTreeMap<ShardId, Option<Signed>>,
+}
+
+impl SignerState {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Writes the state to a temporary file first, so that a crash never
+    /// leaves a truncated state file behind.
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let file = std::fs::File::create(&tmp_path)?;
+        serde_json::to_writer_pretty(&file, self)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    }
+}
+
+/// Checks whether signing `hash` at `height` is allowed given the last
+/// signed message.  Returns `true` if the last signed message needs to be
+/// updated.
+fn check_double_sign(
+    last: &Option<Signed>,
+    height: BlockHeight,
+    hash: &CryptoHash,
+) -> Result<bool, String> {
+    match last {
+        None => Ok(true),
+        Some(last) if height > last.height => Ok(true),
+        // Signing the same message again is harmless.
+        Some(last) if height == last.height && hash == &last.hash => Ok(false),
+        Some(last) if height == last.height => {
+            Err(format!("already signed {} at height {}", last.hash, height))
+        }
+        Some(last) => Err(format!("height {} is lower than last signed {}", height, last.height)),
+    }
+}
+
+pub struct RemoteSigner {
+    key: InMemorySigner,
+    signer: InMemoryValidatorSigner,
+    state_file: PathBuf,
+    state: Mutex<SignerState>,
+}
+
+impl RemoteSigner {
+    pub fn new(key_file: &Path, state_file: &Path) -> anyhow::Result<Self> {
+        let key = InMemorySigner::from_file(key_file)
+            .with_context(|| format!("failed reading key file {}", key_file.display()))?;
+        let signer = InMemoryValidatorSigner::from_file(key_file)?;
+        let state = SignerState::load(state_file)
+            .with_context(|| format!("failed reading state file {}", state_file.display()))?;
+        Ok(Self { key, signer, state_file: state_file.to_path_buf(), state: Mutex::new(state) })
+    }
+
+    /// Checks and records the message to be signed.  The state is persisted
+    /// before returning, so the lock is held until the state file is written.
+    fn guard(
+        &self,
+        slot: impl Fn(&mut SignerState) -> &mut Option<Signed>,
+        height: BlockHeight,
+        hash: CryptoHash,
+    ) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if check_double_sign(slot(&mut state), height, &hash)? {
+            let mut new_state = state.clone();
+            *slot(&mut new_state) = Some(Signed { height, hash });
+            new_state.save(&self.state_file).map_err(|err| format!("saving state: {err}"))?;
+            *state = new_state;
+        }
+        Ok(())
+    }
+
+    pub fn handle(&self, req: SignRequest) -> SignResponse {
+        match self.try_handle(req) {
+            Ok(resp) => resp,
+            Err(reason) => {
+                tracing::warn!(target: "remote_signer", %reason, "Refused to sign");
+                SignResponse::Refused(reason)
+            }
+        }
+    }
+
+    fn try_handle(&self, req: SignRequest) -> Result<SignResponse, String> {
+        let signer = &self.signer;
+        Ok(SignResponse::Signature(match req {
+            SignRequest::ValidatorInfo => {
+                return Ok(SignResponse::ValidatorInfo {
+                    account_id: signer.validator_id().clone(),
+                    public_key: signer.public_key(),
+                })
+            }
+            SignRequest::Telemetry { content } => {
+                // Make sure we are signing JSON rather than e.g. a hash of a block.
+                serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&content)
+                    .map_err(|err| format!("invalid telemetry: {err}"))?;
+                self.key.sign(content.as_bytes())
+            }
+            SignRequest::BlockHeaderParts { prev_hash, inner_lite, inner_rest } => {
+                let height = BlockHeaderInnerLite::try_from_slice(&inner_lite)
+                    .map_err(|err| format!("invalid block header: {err}"))?
+                    .height;
+                let hash = BlockHeader::compute_hash(prev_hash, &inner_lite, &inner_rest);
+                self.guard(|s| &mut s.last_block, height, hash)?;
+                signer.sign_block_header_parts(prev_hash, &inner_lite, &inner_rest).1
+            }
+            SignRequest::ChunkHash(_) => {
+                return Err("chunk hash without the chunk header".to_string());
+            }
+            SignRequest::ChunkHeaderInner(inner) => {
+                let shard_id = inner.shard_id();
+                let hash = ShardChunkHeaderV3::compute_hash(&inner);
+                self.guard(
+                    |s| s.last_chunks.entry(shard_id).or_default(),
+                    inner.height_created(),
+                    hash.0,
+                )?;
+                signer.sign_chunk_hash(&hash)
+            }
+            SignRequest::Approval { inner, target_height } => {
+                let data = Approval::get_data_for_sig(&inner, target_height);
+                self.guard(|s| &mut s.last_approval, target_height, CryptoHash::hash_bytes(&data))?;
+                signer.sign_approval(&inner, target_height)
+            }
+            SignRequest::Challenge(body) => signer.sign_challenge(&body).1,
+            SignRequest::AccountAnnounce { account_id, peer_id, epoch_id } => {
+                signer.sign_account_announce(&account_id, &peer_id, &epoch_id)
+            }
+            SignRequest::AccountKeyPayload(data) => signer.sign_account_key_payload(&data),
+            SignRequest::VrfWithProof(data) => {
+                let (value, proof) = signer.compute_vrf_with_proof(&data);
+                return Ok(SignResponse::VrfWithProof { value, proof });
+            }
+        }))
+    }
+
+    fn serve_connection(&self, mut stream: UnixStream) -> io::Result<()> {
+        loop {
+            let req = match read_frame::<SignRequest>(&mut stream) {
+                Ok(req) => req,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(err) => return Err(err),
+            };
+            write_frame(&mut stream, &self.handle(req))?;
+        }
+    }
+
+    /// Serves connections accepted by the listener, each in a separate thread.
+    pub fn serve(self: Arc<Self>, listener: UnixListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let this = self.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = this.serve_connection(stream) {
+                    tracing::warn!(target: "remote_signer", %err, "Connection failed");
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, Signature};
+    use near_primitives::block::ApprovalInner;
+    use near_primitives::types::EpochId;
+    use near_primitives::validator_signer::RemoteValidatorSigner;
+    use std::time::Duration;
+
+    fn start(dir: &Path, name: &str) -> RemoteValidatorSigner {
+        let signer = RemoteSigner::new(&dir.join("key.json"), &dir.join("state.json")).unwrap();
+        let socket = dir.join(name);
+        let listener = UnixListener::bind(&socket).unwrap();
+        std::thread::spawn(move || Arc::new(signer).serve(listener));
+        RemoteValidatorSigner::connect(&socket, Duration::from_secs(10)).unwrap()
+    }
+
+    fn inner_lite(height: BlockHeight, timestamp: u64) -> Vec<u8> {
+        borsh::BorshSerialize::try_to_vec(&BlockHeaderInnerLite {
+            height,
+            epoch_id: EpochId::default(),
+            next_epoch_id: EpochId::default(),
+            prev_state_root: CryptoHash::default(),
+            outcome_root: CryptoHash::default(),
+            timestamp,
+            next_bp_hash: CryptoHash::default(),
+            block_merkle_root: CryptoHash::default(),
+        })
+        .unwrap()
+    }
+
+    fn chunk(
+        signer: &dyn ValidatorSigner,
+        height: BlockHeight,
+        shard_id: ShardId,
+        gas_used: u64,
+    ) -> ShardChunkHeaderV3 {
+        let hash = CryptoHash::default();
+        ShardChunkHeaderV3::new(
+            hash,
+            hash,
+            hash,
+            hash,
+            0,
+            height,
+            shard_id,
+            gas_used,
+            0,
+            0,
+            hash,
+            hash,
+            vec![],
+            signer,
+        )
+    }
+
+    #[test]
+    fn test_double_sign_protection() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = InMemoryValidatorSigner::from_random("test".parse().unwrap(), KeyType::ED25519);
+        key.write_to_file(&dir.path().join("key.json")).unwrap();
+        let empty = Signature::empty(KeyType::ED25519);
+
+        let signer = start(dir.path(), "signer1.sock");
+        assert_eq!(signer.validator_id(), key.validator_id());
+        assert_eq!(signer.public_key(), key.public_key());
+
+        let prev_hash = CryptoHash::hash_bytes(b"prev");
+        let (hash, signature) = signer.sign_block_header_parts(prev_hash, &inner_lite(5, 1), &[]);
+        assert!(signature.verify(hash.as_ref(), &key.public_key()));
+        // Same block can be signed again, but a different one at the same or lower height can't.
+        assert_eq!(signature, signer.sign_block_header_parts(prev_hash, &inner_lite(5, 1), &[]).1);
+        assert_eq!(empty, signer.sign_block_header_parts(prev_hash, &inner_lite(5, 2), &[]).1);
+        assert_eq!(empty, signer.sign_block_header_parts(prev_hash, &inner_lite(4, 1), &[]).1);
+
+        let endorsement = ApprovalInner::Endorsement(prev_hash);
+        let signature = signer.sign_approval(&endorsement, 6);
+        assert!(signature.verify(&Approval::get_data_for_sig(&endorsement, 6), &key.public_key()));
+        assert_eq!(empty, signer.sign_approval(&ApprovalInner::Skip(4), 6));
+        assert_ne!(empty, signer.sign_approval(&ApprovalInner::Skip(4), 7));
+
+        // Signed heights survive restarts.
+        let signer = start(dir.path(), "signer2.sock");
+        assert_eq!(empty, signer.sign_block_header_parts(prev_hash, &inner_lite(5, 2), &[]).1);
+        assert_eq!(empty, signer.sign_approval(&endorsement, 7));
+        assert_ne!(empty, signer.sign_block_header_parts(prev_hash, &inner_lite(6, 1), &[]).1);
+
+        let header = chunk(&signer, 5, 0, 1);
+        assert!(header.signature.verify(header.hash.as_ref(), &key.public_key()));
+        assert_eq!(header.signature, chunk(&signer, 5, 0, 1).signature);
+        assert_eq!(empty, chunk(&signer, 5, 0, 2).signature);
+        assert_eq!(empty, chunk(&signer, 4, 0, 1).signature);
+        assert_ne!(empty, chunk(&signer, 5, 1, 2).signature);
+        // Chunk hashes can't be checked, so they are never signed.
+        assert_eq!(empty, signer.sign_chunk_hash(&header.hash));
+
+        let signer = start(dir.path(), "signer3.sock");
+        assert_eq!(empty, chunk(&signer, 5, 1, 1).signature);
+        assert_ne!(empty, chunk(&signer, 6, 1, 1).signature);
+    }
+}
@@ -0,0 +1,41 @@
+use anyhow::Context;
+use clap::Parser;
+use near_remote_signer::RemoteSigner;
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Signs blocks, chunks and approvals on behalf of a validator node configured
+/// with `remote_signer` in its config.json, so that the validator key doesn't
+/// need to be stored on the node.
+#[derive(Parser)]
+struct Cli {
+    /// Validator key file, in the same format as validator_key.json.
+    #[clap(long)]
+    key_file: PathBuf,
+    /// File in which the last signed block and approval heights are stored.
+    /// Refusing to sign below them protects against double signing, so the
+    /// file must never be removed while the key is in use.
+    #[clap(long)]
+    state_file: PathBuf,
+    /// Path of the Unix socket to listen on.
+    #[clap(long)]
+    socket: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let env_filter = near_o11y::EnvFilterBuilder::from_env().finish().unwrap();
+    let _subscriber = near_o11y::default_subscriber(env_filter, &Default::default()).global();
+    let cli = Cli::parse();
+
+    let signer = RemoteSigner::new(&cli.key_file, &cli.state_file)?;
+    // A socket left behind by a previous run would make bind fail.
+    if cli.socket.exists() {
+        std::fs::remove_file(&cli.socket)?;
+    }
+    let listener = UnixListener::bind(&cli.socket)
+        .with_context(|| format!("failed binding to {}", cli.socket.display()))?;
+    tracing::info!(target: "remote_signer", socket = %cli.socket.display(), "Listening");
+    Arc::new(signer).serve(listener)?;
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::commands::*;
 use crate::dump_state_parts::dump_state_parts;
 use crate::rocksdb_stats::get_rocksdb_stats;
-use crate::{dump_state_parts, epoch_info};
+use crate::{dump_state_parts, epoch_info, resharding};
 use clap::{Args, Parser, Subcommand};
 use near_chain_configs::{GenesisChangeConfig, GenesisValidationMode};
 use near_primitives::account::id::AccountId;
@@ -64,6 +64,9 @@ pub enum StateViewerSubCommand {
     PartialChunks(PartialChunksCmd),
     /// Prints stored peers information from the DB.
     Peers,
+    /// Proposes a shard layout splitting a shard by storage and gas usage.
+    #[clap(alias = "propose_shard_layout")]
+    ProposeShardLayout(ProposeShardLayoutCmd),
     /// Looks up a certain receipt.
     Receipts(ReceiptsCmd),
     /// Replay headers from chain.
@@ -107,6 +110,7 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::EpochInfo(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::PartialChunks(cmd) => cmd.run(near_config, hot),
             StateViewerSubCommand::Peers => peers(store),
+            StateViewerSubCommand::ProposeShardLayout(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::Receipts(cmd) => cmd.run(near_config, hot),
             StateViewerSubCommand::Replay(cmd) => cmd.run(home_dir, near_config, hot),
             StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(store_opener.path()),
@@ -343,6 +347,10 @@ pub struct DumpStatePartsCmd {
     /// Where to write the state parts to.
     #[clap(long)]
     output_dir: PathBuf,
+    /// Lay the parts out the way state sync from an external storage expects
+    /// them, with `output_dir` being the root directory of the storage.
+    #[clap(long)]
+    external_storage_layout: bool,
 }
 
 impl DumpStatePartsCmd {
@@ -355,6 +363,7 @@ impl DumpStatePartsCmd {
             near_config,
             store,
             &self.output_dir,
+            self.external_storage_layout,
         );
     }
 }
@@ -439,6 +448,34 @@ impl PartialChunksCmd {
     }
 }
 
+#[derive(Parser)]
+pub struct ProposeShardLayoutCmd {
+    /// Shard to split.
+    #[clap(long)]
+    shard_id: ShardId,
+    /// Number of shards to split the shard into.
+    #[clap(long, default_value = "2")]
+    num_shards: usize,
+    /// JSON file mapping account ids to the gas they used.  Without it the
+    /// shard is split by storage usage only.
+    #[clap(long, parse(from_os_str))]
+    gas_stats: Option<PathBuf>,
+}
+
+impl ProposeShardLayoutCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
+        resharding::propose_shard_layout(
+            self.shard_id,
+            self.num_shards,
+            self.gas_stats.as_deref(),
+            home_dir,
+            near_config,
+            store,
+        )
+        .expect("Couldn't propose shard layout");
+    }
+}
+
 #[derive(Parser)]
 pub struct ReceiptsCmd {
     #[clap(long)]
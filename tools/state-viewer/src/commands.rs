@@ -760,7 +760,7 @@ enum LoadTrieMode {
     LastFinalFromHeight(BlockHeight),
 }
 
-fn load_trie(
+pub(crate) fn load_trie(
     store: Store,
     home_dir: &Path,
     near_config: &NearConfig,
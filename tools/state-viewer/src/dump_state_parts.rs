@@ -1,6 +1,7 @@
 use crate::epoch_info::iterate_and_filter;
 use clap::Subcommand;
 use near_chain::{ChainStore, ChainStoreAccess, RuntimeAdapter};
+use near_client::sync::external::external_storage_location;
 use near_epoch_manager::EpochManager;
 use near_primitives::epoch_manager::epoch_info::EpochInfo;
 use near_primitives::state_part::PartId;
@@ -109,6 +110,7 @@ pub(crate) fn dump_state_parts(
     near_config: NearConfig,
     store: Store,
     output_dir: &Path,
+    external_storage_layout: bool,
 ) {
     let runtime_adapter: Arc<dyn RuntimeAdapter> =
         Arc::new(NightshadeRuntime::from_config(home_dir, store.clone(), &near_config));
@@ -160,7 +162,18 @@ pub(crate) fn dump_state_parts(
                 PartId::new(part_id, num_parts),
             )
             .unwrap();
-        let filename = output_dir.join(format!("state_part_{:06}", part_id));
+        let filename = if external_storage_layout {
+            output_dir.join(external_storage_location(
+                &near_config.client_config.chain_id,
+                &epoch_id,
+                shard_id,
+                part_id,
+                num_parts,
+            ))
+        } else {
+            output_dir.join(format!("state_part_{:06}", part_id))
+        };
+        std::fs::create_dir_all(filename.parent().unwrap()).unwrap();
         let len = state_part.len();
         std::fs::write(&filename, state_part).unwrap();
         tracing::info!(
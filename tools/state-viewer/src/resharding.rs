@@ -0,0 +1,158 @@
+//! Proposes boundary accounts for splitting a shard so that the new shards
+//! get similar shares of the storage and gas usage of the split shard.
+use crate::commands::load_trie;
+use near_chain::RuntimeAdapter;
+use near_epoch_manager::EpochManagerAdapter;
+use near_primitives::account::id::AccountId;
+use near_primitives::shard_layout::account_id_to_shard_id;
+use near_primitives::state_record::StateRecord;
+use near_primitives::types::{Gas, ShardId, StorageUsage};
+use near_store::Store;
+use nearcore::NearConfig;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Storage and gas used by an account.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct AccountUsage {
+    pub storage: StorageUsage,
+    pub gas: Gas,
+}
+
+fn share(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        value as f64 / total as f64
+    }
+}
+
+/// Returns at most `num_shards - 1` boundary accounts dividing `accounts`
+/// into ranges of similar load.  The load of an account is its share of the
+/// total storage plus its share of the total gas, so that both are weighted
+/// equally.  Each boundary account starts a new range.
+pub(crate) fn propose_boundary_accounts(
+    accounts: &BTreeMap<AccountId, AccountUsage>,
+    num_shards: usize,
+) -> Vec<AccountId> {
+    let total_storage = accounts.values().map(|usage| usage.storage).sum();
+    let total_gas = accounts.values().map(|usage| usage.gas).sum();
+    let load =
+        |usage: &AccountUsage| share(usage.storage, total_storage) + share(usage.gas, total_gas);
+    let total_load: f64 = accounts.values().map(load).sum();
+
+    let mut boundary_accounts = vec![];
+    let mut cumulative_load = 0.0;
+    for (account_id, usage) in accounts {
+        if boundary_accounts.len() + 1 == num_shards {
+            break;
+        }
+        let target = total_load * (boundary_accounts.len() + 1) as f64 / num_shards as f64;
+        // The account goes to the next range if most of its load is past the
+        // target.  The first range must not be empty.
+        if cumulative_load > 0.0 && cumulative_load + load(usage) / 2.0 >= target {
+            boundary_accounts.push(account_id.clone());
+        }
+        cumulative_load += load(usage);
+    }
+    boundary_accounts
+}
+
+/// Prints the shard layout in which `shard_id` of the current layout is split
+/// into `num_shards` shards based on the storage usage of accounts at the
+/// head of the chain and the gas usage read from `gas_stats_file`.
+pub(crate) fn propose_shard_layout(
+    shard_id: ShardId,
+    num_shards: usize,
+    gas_stats_file: Option<&Path>,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(num_shards >= 2, "the shard must be split into at least two shards");
+    let gas_stats: HashMap<AccountId, Gas> = match gas_stats_file {
+        Some(path) => serde_json::from_slice(&std::fs::read(path)?)?,
+        None => HashMap::new(),
+    };
+
+    let (runtime, state_roots, header) = load_trie(store, home_dir, &near_config);
+    let shard_layout = runtime.get_shard_layout(header.epoch_id())?;
+    let state_root = state_roots
+        .get(shard_id as usize)
+        .ok_or_else(|| anyhow::anyhow!("shard {} doesn't exist", shard_id))?;
+    let trie = runtime.get_trie_for_shard(shard_id, header.prev_hash(), *state_root, false)?;
+    let mut accounts = BTreeMap::new();
+    for item in trie.iter()? {
+        let (key, value) = item?;
+        if let Some(StateRecord::Account { account_id, account }) =
+            StateRecord::from_raw_key_value(key, value)
+        {
+            let gas = gas_stats.get(&account_id).copied().unwrap_or_default();
+            accounts.insert(account_id, AccountUsage { storage: account.storage_usage(), gas });
+        }
+    }
+
+    let boundary_accounts = propose_boundary_accounts(&accounts, num_shards);
+    if boundary_accounts.len() + 1 < num_shards {
+        println!(
+            "Shard {} can only be split into {} shards",
+            shard_id,
+            boundary_accounts.len() + 1
+        );
+    }
+    let new_shard_layout = shard_layout
+        .split_shard(shard_id, boundary_accounts)
+        .map_err(|err| anyhow::anyhow!("can't split shard {}: {:?}", shard_id, err))?;
+
+    let mut new_shard_usage: BTreeMap<ShardId, (usize, AccountUsage)> = BTreeMap::new();
+    for (account_id, usage) in &accounts {
+        let entry = new_shard_usage
+            .entry(account_id_to_shard_id(account_id, &new_shard_layout))
+            .or_default();
+        entry.0 += 1;
+        entry.1.storage += usage.storage;
+        entry.1.gas += usage.gas;
+    }
+    for (new_shard_id, (num_accounts, usage)) in new_shard_usage {
+        println!(
+            "Shard {}: {} accounts, {} bytes of storage, {} gas",
+            new_shard_id, num_accounts, usage.storage, usage.gas
+        );
+    }
+    println!("{}", serde_json::to_string_pretty(&new_shard_layout)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{propose_boundary_accounts, AccountUsage};
+    use near_primitives::account::id::AccountId;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_propose_boundary_accounts() {
+        let accounts: BTreeMap<_, _> = [
+            ("alice", 100, 0),
+            ("bob", 100, 0),
+            ("carol", 0, 500),
+            ("dave", 100, 0),
+            ("eve", 100, 500),
+            ("frank", 0, 0),
+        ]
+        .into_iter()
+        .map(|(account_id, storage, gas)| {
+            (account_id.parse().unwrap(), AccountUsage { storage, gas })
+        })
+        .collect();
+        let names = |accounts: Vec<AccountId>| -> Vec<String> {
+            accounts.iter().map(ToString::to_string).collect()
+        };
+
+        // Loads are 0.25, 0.25, 0.5, 0.25, 0.75 and 0.
+        assert_eq!(names(propose_boundary_accounts(&accounts, 2)), vec!["dave"]);
+        assert_eq!(names(propose_boundary_accounts(&accounts, 3)), vec!["carol", "eve"]);
+        // Every account but the first one starts a new shard.
+        assert_eq!(propose_boundary_accounts(&accounts, 10).len(), 5);
+        assert!(propose_boundary_accounts(&BTreeMap::new(), 2).is_empty());
+    }
+}